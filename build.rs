@@ -0,0 +1,25 @@
+//! 建置腳本：`bundled-font` 功能在字型檔存在時才內嵌
+//!
+//! 字型檔未收錄於儲存庫（見 `assets/fonts/README.md`），缺少時發出警告並略過內嵌，
+//! 讓 `--all-features` 仍可編譯。
+
+use std::path::Path;
+
+const BUNDLED_FONT_PATH: &str = "assets/fonts/NotoSansTC-Regular.ttf";
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(bundled_font)");
+    println!("cargo:rerun-if-changed={}", BUNDLED_FONT_PATH);
+
+    if std::env::var_os("CARGO_FEATURE_BUNDLED_FONT").is_none() {
+        return;
+    }
+    if Path::new(BUNDLED_FONT_PATH).is_file() {
+        println!("cargo:rustc-cfg=bundled_font");
+    } else {
+        println!(
+            "cargo:warning=bundled-font：找不到 {}，不內嵌備用字型（見 assets/fonts/README.md）",
+            BUNDLED_FONT_PATH
+        );
+    }
+}
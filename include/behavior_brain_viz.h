@@ -0,0 +1,53 @@
+/*
+ * behavior_brain_viz C API
+ *
+ * 所有字串皆為 UTF-8 並以 NUL 結尾。由本函式庫配置的物件必須以對應的
+ * *_free 函式釋放；失敗時回傳 NULL，可用 bbv_last_error_message() 取得原因。
+ */
+#ifndef BEHAVIOR_BRAIN_VIZ_H
+#define BEHAVIOR_BRAIN_VIZ_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct BbvEngine BbvEngine;
+typedef struct BbvResult BbvResult;
+
+/* 風險等級 */
+typedef enum BbvRiskLevel {
+    BBV_RISK_INVALID = -1,
+    BBV_RISK_LOW = 0,
+    BBV_RISK_MEDIUM = 1,
+    BBV_RISK_HIGH = 2,
+    BBV_RISK_CRITICAL = 3
+} BbvRiskLevel;
+
+/* 目前執行緒最後一次錯誤訊息；無錯誤時回傳 NULL。指標在下一次呼叫前有效。 */
+const char *bbv_last_error_message(void);
+
+/* 建立 / 釋放計算引擎；risk_map_path 可為 NULL（使用預設對照表） */
+BbvEngine *bbv_engine_new_from_json(const char *mapping_path);
+BbvEngine *bbv_engine_new_with_risk_map(const char *mapping_path, const char *risk_map_path);
+void bbv_engine_free(BbvEngine *engine);
+
+/* 計算單一行為數值；unit 為 NULL 時使用映射表中的預期單位 */
+BbvResult *bbv_engine_score(const BbvEngine *engine, const char *behavior, float value, const char *unit);
+void bbv_result_free(BbvResult *result);
+
+/* 結果查詢；字串指標在 bbv_result_free 前有效 */
+float bbv_result_total_impact(const BbvResult *result);
+int bbv_result_risk_level(const BbvResult *result);
+size_t bbv_result_region_count(const BbvResult *result);
+const char *bbv_result_region_name(const BbvResult *result, size_t index);
+float bbv_result_region_score(const BbvResult *result, size_t index);
+size_t bbv_result_recommendation_count(const BbvResult *result);
+const char *bbv_result_recommendation(const BbvResult *result, size_t index);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BEHAVIOR_BRAIN_VIZ_H */
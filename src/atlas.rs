@@ -0,0 +1,676 @@
+//! 標準腦圖譜（AAL、Harvard-Oxford、Desikan-Killiany、Schaefer）
+//!
+//! 讀入各圖譜的標籤表與 MNI 質心座標，透過 `data/atlas_region_map.json`
+//! 將映射資料中的自訂腦區名稱對應到圖譜標籤，並把影響分數輸出為依標籤編號排列的向量，
+//! 供影像分析軟體（nilearn、BrainNet Viewer、FSLeyes 等）直接使用。
+//!
+//! 支援的標籤表格式：
+//! - FSL 圖譜 XML（Harvard-Oxford）：`<label index="0" x="48" y="94" z="35">Frontal Pole</label>`，
+//!   影像中的值為 `index + 1`，體素座標依 MNI152 範本換算為 MNI 座標
+//! - 有標題列的 CSV/TSV：編號欄（`index`、`ROI Label` 等）、名稱欄（`name`、`ROI Name` 等），
+//!   可另含 `x`/`y`/`z` 或 `R`/`A`/`S` 座標欄（Schaefer 的 `Centroid_RAS.csv`）
+//! - 以空白分隔、無標題列的表格：`編號 名稱 ...`（FreeSurfer `FreeSurferColorLUT.txt`、Schaefer `.txt`、AAL3），
+//!   或 `縮寫 名稱 編號`（AAL `ROI_MNI_V4.txt`）
+//!
+//! 標籤表旁若有同名的標籤影像（`<圖譜名稱>.nii.gz` 或 `.nii`），可匯出 NIfTI 影響分數圖，
+//! 標籤表沒有座標時也會以影像計算各標籤的質心（見 [`crate::nifti`]）。
+
+use crate::brain_map::match_by_name;
+use crate::error::{read_json, BrainVizError, Result};
+use crate::model::CalculationResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 質心檔的副檔名：`<標籤表檔名>.centroids.csv` 會在載入標籤表時一併讀入
+pub const CENTROIDS_SUFFIX: &str = ".centroids.csv";
+
+/// 圖譜種類；決定在腦區對照檔中使用哪一組標籤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AtlasKind {
+    Aal,
+    HarvardOxford,
+    DesikanKilliany,
+    Schaefer,
+    /// 無法辨識的圖譜；以檔名查詢腦區對照
+    Custom,
+}
+
+impl AtlasKind {
+    pub const ALL: [AtlasKind; 5] = [
+        AtlasKind::Aal,
+        AtlasKind::HarvardOxford,
+        AtlasKind::DesikanKilliany,
+        AtlasKind::Schaefer,
+        AtlasKind::Custom,
+    ];
+
+    /// 腦區對照檔中使用的代碼
+    pub fn id(&self) -> &'static str {
+        match self {
+            AtlasKind::Aal => "aal",
+            AtlasKind::HarvardOxford => "harvard_oxford",
+            AtlasKind::DesikanKilliany => "desikan_killiany",
+            AtlasKind::Schaefer => "schaefer",
+            AtlasKind::Custom => "custom",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AtlasKind::Aal => "AAL",
+            AtlasKind::HarvardOxford => "Harvard-Oxford",
+            AtlasKind::DesikanKilliany => "Desikan-Killiany",
+            AtlasKind::Schaefer => "Schaefer 2018",
+            AtlasKind::Custom => "Custom",
+        }
+    }
+
+    /// 依檔名判斷圖譜種類，檔名無法判斷時再依標籤名稱的慣例判斷
+    pub fn detect(file_name: &str, labels: &[AtlasLabel]) -> Self {
+        let file_name = file_name.to_ascii_lowercase();
+        if file_name.contains("schaefer") {
+            return AtlasKind::Schaefer;
+        }
+        if file_name.contains("harvard") || file_name.starts_with("ho-") || file_name.starts_with("ho_") {
+            return AtlasKind::HarvardOxford;
+        }
+        if file_name.contains("desikan") || file_name.contains("aparc") || file_name.starts_with("dk") {
+            return AtlasKind::DesikanKilliany;
+        }
+        if file_name.contains("aal") {
+            return AtlasKind::Aal;
+        }
+
+        let any = |f: &dyn Fn(&str) -> bool| labels.iter().any(|label| f(&label.name));
+        if any(&|name| name.contains("Networks_")) {
+            AtlasKind::Schaefer
+        } else if any(&|name| name.starts_with("ctx-lh-") || name.starts_with("ctx-rh-")) {
+            AtlasKind::DesikanKilliany
+        } else if any(&|name| name == "Precentral_L" || name == "Hippocampus_L") {
+            AtlasKind::Aal
+        } else {
+            AtlasKind::Custom
+        }
+    }
+}
+
+/// 圖譜中的一個標籤
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AtlasLabel {
+    /// 影像中的標籤值
+    pub index: u32,
+    pub name: String,
+    /// MNI 質心座標（mm）；標籤表沒有座標時為 None
+    pub mni: Option<[f32; 3]>,
+}
+
+/// 已載入的標準圖譜
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardAtlas {
+    pub kind: AtlasKind,
+    /// 圖譜名稱（檔名去掉副檔名）
+    pub name: String,
+    /// 依標籤編號排序
+    pub labels: Vec<AtlasLabel>,
+    /// 同目錄下的標籤影像（NIfTI）；沒有時無法匯出影像
+    pub volume: Option<PathBuf>,
+}
+
+impl StandardAtlas {
+    /// 解析標籤表文字；`path` 僅用於錯誤訊息與判斷圖譜種類
+    pub fn parse(path: impl AsRef<Path>, text: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let mut labels = parse_label_table(path, text)?;
+        labels.sort_by_key(|label| label.index);
+        labels.dedup_by_key(|label| label.index);
+
+        let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(Self {
+            kind: AtlasKind::detect(&file_name, &labels),
+            name: atlas_name(&file_name),
+            labels,
+            volume: None,
+        })
+    }
+
+    /// 讀取標籤表；同目錄下有 `<檔名>.centroids.csv` 時合併其中的質心座標
+    ///
+    /// 同目錄下有 `<檔名>.nii.gz` 或 `<檔名>.nii` 時記為標籤影像，
+    /// 仍缺少座標的標籤以影像中的體素質心補上。
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| BrainVizError::io(path, e))?;
+        let mut atlas = Self::parse(path, &text)?;
+
+        let centroids = path.with_file_name(format!("{}{}", atlas.name, CENTROIDS_SUFFIX));
+        if centroids != path && centroids.is_file() {
+            let text = std::fs::read_to_string(&centroids).map_err(|e| BrainVizError::io(&centroids, e))?;
+            atlas.merge_centroids(&parse_label_table(&centroids, &text)?);
+        }
+
+        atlas.volume = ["nii.gz", "nii"]
+            .iter()
+            .map(|ext| path.with_file_name(format!("{}.{}", atlas.name, ext)))
+            .find(|volume| volume.is_file());
+        if let Some(volume) = &atlas.volume {
+            if atlas.labels.iter().any(|label| label.mni.is_none()) {
+                let centroids = crate::nifti::NiftiVolume::read(volume)?.label_centroids();
+                let missing: Vec<AtlasLabel> = centroids
+                    .into_iter()
+                    .filter(|c| atlas.label(c.index).is_some_and(|label| label.mni.is_none()))
+                    .collect();
+                atlas.merge_centroids(&missing);
+            }
+        }
+        Ok(atlas)
+    }
+
+    /// 依標籤編號合併質心座標，回傳補上座標的標籤數
+    pub fn merge_centroids(&mut self, centroids: &[AtlasLabel]) -> usize {
+        let mut merged = 0;
+        for centroid in centroids {
+            let Some(mni) = centroid.mni else {
+                continue;
+            };
+            if let Some(label) = self.labels.iter_mut().find(|label| label.index == centroid.index) {
+                label.mni = Some(mni);
+                merged += 1;
+            }
+        }
+        merged
+    }
+
+    /// 依標籤編號取得標籤
+    pub fn label(&self, index: u32) -> Option<&AtlasLabel> {
+        self.labels
+            .binary_search_by_key(&index, |label| label.index)
+            .ok()
+            .map(|i| &self.labels[i])
+    }
+
+    /// 名稱符合任一樣式（`*` 為萬用字元，不分大小寫）的標籤
+    pub fn find_labels(&self, patterns: &[String]) -> Vec<&AtlasLabel> {
+        self.labels
+            .iter()
+            .filter(|label| patterns.iter().any(|pattern| glob_match(pattern, &label.name)))
+            .collect()
+    }
+}
+
+/// 一個自訂腦區在各圖譜中對應的標籤樣式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegionAtlasLabels {
+    /// 可比對的腦區名稱（中文與英文），比對規則與腦圖相同
+    pub names: Vec<String>,
+    /// 圖譜代碼（[`AtlasKind::id`] 或自訂圖譜的檔名）→ 標籤名稱樣式
+    pub labels: HashMap<String, Vec<String>>,
+}
+
+/// 自訂腦區名稱與圖譜標籤的對照表
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AtlasRegionMap {
+    pub regions: Vec<RegionAtlasLabels>,
+}
+
+impl AtlasRegionMap {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        read_json(path)
+    }
+
+    /// 依名稱找出對照項目；規則同 [`BrainMapLayout::find_region`](crate::brain_map::BrainMapLayout::find_region)
+    pub fn entry(&self, region: &str) -> Option<&RegionAtlasLabels> {
+        match_by_name(region, self.regions.iter().map(|entry| (entry, entry.names.as_slice())))
+    }
+
+    /// 腦區在指定圖譜中的標籤；先以圖譜種類查詢，自訂圖譜則以圖譜名稱查詢
+    pub fn labels_for<'a>(&self, region: &str, atlas: &'a StandardAtlas) -> Vec<&'a AtlasLabel> {
+        let Some(entry) = self.entry(region) else {
+            return Vec::new();
+        };
+        let patterns = entry
+            .labels
+            .get(atlas.kind.id())
+            .filter(|_| atlas.kind != AtlasKind::Custom)
+            .or_else(|| entry.labels.get(&atlas.name));
+        patterns.map(|patterns| atlas.find_labels(patterns)).unwrap_or_default()
+    }
+}
+
+/// 向量中的一個標籤
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AtlasVectorEntry {
+    pub index: u32,
+    pub label: String,
+    pub mni: Option<[f32; 3]>,
+    /// 對應到此標籤的腦區中最高的影響分數；沒有對應時為 0
+    pub impact_score: f32,
+    /// 對應到此標籤的腦區
+    pub regions: Vec<String>,
+}
+
+/// 依圖譜標籤排列的影響分數
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AtlasVector {
+    pub atlas: String,
+    pub kind: AtlasKind,
+    /// 與圖譜標籤一一對應，依標籤編號排序
+    pub entries: Vec<AtlasVectorEntry>,
+    /// 在此圖譜中找不到標籤的腦區
+    pub unmapped: Vec<String>,
+}
+
+impl AtlasVector {
+    /// 將計算結果展開到圖譜標籤；多個腦區對應到同一標籤時取最高分
+    pub fn from_result(result: &CalculationResult, atlas: &StandardAtlas, map: &AtlasRegionMap) -> Self {
+        let mut entries: Vec<AtlasVectorEntry> = atlas
+            .labels
+            .iter()
+            .map(|label| AtlasVectorEntry {
+                index: label.index,
+                label: label.name.clone(),
+                mni: label.mni,
+                impact_score: 0.0,
+                regions: Vec::new(),
+            })
+            .collect();
+        let mut unmapped = Vec::new();
+
+        for impact in &result.impact_scores {
+            let labels = map.labels_for(&impact.region, atlas);
+            if labels.is_empty() {
+                unmapped.push(impact.region.clone());
+                continue;
+            }
+            for label in labels {
+                if let Ok(i) = entries.binary_search_by_key(&label.index, |entry| entry.index) {
+                    let entry = &mut entries[i];
+                    entry.impact_score = entry.impact_score.max(impact.impact_score);
+                    if !entry.regions.contains(&impact.region) {
+                        entry.regions.push(impact.region.clone());
+                    }
+                }
+            }
+        }
+
+        Self {
+            atlas: atlas.name.clone(),
+            kind: atlas.kind,
+            entries,
+            unmapped,
+        }
+    }
+
+    /// 只有分數的向量，順序與標籤編號相同
+    pub fn values(&self) -> Vec<f32> {
+        self.entries.iter().map(|entry| entry.impact_score).collect()
+    }
+}
+
+/// 載入多個圖譜標籤表；回傳成功載入的圖譜與各檔案的錯誤
+pub fn load_atlases(paths: &[impl AsRef<Path>]) -> (Vec<StandardAtlas>, Vec<BrainVizError>) {
+    let mut atlases = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match StandardAtlas::load(path) {
+            Ok(atlas) => atlases.push(atlas),
+            Err(e) => errors.push(e),
+        }
+    }
+    (atlases, errors)
+}
+
+/// 檔名去掉副檔名（`.nii.txt`、`.centroids.csv` 等多重副檔名一併去掉）
+fn atlas_name(file_name: &str) -> String {
+    let name = file_name.strip_suffix(CENTROIDS_SUFFIX).unwrap_or(file_name);
+    let name = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    name.strip_suffix(".nii").unwrap_or(name).to_string()
+}
+
+// CSV/TSV 標題列可辨識的欄位名稱（不分大小寫）
+const INDEX_COLUMNS: [&str; 6] = ["index", "roi label", "label id", "id", "value", "roi_id"];
+const NAME_COLUMNS: [&str; 6] = ["name", "roi name", "label name", "label_name", "roi_name", "label"];
+const COORD_COLUMNS: [[&str; 3]; 3] = [["x", "mni_x", "r"], ["y", "mni_y", "a"], ["z", "mni_z", "s"]];
+
+fn parse_error(path: &Path, line: usize, message: String) -> BrainVizError {
+    BrainVizError::Parse {
+        path: path.to_path_buf(),
+        line,
+        column: 1,
+        message,
+    }
+}
+
+fn parse_label_table(path: &Path, text: &str) -> Result<Vec<AtlasLabel>> {
+    let text = text.trim_start_matches('\u{feff}');
+    if text.trim_start().starts_with('<') {
+        return parse_fsl_xml(path, text);
+    }
+
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .peekable();
+    let Some(&(_, first)) = lines.peek() else {
+        return Ok(Vec::new());
+    };
+
+    // 有可辨識的編號欄標題才視為 CSV/TSV，否則（包括以 tab 分隔的 AAL 表）逐列解析
+    let delimiter = [',', '\t'].into_iter().find(|d| {
+        first
+            .split(*d)
+            .any(|field| INDEX_COLUMNS.contains(&unquote(field).to_ascii_lowercase().as_str()))
+    });
+    match delimiter {
+        Some(delimiter) => parse_delimited(path, lines, delimiter),
+        None => lines.map(|(line, text)| parse_whitespace_line(path, line, text)).collect(),
+    }
+}
+
+/// 有標題列的 CSV/TSV
+fn parse_delimited<'a>(
+    path: &Path,
+    mut lines: impl Iterator<Item = (usize, &'a str)>,
+    delimiter: char,
+) -> Result<Vec<AtlasLabel>> {
+    let Some((header_line, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = header.split(delimiter).map(|h| unquote(h).to_ascii_lowercase()).collect();
+    let column = |names: &[&str]| names.iter().find_map(|name| header.iter().position(|h| h == name));
+    let index_col = column(&INDEX_COLUMNS).ok_or_else(|| parse_error(path, header_line, "找不到標籤編號欄".to_string()))?;
+    let name_col = column(&NAME_COLUMNS).ok_or_else(|| parse_error(path, header_line, "找不到標籤名稱欄".to_string()))?;
+    let coords = COORD_COLUMNS.map(|names| column(&names));
+
+    lines
+        .map(|(line, text)| {
+            let fields: Vec<&str> = text.split(delimiter).map(unquote).collect();
+            let field = |col: usize| fields.get(col).copied().unwrap_or_default();
+            let index = field(index_col)
+                .parse()
+                .map_err(|_| parse_error(path, line, format!("標籤編號「{}」不是非負整數", field(index_col))))?;
+            let mni = match coords {
+                [Some(x), Some(y), Some(z)] => {
+                    let number = |col: usize| {
+                        field(col)
+                            .parse::<f32>()
+                            .map_err(|_| parse_error(path, line, format!("座標「{}」不是數字", field(col))))
+                    };
+                    Some([number(x)?, number(y)?, number(z)?])
+                }
+                _ => None,
+            };
+            Ok(AtlasLabel {
+                index,
+                name: field(name_col).to_string(),
+                mni,
+            })
+        })
+        .collect()
+}
+
+/// `編號 名稱 ...` 或 `縮寫 名稱 編號`
+fn parse_whitespace_line(path: &Path, line: usize, text: &str) -> Result<AtlasLabel> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let (index, name) = match tokens.as_slice() {
+        [index, name, ..] if index.parse::<u32>().is_ok() => (index, name),
+        [_, name, index, ..] => (index, name),
+        _ => return Err(parse_error(path, line, format!("無法辨識的標籤列「{}」", text))),
+    };
+    let index = index
+        .parse()
+        .map_err(|_| parse_error(path, line, format!("標籤編號「{}」不是非負整數", index)))?;
+    Ok(AtlasLabel {
+        index,
+        name: name.to_string(),
+        mni: None,
+    })
+}
+
+/// FSL 圖譜 XML；體素座標依影像解析度（1mm 或 2mm）換算為 MNI 座標
+fn parse_fsl_xml(path: &Path, text: &str) -> Result<Vec<AtlasLabel>> {
+    let voxel_size = if text.contains("1mm") && !text.contains("2mm") { 1.0 } else { 2.0 };
+    let to_mni = |[x, y, z]: [f32; 3]| {
+        [
+            90.0 - voxel_size * x,
+            voxel_size * y - 126.0,
+            voxel_size * z - 72.0,
+        ]
+    };
+
+    let mut labels = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let Some(start) = line.find("<label ") else {
+            continue;
+        };
+        let rest = &line[start..];
+        let (Some(tag_end), Some(close)) = (rest.find('>'), rest.find("</label>")) else {
+            return Err(parse_error(path, i + 1, "label 標籤不完整".to_string()));
+        };
+        let tag = &rest[..tag_end];
+        let number = |name: &str| crate::brain_map::attr(tag, name).and_then(|v| v.parse::<f32>().ok());
+        let index = number("index").ok_or_else(|| parse_error(path, i + 1, "label 缺少 index".to_string()))?;
+        let mni = match (number("x"), number("y"), number("z")) {
+            (Some(x), Some(y), Some(z)) => Some(to_mni([x, y, z])),
+            _ => None,
+        };
+        labels.push(AtlasLabel {
+            // FSL 的 index 從 0 起算，影像中的值為 index + 1
+            index: index as u32 + 1,
+            name: unescape_xml(rest[tag_end + 1..close].trim()),
+            mni,
+        });
+    }
+    Ok(labels)
+}
+
+fn unquote(field: &str) -> &str {
+    let field = field.trim();
+    field
+        .strip_prefix('"')
+        .and_then(|f| f.strip_suffix('"'))
+        .unwrap_or(field)
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 只支援 `*` 的萬用字元比對，不分大小寫
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) || text.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BehaviorInput, RegionImpactScore, RiskLevel};
+
+    const HO_XML: &str = r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<atlas version="1.0">
+  <header><images><imagefile>/HarvardOxford/HarvardOxford-cort-maxprob-thr25-2mm</imagefile></images></header>
+  <data>
+    <label index="0" x="48" y="94" z="35">Frontal Pole</label>
+    <label index="26" x="56" y="74" z="49">Cingulate Gyrus, anterior division</label>
+    <label index="44" x="38" y="59" z="52">Heschl&apos;s Gyrus (includes H1 and H2)</label>
+  </data>
+</atlas>"#;
+
+    const AAL_TXT: &str = "FAG\tPrecentral_L\t2001\nFAD\tPrecentral_R\t2002\nHIPPOG\tHippocampus_L\t4101\nHIPPOD\tHippocampus_R\t4102\n";
+
+    const SCHAEFER_CSV: &str = "ROI Label,ROI Name,R,A,S\n\
+1,7Networks_LH_Vis_1,-33,-42,-21\n\
+2,7Networks_LH_Cont_PFCl_1,-42,30,30\n\
+3,7Networks_RH_Cont_PFCl_1,44,32,28\n";
+
+    fn sample(impacts: &[(&str, f32)]) -> CalculationResult {
+        CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: "工作記憶 N-back 任務".to_string(),
+                value: 1.0,
+                unit: "d'".to_string(),
+                timestamp: chrono::Utc::now(),
+            },
+            impact_scores: impacts
+                .iter()
+                .map(|&(region, score)| RegionImpactScore {
+                    region: region.to_string(),
+                    impact_score: score,
+                    normalized_input: 1.0,
+                    weight: 0.5,
+                })
+                .collect(),
+            total_impact: 0.5,
+            risk_level: RiskLevel::Medium,
+        }
+    }
+
+    fn region_map() -> AtlasRegionMap {
+        serde_json::from_str(
+            r#"{ "regions": [
+                { "names": ["海馬體", "Hippocampus"], "labels": { "aal": ["Hippocampus_*"] } },
+                { "names": ["背外側前額葉皮質", "Dorsolateral Prefrontal Cortex"],
+                  "labels": { "aal": ["Frontal_Mid_L", "Frontal_Mid_R"], "schaefer": ["*_Cont*_PFCl_*"] } },
+                { "names": ["前額葉", "Prefrontal Cortex"], "labels": { "schaefer": ["*_Cont*_PFCl_*", "*_Default_PFC_*"] } }
+            ] }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_fsl_xml() {
+        let atlas = StandardAtlas::parse("HarvardOxford-Cortical.xml", HO_XML).unwrap();
+        assert_eq!(atlas.kind, AtlasKind::HarvardOxford);
+        assert_eq!(atlas.name, "HarvardOxford-Cortical");
+        assert_eq!(atlas.labels.len(), 3);
+        assert_eq!(atlas.labels[0].index, 1);
+        assert_eq!(atlas.labels[0].mni, Some([-6.0, 62.0, -2.0]));
+        assert_eq!(atlas.label(45).unwrap().name, "Heschl's Gyrus (includes H1 and H2)");
+    }
+
+    #[test]
+    fn test_parse_text_tables() {
+        let aal = StandardAtlas::parse("ROI_MNI_V4.txt", AAL_TXT).unwrap();
+        assert_eq!(aal.kind, AtlasKind::Aal);
+        assert_eq!(aal.label(4102).unwrap().name, "Hippocampus_R");
+        assert!(aal.labels.iter().all(|label| label.mni.is_none()));
+
+        let lut = "# FreeSurfer LUT\n1001    ctx-lh-bankssts      25  100 40  0\n1002    ctx-lh-caudalanteriorcingulate 125 100 160 0\n";
+        let dk = StandardAtlas::parse("aparc_lut.txt", lut).unwrap();
+        assert_eq!(dk.kind, AtlasKind::DesikanKilliany);
+        assert_eq!(dk.labels[1].name, "ctx-lh-caudalanteriorcingulate");
+
+        let schaefer = StandardAtlas::parse("Schaefer2018_100Parcels_7Networks.Centroid_RAS.csv", SCHAEFER_CSV).unwrap();
+        assert_eq!(schaefer.kind, AtlasKind::Schaefer);
+        assert_eq!(schaefer.labels[1].mni, Some([-42.0, 30.0, 30.0]));
+
+        let err = StandardAtlas::parse("custom.csv", "index,name\n1,A\nx,B\n").unwrap_err();
+        assert!(matches!(err, BrainVizError::Parse { line: 3, .. }));
+    }
+
+    #[test]
+    fn test_merge_centroids() {
+        let mut aal = StandardAtlas::parse("AAL.txt", AAL_TXT).unwrap();
+        let centroids = parse_label_table(Path::new("AAL.centroids.csv"), "index,name,x,y,z\n4101,Hippocampus_L,-25,-21,-10\n9999,X,0,0,0\n").unwrap();
+        assert_eq!(aal.merge_centroids(&centroids), 1);
+        assert_eq!(aal.label(4101).unwrap().mni, Some([-25.0, -21.0, -10.0]));
+        assert_eq!(atlas_name("AAL3v1.nii.txt"), "AAL3v1");
+        assert_eq!(atlas_name("AAL.centroids.csv"), "AAL");
+    }
+
+    #[test]
+    fn test_load_volume_centroids() {
+        let dir = std::env::temp_dir().join("bbv_atlas_volume");
+        std::fs::create_dir_all(&dir).unwrap();
+        let table = dir.join("custom.csv");
+        std::fs::write(&table, "index,name\n1,Left\n2,Right\n").unwrap();
+        std::fs::write(dir.join("custom.centroids.csv"), "index,name,x,y,z\n2,Right,10,20,30\n").unwrap();
+        let labels = crate::nifti::NiftiVolume {
+            dims: [2, 1, 1],
+            pixdim: [2.0, 2.0, 2.0],
+            qfac: 1.0,
+            qform_code: 0,
+            quatern: [0.0; 3],
+            qoffset: [0.0; 3],
+            sform_code: 4,
+            srow: [[2.0, 0.0, 0.0, -2.0], [0.0, 2.0, 0.0, 0.0], [0.0, 0.0, 2.0, 0.0]],
+            xyzt_units: 2,
+            descrip: String::new(),
+            data: vec![1.0, 2.0],
+        };
+        labels.write(dir.join("custom.nii")).unwrap();
+
+        let atlas = StandardAtlas::load(&table).unwrap();
+        assert_eq!(atlas.volume.as_deref(), Some(dir.join("custom.nii").as_path()));
+        assert_eq!(atlas.label(1).unwrap().mni, Some([-2.0, 0.0, 0.0]));
+        // 質心檔已有的座標不被影像質心覆寫
+        assert_eq!(atlas.label(2).unwrap().mni, Some([10.0, 20.0, 30.0]));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("Hippocampus_*", "Hippocampus_L"));
+        assert!(glob_match("*_cont*_pfcl_*", "17Networks_LH_ContA_PFCl_1"));
+        assert!(glob_match("* Amygdala", "Left Amygdala"));
+        assert!(!glob_match("Frontal_Mid_L", "Frontal_Mid_Orb_L"));
+        assert!(!glob_match("a*a", "a"));
+    }
+
+    #[test]
+    fn test_atlas_vector() {
+        let aal = StandardAtlas::parse("AAL.txt", &format!("{}SFG\tFrontal_Mid_L\t2201\n", AAL_TXT)).unwrap();
+        let map = region_map();
+        let result = sample(&[("Hippocampus", 0.6), ("背外側前額葉皮質", 0.8), ("杏仁核", 0.9)]);
+
+        let vector = AtlasVector::from_result(&result, &aal, &map);
+        assert_eq!(vector.entries.len(), 5);
+        assert_eq!(vector.values(), vec![0.0, 0.0, 0.8, 0.6, 0.6]);
+        assert_eq!(vector.entries[2].regions, vec!["背外側前額葉皮質"]);
+        assert_eq!(vector.unmapped, vec!["杏仁核"]);
+
+        // 兩個腦區對應到同一標籤時取最高分
+        let schaefer = StandardAtlas::parse("Schaefer.csv", SCHAEFER_CSV).unwrap();
+        let vector = AtlasVector::from_result(&sample(&[("Prefrontal Cortex", 0.3), ("背外側前額葉皮質", 0.7)]), &schaefer, &map);
+        assert_eq!(vector.values(), vec![0.0, 0.7, 0.7]);
+        assert_eq!(vector.entries[1].regions.len(), 2);
+    }
+
+    #[test]
+    fn test_bundled_region_map() {
+        let base = env!("CARGO_MANIFEST_DIR");
+        let map = AtlasRegionMap::load(format!("{}/data/atlas_region_map.json", base)).unwrap();
+        let maps = crate::mapping::load_behavior_brain_map(format!("{}/data/behavior_brain_map.json", base)).unwrap();
+        for region in maps.iter().flat_map(|m| &m.brain_regions) {
+            let entry = map.entry(&region.region);
+            assert!(entry.is_some(), "{} 沒有圖譜對照", region.region);
+            assert!(entry.unwrap().labels.contains_key(AtlasKind::Aal.id()), "{} 缺少 AAL 標籤", region.region);
+        }
+    }
+}
@@ -0,0 +1,639 @@
+use crate::error::{BrainVizError, Result};
+use crate::i18n::tr;
+use crate::model::RegionImpactScore;
+use std::path::Path;
+
+/// 腦圖上的一個腦區（取自 SVG 中帶 id 的 `<circle>`）
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapRegion {
+    pub id: String,
+    pub label: String,      // <title> 全文，例如「前額葉 (Prefrontal Cortex)」
+    pub names: Vec<String>, // 可供比對的名稱，例如 ["前額葉", "Prefrontal Cortex"]
+    pub center: [f32; 2],
+    pub radius: f32,
+}
+
+/// 已定位的影響分數
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedImpact {
+    pub region: String,
+    pub impact_score: f32,
+    pub map_region_id: Option<String>, // 未對應到腦圖腦區時為 None
+    pub center: [f32; 2],
+    pub radius: f32,
+}
+
+/// 從 SVG 解析出的腦圖版面
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrainMapLayout {
+    pub width: f32,
+    pub height: f32,
+    pub outline: Option<String>, // 外輪廓 path 的 d 屬性
+    pub regions: Vec<MapRegion>,
+}
+
+impl BrainMapLayout {
+    /// 從 SVG 文字解析腦圖版面
+    pub fn from_svg(svg: &str) -> Self {
+        let (width, height) = find_tag(svg, "svg", 0)
+            .map(|(tag, _)| svg_size(tag))
+            .unwrap_or((400.0, 300.0));
+
+        let outline = find_tag(svg, "path", 0).and_then(|(tag, _)| attr(tag, "d"));
+
+        let mut regions = Vec::new();
+        let mut pos = 0;
+        while let Some((tag, end)) = find_tag(svg, "circle", pos) {
+            pos = end;
+            let Some(id) = attr(tag, "id") else {
+                continue; // 圖例等沒有 id 的圓形
+            };
+            let number = |name: &str| attr(tag, name).and_then(|v| v.parse::<f32>().ok()).unwrap_or(0.0);
+
+            // 緊接在 circle 之後的 <title>
+            let label = svg[end..]
+                .split_once("</circle>")
+                .and_then(|(inner, _)| inner.split_once("<title>"))
+                .and_then(|(_, rest)| rest.split_once("</title>"))
+                .map(|(title, _)| title.trim().to_string())
+                .unwrap_or_else(|| id.clone());
+
+            regions.push(MapRegion {
+                names: split_names(&label),
+                id,
+                label,
+                center: [number("cx"), number("cy")],
+                radius: number("r"),
+            });
+        }
+
+        Self {
+            width,
+            height,
+            outline,
+            regions,
+        }
+    }
+
+    /// 從 SVG 文件載入腦圖版面
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let svg = std::fs::read_to_string(path).map_err(|e| BrainVizError::io(path, e))?;
+        Ok(Self::from_svg(&svg))
+    }
+
+    /// 依名稱尋找腦區；資料中的名稱包含腦圖名稱時視為相符，取最長（最具體）的相符名稱
+    pub fn find_region(&self, name: &str) -> Option<&MapRegion> {
+        match_by_name(name, self.regions.iter().map(|region| (region, region.names.as_slice())))
+    }
+
+    /// 為每個影響分數決定在腦圖上的位置；找不到對應腦區者沿底部排列
+    pub fn place(&self, impacts: &[RegionImpactScore]) -> Vec<PlacedImpact> {
+        let unmatched = impacts.iter().filter(|i| self.find_region(&i.region).is_none()).count();
+        let mut unmatched_index = 0;
+
+        impacts
+            .iter()
+            .map(|impact| match self.find_region(&impact.region) {
+                Some(region) => PlacedImpact {
+                    region: impact.region.clone(),
+                    impact_score: impact.impact_score,
+                    map_region_id: Some(region.id.clone()),
+                    center: region.center,
+                    radius: region.radius,
+                },
+                None => {
+                    unmatched_index += 1;
+                    PlacedImpact {
+                        region: impact.region.clone(),
+                        impact_score: impact.impact_score,
+                        map_region_id: None,
+                        center: [
+                            self.width * unmatched_index as f32 / (unmatched as f32 + 1.0),
+                            self.height * 0.9,
+                        ],
+                        radius: 10.0,
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// 腦圖視角
+///
+/// 單一 2D 腦圖中深部結構（杏仁核、海馬體、基底核）會與皮質重疊，
+/// 因此另以多個視角的 SVG 分別標示腦區座標；同一腦區可出現在多個視角。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BrainView {
+    /// `--atlas` 指定的原始腦圖
+    #[default]
+    Overview,
+    Lateral,
+    Medial,
+    Axial,
+    Cerebellum,
+}
+
+impl BrainView {
+    pub const ALL: [BrainView; 5] = [
+        BrainView::Overview,
+        BrainView::Lateral,
+        BrainView::Medial,
+        BrainView::Axial,
+        BrainView::Cerebellum,
+    ];
+
+    /// 視角代碼；總覽以外的視角亦為視角目錄中的檔名（`<代碼>.svg`）
+    pub fn id(&self) -> &'static str {
+        match self {
+            BrainView::Overview => "overview",
+            BrainView::Lateral => "lateral",
+            BrainView::Medial => "medial",
+            BrainView::Axial => "axial",
+            BrainView::Cerebellum => "cerebellum",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|view| view.id().eq_ignore_ascii_case(id.trim()))
+    }
+
+    /// 顯示名稱（依目前語言）
+    pub fn label(&self) -> &'static str {
+        match self {
+            BrainView::Overview => tr("view.overview"),
+            BrainView::Lateral => tr("view.lateral"),
+            BrainView::Medial => tr("view.medial"),
+            BrainView::Axial => tr("view.axial"),
+            BrainView::Cerebellum => tr("view.cerebellum"),
+        }
+    }
+}
+
+/// 單一視角的 SVG 原文與版面
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasView {
+    pub view: BrainView,
+    pub svg: String,
+    pub layout: BrainMapLayout,
+}
+
+impl AtlasView {
+    pub fn from_svg(view: BrainView, svg: String) -> Self {
+        Self {
+            view,
+            layout: BrainMapLayout::from_svg(&svg),
+            svg,
+        }
+    }
+
+    /// 只保留此視角中有對應腦區的影響分數；其餘由其他視角顯示
+    ///
+    /// 總覽視角維持 [`BrainMapLayout::place`] 的行為，未對應者沿底部排列。
+    pub fn place(&self, impacts: &[RegionImpactScore]) -> Vec<PlacedImpact> {
+        let mut placed = self.layout.place(impacts);
+        if self.view != BrainView::Overview {
+            placed.retain(|p| p.map_region_id.is_some());
+        }
+        placed
+    }
+}
+
+/// 多視角腦圖：總覽加上視角目錄中找得到的各視角
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BrainAtlas {
+    pub views: Vec<AtlasView>,
+}
+
+impl BrainAtlas {
+    /// 載入總覽腦圖與視角目錄中的 `<視角代碼>.svg`
+    ///
+    /// 總覽讀取失敗時以空白 SVG 代替；視角目錄中缺少的檔案直接略過。
+    pub fn load(overview: impl AsRef<Path>, views_dir: impl AsRef<Path>) -> Self {
+        let overview = std::fs::read_to_string(overview).unwrap_or_default();
+        let mut views = vec![AtlasView::from_svg(BrainView::Overview, overview)];
+        for view in &BrainView::ALL[1..] {
+            let path = views_dir.as_ref().join(format!("{}.svg", view.id()));
+            if let Ok(svg) = std::fs::read_to_string(path) {
+                views.push(AtlasView::from_svg(*view, svg));
+            }
+        }
+        Self { views }
+    }
+
+    /// 取得指定視角；未載入時回傳 None
+    pub fn view(&self, view: BrainView) -> Option<&AtlasView> {
+        self.views.iter().find(|v| v.view == view)
+    }
+
+    /// 可在哪些視角找到此腦區（不含總覽）
+    pub fn views_for_region(&self, name: &str) -> Vec<BrainView> {
+        self.views
+            .iter()
+            .filter(|v| v.view != BrainView::Overview && v.layout.find_region(name).is_some())
+            .map(|v| v.view)
+            .collect()
+    }
+}
+
+/// 依影響分數為 SVG 中對應的腦區圓形上色；未對應到腦區的分數以新圓形畫在底部
+///
+/// 同一腦區有多個分數時取最大值。回傳修改後的 SVG 文字。
+pub fn colorize_svg(svg: &str, placed: &[PlacedImpact]) -> String {
+    let mut out = String::with_capacity(svg.len() + placed.len() * 128);
+    let mut pos = 0;
+    while let Some((tag, end)) = find_tag(svg, "circle", pos) {
+        let start = end - tag.len();
+        out.push_str(&svg[pos..start]);
+        let score = attr(tag, "id").and_then(|id| {
+            placed
+                .iter()
+                .filter(|p| p.map_region_id.as_deref() == Some(id.as_str()))
+                .map(|p| p.impact_score)
+                .reduce(f32::max)
+        });
+        match score {
+            Some(score) => {
+                let tag = set_attr(tag, "fill", &hex_color(impact_color(score)));
+                let tag = set_attr(&tag, "opacity", "0.85");
+                out.push_str(&set_attr(&tag, "data-impact", &format!("{:.3}", score)));
+            }
+            None => out.push_str(tag),
+        }
+        pos = end;
+    }
+    let rest = &svg[pos..];
+
+    let extra: String = placed
+        .iter()
+        .filter(|p| p.map_region_id.is_none())
+        .map(|p| {
+            format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" opacity=\"0.85\" data-impact=\"{:.3}\">\
+<title>{}</title></circle><text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" font-size=\"8\" fill=\"#333\">{}</text>\n",
+                p.center[0],
+                p.center[1],
+                p.radius,
+                hex_color(impact_color(p.impact_score)),
+                p.impact_score,
+                escape_xml(&p.region),
+                p.center[0],
+                p.center[1] + p.radius + 9.0,
+                escape_xml(&p.region),
+            )
+        })
+        .collect();
+    match rest.rfind("</svg>") {
+        Some(close) => {
+            out.push_str(&rest[..close]);
+            out.push_str(&extra);
+            out.push_str(&rest[close..]);
+        }
+        None => out.push_str(rest),
+    }
+    out
+}
+
+/// `[r, g, b]` → `#rrggbb`
+pub fn hex_color(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// 跳脫 XML/HTML 特殊字元
+pub fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// SVG path 指令（僅支援絕對座標的 M、L、C、Z）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    Close,
+}
+
+/// 解析 SVG path 的 d 屬性
+pub fn parse_path(d: &str) -> Vec<PathCommand> {
+    let mut commands = Vec::new();
+    let mut numbers = Vec::new();
+    let mut current: Option<char> = None;
+
+    let flush = |command: Option<char>, numbers: &mut Vec<f32>, commands: &mut Vec<PathCommand>| {
+        let point = |i: usize, numbers: &[f32]| [numbers[i], numbers[i + 1]];
+        match command {
+            Some('M') => {
+                for (i, chunk) in numbers.chunks_exact(2).enumerate() {
+                    // M 之後多餘的座標視為 L
+                    let p = point(0, chunk);
+                    commands.push(if i == 0 { PathCommand::MoveTo(p) } else { PathCommand::LineTo(p) });
+                }
+            }
+            Some('L') => commands.extend(numbers.chunks_exact(2).map(|c| PathCommand::LineTo(point(0, c)))),
+            Some('C') => commands.extend(
+                numbers
+                    .chunks_exact(6)
+                    .map(|c| PathCommand::CubicTo(point(0, c), point(2, c), point(4, c))),
+            ),
+            Some('Z') | Some('z') => commands.push(PathCommand::Close),
+            _ => {}
+        }
+        numbers.clear();
+    };
+
+    let mut token = String::new();
+    for c in d.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphabetic() && c != 'e' {
+            if let Ok(n) = token.parse() {
+                numbers.push(n);
+            }
+            token.clear();
+            flush(current, &mut numbers, &mut commands);
+            current = Some(c);
+        } else if c == ',' || c.is_whitespace() || (c == '-' && !token.is_empty() && !token.ends_with('e')) {
+            if let Ok(n) = token.parse() {
+                numbers.push(n);
+            }
+            token.clear();
+            if c == '-' {
+                token.push(c);
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    flush(current, &mut numbers, &mut commands);
+    commands
+}
+
+/// 影響分數對應的顏色（與風險等級配色一致）
+pub fn impact_color(score: f32) -> [u8; 3] {
+    crate::model::RiskLevel::from_score(score).color()
+}
+
+/// 差異圖的發散色階：上升為紅、下降為藍，接近 0 為淺灰；`max_abs` 對應最深的顏色
+pub fn delta_color(delta: f32, max_abs: f32) -> [u8; 3] {
+    const NEUTRAL: [u8; 3] = [240, 240, 240];
+    let end = if delta >= 0.0 { [214, 39, 40] } else { [31, 119, 180] };
+    let t = if max_abs > 0.0 { (delta.abs() / max_abs).clamp(0.0, 1.0) } else { 0.0 };
+    std::array::from_fn(|i| (NEUTRAL[i] as f32 + (end[i] as f32 - NEUTRAL[i] as f32) * t).round() as u8)
+}
+
+/// 從候選項中找出名稱與 `name` 相符者：完全相同、去掉括號註記後相同，
+/// 或 `name` 包含候選名稱；多個相符時取最長（最具體）的名稱
+pub(crate) fn match_by_name<'a, T>(name: &str, candidates: impl IntoIterator<Item = (&'a T, &'a [String])>) -> Option<&'a T> {
+    let key = normalize(name);
+    let key_base = normalize(strip_parenthetical(name));
+
+    candidates
+        .into_iter()
+        .flat_map(|(item, names)| names.iter().map(move |n| (item, normalize(n))))
+        .filter(|(_, n)| !n.is_empty() && (key == *n || key_base == *n || key.contains(n.as_str())))
+        .max_by_key(|(_, n)| n.chars().count())
+        .map(|(item, _)| item)
+}
+
+/// 「前額葉 (Prefrontal Cortex)」→ ["前額葉", "Prefrontal Cortex"]
+fn split_names(label: &str) -> Vec<String> {
+    let mut names = vec![strip_parenthetical(label).trim().to_string()];
+    if let Some((_, rest)) = label.split_once('(') {
+        if let Some((inner, _)) = rest.split_once(')') {
+            names.push(inner.trim().to_string());
+        }
+    }
+    names.retain(|n| !n.is_empty());
+    names
+}
+
+fn strip_parenthetical(name: &str) -> &str {
+    name.split('(').next().unwrap_or(name).trim()
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 找出從 `from` 起第一個 `<name ...>` 標籤，回傳標籤內容與結束位置
+fn find_tag<'a>(svg: &'a str, name: &str, from: usize) -> Option<(&'a str, usize)> {
+    let open = format!("<{}", name);
+    let mut pos = from;
+    loop {
+        let start = pos + svg[pos..].find(&open)?;
+        let after = start + open.len();
+        // 避免 <svg 比對到 <svgx 之類的標籤
+        if svg[after..].starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            let end = after + svg[after..].find('>')? + 1;
+            return Some((&svg[start..end], end));
+        }
+        pos = after;
+    }
+}
+
+/// 取出標籤屬性值
+pub(crate) fn attr(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    let pattern = format!("{}=\"", name);
+    while let Some(idx) = rest.find(&pattern) {
+        let preceded_by_space = rest[..idx].ends_with(|c: char| c.is_whitespace());
+        let value_start = idx + pattern.len();
+        if preceded_by_space {
+            let value_end = value_start + rest[value_start..].find('"')?;
+            return Some(rest[value_start..value_end].to_string());
+        }
+        rest = &rest[value_start..];
+    }
+    None
+}
+
+/// 設定標籤屬性值；屬性不存在時加在標籤結尾
+fn set_attr(tag: &str, name: &str, value: &str) -> String {
+    let pattern = format!(" {}=\"", name);
+    if let Some(idx) = tag.find(&pattern) {
+        let value_start = idx + pattern.len();
+        if let Some(len) = tag[value_start..].find('"') {
+            return format!("{}{}{}", &tag[..value_start], value, &tag[value_start + len..]);
+        }
+    }
+    let close = if tag.ends_with("/>") { tag.len() - 2 } else { tag.len() - 1 };
+    format!("{} {}=\"{}\"{}", tag[..close].trim_end(), name, value, &tag[close..])
+}
+
+fn svg_size(tag: &str) -> (f32, f32) {
+    if let Some(view_box) = attr(tag, "viewBox") {
+        let values: Vec<f32> = view_box.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+        if values.len() == 4 {
+            return (values[2], values[3]);
+        }
+    }
+    let number = |name: &str| attr(tag, name).and_then(|v| v.trim_end_matches("px").parse().ok());
+    (number("width").unwrap_or(400.0), number("height").unwrap_or(300.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SVG: &str = r##"<svg width="400" height="300" viewBox="0 0 400 300">
+      <g id="brain-outline"><path d="M50 150 C50 80 100 50 200 50 Z" fill="#f0f0f0"/></g>
+      <circle id="prefrontal" cx="120" cy="120" r="25" fill="#e8f4f8">
+        <title>前額葉 (Prefrontal Cortex)</title>
+      </circle>
+      <circle id="motor-cortex" cx="220" cy="130" r="18">
+        <title>運動皮質 (Motor Cortex)</title>
+      </circle>
+      <circle cx="10" cy="15" r="6" fill="#4caf50"/>
+    </svg>"##;
+
+    fn impact(region: &str, score: f32) -> RegionImpactScore {
+        RegionImpactScore {
+            region: region.to_string(),
+            impact_score: score,
+            normalized_input: 1.0,
+            weight: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_parse_svg() {
+        let layout = BrainMapLayout::from_svg(SVG);
+
+        assert_eq!(layout.width, 400.0);
+        assert_eq!(layout.height, 300.0);
+        assert!(layout.outline.as_deref().unwrap().starts_with("M50 150"));
+        assert_eq!(layout.regions.len(), 2); // 圖例圓形沒有 id，不算腦區
+        assert_eq!(layout.regions[0].id, "prefrontal");
+        assert_eq!(layout.regions[0].names, vec!["前額葉", "Prefrontal Cortex"]);
+        assert_eq!(layout.regions[1].center, [220.0, 130.0]);
+    }
+
+    #[test]
+    fn test_delta_color() {
+        assert_eq!(delta_color(0.0, 1.0), [240, 240, 240]);
+        assert_eq!(delta_color(0.5, 0.5), [214, 39, 40]);
+        assert_eq!(delta_color(-2.0, 0.5), [31, 119, 180]);
+        assert_eq!(delta_color(0.3, 0.0), [240, 240, 240]);
+        let half = delta_color(-0.25, 0.5);
+        assert!(half[2] > half[0] && half[0] < 240);
+    }
+
+    #[test]
+    fn test_find_region_by_alias() {
+        let layout = BrainMapLayout::from_svg(SVG);
+
+        assert_eq!(layout.find_region("前額葉").unwrap().id, "prefrontal");
+        assert_eq!(layout.find_region("Prefrontal Cortex").unwrap().id, "prefrontal");
+        assert_eq!(layout.find_region("背外側前額葉皮質").unwrap().id, "prefrontal");
+        assert_eq!(layout.find_region("Primary Motor Cortex (M1)").unwrap().id, "motor-cortex");
+        assert!(layout.find_region("Amygdala").is_none());
+    }
+
+    #[test]
+    fn test_place_impacts() {
+        let layout = BrainMapLayout::from_svg(SVG);
+        let placed = layout.place(&[impact("前額葉", 0.7), impact("Amygdala", 0.4)]);
+
+        assert_eq!(placed[0].map_region_id.as_deref(), Some("prefrontal"));
+        assert_eq!(placed[0].center, [120.0, 120.0]);
+        assert!(placed[1].map_region_id.is_none());
+        assert_eq!(placed[1].center[1], 270.0);
+    }
+
+    #[test]
+    fn test_colorize_svg() {
+        let layout = BrainMapLayout::from_svg(SVG);
+        let placed = layout.place(&[impact("前額葉", 0.9), impact("基底核", 0.4)]);
+        let svg = colorize_svg(SVG, &placed);
+
+        let colored = BrainMapLayout::from_svg(&svg);
+        assert_eq!(colored.regions.len(), 2);
+        let (tag, _) = find_tag(&svg, "circle", 0).unwrap();
+        assert_eq!(attr(tag, "fill").as_deref(), Some("#8b0000"));
+        assert_eq!(attr(tag, "data-impact").as_deref(), Some("0.900"));
+        // 未上色的腦區維持原樣
+        assert!(svg.contains(r#"<circle id="motor-cortex" cx="220" cy="130" r="18">"#));
+        // 未對應的腦區加在 </svg> 之前
+        assert!(svg.contains("<title>基底核</title>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_set_attr() {
+        assert_eq!(set_attr(r#"<circle fill="red"/>"#, "fill", "blue"), r#"<circle fill="blue"/>"#);
+        assert_eq!(set_attr(r#"<circle r="1">"#, "fill", "blue"), r#"<circle r="1" fill="blue">"#);
+        assert_eq!(escape_xml("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let commands = parse_path("M50 150 C50 80 100 50 200 50 L210,60 Z");
+        assert_eq!(
+            commands,
+            vec![
+                PathCommand::MoveTo([50.0, 150.0]),
+                PathCommand::CubicTo([50.0, 80.0], [100.0, 50.0], [200.0, 50.0]),
+                PathCommand::LineTo([210.0, 60.0]),
+                PathCommand::Close,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bundled_svg() {
+        let layout = BrainMapLayout::load(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/brain.svg")).unwrap();
+        assert!(layout.regions.len() >= 8);
+        assert!(layout.find_region("Hippocampus").is_some());
+        assert!(layout.find_region("小腦").is_some());
+    }
+
+    #[test]
+    fn test_bundled_views() {
+        let assets = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
+        let atlas = BrainAtlas::load(format!("{}/brain.svg", assets), format!("{}/views", assets));
+        assert_eq!(atlas.views.len(), BrainView::ALL.len());
+
+        // 深部結構在總覽中與皮質重疊，在各視角中則分開標示
+        assert_eq!(atlas.views_for_region("Hippocampus"), vec![BrainView::Medial, BrainView::Axial]);
+        assert_eq!(atlas.views_for_region("Amygdala"), vec![BrainView::Axial]);
+        assert!(atlas.views_for_region("小腦").contains(&BrainView::Cerebellum));
+        let medial = &atlas.view(BrainView::Medial).unwrap().layout;
+        let axial = &atlas.view(BrainView::Axial).unwrap().layout;
+        assert_ne!(medial.find_region("海馬體").unwrap().center, axial.find_region("海馬體").unwrap().center);
+
+        // 映射表中的每個腦區至少出現在一個視角
+        let maps = crate::mapping::load_behavior_brain_map(format!("{}/../data/behavior_brain_map.json", assets)).unwrap();
+        for map in &maps {
+            for region in &map.brain_regions {
+                assert!(!atlas.views_for_region(&region.region).is_empty(), "{} 未出現在任何視角", region.region);
+            }
+        }
+    }
+
+    #[test]
+    fn test_view_place_hides_unmatched() {
+        let impacts = vec![impact("前額葉", 0.5), impact("杏仁核", 0.9)];
+        let overview = AtlasView::from_svg(BrainView::Overview, SVG.to_string());
+        let lateral = AtlasView::from_svg(BrainView::Lateral, SVG.to_string());
+
+        assert_eq!(overview.place(&impacts).len(), 2);
+        let placed = lateral.place(&impacts);
+        assert_eq!(placed.len(), 1);
+        assert_eq!(placed[0].map_region_id.as_deref(), Some("prefrontal"));
+        assert_eq!(BrainView::from_id("Axial"), Some(BrainView::Axial));
+        assert_eq!(BrainView::from_id("coronal"), None);
+    }
+}
@@ -0,0 +1,248 @@
+//! 兩筆計算結果的比較（同一受試者的兩次測量，或兩位受試者）
+//!
+//! 逐腦區計算影響分數的差值（比較 − 基準），並以可靠改變指數
+//! （Reliable Change Index，Jacobson & Truax, 1991）判斷行為表現的改變是否超出測量誤差。
+//! RCI 以兩次測量的 z 分數（`normalized_input`）計算：
+//!
+//! ```text
+//! RCI = (z₂ − z₁) / √(2 (1 − r))
+//! ```
+//!
+//! √(2(1 − r)) 是 z 分數差值的標準誤，只適用於有正負號的 z₂ − z₁；影響分數取 |z|，
+//! 由 −1.5 變為 +1.5 時影響分數不變，行為表現卻改變了 3 個標準差，因此影響分數的差值只用於顯示與差異圖。
+//!
+//! `r` 為常模的再測信度（`normalization_params.reliability`，未提供時為 [`DEFAULT_RELIABILITY`]），
+//! |RCI| ≥ [`RELIABLE_CHANGE_THRESHOLD`] 視為可靠改變。只有同一行為且有常模的兩筆結果可計算 RCI。
+
+use crate::i18n::tr;
+use crate::model::{CalculationResult, NormalizationParams};
+
+/// 常模未提供再測信度時使用的預設值
+pub const DEFAULT_RELIABILITY: f32 = 0.8;
+/// 可靠改變的門檻（雙尾 p < .05）
+pub const RELIABLE_CHANGE_THRESHOLD: f32 = 1.96;
+/// 信度上限，避免 r = 1 時分母為零
+const MAX_RELIABILITY: f32 = 0.99;
+
+/// 腦區變化的判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeFlag {
+    /// 行為表現（z 分數）可靠地上升
+    Increase,
+    /// 行為表現（z 分數）可靠地下降
+    Decrease,
+    /// 差異在測量誤差範圍內
+    Stable,
+    /// 不同行為、沒有常模或腦區只出現在一邊，無法計算 RCI
+    NotComparable,
+}
+
+impl ChangeFlag {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ChangeFlag::Increase => "▲",
+            ChangeFlag::Decrease => "▼",
+            ChangeFlag::Stable => "–",
+            ChangeFlag::NotComparable => "?",
+        }
+    }
+
+    /// 目前語言的判定說明
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeFlag::Increase => tr("compare.flag.increase"),
+            ChangeFlag::Decrease => tr("compare.flag.decrease"),
+            ChangeFlag::Stable => tr("compare.flag.stable"),
+            ChangeFlag::NotComparable => tr("compare.flag.not_comparable"),
+        }
+    }
+}
+
+/// 單一腦區的變化
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionChange {
+    pub region: String,
+    /// 基準結果的影響分數；腦區不在基準結果中時為 `None`
+    pub baseline: Option<f32>,
+    pub comparison: Option<f32>,
+    /// 影響分數的差值（比較 − 基準），缺少的一邊以 0 計
+    pub delta: f32,
+    /// 以 z 分數差值計算的可靠改變指數；正值為行為數值上升
+    pub rci: Option<f32>,
+    pub flag: ChangeFlag,
+}
+
+/// 兩筆結果的逐腦區比較
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultComparison {
+    pub same_behavior: bool,
+    /// 計算 RCI 使用的再測信度；無法計算時為 `None`
+    pub reliability: Option<f32>,
+    /// 信度是否為 [`DEFAULT_RELIABILITY`]（常模未提供）
+    pub reliability_assumed: bool,
+    /// 依基準結果的腦區順序，只出現在比較結果的腦區接在後面
+    pub regions: Vec<RegionChange>,
+    pub total_delta: f32,
+}
+
+impl ResultComparison {
+    /// `params` 為兩筆結果所屬行為的常模（兩筆為不同行為時不會使用）
+    pub fn new(baseline: &CalculationResult, comparison: &CalculationResult, params: Option<&NormalizationParams>) -> Self {
+        let same_behavior = baseline.behavior_input.behavior_type == comparison.behavior_input.behavior_type;
+        let reliability = params.filter(|_| same_behavior).map(|p| p.reliability.unwrap_or(DEFAULT_RELIABILITY));
+        let reliability_assumed = reliability.is_some() && params.is_some_and(|p| p.reliability.is_none());
+        let standard_error = reliability.map(|r| (2.0 * (1.0 - r.clamp(0.0, MAX_RELIABILITY))).sqrt());
+
+        let mut names: Vec<&str> = Vec::new();
+        for impact in baseline.impact_scores.iter().chain(&comparison.impact_scores) {
+            if !names.contains(&impact.region.as_str()) {
+                names.push(&impact.region);
+            }
+        }
+
+        let regions = names
+            .into_iter()
+            .map(|region| {
+                let before = baseline.region_score(region);
+                let after = comparison.region_score(region);
+                let rci = match (before, after, standard_error) {
+                    (Some(before), Some(after), Some(se)) => Some((after.normalized_input - before.normalized_input) / se),
+                    _ => None,
+                };
+                let flag = match rci {
+                    Some(rci) if rci >= RELIABLE_CHANGE_THRESHOLD => ChangeFlag::Increase,
+                    Some(rci) if rci <= -RELIABLE_CHANGE_THRESHOLD => ChangeFlag::Decrease,
+                    Some(_) => ChangeFlag::Stable,
+                    None => ChangeFlag::NotComparable,
+                };
+                let baseline = before.map(|s| s.impact_score);
+                let comparison = after.map(|s| s.impact_score);
+                RegionChange {
+                    region: region.to_string(),
+                    baseline,
+                    comparison,
+                    delta: comparison.unwrap_or(0.0) - baseline.unwrap_or(0.0),
+                    rci,
+                    flag,
+                }
+            })
+            .collect();
+
+        Self {
+            same_behavior,
+            reliability,
+            reliability_assumed,
+            regions,
+            total_delta: comparison.total_impact - baseline.total_impact,
+        }
+    }
+
+    /// 最大的差值絕對值，用於差異圖的色階
+    pub fn max_abs_delta(&self) -> f32 {
+        self.regions.iter().map(|change| change.delta.abs()).fold(0.0, f32::max)
+    }
+
+    pub fn reliable_changes(&self) -> usize {
+        self.regions
+            .iter()
+            .filter(|change| matches!(change.flag, ChangeFlag::Increase | ChangeFlag::Decrease))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BehaviorInput, RegionImpactScore, RiskLevel};
+
+    fn sample(behavior: &str, z: f32, regions: &[(&str, f32)]) -> CalculationResult {
+        let impact_scores: Vec<RegionImpactScore> = regions
+            .iter()
+            .map(|&(region, weight)| RegionImpactScore {
+                region: region.to_string(),
+                impact_score: weight * z.abs(),
+                normalized_input: z,
+                weight,
+            })
+            .collect();
+        CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: behavior.to_string(),
+                value: 0.0,
+                unit: "次".to_string(),
+                timestamp: chrono::Utc::now(),
+            },
+            total_impact: impact_scores.iter().map(|s| s.impact_score).sum(),
+            impact_scores,
+            risk_level: RiskLevel::Low,
+        }
+    }
+
+    fn params(reliability: Option<f32>) -> NormalizationParams {
+        NormalizationParams {
+            mean: 50.0,
+            std_dev: 10.0,
+            sample_size: None,
+            source: None,
+            reliability,
+        }
+    }
+
+    #[test]
+    fn test_reliable_change() {
+        let before = sample("注意力", 0.5, &[("前額葉", 0.8), ("頂葉", 0.5)]);
+        let after = sample("注意力", -1.5, &[("前額葉", 0.8), ("頂葉", 0.5)]);
+
+        // r = 0.9：SE = √0.2 ≈ 0.447，RCI = (−1.5 − 0.5) / 0.447 ≈ −4.47；影響分數卻上升 0.8
+        let comparison = ResultComparison::new(&before, &after, Some(&params(Some(0.9))));
+        assert!(comparison.same_behavior && !comparison.reliability_assumed);
+        assert_eq!(comparison.regions.len(), 2);
+        let prefrontal = &comparison.regions[0];
+        assert!((prefrontal.delta - 0.8).abs() < 1e-6);
+        assert!((prefrontal.rci.unwrap() + 4.472).abs() < 1e-3);
+        assert_eq!(prefrontal.flag, ChangeFlag::Decrease);
+        assert_eq!(comparison.reliable_changes(), 2);
+        assert!((comparison.max_abs_delta() - 0.8).abs() < 1e-6);
+        assert!((comparison.total_delta - 1.3).abs() < 1e-5);
+
+        // 反向比較為可靠上升
+        let comparison = ResultComparison::new(&after, &before, Some(&params(Some(0.9))));
+        assert_eq!(comparison.regions[1].flag, ChangeFlag::Increase);
+        assert!(comparison.regions[1].delta < 0.0);
+
+        // −1.5 → +1.5：影響分數不變，但改變 3 個標準差，RCI = 3 / 0.447 ≈ 6.71
+        let mirrored = sample("注意力", 1.5, &[("前額葉", 0.8), ("頂葉", 0.5)]);
+        let comparison = ResultComparison::new(&after, &mirrored, Some(&params(Some(0.9))));
+        assert!(comparison.regions[0].delta.abs() < 1e-6);
+        assert!((comparison.regions[0].rci.unwrap() - 6.708).abs() < 1e-3);
+        assert_eq!(comparison.regions[0].flag, ChangeFlag::Increase);
+
+        // 預設信度 0.8：SE = √0.4 ≈ 0.632，0.5 → 1.0 的 RCI ≈ 0.79，未達門檻
+        let slight = sample("注意力", 1.0, &[("前額葉", 0.8), ("頂葉", 0.5)]);
+        let comparison = ResultComparison::new(&before, &slight, Some(&params(None)));
+        assert!(comparison.reliability_assumed);
+        assert_eq!(comparison.reliability, Some(DEFAULT_RELIABILITY));
+        assert!((comparison.regions[0].rci.unwrap() - 0.791).abs() < 1e-3);
+        assert_eq!(comparison.regions[0].flag, ChangeFlag::Stable);
+    }
+
+    #[test]
+    fn test_not_comparable() {
+        let before = sample("注意力", 1.0, &[("前額葉", 0.8)]);
+        let other = sample("步行", 2.0, &[("小腦", 0.7), ("前額葉", 0.2)]);
+        let comparison = ResultComparison::new(&before, &other, Some(&params(Some(0.9))));
+
+        assert!(!comparison.same_behavior);
+        assert_eq!(comparison.reliability, None);
+        let regions: Vec<&str> = comparison.regions.iter().map(|c| c.region.as_str()).collect();
+        assert_eq!(regions, vec!["前額葉", "小腦"]);
+        assert!(comparison.regions.iter().all(|c| c.flag == ChangeFlag::NotComparable && c.rci.is_none()));
+        assert_eq!(comparison.regions[1].baseline, None);
+        assert!((comparison.regions[1].delta - 1.4).abs() < 1e-6);
+
+        // 同一行為但沒有常模
+        let after = sample("注意力", 3.0, &[("前額葉", 0.8)]);
+        let comparison = ResultComparison::new(&before, &after, None);
+        assert_eq!(comparison.regions[0].flag, ChangeFlag::NotComparable);
+    }
+}
@@ -0,0 +1,750 @@
+use crate::error::{read_json, BrainVizError, Result};
+use crate::i18n::Language;
+use crate::stream::StreamSource;
+use crate::trials::Task;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 應用程式名稱（用於設定檔目錄）
+pub const APP_DIR_NAME: &str = "behavior_brain_viz";
+
+/// 設定檔檔名
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// 預設的行為-腦區映射檔名
+pub const DEFAULT_MAPPING_FILE: &str = "behavior_brain_map.json";
+
+/// 預設的風險對照檔名
+pub const DEFAULT_RISK_FILE: &str = "risk_map.json";
+
+/// 預設的建議規則檔名
+pub const DEFAULT_RECOMMENDATION_FILE: &str = "recommendation_rules.json";
+
+/// 額外映射資料集所在的子目錄
+pub const MAPPINGS_SUBDIR: &str = "mappings";
+
+/// 標準圖譜標籤表所在的子目錄
+pub const ATLASES_SUBDIR: &str = "atlases";
+
+/// 預設的腦區-圖譜標籤對照檔名
+pub const DEFAULT_ATLAS_REGION_MAP: &str = "atlas_region_map.json";
+
+/// 預設的腦區網路（3D 玻璃腦）檔名
+pub const DEFAULT_BRAIN_NETWORK: &str = "brain_network.json";
+
+/// 預設的作業指標-行為對應檔名
+pub const DEFAULT_TASK_METRICS: &str = "task_metrics.json";
+
+/// 預設的多視角腦圖目錄（位於 assets 下）
+pub const VIEWS_SUBDIR: &str = "views";
+
+/// 環境變數名稱
+pub const ENV_CONFIG: &str = "BBV_CONFIG";
+pub const ENV_DATA_DIR: &str = "BBV_DATA_DIR";
+pub const ENV_MAPPING: &str = "BBV_MAPPING";
+pub const ENV_RISK_MAP: &str = "BBV_RISK_MAP";
+pub const ENV_RECOMMENDATIONS: &str = "BBV_RECOMMENDATIONS";
+pub const ENV_ATLAS: &str = "BBV_ATLAS";
+pub const ENV_VIEWS: &str = "BBV_VIEWS";
+pub const ENV_ATLAS_TABLES: &str = "BBV_ATLAS_TABLES";
+pub const ENV_OUTPUT_DIR: &str = "BBV_OUTPUT_DIR";
+pub const ENV_LANG: &str = "BBV_LANG";
+pub const ENV_FONT: &str = "BBV_FONT";
+pub const ENV_STREAM: &str = "BBV_STREAM";
+
+/// 設定檔內容（所有欄位皆為選填）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigFile {
+    pub data_dir: Option<PathBuf>,
+    pub mapping_files: Vec<PathBuf>,
+    pub risk_map: Option<PathBuf>,
+    pub recommendation_rules: Option<PathBuf>,
+    pub atlas_svg: Option<PathBuf>,
+    /// 各視角腦圖 SVG 所在目錄（lateral.svg、medial.svg 等）
+    pub atlas_views: Option<PathBuf>,
+    /// 標準圖譜標籤表（AAL、Harvard-Oxford、Desikan-Killiany、Schaefer）
+    pub atlas_tables: Vec<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    /// 介面與報告語言（"zh-TW"、"zh-CN"、"en"）
+    pub language: Option<Language>,
+    /// 中文字型檔（TTF/TTC/OTF），未指定時自動尋找系統字型
+    pub font: Option<PathBuf>,
+    /// 啟動介面時開始接收的串流來源（例如 "tcp://127.0.0.1:8751"）
+    pub stream: Option<String>,
+}
+
+/// 執行模式
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Command {
+    /// 啟動圖形介面
+    #[default]
+    Gui,
+    /// 啟動本機 HTTP/JSON 計分服務
+    Serve,
+    /// 不開啟介面，直接輸出腦圖圖檔
+    Render,
+    /// 由批次匯入的結果計算族群統計與兩組比較
+    Group,
+    /// 由試次層級資料推導行為指標並計分
+    Trials,
+}
+
+/// 命令列參數
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliArgs {
+    pub command: Command,
+    pub addr: Option<String>,
+    pub config: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub mapping_files: Vec<PathBuf>,
+    pub risk_map: Option<PathBuf>,
+    pub recommendation_rules: Option<PathBuf>,
+    pub atlas_svg: Option<PathBuf>,
+    pub atlas_views: Option<PathBuf>,
+    pub atlas_tables: Vec<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub language: Option<Language>,
+    pub font: Option<PathBuf>,
+    pub stream: Option<String>,
+    pub show_help: bool,
+    // render 模式參數
+    pub behavior: Option<String>,
+    pub value: Option<f32>,
+    pub unit: Option<String>,
+    pub out: Option<PathBuf>,
+    pub width: Option<u32>,
+    // group 模式參數
+    pub inputs: Vec<PathBuf>,
+    pub groups: Option<PathBuf>,
+    pub compare: Option<[String; 2]>,
+    // trials 模式參數
+    pub task: Option<Task>,
+    pub columns: Vec<String>,
+    pub sample_rate: Option<f64>,
+    pub distance: Option<f64>,
+    pub height: Option<f64>,
+}
+
+impl CliArgs {
+    /// 解析命令列參數（不含程式名稱）
+    pub fn parse<I, S>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut cli = CliArgs::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            // 同時支援 `--flag value` 與 `--flag=value`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            if flag == "-h" || flag == "--help" {
+                cli.show_help = true;
+                continue;
+            }
+            if flag == "serve" {
+                cli.command = Command::Serve;
+                continue;
+            }
+            if flag == "render" {
+                cli.command = Command::Render;
+                continue;
+            }
+            if flag == "group" {
+                cli.command = Command::Group;
+                continue;
+            }
+            if flag == "trials" {
+                cli.command = Command::Trials;
+                continue;
+            }
+
+            let mut value = || -> Result<PathBuf> {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .map(PathBuf::from)
+                    .ok_or_else(|| BrainVizError::Config(format!("參數 {} 需要一個值", flag)))
+            };
+
+            match flag.as_str() {
+                "-c" | "--config" => cli.config = Some(value()?),
+                "-d" | "--data-dir" => cli.data_dir = Some(value()?),
+                "-m" | "--mapping" => cli.mapping_files.push(value()?),
+                "-r" | "--risk-map" => cli.risk_map = Some(value()?),
+                "--recommendations" => cli.recommendation_rules = Some(value()?),
+                "-a" | "--atlas" => cli.atlas_svg = Some(value()?),
+                "--views" => cli.atlas_views = Some(value()?),
+                "--atlas-table" => cli.atlas_tables.push(value()?),
+                "-o" | "--output-dir" => cli.output_dir = Some(value()?),
+                "--font" => cli.font = Some(value()?),
+                "--lang" => cli.language = Some(parse_language(&value()?)?),
+                "--stream" => cli.stream = Some(parse_stream(&value()?)?),
+                "--addr" => cli.addr = Some(value()?.to_string_lossy().into_owned()),
+                "--behavior" => cli.behavior = Some(value()?.to_string_lossy().into_owned()),
+                "--unit" => cli.unit = Some(value()?.to_string_lossy().into_owned()),
+                "--out" => cli.out = Some(value()?),
+                "--value" => cli.value = Some(parse_number(&flag, &value()?)?),
+                "--width" => cli.width = Some(parse_number(&flag, &value()?)?),
+                "--input" => cli.inputs.push(value()?),
+                "--groups" => cli.groups = Some(value()?),
+                "--compare" => cli.compare = Some(parse_group_pair(&value()?)?),
+                "--task" => cli.task = Some(parse_task(&value()?)?),
+                "--column" => cli.columns.push(value()?.to_string_lossy().into_owned()),
+                "--sample-rate" => cli.sample_rate = Some(parse_number(&flag, &value()?)?),
+                "--distance" => cli.distance = Some(parse_number(&flag, &value()?)?),
+                "--height" => cli.height = Some(parse_number(&flag, &value()?)?),
+                _ => return Err(BrainVizError::Config(format!("未知的參數: {}", arg))),
+            }
+        }
+
+        Ok(cli)
+    }
+
+    /// 命令列說明文字
+    pub fn usage() -> &'static str {
+        "用法: behavior_brain_viz [serve | render | group | trials] [選項]\n\
+         \n\
+         命令:\n\
+         \x20 serve                     啟動本機 HTTP/JSON 計分服務（不開啟圖形介面）\n\
+         \x20 render                    計算單筆數值並輸出腦圖 SVG/PNG（不開啟圖形介面）\n\
+         \x20 group                     由匯出的結果計算族群統計、兩組比較與群體平均腦圖\n\
+         \x20 trials                    由 PsychoPy/E-Prime 試次資料推導行為指標、計分並輸出長格式 CSV\n\
+         \n\
+         選項:\n\
+         \x20 -c, --config <檔案>       設定檔路徑 (環境變數 BBV_CONFIG)\n\
+         \x20 -d, --data-dir <目錄>     資料目錄 (環境變數 BBV_DATA_DIR)\n\
+         \x20 -m, --mapping <檔案>      行為-腦區映射檔，可重複指定 (環境變數 BBV_MAPPING)\n\
+         \x20 -r, --risk-map <檔案>     風險對照檔 (環境變數 BBV_RISK_MAP)\n\
+         \x20     --recommendations <檔案> 建議規則檔 (環境變數 BBV_RECOMMENDATIONS)\n\
+         \x20 -a, --atlas <檔案>        腦圖 SVG (環境變數 BBV_ATLAS)\n\
+         \x20     --views <目錄>        多視角腦圖目錄 (環境變數 BBV_VIEWS)\n\
+         \x20     --atlas-table <檔案>  標準圖譜標籤表，可重複指定 (環境變數 BBV_ATLAS_TABLES)\n\
+         \x20 -o, --output-dir <目錄>   輸出目錄 (環境變數 BBV_OUTPUT_DIR)\n\
+         \x20     --font <檔案>         中文字型檔 (環境變數 BBV_FONT)\n\
+         \x20     --lang <語言>         介面與報告語言：zh-TW、zh-CN、en (環境變數 BBV_LANG)\n\
+         \x20     --stream <來源>       介面啟動時接收串流：tcp://位址、udp://位址 或 pipe:路徑 (環境變數 BBV_STREAM)\n\
+         \x20     --addr <位址>         serve 模式的監聽位址 (預設 127.0.0.1:8750)\n\
+         \x20     --behavior <行為>     render 模式的行為類型\n\
+         \x20     --value <數值>        render 模式的行為數值\n\
+         \x20     --unit <單位>         render 模式的單位（預設為映射表中的單位）\n\
+         \x20     --out <檔案>          render 模式的輸出檔，副檔名 .svg 或 .png\n\
+         \x20     --width <像素>        PNG 輸出寬度 (預設 1600)\n\
+         \x20     --input <檔案|目錄>   group 模式的 JSON/長格式 CSV 匯出檔或目錄；trials 模式的試次檔。可重複指定\n\
+         \x20     --groups <檔案>       group 模式的組別對照 CSV（subject_id、group 欄位）\n\
+         \x20     --compare <組1,組2>   group 模式要比較的兩組（只有兩組時可省略）\n\
+         \x20     --task <作業>         trials 模式的作業：flanker、nback、tapping、igt、gait\n\
+         \x20     --column <角色=欄位>  trials 模式指定欄位，例如 rt=Stimulus.RT，可重複指定\n\
+         \x20     --sample-rate <Hz>    gait 作業的取樣率（加速度檔沒有時間欄位時）\n\
+         \x20     --distance <公尺>     gait 作業紀錄期間走的距離，用於計算步長\n\
+         \x20     --height <公尺>       gait 作業的受試者身高，沒有距離時用於推估步長\n\
+         \x20 -h, --help                顯示此說明\n"
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &Path) -> Result<T> {
+    let text = value.to_string_lossy();
+    text.parse()
+        .map_err(|_| BrainVizError::Config(format!("參數 {} 的值「{}」不是有效的數字", flag, text)))
+}
+
+fn parse_stream(value: &Path) -> Result<String> {
+    let text = value.to_string_lossy();
+    StreamSource::parse(&text)?;
+    Ok(text.trim().to_string())
+}
+
+fn parse_task(value: &Path) -> Result<Task> {
+    let text = value.to_string_lossy();
+    Task::parse(&text)
+        .ok_or_else(|| BrainVizError::Config(format!("不支援的作業「{}」，可用值為 flanker、nback、tapping、igt、gait", text)))
+}
+
+fn parse_group_pair(value: &Path) -> Result<[String; 2]> {
+    let text = value.to_string_lossy();
+    match text.split(',').map(str::trim).collect::<Vec<_>>().as_slice() {
+        [first, second] if !first.is_empty() && !second.is_empty() && first != second => {
+            Ok([first.to_string(), second.to_string()])
+        }
+        _ => Err(BrainVizError::Config(format!("參數 --compare 需要兩個不同的組別，例如 patient,control（收到「{}」）", text))),
+    }
+}
+
+fn parse_language(value: &Path) -> Result<Language> {
+    let text = value.to_string_lossy();
+    Language::from_code(&text)
+        .ok_or_else(|| BrainVizError::Config(format!("不支援的語言「{}」，可用值為 zh-TW、zh-CN、en", text)))
+}
+
+/// 已解析的應用程式設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppConfig {
+    pub data_dir: PathBuf,
+    pub mapping_files: Vec<PathBuf>,
+    pub risk_map: PathBuf,
+    pub recommendation_rules: PathBuf,
+    pub atlas_svg: PathBuf,
+    pub atlas_views: PathBuf,
+    pub atlas_tables: Vec<PathBuf>,
+    /// 腦區-圖譜標籤對照檔，固定為資料目錄下的 `atlas_region_map.json`
+    pub atlas_region_map: PathBuf,
+    /// 3D 玻璃腦的腦區質心與網路連線，固定為資料目錄下的 `brain_network.json`
+    pub brain_network: PathBuf,
+    /// 作業指標-行為對應檔，固定為資料目錄下的 `task_metrics.json`
+    pub task_metrics: PathBuf,
+    pub output_dir: PathBuf,
+    pub language: Language,
+    /// 指定的中文字型檔；None 時自動尋找
+    pub font: Option<PathBuf>,
+    /// 啟動時開始接收的串流來源 URI，見 [`StreamSource::parse`]
+    pub stream: Option<String>,
+}
+
+impl AppConfig {
+    /// 依優先順序合併設定：命令列 > 環境變數 > 設定檔 > 預設值
+    pub fn resolve(cli: &CliArgs) -> Result<Self> {
+        let explicit = cli.config.clone().or_else(|| env_path(ENV_CONFIG));
+        let file = read_config_file(explicit, default_config_path())?;
+
+        Ok(Self::merge(cli, &EnvOverrides::from_env(), &file, default_base_dir()))
+    }
+
+    /// 合併各來源的設定
+    fn merge(cli: &CliArgs, env: &EnvOverrides, file: &ConfigFile, base_dir: PathBuf) -> Self {
+        let data_dir = cli
+            .data_dir
+            .clone()
+            .or_else(|| env.data_dir.clone())
+            .or_else(|| file.data_dir.clone())
+            .unwrap_or_else(|| base_dir.join("data"));
+
+        let mapping_files = if !cli.mapping_files.is_empty() {
+            cli.mapping_files.clone()
+        } else if !env.mapping_files.is_empty() {
+            env.mapping_files.clone()
+        } else if !file.mapping_files.is_empty() {
+            file.mapping_files.clone()
+        } else {
+            discover_mapping_files(&data_dir)
+        };
+
+        let risk_map = cli
+            .risk_map
+            .clone()
+            .or_else(|| env.risk_map.clone())
+            .or_else(|| file.risk_map.clone())
+            .unwrap_or_else(|| data_dir.join(DEFAULT_RISK_FILE));
+
+        let recommendation_rules = cli
+            .recommendation_rules
+            .clone()
+            .or_else(|| env.recommendation_rules.clone())
+            .or_else(|| file.recommendation_rules.clone())
+            .unwrap_or_else(|| data_dir.join(DEFAULT_RECOMMENDATION_FILE));
+
+        let atlas_svg = cli
+            .atlas_svg
+            .clone()
+            .or_else(|| env.atlas_svg.clone())
+            .or_else(|| file.atlas_svg.clone())
+            .unwrap_or_else(|| base_dir.join("assets").join("brain.svg"));
+
+        let atlas_views = cli
+            .atlas_views
+            .clone()
+            .or_else(|| env.atlas_views.clone())
+            .or_else(|| file.atlas_views.clone())
+            .unwrap_or_else(|| base_dir.join("assets").join(VIEWS_SUBDIR));
+
+        let atlas_tables = if !cli.atlas_tables.is_empty() {
+            cli.atlas_tables.clone()
+        } else if !env.atlas_tables.is_empty() {
+            env.atlas_tables.clone()
+        } else if !file.atlas_tables.is_empty() {
+            file.atlas_tables.clone()
+        } else {
+            discover_atlas_tables(&data_dir)
+        };
+        let atlas_region_map = data_dir.join(DEFAULT_ATLAS_REGION_MAP);
+        let brain_network = data_dir.join(DEFAULT_BRAIN_NETWORK);
+        let task_metrics = data_dir.join(DEFAULT_TASK_METRICS);
+
+        let output_dir = cli
+            .output_dir
+            .clone()
+            .or_else(|| env.output_dir.clone())
+            .or_else(|| file.output_dir.clone())
+            .unwrap_or_else(|| base_dir.join("output"));
+
+        let language = cli.language.or(env.language).or(file.language).unwrap_or_default();
+        let font = cli
+            .font
+            .clone()
+            .or_else(|| env.font.clone())
+            .or_else(|| file.font.clone());
+        let stream = cli
+            .stream
+            .clone()
+            .or_else(|| env.stream.clone())
+            .or_else(|| file.stream.clone());
+
+        Self {
+            data_dir,
+            mapping_files,
+            risk_map,
+            recommendation_rules,
+            atlas_svg,
+            atlas_views,
+            atlas_tables,
+            atlas_region_map,
+            brain_network,
+            task_metrics,
+            output_dir,
+            language,
+            font,
+            stream,
+        }
+    }
+
+    /// 預設映射資料集（第一個）
+    pub fn primary_mapping(&self) -> Option<&Path> {
+        self.mapping_files.first().map(PathBuf::as_path)
+    }
+}
+
+/// 環境變數覆寫值
+#[derive(Debug, Clone, Default)]
+struct EnvOverrides {
+    data_dir: Option<PathBuf>,
+    mapping_files: Vec<PathBuf>,
+    risk_map: Option<PathBuf>,
+    recommendation_rules: Option<PathBuf>,
+    atlas_svg: Option<PathBuf>,
+    atlas_views: Option<PathBuf>,
+    atlas_tables: Vec<PathBuf>,
+    output_dir: Option<PathBuf>,
+    language: Option<Language>,
+    font: Option<PathBuf>,
+    stream: Option<String>,
+}
+
+impl EnvOverrides {
+    fn from_env() -> Self {
+        Self {
+            data_dir: env_path(ENV_DATA_DIR),
+            // BBV_MAPPING 可用系統路徑分隔符號指定多個檔案
+            mapping_files: std::env::var_os(ENV_MAPPING)
+                .map(|value| std::env::split_paths(&value).collect())
+                .unwrap_or_default(),
+            risk_map: env_path(ENV_RISK_MAP),
+            recommendation_rules: env_path(ENV_RECOMMENDATIONS),
+            atlas_svg: env_path(ENV_ATLAS),
+            atlas_views: env_path(ENV_VIEWS),
+            atlas_tables: std::env::var_os(ENV_ATLAS_TABLES)
+                .map(|value| std::env::split_paths(&value).collect())
+                .unwrap_or_default(),
+            output_dir: env_path(ENV_OUTPUT_DIR),
+            // 無法辨識的語言代碼視同未設定
+            language: std::env::var(ENV_LANG).ok().and_then(|code| Language::from_code(&code)),
+            font: env_path(ENV_FONT),
+            stream: std::env::var(ENV_STREAM).ok().filter(|value| !value.trim().is_empty()),
+        }
+    }
+}
+
+fn env_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 從 JSON 文件載入設定檔
+pub fn load_config_file(path: &Path) -> Result<ConfigFile> {
+    read_json(path)
+}
+
+/// 讀取設定檔：明確指定（`--config` 或 `BBV_CONFIG`）的檔案必須存在，
+/// 只有平台預設位置的設定檔可以省略
+fn read_config_file(explicit: Option<PathBuf>, implicit: Option<PathBuf>) -> Result<ConfigFile> {
+    match (explicit, implicit) {
+        (Some(path), _) => load_config_file(&path),
+        (None, Some(path)) if path.is_file() => load_config_file(&path),
+        _ => Ok(ConfigFile::default()),
+    }
+}
+
+/// 平台設定目錄（Windows: %APPDATA%，macOS: ~/Library/Application Support，其他: $XDG_CONFIG_HOME 或 ~/.config）
+pub fn platform_config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        env_path("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_path("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_path("XDG_CONFIG_HOME").or_else(|| env_path("HOME").map(|home| home.join(".config")))
+    }
+}
+
+/// 預設設定檔路徑
+pub fn default_config_path() -> Option<PathBuf> {
+    platform_config_dir().map(|dir| dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+/// 尋找包含 data/ 的基準目錄：先找目前目錄，再找執行檔所在目錄及其上層
+fn default_base_dir() -> PathBuf {
+    let mut candidates = Vec::new();
+    if let Ok(cwd) = std::env::current_dir() {
+        candidates.push(cwd);
+    }
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        // 執行檔位於 target/{debug,release}/ 時，往上找專案根目錄
+        candidates.extend(exe_dir.ancestors().take(3).map(Path::to_path_buf));
+    }
+
+    candidates
+        .iter()
+        .find(|dir| dir.join("data").join(DEFAULT_MAPPING_FILE).is_file())
+        .cloned()
+        .or_else(|| candidates.into_iter().next())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// 列出資料目錄中的映射資料集：預設映射檔加上 mappings/ 子目錄內的所有 JSON
+pub fn discover_mapping_files(data_dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![data_dir.join(DEFAULT_MAPPING_FILE)];
+
+    if let Ok(entries) = std::fs::read_dir(data_dir.join(MAPPINGS_SUBDIR)) {
+        let mut extra: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        extra.sort();
+        files.extend(extra);
+    }
+
+    files
+}
+
+/// 列出 `data_dir/atlases/` 中的圖譜標籤表（`.txt`、`.xml`、`.csv`、`.tsv`），依檔名排序
+///
+/// `*.centroids.csv` 是標籤表的質心座標，會隨對應的標籤表載入，不另列為圖譜。
+pub fn discover_atlas_tables(data_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(data_dir.join(ATLASES_SUBDIR)) else {
+        return Vec::new();
+    };
+    let mut tables: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+            ["txt", "xml", "csv", "tsv"]
+                .iter()
+                .any(|ext| path.extension().is_some_and(|e| e.eq_ignore_ascii_case(ext)))
+                && !name.ends_with(crate::atlas::CENTROIDS_SUFFIX)
+        })
+        .collect();
+    tables.sort();
+    tables
+}
+
+/// 資料集顯示名稱（檔名去除副檔名）
+pub fn dataset_label(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_parse_flags() {
+        let cli = CliArgs::parse([
+            "--data-dir", "/tmp/data",
+            "-m", "a.json",
+            "--mapping=b.json",
+            "--atlas", "brain.svg",
+            "--views=atlas/views",
+            "--atlas-table", "AAL.txt",
+            "--lang=en-US",
+            "--font", "fonts/NotoSansTC-Regular.ttf",
+            "--stream=tcp://127.0.0.1:8751",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.data_dir, Some(PathBuf::from("/tmp/data")));
+        assert_eq!(cli.mapping_files, vec![PathBuf::from("a.json"), PathBuf::from("b.json")]);
+        assert_eq!(cli.atlas_svg, Some(PathBuf::from("brain.svg")));
+        assert_eq!(cli.atlas_views, Some(PathBuf::from("atlas/views")));
+        assert_eq!(cli.atlas_tables, vec![PathBuf::from("AAL.txt")]);
+        assert_eq!(cli.language, Some(Language::En));
+        assert_eq!(cli.font, Some(PathBuf::from("fonts/NotoSansTC-Regular.ttf")));
+        assert_eq!(cli.stream.as_deref(), Some("tcp://127.0.0.1:8751"));
+        assert!(!cli.show_help);
+    }
+
+    #[test]
+    fn test_cli_parse_serve() {
+        let cli = CliArgs::parse(["serve", "--addr", "0.0.0.0:9000"]).unwrap();
+        assert_eq!(cli.command, Command::Serve);
+        assert_eq!(cli.addr.as_deref(), Some("0.0.0.0:9000"));
+        assert_eq!(CliArgs::parse(Vec::<String>::new()).unwrap().command, Command::Gui);
+    }
+
+    #[test]
+    fn test_cli_parse_render() {
+        let cli = CliArgs::parse(["render", "--behavior", "自然步行", "--value=0.8", "--out", "map.png", "--width", "800"])
+            .unwrap();
+        assert_eq!(cli.command, Command::Render);
+        assert_eq!(cli.behavior.as_deref(), Some("自然步行"));
+        assert_eq!(cli.value, Some(0.8));
+        assert_eq!(cli.out, Some(PathBuf::from("map.png")));
+        assert_eq!(cli.width, Some(800));
+        assert!(CliArgs::parse(["render", "--value", "abc"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_group() {
+        let cli = CliArgs::parse(["group", "--input", "exports", "--input=batch.csv", "--groups", "groups.csv", "--compare", "patient, control"])
+            .unwrap();
+        assert_eq!(cli.command, Command::Group);
+        assert_eq!(cli.inputs, vec![PathBuf::from("exports"), PathBuf::from("batch.csv")]);
+        assert_eq!(cli.groups, Some(PathBuf::from("groups.csv")));
+        assert_eq!(cli.compare, Some(["patient".to_string(), "control".to_string()]));
+        assert!(CliArgs::parse(["group", "--compare", "patient"]).is_err());
+        assert!(CliArgs::parse(["group", "--compare", "a,a"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_trials() {
+        let cli = CliArgs::parse(["trials", "--task", "N-back", "--input", "nback.txt", "--column", "target=IsTarget", "--column=rt=Stimulus.RT"])
+            .unwrap();
+        assert_eq!(cli.command, Command::Trials);
+        assert_eq!(cli.task, Some(Task::NBack));
+        assert_eq!(cli.inputs, vec![PathBuf::from("nback.txt")]);
+        assert_eq!(cli.columns, vec!["target=IsTarget".to_string(), "rt=Stimulus.RT".to_string()]);
+        assert!(CliArgs::parse(["trials", "--task", "stroop"]).is_err());
+
+        let cli = CliArgs::parse(["trials", "--task", "gait", "--input", "walk.csv", "--distance", "10", "--height=1.72", "--sample-rate", "50"])
+            .unwrap();
+        assert_eq!(cli.task, Some(Task::Gait));
+        assert_eq!((cli.distance, cli.height, cli.sample_rate), (Some(10.0), Some(1.72), Some(50.0)));
+        assert!(CliArgs::parse(["trials", "--distance", "ten"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_errors() {
+        assert!(CliArgs::parse(["--unknown"]).is_err());
+        assert!(CliArgs::parse(["--data-dir"]).is_err());
+        assert!(CliArgs::parse(["--lang", "fr"]).is_err());
+        assert!(CliArgs::parse(["--stream", "http://localhost"]).is_err());
+        assert!(CliArgs::parse(["--help"]).unwrap().show_help);
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let cli = CliArgs {
+            risk_map: Some(PathBuf::from("cli_risk.json")),
+            ..Default::default()
+        };
+        let env = EnvOverrides {
+            risk_map: Some(PathBuf::from("env_risk.json")),
+            stream: Some("udp://127.0.0.1:9000".to_string()),
+            language: Some(Language::En),
+            atlas_svg: Some(PathBuf::from("env_brain.svg")),
+            ..Default::default()
+        };
+        let file = ConfigFile {
+            data_dir: Some(PathBuf::from("/file/data")),
+            atlas_svg: Some(PathBuf::from("file_brain.svg")),
+            output_dir: Some(PathBuf::from("/file/out")),
+            language: Some(Language::ZhCn),
+            ..Default::default()
+        };
+
+        let config = AppConfig::merge(&cli, &env, &file, PathBuf::from("/base"));
+
+        assert_eq!(config.risk_map, PathBuf::from("cli_risk.json"));
+        assert_eq!(config.atlas_svg, PathBuf::from("env_brain.svg"));
+        assert_eq!(config.output_dir, PathBuf::from("/file/out"));
+        assert_eq!(config.data_dir, PathBuf::from("/file/data"));
+        assert_eq!(config.language, Language::En);
+        assert_eq!(config.stream.as_deref(), Some("udp://127.0.0.1:9000"));
+        assert_eq!(config.primary_mapping(), Some(Path::new("/file/data/behavior_brain_map.json")));
+    }
+
+    #[test]
+    fn test_merge_defaults() {
+        let config = AppConfig::merge(
+            &CliArgs::default(),
+            &EnvOverrides::default(),
+            &ConfigFile::default(),
+            PathBuf::from("/base"),
+        );
+
+        assert_eq!(config.data_dir, PathBuf::from("/base/data"));
+        assert_eq!(config.risk_map, PathBuf::from("/base/data/risk_map.json"));
+        assert_eq!(config.recommendation_rules, PathBuf::from("/base/data/recommendation_rules.json"));
+        assert_eq!(config.atlas_svg, PathBuf::from("/base/assets/brain.svg"));
+        assert_eq!(config.atlas_views, PathBuf::from("/base/assets/views"));
+        assert!(config.atlas_tables.is_empty());
+        assert_eq!(config.atlas_region_map, PathBuf::from("/base/data/atlas_region_map.json"));
+        assert_eq!(config.brain_network, PathBuf::from("/base/data/brain_network.json"));
+        assert_eq!(config.task_metrics, PathBuf::from("/base/data/task_metrics.json"));
+        assert_eq!(config.output_dir, PathBuf::from("/base/output"));
+        assert_eq!(config.language, Language::ZhTw);
+        assert!(config.font.is_none());
+        assert!(config.stream.is_none());
+    }
+
+    #[test]
+    fn test_config_file_deserialization() {
+        let json = r#"{ "data_dir": "/lab/data", "mapping_files": ["x.json", "y.json"] }"#;
+        let file: ConfigFile = serde_json::from_str(json).unwrap();
+
+        assert_eq!(file.data_dir, Some(PathBuf::from("/lab/data")));
+        assert_eq!(file.mapping_files.len(), 2);
+        assert!(file.risk_map.is_none());
+    }
+
+    #[test]
+    fn test_read_config_file() {
+        let dir = std::env::temp_dir().join("bbv_read_config_file");
+        std::fs::create_dir_all(&dir).unwrap();
+        let present = dir.join(CONFIG_FILE_NAME);
+        std::fs::write(&present, r#"{ "data_dir": "/lab/data" }"#).unwrap();
+        let missing = dir.join("missing.json");
+
+        // 明確指定的設定檔不存在時回報錯誤，而不是改用預設值
+        assert!(matches!(read_config_file(Some(missing.clone()), None), Err(BrainVizError::Io { .. })));
+        assert!(read_config_file(Some(missing.clone()), Some(present.clone())).is_err());
+        // 平台預設位置的設定檔可以不存在
+        assert!(read_config_file(None, Some(missing)).unwrap().data_dir.is_none());
+        assert_eq!(read_config_file(None, Some(present.clone())).unwrap().data_dir, Some(PathBuf::from("/lab/data")));
+        assert_eq!(read_config_file(Some(present), None).unwrap().data_dir, Some(PathBuf::from("/lab/data")));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_discover_atlas_tables() {
+        let data_dir = std::env::temp_dir().join("bbv_discover_atlases");
+        let atlases = data_dir.join(ATLASES_SUBDIR);
+        std::fs::create_dir_all(&atlases).unwrap();
+        for name in ["Schaefer.csv", "AAL.txt", "AAL.centroids.csv", "README.md"] {
+            std::fs::write(atlases.join(name), "").unwrap();
+        }
+
+        assert_eq!(discover_atlas_tables(&data_dir), vec![atlases.join("AAL.txt"), atlases.join("Schaefer.csv")]);
+        assert!(discover_atlas_tables(Path::new("/nonexistent")).is_empty());
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[test]
+    fn test_dataset_label() {
+        assert_eq!(dataset_label(Path::new("data/behavior_brain_map.json")), "behavior_brain_map");
+    }
+}
@@ -0,0 +1,129 @@
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 工具的統一錯誤型別
+#[derive(Debug)]
+pub enum BrainVizError {
+    /// 映射表中找不到此行為
+    UnknownBehavior(String),
+    /// 輸入單位與映射表要求的單位不符
+    UnitMismatch {
+        behavior: String,
+        expected: String,
+        found: String,
+    },
+    /// 正規化參數無效（例如標準差不為正數）
+    InvalidNormalization { behavior: String, reason: String },
+    /// 檔案讀寫錯誤
+    Io { path: PathBuf, source: std::io::Error },
+    /// 資料解析錯誤（含行列位置）
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// 命令列或設定檔內容錯誤
+    Config(String),
+    /// 匯出報告或檔案時發生錯誤
+    Export(String),
+}
+
+/// 以 [`BrainVizError`] 為錯誤型別的 Result
+pub type Result<T> = std::result::Result<T, BrainVizError>;
+
+impl BrainVizError {
+    pub fn io(path: impl AsRef<Path>, source: std::io::Error) -> Self {
+        BrainVizError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn json(path: impl AsRef<Path>, err: serde_json::Error) -> Self {
+        BrainVizError::Parse {
+            path: path.as_ref().to_path_buf(),
+            line: err.line(),
+            column: err.column(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for BrainVizError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrainVizError::UnknownBehavior(behavior) => {
+                write!(f, "未知的行為類型: {}", behavior)
+            }
+            BrainVizError::UnitMismatch { behavior, expected, found } => {
+                write!(f, "行為「{}」的單位應為「{}」，但輸入為「{}」", behavior, expected, found)
+            }
+            BrainVizError::InvalidNormalization { behavior, reason } => {
+                write!(f, "行為「{}」的正規化參數無效: {}", behavior, reason)
+            }
+            BrainVizError::Io { path, source } => {
+                write!(f, "無法讀寫 {}: {}", path.display(), source)
+            }
+            BrainVizError::Parse { path, line, column, message } => {
+                write!(f, "{} 第 {} 行第 {} 欄解析失敗: {}", path.display(), line, column, message)
+            }
+            BrainVizError::Config(message) => write!(f, "設定錯誤: {}", message),
+            BrainVizError::Export(message) => write!(f, "匯出失敗: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for BrainVizError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BrainVizError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// 讀取並解析 JSON 文件
+pub(crate) fn read_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+    let path = path.as_ref();
+    let data = std::fs::read_to_string(path).map_err(|e| BrainVizError::io(path, e))?;
+    serde_json::from_str(&data).map_err(|e| BrainVizError::json(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_location() {
+        let err = serde_json::from_str::<Vec<u32>>("[1,\n 2,\n x]").unwrap_err();
+        let err = BrainVizError::json("data/test.json", err);
+
+        match &err {
+            BrainVizError::Parse { line, column, .. } => {
+                assert_eq!(*line, 3);
+                assert_eq!(*column, 2);
+            }
+            other => panic!("預期為解析錯誤，實際為 {:?}", other),
+        }
+        assert!(err.to_string().contains("第 3 行"));
+    }
+
+    #[test]
+    fn test_read_json_missing_file() {
+        let result: Result<Vec<u32>> = read_json("/nonexistent/behavior_brain_viz.json");
+        assert!(matches!(result, Err(BrainVizError::Io { .. })));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let err = BrainVizError::UnitMismatch {
+            behavior: "自然步行".to_string(),
+            expected: "公尺/秒".to_string(),
+            found: "秒".to_string(),
+        };
+        assert_eq!(err.to_string(), "行為「自然步行」的單位應為「公尺/秒」，但輸入為「秒」");
+        assert!(BrainVizError::UnknownBehavior("X".to_string()).to_string().contains("X"));
+    }
+}
@@ -0,0 +1,355 @@
+//! 分數說明
+//!
+//! 將一筆 [`CalculationResult`] 逐腦區拆解為原始值、單位、常模、z 分數、
+//! 權重與出處、對總影響分數的貢獻，以及跨越的風險等級門檻，
+//! 讓臨床人員能說明結果的由來。
+
+use crate::i18n::{name_in, tr_in, trf_in, Language};
+use crate::model::{BehaviorBrainMap, CalculationResult, RiskLevel};
+use serde::{Deserialize, Serialize};
+
+/// 未提供常模時的預設除數（與 `MappingEngine::calculate_impact` 一致）
+pub const DEFAULT_DIVISOR: f32 = 100.0;
+
+/// 輸入值所使用的正規化方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Normalization {
+    /// (value - mean) / std_dev
+    ZScore {
+        mean: f32,
+        std_dev: f32,
+        sample_size: Option<u32>,
+        source: Option<String>,
+    },
+    /// value / divisor
+    Default { divisor: f32 },
+}
+
+/// 單位檢查與換算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitConversion {
+    pub input_unit: String,
+    pub expected_unit: Option<String>,
+    /// 目前引擎要求單位完全相符，不做換算，因此恆為 1
+    pub factor: f32,
+}
+
+/// 分數落在哪個風險等級，以及距離上下門檻多遠
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskBoundary {
+    pub level: RiskLevel,
+    /// 已跨越的門檻（此等級的下限）
+    pub crossed: f32,
+    pub next_level: Option<RiskLevel>,
+    pub next_threshold: Option<f32>,
+}
+
+impl RiskBoundary {
+    pub fn for_score(score: f32) -> Self {
+        let level = RiskLevel::from_score(score);
+        let next_level = level.next();
+        Self {
+            crossed: level.lower_bound(),
+            next_threshold: next_level.as_ref().map(RiskLevel::lower_bound),
+            next_level,
+            level,
+        }
+    }
+}
+
+/// 單一腦區的分數拆解
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionExplanation {
+    pub region: String,
+    pub weight: f32,
+    pub weight_description: Option<String>,
+    pub weight_source: Option<String>,
+    /// weight × |z|
+    pub impact_score: f32,
+    /// impact_score / 腦區數，即此腦區加入總影響分數（截斷前）的量
+    pub contribution: f32,
+    /// 佔所有腦區分數總和的比例
+    pub share: f32,
+    pub boundary: RiskBoundary,
+}
+
+/// 一次計算的完整說明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    pub behavior: String,
+    pub raw_value: f32,
+    pub unit: UnitConversion,
+    pub normalization: Normalization,
+    /// 正規化後的輸入（有常模時即 z 分數）
+    pub z_score: f32,
+    pub regions: Vec<RegionExplanation>,
+    /// 各腦區分數平均，尚未截斷到 0–1
+    pub unclamped_total: f32,
+    pub total_impact: f32,
+    pub clamped: bool,
+    pub boundary: RiskBoundary,
+}
+
+/// 以映射表補齊權重出處與常模，拆解計算結果
+pub fn explain(map: &BehaviorBrainMap, result: &CalculationResult) -> ScoreExplanation {
+    let input = &result.behavior_input;
+    let normalization = match &map.normalization_params {
+        Some(params) => Normalization::ZScore {
+            mean: params.mean,
+            std_dev: params.std_dev,
+            sample_size: params.sample_size,
+            source: params.source.clone(),
+        },
+        None => Normalization::Default {
+            divisor: DEFAULT_DIVISOR,
+        },
+    };
+
+    let count = result.impact_scores.len().max(1) as f32;
+    let sum: f32 = result.impact_scores.iter().map(|s| s.impact_score).sum();
+    let regions = result
+        .impact_scores
+        .iter()
+        .map(|score| {
+            let provenance = map.brain_regions.iter().find(|r| r.region == score.region);
+            RegionExplanation {
+                region: score.region.clone(),
+                weight: score.weight,
+                weight_description: provenance.and_then(|r| r.description.clone()),
+                weight_source: provenance.and_then(|r| r.source.clone()),
+                impact_score: score.impact_score,
+                contribution: score.impact_score / count,
+                share: if sum > 0.0 { score.impact_score / sum } else { 0.0 },
+                boundary: RiskBoundary::for_score(score.impact_score),
+            }
+        })
+        .collect();
+
+    let unclamped_total = sum / count;
+    ScoreExplanation {
+        behavior: input.behavior_type.clone(),
+        raw_value: input.value,
+        unit: UnitConversion {
+            input_unit: input.unit.clone(),
+            expected_unit: map.unit.clone(),
+            factor: 1.0,
+        },
+        normalization,
+        z_score: result
+            .impact_scores
+            .first()
+            .map(|s| s.normalized_input)
+            .unwrap_or_default(),
+        regions,
+        unclamped_total,
+        total_impact: result.total_impact,
+        clamped: (unclamped_total - result.total_impact).abs() > f32::EPSILON,
+        boundary: RiskBoundary::for_score(result.total_impact),
+    }
+}
+
+impl ScoreExplanation {
+    /// 輸入值如何變成 z 分數，每步一行（`language`）
+    pub fn input_steps(&self, language: Language) -> Vec<String> {
+        let mut steps = vec![trf_in(
+            language,
+            "explain.raw_value",
+            &[&self.raw_value, &name_in(language, &self.unit.input_unit)],
+        )];
+        steps.push(match &self.unit.expected_unit {
+            Some(expected) => {
+                trf_in(language, "explain.unit_checked", &[&name_in(language, expected), &self.unit.factor])
+            }
+            None => tr_in(language, "explain.unit_unchecked").to_string(),
+        });
+        steps.push(match &self.normalization {
+            Normalization::ZScore {
+                mean,
+                std_dev,
+                sample_size,
+                source,
+            } => {
+                let mut norm = trf_in(language, "explain.norm", &[mean, std_dev]);
+                if let Some(n) = sample_size {
+                    norm.push_str(&trf_in(language, "explain.norm_sample_size", &[n]));
+                }
+                if let Some(source) = source {
+                    norm.push_str(&trf_in(language, "explain.norm_source", &[source]));
+                }
+                norm.push('\n');
+                norm.push_str(&trf_in(
+                    language,
+                    "explain.z_score",
+                    &[&self.raw_value, mean, std_dev, &format!("{:.3}", self.z_score)],
+                ));
+                norm
+            }
+            Normalization::Default { divisor } => trf_in(
+                language,
+                "explain.default_norm",
+                &[&self.raw_value, divisor, &format!("{:.3}", self.z_score)],
+            ),
+        });
+        steps
+    }
+
+    /// 單一腦區的計算步驟，每步一行（`language`）
+    pub fn region_steps(&self, region: &RegionExplanation, language: Language) -> Vec<String> {
+        let mut steps = Vec::new();
+        let mut weight = trf_in(language, "explain.weight", &[&region.weight]);
+        if let Some(description) = &region.weight_description {
+            weight.push_str(&trf_in(language, "explain.weight_description", &[description]));
+        }
+        let source = region
+            .weight_source
+            .as_deref()
+            .unwrap_or_else(|| tr_in(language, "explain.source_unknown"));
+        weight.push_str(&trf_in(language, "explain.weight_source", &[&source]));
+        steps.push(weight);
+        steps.push(trf_in(
+            language,
+            "explain.impact",
+            &[
+                &region.weight,
+                &format!("{:.3}", self.z_score),
+                &format!("{:.3}", region.impact_score),
+            ],
+        ));
+        steps.push(trf_in(
+            language,
+            "explain.contribution",
+            &[
+                &format!("{:.3}", region.impact_score),
+                &self.regions.len(),
+                &format!("{:.3}", region.contribution),
+                &format!("{:.0}", region.share * 100.0),
+            ],
+        ));
+        steps.push(boundary_text(region.impact_score, &region.boundary, language));
+        steps
+    }
+
+    /// 總影響分數的計算與截斷，每步一行（`language`）
+    pub fn total_steps(&self, language: Language) -> Vec<String> {
+        let mut steps = vec![trf_in(language, "explain.total", &[&format!("{:.3}", self.unclamped_total)])];
+        if self.clamped {
+            steps.push(trf_in(language, "explain.clamped", &[&format!("{:.3}", self.total_impact)]));
+        }
+        steps.push(boundary_text(self.total_impact, &self.boundary, language));
+        steps
+    }
+}
+
+fn boundary_text(score: f32, boundary: &RiskBoundary, language: Language) -> String {
+    let mut text = trf_in(
+        language,
+        "explain.boundary",
+        &[&format!("{:.3}", score), &boundary.crossed, &boundary.level.description_in(language)],
+    );
+    if let (Some(next), Some(threshold)) = (&boundary.next_level, boundary.next_threshold) {
+        text.push_str(&trf_in(
+            language,
+            "explain.next_boundary",
+            &[&next.description_in(language), &threshold, &format!("{:.3}", threshold - score)],
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BehaviorInput, BrainRegionImpact, NormalizationParams};
+    use crate::MappingEngine;
+
+    fn engine(normalization_params: Option<NormalizationParams>) -> MappingEngine {
+        MappingEngine::new(vec![BehaviorBrainMap {
+            behavior: "反應時間".to_string(),
+            unit: Some("毫秒".to_string()),
+            brain_regions: vec![
+                BrainRegionImpact {
+                    region: "前額葉".to_string(),
+                    weight: 0.8,
+                    description: Some("執行控制".to_string()),
+                    source: Some("Smith 2020".to_string()),
+                },
+                BrainRegionImpact {
+                    region: "頂葉".to_string(),
+                    weight: 0.2,
+                    description: None,
+                    source: None,
+                },
+            ],
+            normalization_params,
+        }])
+    }
+
+    fn input(value: f32) -> BehaviorInput {
+        BehaviorInput {
+            behavior_type: "反應時間".to_string(),
+            value,
+            unit: "毫秒".to_string(),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_explain_z_score() {
+        let engine = engine(Some(NormalizationParams {
+            mean: 300.0,
+            std_dev: 100.0,
+            sample_size: Some(50),
+            source: Some("常模研究".to_string()),
+            reliability: None,
+        }));
+        let result = engine.calculate_impact(&input(450.0)).unwrap();
+        let explanation = engine.explain(&result).unwrap();
+
+        assert!((explanation.z_score - 1.5).abs() < 1e-6);
+        assert!(matches!(explanation.normalization, Normalization::ZScore { sample_size: Some(50), .. }));
+        assert_eq!(explanation.unit.expected_unit.as_deref(), Some("毫秒"));
+
+        let prefrontal = &explanation.regions[0];
+        assert_eq!(prefrontal.weight_source.as_deref(), Some("Smith 2020"));
+        assert!((prefrontal.impact_score - 1.2).abs() < 1e-6);
+        assert!((prefrontal.contribution - 0.6).abs() < 1e-6);
+        assert!((prefrontal.share - 0.8).abs() < 1e-6);
+        assert_eq!(prefrontal.boundary.level, RiskLevel::Critical);
+        assert_eq!(prefrontal.boundary.next_threshold, None);
+
+        let parietal = &explanation.regions[1];
+        assert!((parietal.impact_score - 0.3).abs() < 1e-6);
+        assert_eq!(parietal.boundary.crossed, 0.3);
+        assert_eq!(parietal.boundary.next_level, Some(RiskLevel::High));
+
+        // 平均 0.75，未截斷
+        assert!(!explanation.clamped);
+        assert_eq!(explanation.boundary.level, RiskLevel::High);
+        assert!(explanation.input_steps(Language::ZhTw).iter().any(|s| s.contains("常模研究")));
+        assert!(explanation.region_steps(parietal, Language::ZhTw)[0].contains("映射表未註明"));
+        assert!(explanation.region_steps(parietal, Language::En)[0].contains("not stated in the mapping"));
+    }
+
+    #[test]
+    fn test_explain_default_normalization_and_clamp() {
+        let engine = engine(None);
+        let result = engine.calculate_impact(&input(500.0)).unwrap();
+        let explanation = engine.explain(&result).unwrap();
+
+        assert_eq!(explanation.normalization, Normalization::Default { divisor: 100.0 });
+        assert!((explanation.unclamped_total - 2.5).abs() < 1e-6);
+        assert!(explanation.clamped);
+        assert_eq!(explanation.total_impact, 1.0);
+        assert!(explanation.total_steps(Language::ZhTw).iter().any(|s| s.contains("截斷")));
+        assert!(explanation.total_steps(Language::En).iter().any(|s| s.contains("clamped to 1")));
+    }
+
+    #[test]
+    fn test_explain_unknown_behavior() {
+        let engine = engine(None);
+        let mut result = engine.calculate_impact(&input(10.0)).unwrap();
+        result.behavior_input.behavior_type = "不存在".to_string();
+        assert!(engine.explain(&result).is_err());
+    }
+}
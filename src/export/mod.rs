@@ -0,0 +1,704 @@
+//! 計算結果匯出（CSV、JSON、XLSX）
+//!
+//! CSV 與 XLSX 為長格式：每次測量的每個腦區一列，欄位名稱固定為
+//! [`LONG_FORMAT_COLUMNS`]，可直接以 R（`read.csv`、`readxl`）或 SPSS 讀入。
+//! JSON 保留巢狀結構：受試者 → 測量 → 腦區，可由 [`import_json`] 讀回（例如與另一位受試者比較）。
+//! 圖譜向量 CSV 則以標準圖譜的標籤為列，見 [`atlas_vector_csv`]；有標籤影像時另可輸出 NIfTI 影像。
+
+mod xlsx;
+
+use crate::atlas::{AtlasVector, StandardAtlas};
+use crate::error::{read_json, BrainVizError, Result};
+use crate::i18n::tr;
+use crate::model::{BehaviorInput, CalculationResult, RegionImpactScore, RiskLevel, SubjectInfo};
+use crate::nifti::{impact_volume, NiftiVolume};
+use crate::report::file_name_prefix;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 長格式欄位名稱；新增欄位只能加在最後，以免破壞既有分析腳本
+pub const LONG_FORMAT_COLUMNS: [&str; 13] = [
+    "subject_id",
+    "measurement_id",
+    "timestamp",
+    "behavior_type",
+    "value",
+    "unit",
+    "region",
+    "weight",
+    "normalized_input",
+    "impact_score",
+    "region_risk_level",
+    "total_impact",
+    "risk_level",
+];
+
+/// 圖譜向量 CSV 的欄位名稱
+pub const ATLAS_VECTOR_COLUMNS: [&str; 7] = ["index", "label", "mni_x", "mni_y", "mni_z", "impact_score", "regions"];
+
+/// 匯出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::Json, ExportFormat::Xlsx];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    /// 選單顯示名稱（目前語言）
+    pub fn label(&self) -> &'static str {
+        tr(match self {
+            ExportFormat::Csv => "export.format.csv",
+            ExportFormat::Json => "export.format.json",
+            ExportFormat::Xlsx => "export.format.xlsx",
+        })
+    }
+}
+
+/// 長格式的一列：一次測量中的一個腦區
+#[derive(Debug, Clone, PartialEq)]
+pub struct LongRow {
+    pub subject_id: String,
+    pub measurement_id: usize, // 從 1 起算
+    pub timestamp: DateTime<Utc>,
+    pub behavior_type: String,
+    pub value: f32,
+    pub unit: String,
+    pub region: String,
+    pub weight: f32,
+    pub normalized_input: f32,
+    pub impact_score: f32,
+    pub region_risk_level: RiskLevel,
+    pub total_impact: f32,
+    pub risk_level: RiskLevel,
+}
+
+/// 儲存格內容
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl Cell {
+    /// 以 f32 的最短十進位表示轉為 f64，避免 0.8 變成 0.800000011920929
+    fn number(value: f32) -> Self {
+        Cell::Number(value.to_string().parse().unwrap_or(f64::NAN))
+    }
+}
+
+impl LongRow {
+    /// 依 [`LONG_FORMAT_COLUMNS`] 的順序輸出儲存格
+    pub fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.subject_id.clone()),
+            Cell::Number(self.measurement_id as f64),
+            Cell::Text(self.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            Cell::Text(self.behavior_type.clone()),
+            Cell::number(self.value),
+            Cell::Text(self.unit.clone()),
+            Cell::Text(self.region.clone()),
+            Cell::number(self.weight),
+            Cell::number(self.normalized_input),
+            Cell::number(self.impact_score),
+            Cell::Text(format!("{:?}", self.region_risk_level)),
+            Cell::number(self.total_impact),
+            Cell::Text(format!("{:?}", self.risk_level)),
+        ]
+    }
+}
+
+/// 將計算結果展開為長格式
+pub fn long_format_rows(subject: Option<&SubjectInfo>, results: &[CalculationResult]) -> Vec<LongRow> {
+    let subject_id = subject.map(|s| s.subject_id.clone()).unwrap_or_default();
+    results
+        .iter()
+        .enumerate()
+        .flat_map(|(i, result)| {
+            let subject_id = subject_id.clone();
+            result.impact_scores.iter().map(move |impact| LongRow {
+                subject_id: subject_id.clone(),
+                measurement_id: i + 1,
+                timestamp: result.behavior_input.timestamp,
+                behavior_type: result.behavior_input.behavior_type.clone(),
+                value: result.behavior_input.value,
+                unit: result.behavior_input.unit.clone(),
+                region: impact.region.clone(),
+                weight: impact.weight,
+                normalized_input: impact.normalized_input,
+                impact_score: impact.impact_score,
+                region_risk_level: RiskLevel::from_score(impact.impact_score),
+                total_impact: result.total_impact,
+                risk_level: result.risk_level.clone(),
+            })
+        })
+        .collect()
+}
+
+/// 長格式 CSV（RFC 4180，UTF-8，換行為 CRLF）
+pub fn to_csv(subject: Option<&SubjectInfo>, results: &[CalculationResult]) -> String {
+    let mut csv = LONG_FORMAT_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for row in long_format_rows(subject, results) {
+        let fields: Vec<String> = row
+            .cells()
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Text(text) => csv_field(&text),
+                Cell::Number(n) => n.to_string(),
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// 讀回 [`to_csv`] 寫出的長格式 CSV；檔案中可有多位受試者，依 `subject_id` 與 `measurement_id` 還原各次測量
+pub fn import_long_csv(path: impl AsRef<Path>) -> Result<Vec<ImportedResults>> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| BrainVizError::io(path, e))?;
+    let mut lines = text.trim_start_matches('\u{feff}').lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let parse_error = |line: usize, column: usize, message: String| BrainVizError::Parse {
+        path: path.to_path_buf(),
+        line: line + 1,
+        column,
+        message,
+    };
+
+    let Some((header_line, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let header = split_csv_line(header);
+    let columns: Vec<usize> = LONG_FORMAT_COLUMNS
+        .iter()
+        .map(|name| {
+            header
+                .iter()
+                .position(|h| h == name)
+                .ok_or_else(|| parse_error(header_line, 1, format!("缺少欄位 {}", name)))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut subjects: Vec<ImportedResults> = Vec::new();
+    let mut measurements: Vec<(String, String)> = Vec::new(); // 每位受試者目前最後一筆測量的鍵
+    for (line, text) in lines {
+        let fields = split_csv_line(text);
+        let field = |column: usize| fields.get(columns[column]).map(String::as_str).unwrap_or_default();
+        let number = |column: usize| {
+            field(column)
+                .parse::<f32>()
+                .map_err(|_| parse_error(line, columns[column] + 1, format!("{}「{}」不是數字", LONG_FORMAT_COLUMNS[column], field(column))))
+        };
+        let risk_level = |column: usize| {
+            RiskLevel::ALL
+                .iter()
+                .find(|level| format!("{:?}", level) == field(column))
+                .cloned()
+                .ok_or_else(|| parse_error(line, columns[column] + 1, format!("未知的風險等級「{}」", field(column))))
+        };
+
+        let subject_id = field(0).to_string();
+        let index = match subjects.iter().position(|s| s.subject.as_ref().map_or("", |s| s.subject_id.as_str()) == subject_id) {
+            Some(index) => index,
+            None => {
+                subjects.push(ImportedResults {
+                    subject: (!subject_id.is_empty()).then(|| SubjectInfo { subject_id: subject_id.clone(), ..SubjectInfo::default() }),
+                    results: Vec::new(),
+                });
+                measurements.push((String::new(), String::new()));
+                subjects.len() - 1
+            }
+        };
+
+        let key = (field(1).to_string(), field(2).to_string());
+        if measurements[index] != key || subjects[index].results.is_empty() {
+            let timestamp = DateTime::parse_from_rfc3339(field(2))
+                .map_err(|e| parse_error(line, columns[2] + 1, format!("時間「{}」格式錯誤: {}", field(2), e)))?
+                .with_timezone(&Utc);
+            subjects[index].results.push(CalculationResult {
+                behavior_input: BehaviorInput {
+                    behavior_type: field(3).to_string(),
+                    value: number(4)?,
+                    unit: field(5).to_string(),
+                    timestamp,
+                },
+                impact_scores: Vec::new(),
+                total_impact: number(11)?,
+                risk_level: risk_level(12)?,
+            });
+            measurements[index] = key;
+        }
+        let result = subjects[index].results.last_mut().expect("剛加入的測量");
+        result.impact_scores.push(RegionImpactScore {
+            region: field(6).to_string(),
+            weight: number(7)?,
+            normalized_input: number(8)?,
+            impact_score: number(9)?,
+        });
+    }
+    Ok(subjects)
+}
+
+/// 拆開一列 CSV（支援以雙引號包住含逗號或引號的欄位）
+pub(crate) fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches(['\r', '\n']).chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// 巢狀 JSON 匯出的根物件
+#[derive(Debug, Serialize)]
+struct JsonExport<'a> {
+    subject: Option<&'a SubjectInfo>,
+    exported_at: DateTime<Utc>,
+    measurements: Vec<JsonMeasurement<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonMeasurement<'a> {
+    measurement_id: usize,
+    timestamp: DateTime<Utc>,
+    behavior_type: &'a str,
+    value: f32,
+    unit: &'a str,
+    total_impact: f32,
+    risk_level: String,
+    regions: Vec<JsonRegion<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRegion<'a> {
+    region: &'a str,
+    weight: f32,
+    normalized_input: f32,
+    impact_score: f32,
+    risk_level: String,
+}
+
+/// 巢狀 JSON：受試者 → 測量 → 腦區
+pub fn to_json(subject: Option<&SubjectInfo>, results: &[CalculationResult]) -> Result<String> {
+    let export = JsonExport {
+        subject,
+        exported_at: Utc::now(),
+        measurements: results
+            .iter()
+            .enumerate()
+            .map(|(i, result)| JsonMeasurement {
+                measurement_id: i + 1,
+                timestamp: result.behavior_input.timestamp,
+                behavior_type: &result.behavior_input.behavior_type,
+                value: result.behavior_input.value,
+                unit: &result.behavior_input.unit,
+                total_impact: result.total_impact,
+                risk_level: format!("{:?}", result.risk_level),
+                regions: result
+                    .impact_scores
+                    .iter()
+                    .map(|impact| JsonRegion {
+                        region: &impact.region,
+                        weight: impact.weight,
+                        normalized_input: impact.normalized_input,
+                        impact_score: impact.impact_score,
+                        risk_level: format!("{:?}", RiskLevel::from_score(impact.impact_score)),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| BrainVizError::Export(e.to_string()))
+}
+
+/// 由 JSON 匯出檔讀回的受試者與計算結果
+#[derive(Debug, Clone)]
+pub struct ImportedResults {
+    pub subject: Option<SubjectInfo>,
+    pub results: Vec<CalculationResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportedExport {
+    subject: Option<SubjectInfo>,
+    measurements: Vec<ImportedMeasurement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportedMeasurement {
+    timestamp: DateTime<Utc>,
+    behavior_type: String,
+    value: f32,
+    unit: String,
+    total_impact: f32,
+    risk_level: RiskLevel,
+    regions: Vec<RegionImpactScore>,
+}
+
+/// 讀回 [`to_json`] 寫出的檔案；各腦區的 `risk_level` 由分數推得，讀取時略過
+pub fn import_json(path: impl AsRef<Path>) -> Result<ImportedResults> {
+    let export: ImportedExport = read_json(path)?;
+    Ok(ImportedResults {
+        subject: export.subject,
+        results: export
+            .measurements
+            .into_iter()
+            .map(|m| CalculationResult {
+                behavior_input: BehaviorInput {
+                    behavior_type: m.behavior_type,
+                    value: m.value,
+                    unit: m.unit,
+                    timestamp: m.timestamp,
+                },
+                impact_scores: m.regions,
+                total_impact: m.total_impact,
+                risk_level: m.risk_level,
+            })
+            .collect(),
+    })
+}
+
+/// 目錄中的 JSON 匯出檔（依檔名排序）；無法讀取目錄時為空
+pub fn discover_json_exports(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case(ExportFormat::Json.extension())))
+        .collect();
+    files.sort();
+    files
+}
+
+/// 長格式 XLSX 活頁簿（單一工作表 `results`）
+pub fn to_xlsx(subject: Option<&SubjectInfo>, results: &[CalculationResult]) -> Vec<u8> {
+    let rows: Vec<Vec<Cell>> = long_format_rows(subject, results).iter().map(LongRow::cells).collect();
+    xlsx::workbook("results", &LONG_FORMAT_COLUMNS, &rows)
+}
+
+/// 依格式將結果寫入檔案
+pub fn write_results(
+    subject: Option<&SubjectInfo>,
+    results: &[CalculationResult],
+    format: ExportFormat,
+    out: &Path,
+) -> Result<()> {
+    let bytes = match format {
+        ExportFormat::Csv => to_csv(subject, results).into_bytes(),
+        ExportFormat::Json => to_json(subject, results)?.into_bytes(),
+        ExportFormat::Xlsx => to_xlsx(subject, results),
+    };
+    std::fs::write(out, bytes).map_err(|e| BrainVizError::io(out, e))
+}
+
+/// 以預設檔名將結果寫入 `output_dir`（目錄不存在時建立），回傳檔案路徑
+///
+/// 檔名為 `<受試者編號>_<scope>_<匯出時間>.<副檔名>`，例如 `S001_history_20240101_120000.csv`。
+pub fn export_results(
+    subject: Option<&SubjectInfo>,
+    results: &[CalculationResult],
+    format: ExportFormat,
+    scope: &str,
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    if results.is_empty() {
+        return Err(BrainVizError::Export("沒有可匯出的計算結果".to_string()));
+    }
+    std::fs::create_dir_all(output_dir).map_err(|e| BrainVizError::io(output_dir, e))?;
+    let out = output_dir.join(format!(
+        "{}_{}_{}.{}",
+        file_name_prefix(subject),
+        scope,
+        chrono::Local::now().format("%Y%m%d_%H%M%S"),
+        format.extension()
+    ));
+    write_results(subject, results, format, &out)?;
+    Ok(out)
+}
+
+/// 圖譜向量 CSV：每個標籤一列，依標籤編號排序；沒有質心座標時座標欄留空
+pub fn atlas_vector_csv(vector: &AtlasVector) -> String {
+    let mut csv = ATLAS_VECTOR_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for entry in &vector.entries {
+        let coords: Vec<String> = match entry.mni {
+            Some(mni) => mni.iter().map(f32::to_string).collect(),
+            None => vec![String::new(); 3],
+        };
+        csv.push_str(&format!(
+            "{},{},{},{},{}\r\n",
+            entry.index,
+            csv_field(&entry.label),
+            coords.join(","),
+            entry.impact_score,
+            csv_field(&entry.regions.join("; "))
+        ));
+    }
+    csv
+}
+
+/// 將圖譜向量寫入 `output_dir`，檔名為 `<受試者編號>_<圖譜名稱>_<測量時間>.csv`
+pub fn export_atlas_vector(subject: Option<&SubjectInfo>, result: &CalculationResult, vector: &AtlasVector, output_dir: &Path) -> Result<PathBuf> {
+    let out = atlas_output_path(subject, result, vector, "csv", output_dir)?;
+    std::fs::write(&out, atlas_vector_csv(vector)).map_err(|e| BrainVizError::io(&out, e))?;
+    Ok(out)
+}
+
+/// 以圖譜的標籤影像為底，將圖譜向量寫成 `.nii.gz` 影響分數圖（檔名規則同 [`export_atlas_vector`]）
+pub fn export_impact_nifti(
+    subject: Option<&SubjectInfo>,
+    result: &CalculationResult,
+    atlas: &StandardAtlas,
+    vector: &AtlasVector,
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    let volume = atlas
+        .volume
+        .as_ref()
+        .ok_or_else(|| BrainVizError::Export(format!("圖譜 {} 沒有標籤影像（{}.nii.gz）", atlas.name, atlas.name)))?;
+    let labels = NiftiVolume::read(volume)?;
+    let out = atlas_output_path(subject, result, vector, "nii.gz", output_dir)?;
+    impact_volume(&labels, vector).write(&out)?;
+    Ok(out)
+}
+
+fn atlas_output_path(
+    subject: Option<&SubjectInfo>,
+    result: &CalculationResult,
+    vector: &AtlasVector,
+    extension: &str,
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(output_dir).map_err(|e| BrainVizError::io(output_dir, e))?;
+    Ok(output_dir.join(format!(
+        "{}_{}_{}.{}",
+        file_name_prefix(subject),
+        vector.atlas,
+        result.behavior_input.timestamp.format("%Y%m%d_%H%M%S"),
+        extension
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_results() -> Vec<CalculationResult> {
+        let impact = |region: &str, score: f32| RegionImpactScore {
+            region: region.to_string(),
+            impact_score: score,
+            normalized_input: 0.5,
+            weight: 0.8,
+        };
+        vec![
+            CalculationResult {
+                behavior_input: BehaviorInput {
+                    behavior_type: "自然步行".to_string(),
+                    value: 0.9,
+                    unit: "公尺/秒".to_string(),
+                    timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap(),
+                },
+                impact_scores: vec![impact("運動皮質", 0.4), impact("小腦, \"前葉\"", 0.7)],
+                total_impact: 0.55,
+                risk_level: RiskLevel::Medium,
+            },
+            CalculationResult {
+                behavior_input: BehaviorInput {
+                    behavior_type: "持續注意力指敲".to_string(),
+                    value: 72.0,
+                    unit: "次".to_string(),
+                    timestamp: Utc.with_ymd_and_hms(2024, 1, 2, 9, 0, 0).unwrap(),
+                },
+                impact_scores: vec![impact("前額葉", 0.2)],
+                total_impact: 0.2,
+                risk_level: RiskLevel::Low,
+            },
+        ]
+    }
+
+    fn subject() -> SubjectInfo {
+        SubjectInfo {
+            subject_id: "S001".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_long_format_rows() {
+        let rows = long_format_rows(Some(&subject()), &sample_results());
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].measurement_id, 1);
+        assert_eq!(rows[1].region_risk_level, RiskLevel::High);
+        assert_eq!(rows[2].measurement_id, 2);
+        assert_eq!(rows[2].subject_id, "S001");
+        assert!(rows.iter().all(|row| row.cells().len() == LONG_FORMAT_COLUMNS.len()));
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let csv = to_csv(Some(&subject()), &sample_results());
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "subject_id,measurement_id,timestamp,behavior_type,value,unit,region,weight,normalized_input,impact_score,region_risk_level,total_impact,risk_level"
+        );
+        assert_eq!(lines.len(), 5); // 標題 + 3 列 + 結尾空字串
+        assert!(lines[2].contains("\"小腦, \"\"前葉\"\"\""));
+        assert_eq!(lines[3], "S001,2,2024-01-02T09:00:00Z,持續注意力指敲,72,次,前額葉,0.8,0.5,0.2,Low,0.2,Low");
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = to_json(Some(&subject()), &sample_results()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["subject"]["subject_id"], "S001");
+        assert_eq!(value["measurements"].as_array().unwrap().len(), 2);
+        assert_eq!(value["measurements"][0]["regions"][1]["risk_level"], "High");
+        assert_eq!(value["measurements"][1]["unit"], "次");
+    }
+
+    #[test]
+    fn test_import_json_round_trip() {
+        let dir = std::env::temp_dir().join("bbv_import_json");
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = export_results(Some(&subject()), &sample_results(), ExportFormat::Json, "history", &dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        assert_eq!(discover_json_exports(&dir), vec![out.clone()]);
+
+        let imported = import_json(&out).unwrap();
+        assert_eq!(imported.subject, Some(subject()));
+        assert_eq!(imported.results.len(), 2);
+        let first = &imported.results[0];
+        assert_eq!(first.behavior_input.behavior_type, "自然步行");
+        assert_eq!(first.behavior_input.timestamp, sample_results()[0].behavior_input.timestamp);
+        assert_eq!(first.risk_level, RiskLevel::Medium);
+        assert_eq!(first.impact_scores[1].region, "小腦, \"前葉\"");
+        assert_eq!(first.impact_scores[1].impact_score, 0.7);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_import_long_csv() {
+        let path = std::env::temp_dir().join("bbv_import_long.csv");
+        let other = SubjectInfo { subject_id: "S002".to_string(), ..Default::default() };
+        let second = to_csv(Some(&other), &sample_results()[1..]);
+        let csv = to_csv(Some(&subject()), &sample_results()) + second.split_once("\r\n").unwrap().1;
+        std::fs::write(&path, csv).unwrap();
+
+        let imported = import_long_csv(&path).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[0].subject, Some(subject()));
+        assert_eq!(imported[0].results.len(), 2);
+        let first = &imported[0].results[0];
+        assert_eq!(first.behavior_input.timestamp, sample_results()[0].behavior_input.timestamp);
+        assert_eq!(first.risk_level, RiskLevel::Medium);
+        assert_eq!(first.impact_scores.len(), 2);
+        assert_eq!(first.impact_scores[1].region, "小腦, \"前葉\"");
+        assert_eq!(imported[1].subject.as_ref().unwrap().subject_id, "S002");
+        assert_eq!(imported[1].results[0].behavior_input.value, 72.0);
+
+        std::fs::write(&path, "subject_id,region\r\nS001,前額葉\r\n").unwrap();
+        assert!(matches!(import_long_csv(&path), Err(BrainVizError::Parse { line: 1, .. })));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_atlas_vector_csv() {
+        use crate::atlas::{AtlasRegionMap, StandardAtlas};
+
+        let atlas = StandardAtlas::parse("custom.csv", "index,name,x,y,z\n1,Cerebellum_L,-20,-60,-30\n2,Precentral_L,-38,-20,56\n").unwrap();
+        let map: AtlasRegionMap =
+            serde_json::from_str(r#"{ "regions": [{ "names": ["小腦"], "labels": { "custom": ["Cerebellum_*"] } }] }"#).unwrap();
+        let vector = AtlasVector::from_result(&sample_results()[0], &atlas, &map);
+
+        let csv = atlas_vector_csv(&vector);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], ATLAS_VECTOR_COLUMNS.join(","));
+        assert_eq!(lines[1], "1,Cerebellum_L,-20,-60,-30,0.7,\"小腦, \"\"前葉\"\"\"");
+        assert_eq!(lines[2], "2,Precentral_L,-38,-20,56,0,");
+    }
+
+    #[test]
+    fn test_export_impact_nifti_round_trip() {
+        use crate::atlas::{AtlasRegionMap, StandardAtlas};
+        use crate::nifti::parcel_values;
+
+        let dir = std::env::temp_dir().join("bbv_export_nifti");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut atlas = StandardAtlas::parse("custom.csv", "index,name\n1,Cerebellum_L\n2,Precentral_L\n").unwrap();
+        let map: AtlasRegionMap =
+            serde_json::from_str(r#"{ "regions": [{ "names": ["小腦"], "labels": { "custom": ["Cerebellum_*"] } }] }"#).unwrap();
+        let vector = AtlasVector::from_result(&sample_results()[0], &atlas, &map);
+        assert!(matches!(
+            export_impact_nifti(None, &sample_results()[0], &atlas, &vector, &dir),
+            Err(BrainVizError::Export(_))
+        ));
+
+        let labels = NiftiVolume {
+            dims: [2, 2, 1],
+            pixdim: [2.0, 2.0, 2.0],
+            qfac: 1.0,
+            qform_code: 0,
+            quatern: [0.0; 3],
+            qoffset: [0.0; 3],
+            sform_code: 4,
+            srow: [[2.0, 0.0, 0.0, -2.0], [0.0, 2.0, 0.0, -2.0], [0.0, 0.0, 2.0, 0.0]],
+            xyzt_units: 2,
+            descrip: "custom".to_string(),
+            data: vec![1.0, 2.0, 1.0, 0.0],
+        };
+        let volume = dir.join("custom.nii.gz");
+        labels.write(&volume).unwrap();
+        atlas.volume = Some(volume);
+
+        let out = export_impact_nifti(None, &sample_results()[0], &atlas, &vector, &dir).unwrap();
+        assert!(out.to_string_lossy().ends_with(".nii.gz"));
+        let image = NiftiVolume::read(&out).unwrap();
+        let values = parcel_values(&labels, &image).unwrap();
+        assert!((values[&1] - 0.7).abs() < 1e-6);
+        assert_eq!(values[&2], 0.0);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_export_results_requires_data() {
+        let dir = std::env::temp_dir().join("bbv_export_empty");
+        assert!(matches!(
+            export_results(None, &[], ExportFormat::Csv, "current", &dir),
+            Err(BrainVizError::Export(_))
+        ));
+    }
+}
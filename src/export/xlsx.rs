@@ -0,0 +1,261 @@
+//! 最小的 XLSX 寫入器
+//!
+//! 只產生單一工作表，字串以 inline string 儲存，ZIP 容器不壓縮（stored），
+//! Excel、LibreOffice、R `readxl` 與 SPSS 皆可開啟。
+
+use super::Cell;
+use crate::brain_map::escape_xml;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>
+</Types>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"#;
+
+const WORKBOOK_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>
+</Relationships>"#;
+
+/// 產生含一個工作表的活頁簿；第一列為欄位名稱並凍結
+pub(super) fn workbook(sheet_name: &str, headers: &[&str], rows: &[Vec<Cell>]) -> Vec<u8> {
+    let workbook = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets>
+</workbook>"#,
+        escape_xml(sheet_name)
+    );
+
+    let mut sheet = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+<sheetViews><sheetView workbookViewId="0"><pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/></sheetView></sheetViews>
+<sheetData>
+"#,
+    );
+    let header: Vec<Cell> = headers.iter().map(|h| Cell::Text(h.to_string())).collect();
+    for (r, row) in std::iter::once(&header).chain(rows).enumerate() {
+        sheet.push_str(&format!("<row r=\"{}\">", r + 1));
+        for (c, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Number(n) if n.is_finite() => {
+                    sheet.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, n));
+                }
+                Cell::Number(_) => {} // NaN 與無限大留空
+                Cell::Text(text) => sheet.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                    reference,
+                    escape_xml(text)
+                )),
+            }
+        }
+        sheet.push_str("</row>\n");
+    }
+    sheet.push_str("</sheetData>\n</worksheet>");
+
+    let mut zip = ZipWriter::default();
+    zip.add("[Content_Types].xml", CONTENT_TYPES.as_bytes());
+    zip.add("_rels/.rels", ROOT_RELS.as_bytes());
+    zip.add("xl/workbook.xml", workbook.as_bytes());
+    zip.add("xl/_rels/workbook.xml.rels", WORKBOOK_RELS.as_bytes());
+    zip.add("xl/worksheets/sheet1.xml", sheet.as_bytes());
+    zip.finish()
+}
+
+/// 0 → A、25 → Z、26 → AA
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// 不壓縮的 ZIP 寫入器
+#[derive(Default)]
+struct ZipWriter {
+    data: Vec<u8>,
+    central: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, content: &[u8]) {
+        let offset = self.data.len() as u32;
+        let crc = crc32(content);
+        let size = content.len() as u32;
+        // 1980-01-01 00:00，避免輸出因時間不同而變動
+        let (time, date) = (0u16, (1u16 << 5) | 1);
+
+        let header = |signature: u32, out: &mut Vec<u8>, central: bool| {
+            out.extend_from_slice(&signature.to_le_bytes());
+            if central {
+                out.extend_from_slice(&20u16.to_le_bytes()); // version made by
+            }
+            out.extend_from_slice(&20u16.to_le_bytes()); // version needed
+            out.extend_from_slice(&0u16.to_le_bytes()); // flags
+            out.extend_from_slice(&0u16.to_le_bytes()); // stored
+            out.extend_from_slice(&time.to_le_bytes());
+            out.extend_from_slice(&date.to_le_bytes());
+            out.extend_from_slice(&crc.to_le_bytes());
+            out.extend_from_slice(&size.to_le_bytes());
+            out.extend_from_slice(&size.to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        };
+
+        header(0x0403_4b50, &mut self.data, false);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(content);
+
+        header(0x0201_4b50, &mut self.central, true);
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        self.central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        self.central.extend_from_slice(&offset.to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let central_offset = self.data.len() as u32;
+        let central_size = self.central.len() as u32;
+        self.data.append(&mut self.central);
+        self.data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]); // disk numbers
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&central_size.to_le_bytes());
+        self.data.extend_from_slice(&central_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.data
+    }
+}
+
+/// CRC-32 (IEEE 802.3)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_and_column_name() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(12), "M");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27 * 26), "AAA");
+    }
+
+    #[test]
+    fn test_workbook_structure() {
+        let rows = vec![vec![Cell::Text("前額葉 <A&B>".to_string()), Cell::Number(0.5)]];
+        let bytes = workbook("results", &["region", "score"], &rows);
+
+        assert!(bytes.starts_with(b"PK\x03\x04"));
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("xl/worksheets/sheet1.xml"));
+        assert!(text.contains("<c r=\"B2\"><v>0.5</v></c>"));
+        assert!(text.contains("前額葉 &lt;A&amp;B&gt;"));
+        // 中央目錄結尾記錄：5 個項目
+        let end = bytes.len() - 22;
+        assert_eq!(&bytes[end..end + 4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([bytes[end + 10], bytes[end + 11]]), 5);
+    }
+
+    fn u16_at(bytes: &[u8], at: usize) -> usize {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    }
+
+    #[test]
+    fn test_zip_round_trip() {
+        let rows = vec![
+            vec![Cell::Text("受試者 A".to_string()), Cell::Number(1.25)],
+            vec![Cell::Text("受試者 B".to_string()), Cell::Number(f64::NAN)],
+        ];
+        let bytes = workbook("results", &["subject", "score"], &rows);
+
+        // 中央目錄結尾記錄
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(&bytes, end), 0x0605_4b50);
+        let entries = u16_at(&bytes, end + 10);
+        assert_eq!(u16_at(&bytes, end + 8), entries);
+        let central_size = u32_at(&bytes, end + 12) as usize;
+        let central_offset = u32_at(&bytes, end + 16) as usize;
+        assert_eq!(central_offset + central_size, end);
+
+        // 逐一走訪中央目錄，並回到各自的本地檔頭核對內容
+        let mut names = Vec::new();
+        let mut at = central_offset;
+        let mut expected_local = 0;
+        for _ in 0..entries {
+            assert_eq!(u32_at(&bytes, at), 0x0201_4b50);
+            assert_eq!(u16_at(&bytes, at + 10), 0, "應為不壓縮");
+            let crc = u32_at(&bytes, at + 16);
+            let size = u32_at(&bytes, at + 20) as usize;
+            assert_eq!(u32_at(&bytes, at + 24) as usize, size);
+            let name_len = u16_at(&bytes, at + 28);
+            let extra_len = u16_at(&bytes, at + 30);
+            let comment_len = u16_at(&bytes, at + 32);
+            let local = u32_at(&bytes, at + 42) as usize;
+            let name = &bytes[at + 46..at + 46 + name_len];
+
+            assert_eq!(local, expected_local, "本地檔頭應緊接前一個項目");
+            assert_eq!(u32_at(&bytes, local), 0x0403_4b50);
+            assert_eq!(u32_at(&bytes, local + 14), crc);
+            assert_eq!(u32_at(&bytes, local + 18) as usize, size);
+            assert_eq!(u32_at(&bytes, local + 22) as usize, size);
+            let local_name_len = u16_at(&bytes, local + 26);
+            let local_extra_len = u16_at(&bytes, local + 28);
+            let data_start = local + 30 + local_name_len + local_extra_len;
+            assert_eq!(&bytes[local + 30..local + 30 + local_name_len], name);
+            let data = &bytes[data_start..data_start + size];
+            assert_eq!(crc32(data), crc, "{} 的 CRC 不符", String::from_utf8_lossy(name));
+
+            names.push(String::from_utf8(name.to_vec()).unwrap());
+            expected_local = data_start + size;
+            at += 46 + name_len + extra_len + comment_len;
+        }
+        assert_eq!(at, end);
+        assert_eq!(expected_local, central_offset);
+        assert_eq!(
+            names,
+            [
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "xl/workbook.xml",
+                "xl/_rels/workbook.xml.rels",
+                "xl/worksheets/sheet1.xml",
+            ]
+        );
+    }
+}
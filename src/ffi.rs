@@ -0,0 +1,328 @@
+//! C ABI 介面（對應 `include/behavior_brain_viz.h`）
+//!
+//! 所有字串皆為 UTF-8 並以 NUL 結尾。由本函式庫配置的物件必須以對應的
+//! `*_free` 函式釋放；失敗時回傳 NULL，可用 `bbv_last_error_message` 取得原因。
+
+use crate::i18n::Language;
+use crate::mapping::{load_behavior_brain_map, MappingEngine};
+use crate::model::{BehaviorInput, CalculationResult, RiskLevel};
+use crate::risk::{load_risk_map, RiskAssessment};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+/// 計算引擎控制代碼
+pub struct BbvEngine {
+    engine: MappingEngine,
+    risk_assessment: RiskAssessment,
+}
+
+/// 計算結果控制代碼
+pub struct BbvResult {
+    result: CalculationResult,
+    region_names: Vec<CString>,
+    recommendations: Vec<CString>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+}
+
+/// 將 C 字串轉為 &str，NULL 或非 UTF-8 時記錄錯誤
+unsafe fn c_str<'a>(ptr: *const c_char, name: &str) -> Option<&'a str> {
+    if ptr.is_null() {
+        set_last_error(format!("{} 不可為 NULL", name));
+        return None;
+    }
+    match CStr::from_ptr(ptr).to_str() {
+        Ok(s) => Some(s),
+        Err(_) => {
+            set_last_error(format!("{} 不是有效的 UTF-8 字串", name));
+            None
+        }
+    }
+}
+
+fn to_cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', " ")).unwrap_or_default()
+}
+
+/// 風險等級對應的 C 列舉值
+fn risk_level_code(level: &RiskLevel) -> c_int {
+    match level {
+        RiskLevel::Low => 0,
+        RiskLevel::Medium => 1,
+        RiskLevel::High => 2,
+        RiskLevel::Critical => 3,
+    }
+}
+
+/// 取得目前執行緒最後一次錯誤訊息；無錯誤時回傳 NULL
+///
+/// 回傳的指標在下一次呼叫本函式庫前有效。
+#[no_mangle]
+pub extern "C" fn bbv_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// 從映射 JSON 建立計算引擎（使用預設風險對照表）
+///
+/// # Safety
+/// `mapping_path` 必須是有效的 NUL 結尾字串。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_engine_new_from_json(mapping_path: *const c_char) -> *mut BbvEngine {
+    bbv_engine_new_with_risk_map(mapping_path, ptr::null())
+}
+
+/// 從映射 JSON 與風險對照 JSON 建立計算引擎；`risk_map_path` 可為 NULL
+///
+/// # Safety
+/// `mapping_path` 必須是有效的 NUL 結尾字串，`risk_map_path` 為 NULL 或有效字串。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_engine_new_with_risk_map(
+    mapping_path: *const c_char,
+    risk_map_path: *const c_char,
+) -> *mut BbvEngine {
+    clear_last_error();
+    let Some(mapping_path) = c_str(mapping_path, "mapping_path") else {
+        return ptr::null_mut();
+    };
+
+    let behavior_maps = match load_behavior_brain_map(mapping_path) {
+        Ok(maps) => maps,
+        Err(e) => {
+            set_last_error(e.to_string());
+            return ptr::null_mut();
+        }
+    };
+
+    let risk_assessment = if risk_map_path.is_null() {
+        RiskAssessment::default()
+    } else {
+        let Some(risk_map_path) = c_str(risk_map_path, "risk_map_path") else {
+            return ptr::null_mut();
+        };
+        match load_risk_map(risk_map_path) {
+            Ok(entries) => RiskAssessment::default().with_risk_map(entries),
+            Err(e) => {
+                set_last_error(e.to_string());
+                return ptr::null_mut();
+            }
+        }
+    };
+
+    Box::into_raw(Box::new(BbvEngine {
+        engine: MappingEngine::new(behavior_maps),
+        risk_assessment,
+    }))
+}
+
+/// 釋放計算引擎；傳入 NULL 時不做任何事
+///
+/// # Safety
+/// `engine` 必須由 `bbv_engine_new_*` 取得且尚未釋放。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_engine_free(engine: *mut BbvEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// 計算單一行為數值的影響分數；`unit` 為 NULL 時使用映射表中的預期單位
+///
+/// # Safety
+/// `engine` 必須是有效的控制代碼，`behavior` 為有效字串，`unit` 為 NULL 或有效字串。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_engine_score(
+    engine: *const BbvEngine,
+    behavior: *const c_char,
+    value: f32,
+    unit: *const c_char,
+) -> *mut BbvResult {
+    clear_last_error();
+    let Some(engine) = engine.as_ref() else {
+        set_last_error("engine 不可為 NULL");
+        return ptr::null_mut();
+    };
+    let Some(behavior) = c_str(behavior, "behavior") else {
+        return ptr::null_mut();
+    };
+    let unit = if unit.is_null() {
+        engine.engine.get_behavior_unit(behavior).unwrap_or_default()
+    } else {
+        match c_str(unit, "unit") {
+            Some(unit) => unit,
+            None => return ptr::null_mut(),
+        }
+    };
+
+    let input = BehaviorInput {
+        behavior_type: behavior.to_string(),
+        value,
+        unit: unit.to_string(),
+        timestamp: chrono::Utc::now(),
+    };
+
+    match engine.engine.calculate_impact(&input) {
+        Ok(result) => {
+            // C API 沒有語言設定，建議文字使用資料檔原文（繁體中文）
+            let report = engine.risk_assessment.generate_risk_report(&result, Language::default());
+            Box::into_raw(Box::new(BbvResult {
+                region_names: result.impact_scores.iter().map(|s| to_cstring(&s.region)).collect(),
+                recommendations: report.recommendations.iter().map(|r| to_cstring(r)).collect(),
+                result,
+            }))
+        }
+        Err(e) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// 釋放計算結果；傳入 NULL 時不做任何事
+///
+/// # Safety
+/// `result` 必須由 `bbv_engine_score` 取得且尚未釋放。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_free(result: *mut BbvResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// 總影響分數（0-1）；`result` 為 NULL 時回傳 NaN
+///
+/// # Safety
+/// `result` 必須為 NULL 或有效的控制代碼。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_total_impact(result: *const BbvResult) -> f32 {
+    result.as_ref().map_or(f32::NAN, |r| r.result.total_impact)
+}
+
+/// 風險等級（0 低、1 中、2 高、3 極高）；`result` 為 NULL 時回傳 -1
+///
+/// # Safety
+/// `result` 必須為 NULL 或有效的控制代碼。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_risk_level(result: *const BbvResult) -> c_int {
+    result.as_ref().map_or(-1, |r| risk_level_code(&r.result.risk_level))
+}
+
+/// 腦區數量
+///
+/// # Safety
+/// `result` 必須為 NULL 或有效的控制代碼。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_region_count(result: *const BbvResult) -> usize {
+    result.as_ref().map_or(0, |r| r.result.impact_scores.len())
+}
+
+/// 第 `index` 個腦區名稱；超出範圍時回傳 NULL，指標在結果釋放前有效
+///
+/// # Safety
+/// `result` 必須為 NULL 或有效的控制代碼。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_region_name(result: *const BbvResult, index: usize) -> *const c_char {
+    result
+        .as_ref()
+        .and_then(|r| r.region_names.get(index))
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// 第 `index` 個腦區的影響分數；超出範圍時回傳 NaN
+///
+/// # Safety
+/// `result` 必須為 NULL 或有效的控制代碼。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_region_score(result: *const BbvResult, index: usize) -> f32 {
+    result
+        .as_ref()
+        .and_then(|r| r.result.impact_scores.get(index))
+        .map_or(f32::NAN, |score| score.impact_score)
+}
+
+/// 建議事項數量
+///
+/// # Safety
+/// `result` 必須為 NULL 或有效的控制代碼。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_recommendation_count(result: *const BbvResult) -> usize {
+    result.as_ref().map_or(0, |r| r.recommendations.len())
+}
+
+/// 第 `index` 項建議；超出範圍時回傳 NULL，指標在結果釋放前有效
+///
+/// # Safety
+/// `result` 必須為 NULL 或有效的控制代碼。
+#[no_mangle]
+pub unsafe extern "C" fn bbv_result_recommendation(result: *const BbvResult, index: usize) -> *const c_char {
+    result
+        .as_ref()
+        .and_then(|r| r.recommendations.get(index))
+        .map_or(ptr::null(), |text| text.as_ptr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping_path() -> CString {
+        CString::new(concat!(env!("CARGO_MANIFEST_DIR"), "/data/behavior_brain_map.json")).unwrap()
+    }
+
+    #[test]
+    fn test_engine_score_roundtrip() {
+        unsafe {
+            let engine = bbv_engine_new_from_json(mapping_path().as_ptr());
+            assert!(!engine.is_null());
+
+            let behavior = CString::new("持續注意力指敲").unwrap();
+            let result = bbv_engine_score(engine, behavior.as_ptr(), 95.0, ptr::null());
+            assert!(!result.is_null());
+
+            assert_eq!(bbv_result_risk_level(result), 3);
+            assert_eq!(bbv_result_region_count(result), 3);
+            let name = CStr::from_ptr(bbv_result_region_name(result, 0)).to_str().unwrap();
+            assert_eq!(name, "前額葉");
+            assert!(bbv_result_region_score(result, 0) > 0.0);
+            assert!(bbv_result_region_name(result, 99).is_null());
+            assert!(bbv_result_recommendation_count(result) > 0);
+
+            bbv_result_free(result);
+            bbv_engine_free(engine);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            let missing = CString::new("/nonexistent/map.json").unwrap();
+            assert!(bbv_engine_new_from_json(missing.as_ptr()).is_null());
+            assert!(!bbv_last_error_message().is_null());
+
+            let engine = bbv_engine_new_from_json(mapping_path().as_ptr());
+            let behavior = CString::new("不存在的行為").unwrap();
+            assert!(bbv_engine_score(engine, behavior.as_ptr(), 1.0, ptr::null()).is_null());
+            let message = CStr::from_ptr(bbv_last_error_message()).to_str().unwrap();
+            assert!(message.contains("不存在的行為"));
+
+            assert_eq!(bbv_result_risk_level(ptr::null()), -1);
+            assert!(bbv_result_total_impact(ptr::null()).is_nan());
+            bbv_engine_free(engine);
+        }
+    }
+}
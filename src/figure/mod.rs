@@ -0,0 +1,519 @@
+//! 腦圖圖檔輸出（SVG、PNG）
+//!
+//! 由 [`CalculationResult`] 與腦圖 SVG 建立與介面無關的 [`Figure`]（圖形元素清單），
+//! 再輸出為獨立的 SVG，或在啟用 `png` 功能時以指定寬度點陣化為 PNG。
+//! 圖中包含依影響分數上色的腦區、標籤、風險等級圖例與色條。
+
+#[cfg(feature = "png")]
+mod raster;
+
+use crate::brain_map::{escape_xml, hex_color, impact_color, parse_path, BrainMapLayout, PathCommand};
+use crate::error::{BrainVizError, Result};
+use crate::i18n::{name_in, tr_in, trf_in, Language};
+use crate::model::{CalculationResult, RiskLevel};
+use std::path::Path;
+
+const TITLE_HEIGHT: f32 = 36.0;
+const PANEL_WIDTH: f32 = 140.0;
+const PANEL_HEIGHT: f32 = 280.0;
+const NEUTRAL_FILL: [u8; 3] = [232, 244, 248];
+const NEUTRAL_STROKE: [u8; 3] = [33, 150, 243];
+const OUTLINE_FILL: [u8; 3] = [240, 240, 240];
+const TEXT_COLOR: [u8; 3] = [51, 51, 51];
+
+/// 風險等級區間（下限, 上限, 等級），與 `RiskLevel::from_score` 一致
+const LEVEL_BANDS: [(f32, f32, RiskLevel); 4] = [
+    (0.0, 0.3, RiskLevel::Low),
+    (0.3, 0.6, RiskLevel::Medium),
+    (0.6, 0.8, RiskLevel::High),
+    (0.8, 1.0, RiskLevel::Critical),
+];
+
+/// 圖檔內容選項
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FigureOptions {
+    pub show_title: bool,
+    pub show_labels: bool,
+    pub show_legend: bool,
+    pub show_colorbar: bool,
+}
+
+impl Default for FigureOptions {
+    fn default() -> Self {
+        Self {
+            show_title: true,
+            show_labels: true,
+            show_legend: true,
+            show_colorbar: true,
+        }
+    }
+}
+
+/// 文字對齊方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// 圖形元素；座標單位與腦圖 SVG 相同
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Path {
+        commands: Vec<PathCommand>,
+        fill: Option<[u8; 3]>,
+        stroke: Option<([u8; 3], f32)>,
+    },
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+        fill: [u8; 3],
+        stroke: Option<([u8; 3], f32)>,
+        opacity: f32,
+    },
+    Rect {
+        origin: [f32; 2],
+        size: [f32; 2],
+        fill: [u8; 3],
+    },
+    Text {
+        position: [f32; 2], // 基線位置
+        text: String,
+        size: f32,
+        color: [u8; 3],
+        anchor: Anchor,
+        bold: bool,
+    },
+}
+
+/// 可輸出為 SVG 或 PNG 的腦圖
+#[derive(Debug, Clone, PartialEq)]
+pub struct Figure {
+    pub width: f32,
+    pub height: f32,
+    pub shapes: Vec<Shape>,
+}
+
+impl Figure {
+    /// 由計算結果建立腦圖；標題、圖例與腦區名稱依 `language`
+    pub fn from_result(
+        result: &CalculationResult,
+        atlas_svg: &str,
+        options: &FigureOptions,
+        language: Language,
+    ) -> Self {
+        let input = &result.behavior_input;
+        let title = trf_in(
+            language,
+            "figure.title",
+            &[
+                &name_in(language, &input.behavior_type),
+                &input.value,
+                &name_in(language, &input.unit),
+                &format!("{:.2}", result.total_impact),
+                &result.risk_level.label_in(language),
+            ],
+        );
+        Self::with_title(result, &title, atlas_svg, options, language)
+    }
+
+    /// 以自訂標題建立腦圖，例如群體平均圖（結果並非單次測量）；圖例與腦區名稱依 `language`
+    pub fn with_title(
+        result: &CalculationResult,
+        title: &str,
+        atlas_svg: &str,
+        options: &FigureOptions,
+        language: Language,
+    ) -> Self {
+        let layout = BrainMapLayout::from_svg(atlas_svg);
+        let top = if options.show_title { TITLE_HEIGHT } else { 0.0 };
+        let has_panel = options.show_legend || options.show_colorbar;
+        let mut shapes = Vec::new();
+
+        if options.show_title {
+            shapes.push(text(
+                [12.0, 22.0],
+                title.to_string(),
+                14.0,
+                Anchor::Start,
+                true,
+            ));
+        }
+
+        // 以平移後的座標繪製腦圖
+        let offset = |p: [f32; 2]| [p[0], p[1] + top];
+        if let Some(outline) = &layout.outline {
+            let commands = parse_path(outline)
+                .into_iter()
+                .map(|command| match command {
+                    PathCommand::MoveTo(p) => PathCommand::MoveTo(offset(p)),
+                    PathCommand::LineTo(p) => PathCommand::LineTo(offset(p)),
+                    PathCommand::CubicTo(a, b, p) => PathCommand::CubicTo(offset(a), offset(b), offset(p)),
+                    PathCommand::Close => PathCommand::Close,
+                })
+                .collect();
+            shapes.push(Shape::Path {
+                commands,
+                fill: Some(OUTLINE_FILL),
+                stroke: Some((TEXT_COLOR, 2.0)),
+            });
+        }
+
+        let placed = layout.place(&result.impact_scores);
+        // 沒有分數的腦區以中性色顯示，作為解剖位置參考
+        for region in &layout.regions {
+            if placed.iter().any(|p| p.map_region_id.as_deref() == Some(region.id.as_str())) {
+                continue;
+            }
+            shapes.push(Shape::Circle {
+                center: offset(region.center),
+                radius: region.radius,
+                fill: NEUTRAL_FILL,
+                stroke: Some((NEUTRAL_STROKE, 1.5)),
+                opacity: 0.7,
+            });
+            if options.show_labels {
+                let label = region.names.first().map_or(region.id.as_str(), |n| name_in(language, n)).to_string();
+                shapes.push(text(offset([region.center[0], region.center[1] + 4.0]), label, 9.0, Anchor::Middle, false));
+            }
+        }
+        for impact in &placed {
+            let center = offset(impact.center);
+            shapes.push(Shape::Circle {
+                center,
+                radius: impact.radius,
+                fill: impact_color(impact.impact_score),
+                stroke: Some((TEXT_COLOR, 1.0)),
+                opacity: 0.9,
+            });
+        }
+        if options.show_labels {
+            for impact in &placed {
+                let center = offset(impact.center);
+                let canonical = impact
+                    .map_region_id
+                    .as_ref()
+                    .and_then(|id| layout.regions.iter().find(|r| &r.id == id))
+                    .and_then(|r| r.names.first())
+                    .unwrap_or(&impact.region);
+                shapes.push(text(
+                    [center[0], center[1] + impact.radius + 11.0],
+                    name_in(language, canonical).to_string(),
+                    10.0,
+                    Anchor::Middle,
+                    false,
+                ));
+                let score_color = if impact.impact_score >= 0.6 { [255, 255, 255] } else { TEXT_COLOR };
+                shapes.push(Shape::Text {
+                    position: [center[0], center[1] + 3.5],
+                    text: format!("{:.2}", impact.impact_score),
+                    size: 9.0,
+                    color: score_color,
+                    anchor: Anchor::Middle,
+                    bold: true,
+                });
+            }
+        }
+
+        let panel_x = layout.width + 10.0;
+        let mut panel_y = top + 20.0;
+        if options.show_legend {
+            shapes.push(text([panel_x, panel_y], tr_in(language, "figure.legend.risk").to_string(), 11.0, Anchor::Start, true));
+            for (low, high, level) in LEVEL_BANDS {
+                panel_y += 18.0;
+                shapes.push(Shape::Circle {
+                    center: [panel_x + 6.0, panel_y - 4.0],
+                    radius: 6.0,
+                    fill: level.color(),
+                    stroke: None,
+                    opacity: 1.0,
+                });
+                let range = if high >= 1.0 { format!("≥ {}", low) } else { format!("{}–{}", low, high) };
+                shapes.push(text(
+                    [panel_x + 16.0, panel_y],
+                    format!("{} ({})", level.label_in(language), range),
+                    10.0,
+                    Anchor::Start,
+                    false,
+                ));
+            }
+            panel_y += 30.0;
+        }
+        if options.show_colorbar {
+            shapes.push(text([panel_x, panel_y], tr_in(language, "figure.legend.impact").to_string(), 11.0, Anchor::Start, true));
+            let bar_top = panel_y + 10.0;
+            let bar_height = 120.0;
+            let y_of = |score: f32| bar_top + (1.0 - score) * bar_height;
+            for (low, high, level) in LEVEL_BANDS {
+                shapes.push(Shape::Rect {
+                    origin: [panel_x, y_of(high)],
+                    size: [16.0, y_of(low) - y_of(high)],
+                    fill: level.color(),
+                });
+            }
+            for tick in [0.0, 0.3, 0.6, 0.8, 1.0] {
+                shapes.push(Shape::Rect {
+                    origin: [panel_x + 16.0, y_of(tick) - 0.5],
+                    size: [4.0, 1.0],
+                    fill: TEXT_COLOR,
+                });
+                shapes.push(text([panel_x + 24.0, y_of(tick) + 3.5], format!("{:.1}", tick), 9.0, Anchor::Start, false));
+            }
+        }
+
+        let width = layout.width + if has_panel { PANEL_WIDTH } else { 0.0 };
+        let height = top + layout.height.max(if has_panel { PANEL_HEIGHT } else { 0.0 });
+        Figure { width, height, shapes }
+    }
+
+    /// 輸出為獨立的 SVG 文件
+    pub fn to_svg(&self) -> String {
+        let mut svg = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" xmlns=\"http://www.w3.org/2000/svg\" \
+             font-family=\"'Microsoft JhengHei', 'PingFang TC', 'Noto Sans CJK TC', sans-serif\">\n\
+             <rect width=\"{w}\" height=\"{h}\" fill=\"#ffffff\"/>\n",
+            w = self.width,
+            h = self.height
+        );
+        for shape in &self.shapes {
+            svg.push_str(&shape_to_svg(shape));
+            svg.push('\n');
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// 以指定像素寬度點陣化為 PNG；`font` 為單一字型（TTF/OTF）內容，傳入 `None` 表示不繪製文字。
+    /// 需要自動選字型時使用 [`write_figure`]
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, pixel_width: u32, font: Option<&[u8]>) -> Result<Vec<u8>> {
+        raster::render_png(self, pixel_width, font)
+    }
+}
+
+fn text(position: [f32; 2], text: String, size: f32, anchor: Anchor, bold: bool) -> Shape {
+    Shape::Text {
+        position,
+        text,
+        size,
+        color: TEXT_COLOR,
+        anchor,
+        bold,
+    }
+}
+
+fn stroke_attrs(stroke: &Option<([u8; 3], f32)>) -> String {
+    match stroke {
+        Some((color, width)) => format!(" stroke=\"{}\" stroke-width=\"{}\"", hex_color(*color), width),
+        None => String::new(),
+    }
+}
+
+fn shape_to_svg(shape: &Shape) -> String {
+    match shape {
+        Shape::Path { commands, fill, stroke } => {
+            let d: Vec<String> = commands
+                .iter()
+                .map(|command| match command {
+                    PathCommand::MoveTo(p) => format!("M{} {}", p[0], p[1]),
+                    PathCommand::LineTo(p) => format!("L{} {}", p[0], p[1]),
+                    PathCommand::CubicTo(a, b, p) => {
+                        format!("C{} {} {} {} {} {}", a[0], a[1], b[0], b[1], p[0], p[1])
+                    }
+                    PathCommand::Close => "Z".to_string(),
+                })
+                .collect();
+            format!(
+                "<path d=\"{}\" fill=\"{}\"{}/>",
+                d.join(" "),
+                fill.map_or("none".to_string(), hex_color),
+                stroke_attrs(stroke)
+            )
+        }
+        Shape::Circle {
+            center,
+            radius,
+            fill,
+            stroke,
+            opacity,
+        } => format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" opacity=\"{}\"{}/>",
+            center[0],
+            center[1],
+            radius,
+            hex_color(*fill),
+            opacity,
+            stroke_attrs(stroke)
+        ),
+        Shape::Rect { origin, size, fill } => format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            origin[0],
+            origin[1],
+            size[0],
+            size[1],
+            hex_color(*fill)
+        ),
+        Shape::Text {
+            position,
+            text,
+            size,
+            color,
+            anchor,
+            bold,
+        } => format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\" text-anchor=\"{}\"{}>{}</text>",
+            position[0],
+            position[1],
+            size,
+            hex_color(*color),
+            match anchor {
+                Anchor::Start => "start",
+                Anchor::Middle => "middle",
+                Anchor::End => "end",
+            },
+            if *bold { " font-weight=\"bold\"" } else { "" },
+            escape_xml(text)
+        ),
+    }
+}
+
+/// 圖檔格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    /// 指定輸出寬度（像素），高度依比例計算
+    #[cfg(feature = "png")]
+    Png { width: u32 },
+}
+
+impl ImageFormat {
+    /// 依副檔名判斷格式；PNG 使用 `png_width` 作為輸出寬度
+    #[cfg_attr(not(feature = "png"), allow(unused_variables))]
+    pub fn from_path(path: &Path, png_width: u32) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        match extension.as_str() {
+            "svg" => Ok(ImageFormat::Svg),
+            #[cfg(feature = "png")]
+            "png" => Ok(ImageFormat::Png { width: png_width }),
+            _ => Err(BrainVizError::Export(format!("不支援的圖檔格式: {}", path.display()))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            #[cfg(feature = "png")]
+            ImageFormat::Png { .. } => "png",
+        }
+    }
+}
+
+/// 將計算結果的腦圖寫入檔案；文字語言依 `language`
+///
+/// PNG 的文字需要字型：依序嘗試 `font_path` 指定的字型檔 > 系統中文字型 > 內嵌備用字型 > 系統的英文字型，
+/// 使用第一個涵蓋圖中所有文字的字型；都不涵蓋時回傳 [`BrainVizError::Export`]，不會輸出缺少標籤的圖檔。
+#[cfg_attr(not(feature = "png"), allow(unused_variables))]
+pub fn export_brain_map(
+    result: &CalculationResult,
+    atlas_svg: &str,
+    options: &FigureOptions,
+    language: Language,
+    format: ImageFormat,
+    font_path: Option<&Path>,
+    out: &Path,
+) -> Result<()> {
+    write_figure(&Figure::from_result(result, atlas_svg, options, language), format, font_path, out)
+}
+
+/// 將已建立的腦圖寫入檔案，字型規則同 [`export_brain_map`]
+#[cfg_attr(not(feature = "png"), allow(unused_variables))]
+pub fn write_figure(figure: &Figure, format: ImageFormat, font_path: Option<&Path>, out: &Path) -> Result<()> {
+    let bytes = match format {
+        ImageFormat::Svg => figure.to_svg().into_bytes(),
+        #[cfg(feature = "png")]
+        ImageFormat::Png { width } => {
+            let candidates = crate::fonts::load_cjk_font(font_path)
+                .map(|font| font.data)
+                .into_iter()
+                .chain(crate::fonts::find_fallback_font().and_then(|font| crate::fonts::load_font_bytes(&font.path).ok()));
+            let font = raster::select_font(figure, candidates)?;
+            figure.to_png(width, font.as_deref())?
+        }
+    };
+    std::fs::write(out, bytes).map_err(|e| BrainVizError::io(out, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{load_behavior_brain_map, MappingEngine};
+    use crate::model::BehaviorInput;
+
+    pub(super) fn sample() -> (CalculationResult, String) {
+        let base = env!("CARGO_MANIFEST_DIR");
+        let engine = MappingEngine::new(load_behavior_brain_map(format!("{}/data/behavior_brain_map.json", base)).unwrap());
+        let result = engine
+            .calculate_impact(&BehaviorInput {
+                behavior_type: "自然步行".to_string(),
+                value: 0.8,
+                unit: "公尺/秒".to_string(),
+                timestamp: chrono::Utc::now(),
+            })
+            .unwrap();
+        let atlas_svg = std::fs::read_to_string(format!("{}/assets/brain.svg", base)).unwrap();
+        (result, atlas_svg)
+    }
+
+    #[test]
+    fn test_figure_to_svg() {
+        let (result, atlas_svg) = sample();
+        let figure = Figure::from_result(&result, &atlas_svg, &FigureOptions::default(), Language::ZhTw);
+        let svg = figure.to_svg();
+
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        // 色條含四個等級區間，圖例與標題存在
+        assert!(svg.contains("影響分數"));
+        assert!(svg.contains(&format!("{} (≥ 0.8)", RiskLevel::Critical.label_in(Language::ZhTw))));
+        assert!(svg.contains("自然步行"));
+        assert!(svg.contains(&hex_color(impact_color(result.impact_scores[0].impact_score))));
+        assert!(figure.width > 400.0);
+    }
+
+    #[test]
+    fn test_figure_options() {
+        let (result, atlas_svg) = sample();
+        let options = FigureOptions {
+            show_title: false,
+            show_labels: false,
+            show_legend: false,
+            show_colorbar: false,
+        };
+        let figure = Figure::from_result(&result, &atlas_svg, &options, Language::ZhTw);
+        assert_eq!((figure.width, figure.height), (400.0, 300.0));
+        assert!(!figure.shapes.iter().any(|s| matches!(s, Shape::Text { .. })));
+    }
+
+    #[test]
+    fn test_figure_language() {
+        let (result, atlas_svg) = sample();
+        let svg = Figure::from_result(&result, &atlas_svg, &FigureOptions::default(), Language::En).to_svg();
+
+        assert!(svg.contains(&format!("{} (≥ 0.8)", RiskLevel::Critical.label_in(Language::En))));
+        assert!(svg.contains(name_in(Language::En, "自然步行")));
+        assert!(!svg.contains("影響分數"));
+        assert!(!svg.contains("自然步行"));
+    }
+
+    #[test]
+    fn test_image_format_from_path() {
+        assert_eq!(ImageFormat::from_path(Path::new("a.SVG"), 800).unwrap(), ImageFormat::Svg);
+        assert!(ImageFormat::from_path(Path::new("a.gif"), 800).is_err());
+    }
+}
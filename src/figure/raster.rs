@@ -0,0 +1,399 @@
+//! 腦圖點陣化（`png` 功能）
+//!
+//! 以 `ab_glyph_rasterizer` 計算每個圖形的覆蓋率並混色到 RGBA 畫布。
+//! 每個圖形只在其外框範圍內點陣化，超出畫布的部分直接略過。
+
+use super::{Anchor, Figure, Shape};
+use crate::brain_map::PathCommand;
+use crate::error::{BrainVizError, Result};
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use ab_glyph_rasterizer::{point, Rasterizer};
+
+/// 貝茲曲線近似圓形的控制點係數
+const KAPPA: f32 = 0.552_284_8;
+/// 描邊時每段三次曲線切分的線段數
+const CURVE_STEPS: usize = 16;
+
+pub(super) fn render_png(figure: &Figure, pixel_width: u32, font: Option<&[u8]>) -> Result<Vec<u8>> {
+    if pixel_width == 0 || pixel_width > 16_384 {
+        return Err(BrainVizError::Export(format!("PNG 寬度需介於 1 至 16384 像素，實際為 {}", pixel_width)));
+    }
+    let font = font
+        .map(|bytes| FontVec::try_from_vec(bytes.to_vec()))
+        .transpose()
+        .map_err(|e| BrainVizError::Export(format!("無法解析字型: {}", e)))?;
+
+    let scale = pixel_width as f32 / figure.width;
+    let height = (figure.height * scale).round().max(1.0) as u32;
+    let mut canvas = Canvas::new(pixel_width, height);
+
+    for shape in &figure.shapes {
+        match shape {
+            Shape::Path { commands, fill, stroke } => {
+                let contours = flatten(commands, scale);
+                if let Some(fill) = fill {
+                    canvas.fill(&contours, *fill, 1.0);
+                }
+                if let Some((color, width)) = stroke {
+                    canvas.stroke(&contours, *color, width * scale);
+                }
+            }
+            Shape::Circle {
+                center,
+                radius,
+                fill,
+                stroke,
+                opacity,
+            } => {
+                let contours = flatten(&circle(*center, *radius), scale);
+                canvas.fill(&contours, *fill, *opacity);
+                if let Some((color, width)) = stroke {
+                    canvas.stroke(&contours, *color, width * scale);
+                }
+            }
+            Shape::Rect { origin, size, fill } => {
+                let [x, y] = *origin;
+                let [w, h] = *size;
+                let rect = vec![
+                    PathCommand::MoveTo([x, y]),
+                    PathCommand::LineTo([x + w, y]),
+                    PathCommand::LineTo([x + w, y + h]),
+                    PathCommand::LineTo([x, y + h]),
+                    PathCommand::Close,
+                ];
+                canvas.fill(&flatten(&rect, scale), *fill, 1.0);
+            }
+            Shape::Text {
+                position,
+                text,
+                size,
+                color,
+                anchor,
+                bold,
+            } => {
+                if let Some(font) = &font {
+                    let origin = [position[0] * scale, position[1] * scale];
+                    canvas.text(font, text, size * scale, origin, *anchor, *color, *bold);
+                }
+            }
+        }
+    }
+    canvas.encode()
+}
+
+/// 選出第一個涵蓋圖中所有文字的字型；圖中沒有文字時回傳 `None`
+pub(super) fn select_font(figure: &Figure, candidates: impl IntoIterator<Item = Vec<u8>>) -> Result<Option<Vec<u8>>> {
+    let mut chars: Vec<char> = figure
+        .shapes
+        .iter()
+        .filter_map(|shape| match shape {
+            Shape::Text { text, .. } => Some(text.chars()),
+            _ => None,
+        })
+        .flatten()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    chars.sort_unstable();
+    chars.dedup();
+    if chars.is_empty() {
+        return Ok(None);
+    }
+
+    let mut fewest_missing: Option<Vec<char>> = None;
+    for data in candidates {
+        let Ok(font) = FontVec::try_from_vec(data.clone()) else {
+            continue;
+        };
+        let missing: Vec<char> = chars.iter().copied().filter(|c| font.glyph_id(*c).0 == 0).collect();
+        if missing.is_empty() {
+            return Ok(Some(data));
+        }
+        if fewest_missing.as_ref().is_none_or(|best| missing.len() < best.len()) {
+            fewest_missing = Some(missing);
+        }
+    }
+    Err(BrainVizError::Export(match fewest_missing {
+        Some(missing) => format!(
+            "找到的字型缺少圖中文字「{}」，PNG 無法繪製標籤、圖例與色條；請以 --font 指定中文字型檔，或以 --lang en 輸出英文圖檔",
+            missing.iter().take(10).collect::<String>()
+        ),
+        None => "找不到可用的字型，PNG 無法繪製標籤、圖例與色條；請以 --font 指定字型檔".to_string(),
+    }))
+}
+
+/// 以四段三次貝茲曲線表示的圓形
+fn circle(center: [f32; 2], radius: f32) -> Vec<PathCommand> {
+    let [cx, cy] = center;
+    let k = radius * KAPPA;
+    vec![
+        PathCommand::MoveTo([cx + radius, cy]),
+        PathCommand::CubicTo([cx + radius, cy + k], [cx + k, cy + radius], [cx, cy + radius]),
+        PathCommand::CubicTo([cx - k, cy + radius], [cx - radius, cy + k], [cx - radius, cy]),
+        PathCommand::CubicTo([cx - radius, cy - k], [cx - k, cy - radius], [cx, cy - radius]),
+        PathCommand::CubicTo([cx + k, cy - radius], [cx + radius, cy - k], [cx + radius, cy]),
+        PathCommand::Close,
+    ]
+}
+
+/// 將 path 指令縮放並展開為折線輪廓（每個子路徑一條，皆視為封閉）
+fn flatten(commands: &[PathCommand], scale: f32) -> Vec<Vec<[f32; 2]>> {
+    let s = |p: [f32; 2]| [p[0] * scale, p[1] * scale];
+    let mut contours = Vec::new();
+    let mut current: Vec<[f32; 2]> = Vec::new();
+    for command in commands {
+        match *command {
+            PathCommand::MoveTo(p) => {
+                if current.len() > 1 {
+                    contours.push(std::mem::take(&mut current));
+                }
+                current = vec![s(p)];
+            }
+            PathCommand::LineTo(p) => current.push(s(p)),
+            PathCommand::CubicTo(a, b, p) => {
+                let Some(&p0) = current.last() else {
+                    continue;
+                };
+                let (p1, p2, p3) = (s(a), s(b), s(p));
+                for step in 1..=CURVE_STEPS {
+                    let t = step as f32 / CURVE_STEPS as f32;
+                    let u = 1.0 - t;
+                    let w = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+                    current.push([
+                        w[0] * p0[0] + w[1] * p1[0] + w[2] * p2[0] + w[3] * p3[0],
+                        w[0] * p0[1] + w[1] * p1[1] + w[2] * p2[1] + w[3] * p3[1],
+                    ]);
+                }
+            }
+            PathCommand::Close => {
+                if current.len() > 1 {
+                    contours.push(std::mem::take(&mut current));
+                }
+            }
+        }
+    }
+    if current.len() > 1 {
+        contours.push(current);
+    }
+    contours
+}
+
+/// RGBA 畫布（白色背景）
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![255; (width * height * 4) as usize],
+        }
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: [u8; 3], alpha: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 || alpha <= 0.0 {
+            return;
+        }
+        let alpha = alpha.min(1.0);
+        let index = ((y as u32 * self.width + x as u32) * 4) as usize;
+        for (channel, value) in color.iter().enumerate() {
+            let dst = self.pixels[index + channel] as f32;
+            self.pixels[index + channel] = (dst + (*value as f32 - dst) * alpha).round() as u8;
+        }
+    }
+
+    /// 填滿封閉折線（非零環繞規則）
+    fn fill(&mut self, contours: &[Vec<[f32; 2]>], color: [u8; 3], opacity: f32) {
+        let points = contours.iter().flatten();
+        let (min_x, min_y) = points.clone().fold((f32::MAX, f32::MAX), |(x, y), p| (x.min(p[0]), y.min(p[1])));
+        let (max_x, max_y) = points.fold((f32::MIN, f32::MIN), |(x, y), p| (x.max(p[0]), y.max(p[1])));
+        if !(min_x.is_finite() && max_x.is_finite()) || max_x <= min_x || max_y <= min_y {
+            return;
+        }
+
+        // 只在外框範圍內點陣化
+        let (origin_x, origin_y) = (min_x.floor(), min_y.floor());
+        let width = (max_x - origin_x).ceil() as usize + 2;
+        let height = (max_y - origin_y).ceil() as usize + 2;
+        let mut rasterizer = Rasterizer::new(width, height);
+        for contour in contours {
+            for (i, p0) in contour.iter().enumerate() {
+                let p1 = contour[(i + 1) % contour.len()];
+                rasterizer.draw_line(
+                    point(p0[0] - origin_x, p0[1] - origin_y),
+                    point(p1[0] - origin_x, p1[1] - origin_y),
+                );
+            }
+        }
+        rasterizer.for_each_pixel_2d(|x, y, coverage| {
+            self.blend(origin_x as i64 + x as i64, origin_y as i64 + y as i64, color, coverage * opacity);
+        });
+    }
+
+    /// 以固定寬度描繪封閉折線的邊
+    fn stroke(&mut self, contours: &[Vec<[f32; 2]>], color: [u8; 3], width: f32) {
+        let half = (width / 2.0).max(0.5);
+        for contour in contours {
+            for (i, p0) in contour.iter().enumerate() {
+                let p1 = contour[(i + 1) % contour.len()];
+                let (dx, dy) = (p1[0] - p0[0], p1[1] - p0[1]);
+                let length = (dx * dx + dy * dy).sqrt();
+                if length < f32::EPSILON {
+                    continue;
+                }
+                // 沿線段方向各延長半個線寬，讓相鄰線段接合處不留縫
+                let (ux, uy) = (dx / length * half, dy / length * half);
+                let (nx, ny) = (-uy, ux);
+                let quad = vec![
+                    [p0[0] - ux + nx, p0[1] - uy + ny],
+                    [p1[0] + ux + nx, p1[1] + uy + ny],
+                    [p1[0] + ux - nx, p1[1] + uy - ny],
+                    [p0[0] - ux - nx, p0[1] - uy - ny],
+                ];
+                self.fill(&[quad], color, 1.0);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn text(&mut self, font: &FontVec, text: &str, size: f32, origin: [f32; 2], anchor: Anchor, color: [u8; 3], bold: bool) {
+        let scaled = font.as_scaled(PxScale::from(size * 1.25));
+        let width: f32 = text.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum();
+        let mut x = match anchor {
+            Anchor::Start => origin[0],
+            Anchor::Middle => origin[0] - width / 2.0,
+            Anchor::End => origin[0] - width,
+        };
+
+        // 粗體以水平偏移重疊繪製模擬
+        let passes: &[f32] = if bold { &[0.0, 0.6] } else { &[0.0] };
+        for c in text.chars() {
+            let glyph_id = scaled.glyph_id(c);
+            for dx in passes {
+                let glyph = glyph_id.with_scale_and_position(scaled.scale, ab_glyph::point(x + dx, origin[1]));
+                if let Some(outlined) = font.outline_glyph(glyph) {
+                    let bounds = outlined.px_bounds();
+                    outlined.draw(|gx, gy, coverage| {
+                        self.blend(
+                            bounds.min.x as i64 + gx as i64,
+                            bounds.min.y as i64 + gy as i64,
+                            color,
+                            coverage,
+                        );
+                    });
+                }
+            }
+            x += scaled.h_advance(glyph_id);
+        }
+    }
+
+    fn encode(self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let export_error = |e: png::EncodingError| BrainVizError::Export(e.to_string());
+        let mut writer = encoder.write_header().map_err(export_error)?;
+        writer.write_image_data(&self.pixels).map_err(export_error)?;
+        writer.finish().map_err(export_error)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::sample;
+    use super::super::FigureOptions;
+    use crate::i18n::Language;
+    use super::*;
+
+    #[test]
+    fn test_fill_circle_coverage() {
+        let mut canvas = Canvas::new(20, 20);
+        canvas.fill(&flatten(&circle([10.0, 10.0], 6.0), 1.0), [0, 0, 0], 1.0);
+        let pixel = |x: u32, y: u32| canvas.pixels[((y * 20 + x) * 4) as usize];
+        assert_eq!(pixel(10, 10), 0); // 圓心塗滿
+        assert_eq!(pixel(1, 1), 255); // 角落維持背景
+    }
+
+    #[test]
+    fn test_fill_outside_canvas_is_clipped() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.fill(&flatten(&circle([-5.0, 30.0], 20.0), 1.0), [0, 0, 0], 1.0);
+        canvas.stroke(&flatten(&circle([50.0, 50.0], 5.0), 1.0), [0, 0, 0], 2.0);
+    }
+
+    #[test]
+    fn test_render_png() {
+        let (result, atlas_svg) = sample();
+        // 英文圖只需要拉丁字型，與 PDF 報告測試相同使用系統的備用字型
+        let figure = Figure::from_result(&result, &atlas_svg, &FigureOptions::default(), Language::En);
+        let fallback = crate::fonts::find_fallback_font().expect("找不到備用字型（如 DejaVu Sans），無法測試文字點陣化");
+        let font = crate::fonts::load_font_bytes(&fallback.path).unwrap();
+        let decode = |png: &[u8]| {
+            assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+            let mut reader = png::Decoder::new(png).read_info().unwrap();
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut pixels).unwrap();
+            (info.width, info.height, pixels)
+        };
+
+        let (width, height, with_text) = decode(&render_png(&figure, 1080, Some(&font)).unwrap());
+        assert_eq!(width, 1080);
+        assert_eq!(height, (figure.height * 1080.0 / figure.width).round() as u32);
+
+        // 與不繪製文字的輸出比較，確認標題、標籤與圖例確實畫出
+        let (_, _, without_text) = decode(&render_png(&figure, 1080, None).unwrap());
+        let text_pixels = with_text.chunks_exact(4).zip(without_text.chunks_exact(4)).filter(|(a, b)| a != b).count();
+        assert!(text_pixels > 2000, "文字像素只有 {} 個", text_pixels);
+
+        assert!(render_png(&figure, 0, None).is_err());
+    }
+
+    fn text_figure(text: &str) -> Figure {
+        Figure {
+            width: 200.0,
+            height: 40.0,
+            shapes: vec![Shape::Text {
+                position: [10.0, 30.0],
+                text: text.to_string(),
+                size: 20.0,
+                color: [0, 0, 0],
+                anchor: Anchor::Start,
+                bold: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_select_font() {
+        // 沒有字型時回傳錯誤，而不是輸出沒有文字的圖檔
+        let figure = text_figure("Cerebellum 0.80");
+        assert!(matches!(select_font(&figure, Vec::new()), Err(BrainVizError::Export(_))));
+        assert!(select_font(&Figure { width: 1.0, height: 1.0, shapes: Vec::new() }, Vec::new()).unwrap().is_none());
+
+        let Some(fallback) = crate::fonts::find_fallback_font() else {
+            eprintln!("警告：找不到備用字型，略過文字點陣化檢查");
+            return;
+        };
+        let data = crate::fonts::load_font_bytes(&fallback.path).unwrap();
+
+        // 選到的字型確實畫出文字
+        let font = select_font(&figure, vec![data.clone()]).unwrap().unwrap();
+        let png = render_png(&figure, 200, Some(&font)).unwrap();
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        let dark = pixels.chunks_exact(4).filter(|p| p[0] < 128).count();
+        assert!(dark > 50, "文字像素只有 {} 個", dark);
+
+        // 不涵蓋中文的字型不會被選用
+        if FontVec::try_from_vec(data.clone()).unwrap().glyph_id('腦').0 == 0 {
+            let err = select_font(&text_figure("小腦"), vec![data]).unwrap_err();
+            assert!(err.to_string().contains('腦'));
+        }
+    }
+}
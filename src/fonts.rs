@@ -0,0 +1,333 @@
+use crate::error::{BrainVizError, Result};
+use std::path::{Path, PathBuf};
+
+/// 候選中文字體（名稱, 路徑），依目前平台選用
+#[cfg(target_os = "windows")]
+pub const CJK_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("Microsoft JhengHei", "C:/Windows/Fonts/msjh.ttc"),
+    ("Microsoft YaHei", "C:/Windows/Fonts/msyh.ttc"),
+    ("DFKai-SB", "C:/Windows/Fonts/kaiu.ttf"),
+    ("SimSun", "C:/Windows/Fonts/simsun.ttc"),
+];
+
+#[cfg(target_os = "macos")]
+pub const CJK_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("PingFang", "/System/Library/Fonts/PingFang.ttc"),
+    ("Hiragino Sans GB", "/System/Library/Fonts/Hiragino Sans GB.ttc"),
+    ("STHeiti", "/System/Library/Fonts/STHeiti Medium.ttc"),
+    ("Heiti", "/System/Library/Fonts/Supplemental/Songti.ttc"),
+    ("Arial Unicode MS", "/Library/Fonts/Arial Unicode.ttf"),
+];
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub const CJK_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("Noto Sans CJK", "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc"),
+    ("Noto Sans CJK", "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc"),
+    ("Noto Sans CJK", "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc"),
+    ("Noto Sans CJK", "/usr/share/fonts/google-noto-sans-cjk-fonts/NotoSansCJK-Regular.ttc"),
+    ("WenQuanYi Micro Hei", "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc"),
+    ("WenQuanYi Micro Hei", "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc"),
+    ("WenQuanYi Zen Hei", "/usr/share/fonts/truetype/wqy/wqy-zenhei.ttc"),
+    ("WenQuanYi Zen Hei", "/usr/share/fonts/wenquanyi/wqy-zenhei/wqy-zenhei.ttc"),
+    ("Droid Sans Fallback", "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf"),
+    ("AR PL UMing", "/usr/share/fonts/truetype/arphic/uming.ttc"),
+];
+
+/// 掃描字型目錄時辨識中文字型的檔名關鍵字（小寫），越前面越優先
+pub const CJK_FONT_FILE_PATTERNS: &[&str] = &[
+    "notosanscjk",
+    "notosanstc",
+    "notosanssc",
+    "notosanshk",
+    "sourcehansans",
+    "wqy-microhei",
+    "wqy-zenhei",
+    "pingfang",
+    "hiragino sans gb",
+    "msjh",
+    "msyh",
+    "droidsansfallback",
+    "notoserifcjk",
+    "sourcehanserif",
+    "uming",
+    "ukai",
+];
+
+/// 沒有中文字型時，點陣化英文標籤可用的字型檔名關鍵字（小寫），越前面越優先
+pub const FALLBACK_FONT_FILE_PATTERNS: &[&str] = &[
+    "dejavusans.",
+    "liberationsans-regular",
+    "notosans-regular",
+    "arial.",
+    "helvetica",
+    "freesans.",
+    "segoeui.",
+];
+
+/// 掃描字型目錄的最大深度
+const FONT_SCAN_DEPTH: usize = 4;
+
+/// 內嵌的備用中文字型（`bundled-font` 功能且字型檔存在時，見 `build.rs`）
+#[cfg(bundled_font)]
+pub const BUNDLED_FONT: &[u8] = include_bytes!("../assets/fonts/NotoSansTC-Regular.ttf");
+
+/// 內嵌備用字型的名稱
+#[cfg(bundled_font)]
+pub const BUNDLED_FONT_NAME: &str = "Noto Sans TC";
+
+/// 找到的字體
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontSource {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl FontSource {
+    /// 以檔名（不含副檔名）作為字型名稱
+    fn from_path(path: PathBuf) -> Self {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self { name, path }
+    }
+}
+
+/// 已載入的中文字型內容；TTC 已取出第一個字型
+#[derive(Debug, Clone)]
+pub struct CjkFont {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// 尋找系統中文字體：先檢查平台的常見路徑，再掃描字型目錄
+pub fn find_cjk_font() -> Option<FontSource> {
+    CJK_FONT_CANDIDATES
+        .iter()
+        .find(|(_, path)| Path::new(path).is_file())
+        .map(|(name, path)| FontSource {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+        })
+        .or_else(|| find_cjk_font_in(&system_font_dirs()))
+}
+
+/// 優先使用 `custom` 指定的字型檔（存在時），否則尋找系統中文字體
+pub fn resolve_cjk_font(custom: Option<&Path>) -> Option<FontSource> {
+    custom
+        .filter(|path| path.is_file())
+        .map(|path| FontSource::from_path(path.to_path_buf()))
+        .or_else(find_cjk_font)
+}
+
+/// 載入中文字型：`custom` 指定的字型檔 > 系統字體 > 內嵌備用字型（`bundled-font` 功能）
+pub fn load_cjk_font(custom: Option<&Path>) -> Option<CjkFont> {
+    let system = resolve_cjk_font(custom).and_then(|font| {
+        let data = load_font_bytes(&font.path).ok()?;
+        Some(CjkFont { name: font.name, data })
+    });
+
+    #[cfg(bundled_font)]
+    let system = system.or_else(|| {
+        Some(CjkFont {
+            name: BUNDLED_FONT_NAME.to_string(),
+            data: BUNDLED_FONT.to_vec(),
+        })
+    });
+
+    system
+}
+
+/// 平台的字型目錄（含使用者字型目錄）
+pub fn system_font_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "windows") {
+        dirs.push(PathBuf::from("C:/Windows/Fonts"));
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local).join("Microsoft").join("Windows").join("Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        dirs.extend(home.map(|home| home.join("Library").join("Fonts")));
+    } else {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(".local").join("share")));
+        dirs.extend(data_home.map(|dir| dir.join("fonts")));
+        dirs.extend(home.map(|home| home.join(".fonts")));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+    }
+    dirs
+}
+
+/// 在指定目錄（含子目錄）中依 [`CJK_FONT_FILE_PATTERNS`] 的優先順序尋找中文字型
+pub fn find_cjk_font_in(dirs: &[PathBuf]) -> Option<FontSource> {
+    find_font_in(dirs, CJK_FONT_FILE_PATTERNS)
+}
+
+fn find_font_in(dirs: &[PathBuf], patterns: &[&str]) -> Option<FontSource> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        collect_font_files(dir, FONT_SCAN_DEPTH, &mut files);
+    }
+
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+            let rank = patterns
+                .iter()
+                .position(|pattern| file_name.contains(pattern))?;
+            // 同一關鍵字優先選 Regular
+            let not_regular = !file_name.contains("regular");
+            Some((rank, not_regular, path))
+        })
+        .min()
+        .map(|(_, _, path)| FontSource::from_path(path))
+}
+
+/// 在系統字型目錄中尋找非中文的備用字型（見 [`FALLBACK_FONT_FILE_PATTERNS`]）
+pub fn find_fallback_font() -> Option<FontSource> {
+    find_font_in(&system_font_dirs(), FALLBACK_FONT_FILE_PATTERNS)
+}
+
+fn collect_font_files(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+        if path.is_dir() {
+            if depth > 0 {
+                collect_font_files(&path, depth - 1, files);
+            }
+        } else if path.extension().is_some_and(|ext| {
+            let ext = ext.to_string_lossy().to_lowercase();
+            matches!(ext.as_str(), "ttf" | "ttc" | "otf" | "otc")
+        }) {
+            files.push(path);
+        }
+    }
+}
+
+/// 讀取字體檔；TrueType Collection (.ttc) 會取出第一個字型成為獨立的 TTF
+///
+/// PDF 內嵌字型只接受單一字型，因此匯出報告時需要此轉換。
+pub fn load_font_bytes(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|e| BrainVizError::io(path, e))?;
+    if data.starts_with(b"ttcf") {
+        extract_ttc_face(&data, 0).ok_or_else(|| {
+            BrainVizError::Config(format!("無法解析字型集合 {}", path.display()))
+        })
+    } else {
+        Ok(data)
+    }
+}
+
+/// 從 TrueType Collection 取出第 `index` 個字型，重建為獨立的字型檔
+pub fn extract_ttc_face(data: &[u8], index: usize) -> Option<Vec<u8>> {
+    let u16_at = |pos: usize| data.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    let u32_at = |pos: usize| data.get(pos..pos + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+
+    if !data.starts_with(b"ttcf") {
+        return None;
+    }
+    let num_fonts = u32_at(8)? as usize;
+    if index >= num_fonts {
+        return None;
+    }
+    let face_offset = u32_at(12 + index * 4)? as usize;
+    let num_tables = u16_at(face_offset + 4)? as usize;
+    let header_len = 12 + 16 * num_tables;
+
+    // 複製 offset table 標頭，再依序附加各表格並改寫偏移量
+    let mut out = data.get(face_offset..face_offset + header_len)?.to_vec();
+    for i in 0..num_tables {
+        let record = face_offset + 12 + i * 16;
+        let table_offset = u32_at(record + 8)? as usize;
+        let table_len = u32_at(record + 12)? as usize;
+
+        let new_offset = out.len() as u32;
+        out[12 + i * 16 + 8..12 + i * 16 + 12].copy_from_slice(&new_offset.to_be_bytes());
+        out.extend_from_slice(data.get(table_offset..table_offset + table_len)?);
+        while out.len() % 4 != 0 {
+            out.push(0);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 建立含兩個字型、各一個表格的最小 TTC
+    fn tiny_ttc() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"ttcf");
+        data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend_from_slice(&20u32.to_be_bytes()); // 字型 0 的 offset table
+        data.extend_from_slice(&48u32.to_be_bytes()); // 字型 1 的 offset table
+
+        for table_offset in [76u32, 80u32] {
+            data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+            data.extend_from_slice(&1u16.to_be_bytes());
+            data.extend_from_slice(&[0; 6]);
+            data.extend_from_slice(b"name");
+            data.extend_from_slice(&0u32.to_be_bytes());
+            data.extend_from_slice(&table_offset.to_be_bytes());
+            data.extend_from_slice(&3u32.to_be_bytes());
+        }
+        data.extend_from_slice(b"AAA\0BBB\0");
+        data
+    }
+
+    #[test]
+    fn test_extract_ttc_face() {
+        let data = tiny_ttc();
+
+        let face = extract_ttc_face(&data, 1).unwrap();
+        assert_eq!(&face[0..4], &0x0001_0000u32.to_be_bytes());
+        assert_eq!(&face[12..16], b"name");
+        let offset = u32::from_be_bytes([face[20], face[21], face[22], face[23]]) as usize;
+        assert_eq!(offset, 28);
+        assert_eq!(&face[offset..offset + 3], b"BBB");
+
+        assert!(extract_ttc_face(&data, 2).is_none());
+        assert!(extract_ttc_face(b"not a font", 0).is_none());
+    }
+
+    #[test]
+    fn test_find_cjk_font_in() {
+        let dir = std::env::temp_dir().join(format!("bbv_fonts_{}", std::process::id()));
+        let nested = dir.join("truetype").join("wqy");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(dir.join("opentype")).unwrap();
+        for path in [
+            dir.join("DejaVuSans.ttf"),
+            nested.join("wqy-microhei.ttc"),
+            dir.join("opentype").join("NotoSansCJK-Bold.ttc"),
+            dir.join("opentype").join("NotoSansCJK-Regular.ttc"),
+            dir.join("opentype").join("NotoSansCJK-Regular.txt"),
+        ] {
+            std::fs::write(path, b"").unwrap();
+        }
+
+        let font = find_cjk_font_in(std::slice::from_ref(&dir)).unwrap();
+        assert_eq!(font.name, "NotoSansCJK-Regular");
+        assert_eq!(font.path, dir.join("opentype").join("NotoSansCJK-Regular.ttc"));
+
+        std::fs::remove_dir_all(dir.join("opentype")).unwrap();
+        assert_eq!(find_cjk_font_in(std::slice::from_ref(&dir)).unwrap().name, "wqy-microhei");
+        assert!(find_cjk_font_in(&[dir.join("missing")]).is_none());
+
+        // 指定的字型檔優先於系統字型
+        let custom = dir.join("DejaVuSans.ttf");
+        assert_eq!(resolve_cjk_font(Some(&custom)).unwrap().path, custom);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,429 @@
+//! 步態與加速度計資料處理
+//!
+//! 穿戴式裝置（腰部、下背或手機放在口袋）的三軸加速度 CSV 每列一個取樣。
+//! 以加速度向量長度偵測步伐（[`detect_steps`]），推導步行速度、步頻、步幅時間變異係數與步時間不對稱（[`GaitAnalysis`]），
+//! 再交給 [`TaskProfiles`](crate::trials::TaskProfiles) 以 `gait` 作業對應到行為。
+//!
+//! 檔案讀取沿用 [`TrialTable`]：編碼、分隔符號、受試者欄位與 `角色=欄位名稱` 覆寫的規則相同。
+
+use crate::error::{BrainVizError, Result};
+use crate::trials::{parse_numbers, ColumnOverrides, ColumnRole, DerivedMetric, Metric, SubjectMetrics, TrialTable};
+
+/// 標準重力加速度（m/s²）
+pub const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// Weinberg 步長模型的預設係數；腰部配戴時常見值約 0.4–0.5，有步行距離時建議改用距離
+pub const DEFAULT_WEINBERG_K: f64 = 0.45;
+
+/// 以身高推估步長的比例（步長約為身高的 0.415 倍）
+pub const STEP_LENGTH_PER_HEIGHT: f64 = 0.415;
+
+/// 平滑視窗（秒）
+const SMOOTHING_SECS: f64 = 0.2;
+
+/// 兩步之間的最短間隔（秒），相當於每分鐘 200 步
+const MIN_STEP_INTERVAL: f64 = 0.3;
+
+/// 超過此間隔（秒）視為停頓，不計入步頻與變異
+const MAX_STEP_INTERVAL: f64 = 2.0;
+
+/// 推導指標所需的最少步數
+const MIN_STEPS: usize = 4;
+
+/// 步態分析選項
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaitOptions {
+    /// 沒有時間欄位時的取樣率（Hz）
+    pub sample_rate: Option<f64>,
+    /// 紀錄期間實際走的距離（公尺），例如 10 公尺步行測驗；優先用於計算步長
+    pub distance_m: Option<f64>,
+    /// 受試者身高（公尺）；沒有距離時以身高推估步長
+    pub height_m: Option<f64>,
+    /// 兩者皆無時以 Weinberg 模型 `K × (最大 − 最小加速度)^¼` 推估步長
+    pub weinberg_k: f64,
+}
+
+impl Default for GaitOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: None,
+            distance_m: None,
+            height_m: None,
+            weinberg_k: DEFAULT_WEINBERG_K,
+        }
+    }
+}
+
+/// 加速度向量長度的時間序列
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccelerationSignal {
+    /// 自第一個取樣起算的秒數
+    pub times: Vec<f64>,
+    /// 加速度向量長度（m/s²，含重力）
+    pub magnitude: Vec<f64>,
+}
+
+impl AccelerationSignal {
+    /// 由試次表的列讀取訊號
+    ///
+    /// 時間欄位可為秒、毫秒、微秒或奈秒，依取樣間隔自動判斷（取樣率需高於 1 Hz）；
+    /// 沒有時間欄位時需提供取樣率。加速度單位依欄位名稱（`m/s`、`(g)`）判斷，
+    /// 無法判斷時以向量長度的中位數判斷：小於 3 視為 g。
+    pub fn from_rows(table: &TrialTable, rows: &[&Vec<String>], overrides: &ColumnOverrides, sample_rate: Option<f64>) -> Result<Self> {
+        let axis = |role| table.find_column(role, overrides).ok_or_else(|| table.missing_column(role, overrides));
+        let (x, y, z) = (axis(ColumnRole::AccX)?, axis(ColumnRole::AccY)?, axis(ColumnRole::AccZ)?);
+        let time = table.find_column(ColumnRole::Time, overrides);
+        if time.is_none() && sample_rate.is_none() {
+            return Err(table.header_error("找不到時間欄位，可用 time=欄位名稱 指定，或提供取樣率".to_string()));
+        }
+
+        let number = |row: &Vec<String>, column| table.cell(row, Some(column)).and_then(|v| parse_numbers(v).first().copied());
+        let mut raw_times = Vec::new();
+        let mut magnitude = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            let (Some(ax), Some(ay), Some(az)) = (number(row, x), number(row, y), number(row, z)) else {
+                continue;
+            };
+            let t = match (time, sample_rate) {
+                (Some(column), _) => match number(row, column) {
+                    Some(t) => t,
+                    None => continue,
+                },
+                (None, Some(rate)) => i as f64 / rate,
+                (None, None) => unreachable!(),
+            };
+            // 時間需遞增；重複或倒退的取樣略過
+            if raw_times.last().is_some_and(|last| t <= *last) {
+                continue;
+            }
+            raw_times.push(t);
+            magnitude.push((ax * ax + ay * ay + az * az).sqrt());
+        }
+        if magnitude.len() < 2 {
+            return Err(table.header_error("加速度取樣不足".to_string()));
+        }
+
+        let scale = if time.is_some() {
+            let interval = median(raw_times.windows(2).map(|w| w[1] - w[0]).collect());
+            [1.0, 1e-3, 1e-6, 1e-9].into_iter().find(|scale| interval * scale < 1.0).unwrap_or(1e-9)
+        } else {
+            1.0
+        };
+        let header = table.columns[x].to_lowercase();
+        let in_g = if header.contains("m/s") {
+            false
+        } else if header.contains("(g)") || header.ends_with("_g") {
+            true
+        } else {
+            median(magnitude.clone()) < 3.0
+        };
+        if in_g {
+            magnitude.iter_mut().for_each(|m| *m *= STANDARD_GRAVITY);
+        }
+
+        let start = raw_times[0];
+        Ok(Self {
+            times: raw_times.into_iter().map(|t| (t - start) * scale).collect(),
+            magnitude,
+        })
+    }
+
+    /// 取樣率（Hz），以取樣間隔的中位數計算
+    pub fn sample_rate(&self) -> f64 {
+        1.0 / median(self.times.windows(2).map(|w| w[1] - w[0]).collect())
+    }
+
+    /// 去除重力（平均值）後以移動平均平滑，保留每步一個波峰
+    pub fn smoothed(&self) -> Vec<f64> {
+        let mean = self.magnitude.iter().sum::<f64>() / self.magnitude.len() as f64;
+        let half = ((SMOOTHING_SECS * self.sample_rate()).round() as usize / 2).max(1);
+        (0..self.magnitude.len())
+            .map(|i| {
+                let window = &self.magnitude[i.saturating_sub(half)..(i + half + 1).min(self.magnitude.len())];
+                window.iter().sum::<f64>() / window.len() as f64 - mean
+            })
+            .collect()
+    }
+}
+
+/// 偵測步伐，回傳各步波峰的取樣索引
+///
+/// 平滑後的訊號中，高於半個標準差（且至少 0.1 m/s²）的區域最大值為一步；
+/// 與前一步相距不到 0.3 秒時保留較高者。
+pub fn detect_steps(signal: &AccelerationSignal) -> Vec<usize> {
+    let smoothed = signal.smoothed();
+    let sd = (smoothed.iter().map(|v| v * v).sum::<f64>() / smoothed.len() as f64).sqrt();
+    let threshold = (0.5 * sd).max(0.1);
+
+    let mut steps: Vec<usize> = Vec::new();
+    for i in 1..smoothed.len().saturating_sub(1) {
+        let value = smoothed[i];
+        if value <= threshold || value <= smoothed[i - 1] || value < smoothed[i + 1] {
+            continue;
+        }
+        match steps.last() {
+            Some(&last) if signal.times[i] - signal.times[last] < MIN_STEP_INTERVAL => {
+                if value > smoothed[last] {
+                    *steps.last_mut().unwrap() = i;
+                }
+            }
+            _ => steps.push(i),
+        }
+    }
+    steps
+}
+
+/// 步長的推估方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepLengthSource {
+    Distance,
+    Height,
+    Weinberg,
+}
+
+/// 一段步行紀錄的分析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaitAnalysis {
+    /// 各步的時間（秒）
+    pub step_times: Vec<f64>,
+    /// 平均步長（公尺）
+    pub step_length: f64,
+    pub step_length_source: StepLengthSource,
+    /// 步頻（步/分），不含停頓
+    pub cadence: f64,
+    /// 步行速度（公尺/秒）= 步長 × 步頻 / 60
+    pub speed: f64,
+    /// 步幅時間（同一腳兩次著地，即相隔兩步）的變異係數（%）；步幅不足三個時為 None
+    pub stride_time_cv: Option<f64>,
+    /// 奇數步與偶數步平均步時間的對稱指數 100 × |a − b| / ((a + b) / 2)（%）
+    pub step_asymmetry: Option<f64>,
+}
+
+impl GaitAnalysis {
+    /// 分析訊號；偵測到的步數不足四步時回傳 None
+    pub fn analyze(signal: &AccelerationSignal, options: &GaitOptions) -> Option<Self> {
+        let steps = detect_steps(signal);
+        if steps.len() < MIN_STEPS {
+            return None;
+        }
+        let step_times: Vec<f64> = steps.iter().map(|&i| signal.times[i]).collect();
+        let intervals: Vec<f64> = step_times.windows(2).map(|w| w[1] - w[0]).collect();
+        let valid = |interval: &f64| *interval <= MAX_STEP_INTERVAL;
+        let walking: Vec<f64> = intervals.iter().copied().filter(valid).collect();
+        if walking.is_empty() {
+            return None;
+        }
+        let cadence = 60.0 / mean(&walking);
+
+        let strides: Vec<f64> = intervals.windows(2).filter(|pair| pair.iter().all(valid)).map(|pair| pair[0] + pair[1]).collect();
+        let stride_time_cv = (strides.len() >= 3).then(|| 100.0 * sample_sd(&strides) / mean(&strides));
+
+        let alternate = |parity| -> Vec<f64> {
+            intervals.iter().copied().enumerate().filter(|(i, interval)| i % 2 == parity && valid(interval)).map(|(_, v)| v).collect()
+        };
+        let (odd, even) = (alternate(1), alternate(0));
+        let step_asymmetry = (odd.len() >= 2 && even.len() >= 2).then(|| {
+            let (a, b) = (mean(&odd), mean(&even));
+            100.0 * (a - b).abs() / ((a + b) / 2.0)
+        });
+
+        let (step_length, step_length_source) = match (options.distance_m, options.height_m) {
+            (Some(distance), _) if distance > 0.0 => (distance / steps.len() as f64, StepLengthSource::Distance),
+            (_, Some(height)) if height > 0.0 => (STEP_LENGTH_PER_HEIGHT * height, StepLengthSource::Height),
+            _ => {
+                let lengths: Vec<f64> = steps
+                    .windows(2)
+                    .filter(|pair| valid(&(signal.times[pair[1]] - signal.times[pair[0]])))
+                    .map(|pair| {
+                        let window = &signal.magnitude[pair[0]..=pair[1]];
+                        let range = window.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+                            - window.iter().copied().fold(f64::INFINITY, f64::min);
+                        options.weinberg_k * range.max(0.0).powf(0.25)
+                    })
+                    .collect();
+                (mean(&lengths), StepLengthSource::Weinberg)
+            }
+        };
+
+        Some(Self {
+            step_times,
+            step_length,
+            step_length_source,
+            cadence,
+            speed: step_length * cadence / 60.0,
+            stride_time_cv,
+            step_asymmetry,
+        })
+    }
+
+    /// 轉成 `gait` 作業的指標
+    pub fn metrics(&self) -> Vec<DerivedMetric> {
+        let steps = self.step_times.len();
+        let derived = |metric, value: f64| DerivedMetric { metric, value: value as f32, trials: steps };
+        let mut metrics = vec![derived(Metric::GaitSpeed, self.speed), derived(Metric::Cadence, self.cadence)];
+        metrics.extend(self.stride_time_cv.map(|cv| derived(Metric::StrideTimeCv, cv)));
+        metrics.extend(self.step_asymmetry.map(|si| derived(Metric::StepAsymmetry, si)));
+        metrics
+    }
+}
+
+/// 推導各受試者的步態指標
+///
+/// 個別受試者的訊號無法讀取或步伐不足時記錄在 `skipped`；所有受試者都沒有指標時回傳錯誤，
+/// 有讀取錯誤時回傳第一個。
+pub fn derive_metrics(table: &TrialTable, overrides: &ColumnOverrides, options: &GaitOptions) -> Result<Vec<SubjectMetrics>> {
+    const ALL_METRICS: [Metric; 4] = [Metric::GaitSpeed, Metric::Cadence, Metric::StrideTimeCv, Metric::StepAsymmetry];
+    let mut subjects = Vec::new();
+    let mut first_error = None;
+    for (subject_id, rows) in table.subjects(overrides) {
+        let signal = match AccelerationSignal::from_rows(table, &rows, overrides, options.sample_rate) {
+            Ok(signal) => signal,
+            Err(e) => {
+                let skipped = ALL_METRICS.map(|m| (m, e.to_string())).to_vec();
+                first_error.get_or_insert(e);
+                subjects.push(SubjectMetrics { subject_id, metrics: Vec::new(), skipped });
+                continue;
+            }
+        };
+        let (metrics, skipped) = match GaitAnalysis::analyze(&signal, options) {
+            Some(analysis) => {
+                let metrics = analysis.metrics();
+                let skipped = [Metric::StrideTimeCv, Metric::StepAsymmetry]
+                    .into_iter()
+                    .filter(|metric| !metrics.iter().any(|m| m.metric == *metric))
+                    .map(|metric| (metric, "連續步伐不足".to_string()))
+                    .collect();
+                (metrics, skipped)
+            }
+            None => {
+                let reason = format!("偵測到的步伐少於 {} 步", MIN_STEPS);
+                (Vec::new(), ALL_METRICS.map(|m| (m, reason.clone())).to_vec())
+            }
+        };
+        subjects.push(SubjectMetrics { subject_id, metrics, skipped });
+    }
+    if subjects.iter().all(|s| s.metrics.is_empty()) {
+        return Err(first_error.unwrap_or_else(|| BrainVizError::Parse {
+            path: table.path.clone(),
+            line: 1,
+            column: 1,
+            message: format!("偵測到的步伐少於 {} 步，無法推導步態指標", MIN_STEPS),
+        }));
+    }
+    Ok(subjects)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn sample_sd(values: &[f64]) -> f64 {
+    let m = mean(values);
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trials::{self, Task};
+
+    /// 100 Hz、單位 g 的合成步行紀錄：步伐間隔交替為 0.48 與 0.52 秒
+    fn walking_csv(header: &str, time_scale: f64, with_time: bool) -> String {
+        let mut step_times = Vec::new();
+        let mut t = 0.3;
+        while t < 20.0 {
+            step_times.push(t);
+            t += if step_times.len() % 2 == 1 { 0.48 } else { 0.52 };
+        }
+        let mut csv = format!("{}\n", header);
+        for i in 0..2000 {
+            let t = i as f64 / 100.0;
+            let bump: f64 = step_times.iter().map(|s| (-((t - s) / 0.05).powi(2) / 2.0).exp()).sum();
+            let z = 1.0 + 0.3 * bump;
+            if with_time {
+                csv.push_str(&format!("{},0.02,-0.01,{:.5}\n", t * time_scale, z));
+            } else {
+                csv.push_str(&format!("0.02,-0.01,{:.5}\n", z));
+            }
+        }
+        csv
+    }
+
+    #[test]
+    fn test_gait_metrics() {
+        let table = TrialTable::parse("walk.csv", &walking_csv("time,acc_x,acc_y,acc_z", 1.0, true)).unwrap();
+        let rows: Vec<&Vec<String>> = table.rows.iter().collect();
+        let signal = AccelerationSignal::from_rows(&table, &rows, &ColumnOverrides::default(), None).unwrap();
+        assert!((signal.sample_rate() - 100.0).abs() < 1e-6);
+
+        let options = GaitOptions { distance_m: Some(20.0), ..Default::default() };
+        let analysis = GaitAnalysis::analyze(&signal, &options).unwrap();
+        assert_eq!(analysis.step_times.len(), 40);
+        assert!((analysis.cadence - 120.0).abs() < 0.5);
+        // 步幅（相隔兩步）皆為 1 秒，變異接近 0；步時間 0.48 與 0.52 的對稱指數為 8%
+        assert!(analysis.stride_time_cv.unwrap() < 0.5);
+        assert!((analysis.step_asymmetry.unwrap() - 8.0).abs() < 0.5);
+        assert_eq!(analysis.step_length_source, StepLengthSource::Distance);
+        assert!((analysis.step_length - 0.5).abs() < 1e-9);
+        assert!((analysis.speed - 1.0).abs() < 0.01);
+
+        let by_height = GaitAnalysis::analyze(&signal, &GaitOptions { height_m: Some(1.7), ..Default::default() }).unwrap();
+        assert!((by_height.step_length - 0.7055).abs() < 1e-9);
+        let weinberg = GaitAnalysis::analyze(&signal, &GaitOptions::default()).unwrap();
+        assert_eq!(weinberg.step_length_source, StepLengthSource::Weinberg);
+        assert!(weinberg.speed > 0.5 && weinberg.speed < 2.0);
+    }
+
+    #[test]
+    fn test_gait_units_and_columns() {
+        // 毫秒時間戳記與作業介面
+        let table = TrialTable::parse("walk.csv", &walking_csv("timestamp,ax,ay,az", 1000.0, true)).unwrap();
+        let subject = &trials::derive_metrics(&table, Task::Gait, &ColumnOverrides::default()).unwrap()[0];
+        let cadence = subject.metrics.iter().find(|m| m.metric == Metric::Cadence).unwrap();
+        assert!((cadence.value - 120.0).abs() < 0.5);
+        assert_eq!(subject.metrics.len(), 4);
+
+        // 沒有時間欄位時需要取樣率
+        let table = TrialTable::parse("walk.csv", &walking_csv("x;y;z", 1.0, false).replace(',', ";")).unwrap();
+        assert!(derive_metrics(&table, &ColumnOverrides::default(), &GaitOptions::default()).is_err());
+        let options = GaitOptions { sample_rate: Some(100.0), ..Default::default() };
+        let subject = &derive_metrics(&table, &ColumnOverrides::default(), &options).unwrap()[0];
+        assert_eq!(subject.metrics[0].trials, 40);
+
+        // 靜止紀錄沒有步伐
+        let table = TrialTable::parse("still.csv", "t,ax,ay,az\n0,0,0,1\n0.01,0,0,1\n0.02,0,0,1\n0.03,0,0,1\n").unwrap();
+        assert!(derive_metrics(&table, &ColumnOverrides::default(), &GaitOptions::default()).is_err());
+        let table = TrialTable::parse("walk.csv", "time,speed\n0,1\n").unwrap();
+        assert!(derive_metrics(&table, &ColumnOverrides::default(), &GaitOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_gait_subject_errors_are_skipped() {
+        // 受試者 B 只有一個取樣，記錄在 skipped，不影響 A
+        let walk = walking_csv("time,acc_x,acc_y,acc_z", 1.0, true);
+        let mut lines = walk.lines();
+        let mut csv = format!("subject,{}\n", lines.next().unwrap());
+        for line in lines {
+            csv.push_str(&format!("A,{}\n", line));
+        }
+        csv.push_str("B,0,0,0,1\n");
+        let table = TrialTable::parse("walk.csv", &csv).unwrap();
+
+        let subjects = derive_metrics(&table, &ColumnOverrides::default(), &GaitOptions::default()).unwrap();
+        assert_eq!(subjects.len(), 2);
+        assert_eq!(subjects[0].metrics.len(), 4);
+        assert!(subjects[1].metrics.is_empty());
+        assert_eq!(subjects[1].skipped.len(), 4);
+        assert!(subjects[1].skipped[0].1.contains("加速度取樣不足"));
+
+        // 只有讀取失敗的受試者時回傳該錯誤
+        let table = TrialTable::parse("walk.csv", "subject,time,acc_x,acc_y,acc_z\nB,0,0,0,1\n").unwrap();
+        let err = derive_metrics(&table, &ColumnOverrides::default(), &GaitOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("加速度取樣不足"));
+    }
+}
@@ -0,0 +1,563 @@
+//! 3D 玻璃腦（軟體點陣化）
+//!
+//! 以 `data/brain_network.json` 中各腦區的 MNI 質心與網路連線建立 [`GlassBrainScene`]，
+//! 依 [`Camera`] 的旋轉角度做正交投影，全部在 CPU 上點陣化為 RGBA 影像：
+//! 半透明的大腦半球、小腦與腦幹外殼（邊緣較亮的玻璃效果）、依影響分數決定大小與顏色的球體，
+//! 以及同一網路中腦區之間的連線。不需要 GPU，介面只把結果影像當作材質顯示。
+//!
+//! 座標系統：MNI 的 x 往右、y 往前、z 往上（mm）；檢視座標的 x 往右、y 往上、z 朝向觀看者。
+//! 相機未旋轉時由正前方面對受試者，受試者的右半球在畫面左方。
+
+use crate::brain_map::{impact_color, match_by_name};
+use crate::error::{read_json, Result};
+use crate::model::RegionImpactScore;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 旋轉中心（MNI，約為腦的幾何中心）
+const BRAIN_CENTER: [f32; 3] = [0.0, -18.0, 10.0];
+/// 縮放為 1 時，畫面短邊涵蓋的半徑（mm）
+const VIEW_EXTENT: f32 = 105.0;
+/// 玻璃外殼：(中心, 半軸長)，依序為左、右大腦半球、小腦與腦幹
+const SHELLS: [([f32; 3], [f32; 3]); 4] = [
+    ([-34.0, -17.0, 14.0], [36.0, 88.0, 64.0]),
+    ([34.0, -17.0, 14.0], [36.0, 88.0, 64.0]),
+    ([0.0, -62.0, -34.0], [50.0, 28.0, 20.0]),
+    ([0.0, -26.0, -38.0], [12.0, 12.0, 26.0]),
+];
+/// 外殼正面的基本不透明度與邊緣增加量
+const SHELL_ALPHA: f32 = 0.04;
+const SHELL_RIM_ALPHA: f32 = 0.5;
+/// 球體半徑（mm）：無影響分數者固定為最小值，其餘依分數（0–1）線性放大
+const NODE_RADIUS_MIN: f32 = 3.0;
+const NODE_RADIUS_MAX: f32 = 11.0;
+const NEUTRAL_NODE_COLOR: [u8; 3] = [176, 176, 176];
+const HIGHLIGHT_COLOR: [f32; 3] = [0.05, 0.05, 0.05];
+/// 光源方向（檢視座標，左上前方）
+const LIGHT: [f32; 3] = [-0.35, 0.55, 0.76];
+
+/// 網路中的一個腦區；兩側皆有的腦區列出左右兩個質心
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkRegion {
+    /// 可比對的腦區名稱（中文與英文），比對規則與腦圖相同
+    pub names: Vec<String>,
+    /// MNI 質心座標（mm）
+    pub mni: Vec<[f32; 3]>,
+}
+
+/// 功能網路：名稱、連線顏色與腦區之間的連線
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BrainNetwork {
+    pub name: String,
+    /// `#rrggbb`
+    pub color: String,
+    pub edges: Vec<[String; 2]>,
+}
+
+impl BrainNetwork {
+    /// 連線顏色；格式錯誤時為灰色
+    pub fn rgb(&self) -> [u8; 3] {
+        let hex = self.color.trim_start_matches('#');
+        let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => [r, g, b],
+            _ => [128, 128, 128],
+        }
+    }
+}
+
+/// 腦區質心與功能網路（`data/brain_network.json`）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BrainNetworkModel {
+    pub regions: Vec<NetworkRegion>,
+    #[serde(default)]
+    pub networks: Vec<BrainNetwork>,
+}
+
+impl BrainNetworkModel {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        read_json(path)
+    }
+
+    /// 依名稱找出腦區的索引；規則同 [`BrainMapLayout::find_region`](crate::brain_map::BrainMapLayout::find_region)
+    pub fn region_index(&self, name: &str) -> Option<usize> {
+        let region = match_by_name(name, self.regions.iter().map(|region| (region, region.names.as_slice())))?;
+        self.regions.iter().position(|candidate| std::ptr::eq(candidate, region))
+    }
+}
+
+/// 場景中的一個球體
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneNode {
+    /// 有影響分數時為映射表中的腦區名稱，否則為網路資料中的第一個名稱
+    pub region: String,
+    pub position: [f32; 3],
+    pub impact_score: Option<f32>,
+    /// 半徑（mm）
+    pub radius: f32,
+}
+
+/// 兩個球體之間的網路連線
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneEdge {
+    pub from: usize,
+    pub to: usize,
+    pub color: [u8; 3],
+    /// 兩端影響分數的較小值，決定連線的粗細與不透明度
+    pub strength: f32,
+}
+
+/// 一次計算結果的 3D 場景
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GlassBrainScene {
+    pub nodes: Vec<SceneNode>,
+    pub edges: Vec<SceneEdge>,
+}
+
+impl GlassBrainScene {
+    /// 每個網路腦區的每個質心各一個球體；多個影響分數對應到同一腦區時取最高分，找不到質心的腦區略過
+    pub fn build(model: &BrainNetworkModel, impacts: &[RegionImpactScore]) -> Self {
+        let mut scores: Vec<Option<&RegionImpactScore>> = vec![None; model.regions.len()];
+        for impact in impacts {
+            if let Some(i) = model.region_index(&impact.region) {
+                if scores[i].is_none_or(|best| impact.impact_score > best.impact_score) {
+                    scores[i] = Some(impact);
+                }
+            }
+        }
+
+        let mut nodes = Vec::new();
+        let mut region_nodes = Vec::with_capacity(model.regions.len());
+        for (region, score) in model.regions.iter().zip(&scores) {
+            let first = nodes.len();
+            for &position in &region.mni {
+                nodes.push(SceneNode {
+                    region: score.map_or_else(|| region.names.first().cloned().unwrap_or_default(), |s| s.region.clone()),
+                    position,
+                    impact_score: score.map(|s| s.impact_score),
+                    radius: score.map_or(NODE_RADIUS_MIN, |s| {
+                        NODE_RADIUS_MIN + (NODE_RADIUS_MAX - NODE_RADIUS_MIN) * s.impact_score.clamp(0.0, 1.0)
+                    }),
+                });
+            }
+            region_nodes.push(first..nodes.len());
+        }
+
+        // 兩側腦區只連到同側（或中線）的質心
+        let same_side = |a: [f32; 3], b: [f32; 3]| a[0].abs() < 5.0 || b[0].abs() < 5.0 || (a[0] < 0.0) == (b[0] < 0.0);
+        let mut edges = Vec::new();
+        for network in &model.networks {
+            let color = network.rgb();
+            for [a, b] in &network.edges {
+                let (Some(a), Some(b)) = (model.region_index(a), model.region_index(b)) else {
+                    continue;
+                };
+                for from in region_nodes[a].clone() {
+                    for to in region_nodes[b].clone() {
+                        if same_side(nodes[from].position, nodes[to].position) {
+                            let strength = nodes[from].impact_score.unwrap_or(0.0).min(nodes[to].impact_score.unwrap_or(0.0));
+                            edges.push(SceneEdge { from, to, color, strength: strength.clamp(0.0, 1.0) });
+                        }
+                    }
+                }
+            }
+        }
+
+        Self { nodes, edges }
+    }
+}
+
+/// 繞腦中心旋轉的正交相機
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// 繞垂直軸的角度（弧度）；0 為正面，-π/2 為左側面
+    pub yaw: f32,
+    /// 俯仰角（弧度）；正值為由上往下看
+    pub pitch: f32,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    /// 左前上方的斜視角
+    fn default() -> Self {
+        Self { yaw: -0.7, pitch: 0.35, zoom: 1.0 }
+    }
+}
+
+impl Camera {
+    /// 依拖曳量旋轉（弧度）；俯仰角限制在正上方與正下方之間
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw) % std::f32::consts::TAU;
+        self.pitch = (self.pitch + pitch).clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+    }
+
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(0.5, 4.0);
+    }
+
+    /// MNI 座標 → 以腦中心為原點的檢視座標
+    fn mni_to_view(self, p: [f32; 3]) -> [f32; 3] {
+        self.rotate([BRAIN_CENTER[0] - p[0], p[2] - BRAIN_CENTER[2], p[1] - BRAIN_CENTER[1]])
+    }
+
+    /// 檢視座標中的向量 → MNI 座標中的向量（不含平移）
+    fn view_to_mni(self, v: [f32; 3]) -> [f32; 3] {
+        let [x, up, front] = self.unrotate(v);
+        [-x, front, up]
+    }
+
+    /// 先繞垂直軸（yaw）再繞水平軸（pitch）旋轉；`[x, 上, 前]` → 檢視座標
+    fn rotate(self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        let (x, z) = (x * cy + z * sy, -x * sy + z * cy);
+        [x, y * cp - z * sp, y * sp + z * cp]
+    }
+
+    /// [`Self::rotate`] 的反向旋轉
+    fn unrotate(self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        let (y, z) = (y * cp + z * sp, -y * sp + z * cp);
+        [x * cy - z * sy, y, x * sy + z * cy]
+    }
+
+    /// 每 mm 對應的像素數
+    pub fn pixels_per_mm(self, width: f32, height: f32) -> f32 {
+        self.zoom * width.min(height) / (2.0 * VIEW_EXTENT)
+    }
+
+    /// MNI 座標 → `[畫面 x, 畫面 y, 深度]`；深度以 mm 計，越大越靠近觀看者
+    pub fn project(self, p: [f32; 3], width: f32, height: f32) -> [f32; 3] {
+        let [x, y, z] = self.mni_to_view(p);
+        let scale = self.pixels_per_mm(width, height);
+        [width / 2.0 + x * scale, height / 2.0 - y * scale, z]
+    }
+}
+
+/// 點陣化結果（RGBA，逐列排列）
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlassBrainImage {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+/// 點陣化場景；`highlight` 為要加上外框的腦區名稱
+pub fn render(scene: &GlassBrainScene, camera: Camera, width: usize, height: usize, background: [u8; 3], highlight: Option<&str>) -> GlassBrainImage {
+    let mut canvas = Canvas {
+        width,
+        height,
+        color: vec![to_linear(background); width * height],
+        depth: vec![f32::NEG_INFINITY; width * height],
+    };
+    let scale = camera.pixels_per_mm(width as f32, height as f32);
+
+    // 先畫不透明的球體並寫入深度，連線與外殼再依深度混色
+    for node in &scene.nodes {
+        let base = to_linear(node.impact_score.map_or(NEUTRAL_NODE_COLOR, impact_color));
+        let highlighted = highlight == Some(node.region.as_str());
+        canvas.sphere(camera.project(node.position, width as f32, height as f32), node.radius, scale, base, highlighted);
+    }
+    for edge in &scene.edges {
+        let from = camera.project(scene.nodes[edge.from].position, width as f32, height as f32);
+        let to = camera.project(scene.nodes[edge.to].position, width as f32, height as f32);
+        let half_width = (0.6 + 1.6 * edge.strength) * scale.max(0.5).sqrt();
+        canvas.line(from, to, half_width, to_linear(edge.color), 0.3 + 0.6 * edge.strength);
+    }
+    // 淺色背景用深色玻璃，深色背景用淺色玻璃
+    let luminance = 0.299 * background[0] as f32 + 0.587 * background[1] as f32 + 0.114 * background[2] as f32;
+    let shell_color = to_linear(if luminance > 128.0 { [60, 80, 110] } else { [200, 215, 235] });
+    canvas.shells(camera, scale, shell_color);
+
+    let rgba = canvas
+        .color
+        .iter()
+        .flat_map(|&c| {
+            let [r, g, b] = from_linear(c);
+            [r, g, b, 255]
+        })
+        .collect();
+    GlassBrainImage { width, height, rgba }
+}
+
+/// 畫面上 `point` 處最靠近觀看者的球體
+pub fn pick(scene: &GlassBrainScene, camera: Camera, width: f32, height: f32, point: [f32; 2]) -> Option<usize> {
+    let scale = camera.pixels_per_mm(width, height);
+    scene
+        .nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, node)| {
+            let [x, y, depth] = camera.project(node.position, width, height);
+            let r = node.radius * scale;
+            let d2 = (point[0] - x).powi(2) + (point[1] - y).powi(2);
+            (d2 <= r * r).then(|| (i, depth + (r * r - d2).sqrt() / scale))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// 以線性色彩空間混色的畫布與深度緩衝
+struct Canvas {
+    width: usize,
+    height: usize,
+    color: Vec<[f32; 3]>,
+    depth: Vec<f32>,
+}
+
+impl Canvas {
+    /// 外框範圍內的像素索引，超出畫布的部分略過
+    fn pixels(&self, min: [f32; 2], max: [f32; 2]) -> impl Iterator<Item = (usize, usize)> {
+        let clamp = |v: f32, limit: usize| (v.max(0.0) as usize).min(limit);
+        let (x0, x1) = (clamp(min[0].floor(), self.width), clamp(max[0].ceil(), self.width));
+        let (y0, y1) = (clamp(min[1].floor(), self.height), clamp(max[1].ceil(), self.height));
+        (y0..y1).flat_map(move |y| (x0..x1).map(move |x| (x, y)))
+    }
+
+    fn blend(&mut self, index: usize, color: [f32; 3], alpha: f32) {
+        let dst = &mut self.color[index];
+        for (d, s) in dst.iter_mut().zip(color) {
+            *d += (s - *d) * alpha.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Blinn-Phong 著色的球體；邊緣依覆蓋率反鋸齒
+    fn sphere(&mut self, [cx, cy, cz]: [f32; 3], radius: f32, scale: f32, base: [f32; 3], highlighted: bool) {
+        let r = radius * scale;
+        if r < 0.5 {
+            return;
+        }
+        let light = normalize(LIGHT);
+        let half = normalize([light[0], light[1], light[2] + 1.0]);
+        let rim = 2.5 / r;
+        for (x, y) in self.pixels([cx - r - 1.0, cy - r - 1.0], [cx + r + 1.0, cy + r + 1.0]) {
+            let dx = (x as f32 + 0.5 - cx) / r;
+            let dy = (y as f32 + 0.5 - cy) / r;
+            let dist = (dx * dx + dy * dy).sqrt();
+            let coverage = ((1.0 - dist) * r + 0.5).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let nz = (1.0 - dist.min(1.0).powi(2)).sqrt();
+            let depth = cz + nz * radius;
+            let index = y * self.width + x;
+            if depth <= self.depth[index] {
+                continue;
+            }
+            let color = if highlighted && dist > 1.0 - rim {
+                HIGHLIGHT_COLOR
+            } else {
+                let normal = [dx, -dy, nz];
+                let diffuse = dot(normal, light).max(0.0);
+                let specular = dot(normal, half).max(0.0).powi(24) * 0.35;
+                base.map(|c| c * (0.35 + 0.65 * diffuse) + specular)
+            };
+            self.blend(index, color, coverage);
+            if coverage > 0.5 {
+                self.depth[index] = depth;
+            }
+        }
+    }
+
+    /// 兩個投影點之間的粗線；被球體遮住的部分不畫
+    fn line(&mut self, a: [f32; 3], b: [f32; 3], half_width: f32, color: [f32; 3], alpha: f32) {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let length2 = (dx * dx + dy * dy).max(1e-6);
+        let pad = half_width + 1.0;
+        let min = [a[0].min(b[0]) - pad, a[1].min(b[1]) - pad];
+        let max = [a[0].max(b[0]) + pad, a[1].max(b[1]) + pad];
+        for (x, y) in self.pixels(min, max) {
+            let (px, py) = (x as f32 + 0.5 - a[0], y as f32 + 0.5 - a[1]);
+            let t = ((px * dx + py * dy) / length2).clamp(0.0, 1.0);
+            let dist = ((px - t * dx).powi(2) + (py - t * dy).powi(2)).sqrt();
+            let coverage = (half_width + 0.5 - dist).clamp(0.0, 1.0);
+            let index = y * self.width + x;
+            if coverage > 0.0 && a[2] + t * (b[2] - a[2]) > self.depth[index] {
+                self.blend(index, color, coverage * alpha);
+            }
+        }
+    }
+
+    /// 半透明外殼：沿視線求與各橢球的交點，正面與背面依法向量與視線的夾角加亮邊緣
+    fn shells(&mut self, camera: Camera, scale: f32, color: [f32; 3]) {
+        // 檢視方向（朝向觀看者）換回 MNI 座標系
+        let toward_viewer = camera.view_to_mni([0.0, 0.0, 1.0]);
+        // 檢視平面的兩個軸向換回 MNI 座標，像素位置為其線性組合
+        let right = camera.view_to_mni([1.0 / scale, 0.0, 0.0]);
+        let up = camera.view_to_mni([0.0, 1.0 / scale, 0.0]);
+        let (cx, cy) = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+        for (center, axes) in SHELLS {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    // 像素在檢視平面上的位置換回 MNI 座標（相對於橢球中心並以半軸長正規化）
+                    let (px, py) = (x as f32 + 0.5 - cx, cy - y as f32 - 0.5);
+                    let o: [f32; 3] = std::array::from_fn(|i| (px * right[i] + py * up[i] + BRAIN_CENTER[i] - center[i]) / axes[i]);
+                    let d: [f32; 3] = std::array::from_fn(|i| toward_viewer[i] / axes[i]);
+                    // |o + t·d|² = 1，t 為沿視線朝向觀看者的距離（mm）
+                    let (qa, qb, qc) = (dot(d, d), 2.0 * dot(o, d), dot(o, o) - 1.0);
+                    let discriminant = qb * qb - 4.0 * qa * qc;
+                    if discriminant < 0.0 {
+                        continue;
+                    }
+                    let root = discriminant.sqrt();
+                    let index = y * self.width + x;
+                    for (t, weight) in [((-qb + root) / (2.0 * qa), 1.0), ((-qb - root) / (2.0 * qa), 0.5)] {
+                        let hit: [f32; 3] = std::array::from_fn(|i| (o[i] + t * d[i]) / axes[i]);
+                        let facing = dot(normalize(hit), toward_viewer).abs();
+                        // 像素位於檢視平面（深度 0），交點深度即為 t
+                        if t > self.depth[index] {
+                            self.blend(index, color, weight * (SHELL_ALPHA + SHELL_RIM_ALPHA * (1.0 - facing).powi(3)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = dot(v, v).sqrt().max(1e-6);
+    v.map(|c| c / length)
+}
+
+fn to_linear(color: [u8; 3]) -> [f32; 3] {
+    color.map(|c| (c as f32 / 255.0).powf(2.2))
+}
+
+fn from_linear(color: [f32; 3]) -> [u8; 3] {
+    color.map(|c| (c.clamp(0.0, 1.0).powf(1.0 / 2.2) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impact(region: &str, score: f32) -> RegionImpactScore {
+        RegionImpactScore {
+            region: region.to_string(),
+            impact_score: score,
+            normalized_input: 1.0,
+            weight: score,
+        }
+    }
+
+    fn model() -> BrainNetworkModel {
+        serde_json::from_str(
+            r##"{
+                "regions": [
+                    { "names": ["前額葉", "Prefrontal Cortex"], "mni": [[-30, 46, 20], [30, 46, 20]] },
+                    { "names": ["前扣帶皮質", "ACC"], "mni": [[0, 30, 22]] },
+                    { "names": ["頂葉", "Parietal Lobe"], "mni": [[-26, -62, 58], [26, -62, 58]] }
+                ],
+                "networks": [{ "name": "額頂控制網路", "color": "#ff9800", "edges": [["前額葉", "頂葉"], ["前額葉", "前扣帶皮質"]] }]
+            }"##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bundled_network() {
+        let base = env!("CARGO_MANIFEST_DIR");
+        let model = BrainNetworkModel::load(format!("{}/data/brain_network.json", base)).unwrap();
+        for network in &model.networks {
+            assert_ne!(network.rgb(), [128, 128, 128], "{} 的顏色格式錯誤", network.name);
+            for name in network.edges.iter().flatten() {
+                assert!(model.region_index(name).is_some(), "{} 的連線腦區 {} 沒有質心", network.name, name);
+            }
+        }
+        // 映射表中的每個腦區都有質心
+        let maps = crate::mapping::load_behavior_brain_map(format!("{}/data/behavior_brain_map.json", base)).unwrap();
+        for map in &maps {
+            for region in &map.brain_regions {
+                assert!(model.region_index(&region.region).is_some(), "{} 沒有質心", region.region);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scene_build() {
+        let impacts = vec![impact("前額葉", 0.4), impact("Prefrontal Cortex (PFC)", 0.8), impact("頂葉", 0.5), impact("未知區", 0.3)];
+        let scene = GlassBrainScene::build(&model(), &impacts);
+
+        assert_eq!(scene.nodes.len(), 5);
+        assert_eq!(scene.nodes[0].region, "Prefrontal Cortex (PFC)");
+        assert_eq!(scene.nodes[0].impact_score, Some(0.8));
+        assert!(scene.nodes[0].radius > scene.nodes[3].radius);
+        assert_eq!(scene.nodes[2].region, "前扣帶皮質");
+        assert_eq!(scene.nodes[2].radius, NODE_RADIUS_MIN);
+
+        // 前額葉–頂葉只連同側，前額葉–前扣帶（中線）兩側都連
+        let pairs: Vec<(usize, usize)> = scene.edges.iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(pairs, vec![(0, 3), (1, 4), (0, 2), (1, 2)]);
+        assert_eq!(scene.edges[0].strength, 0.5);
+        assert_eq!(scene.edges[2].strength, 0.0);
+        assert_eq!(scene.edges[0].color, [0xff, 0x98, 0x00]);
+    }
+
+    #[test]
+    fn test_camera_projection() {
+        let front = Camera { yaw: 0.0, pitch: 0.0, zoom: 1.0 };
+        let [x, y, _] = front.project(BRAIN_CENTER, 200.0, 100.0);
+        assert_eq!((x, y), (100.0, 50.0));
+        // 正面：右半球在畫面左方、上方在畫面上方、前方靠近觀看者
+        let right = front.project([40.0, -18.0, 10.0], 200.0, 100.0);
+        let top = front.project([0.0, -18.0, 50.0], 200.0, 100.0);
+        let anterior = front.project([0.0, 40.0, 10.0], 200.0, 100.0);
+        assert!(right[0] < 100.0 && (right[1] - 50.0).abs() < 1e-4);
+        assert!(top[1] < 50.0);
+        assert!(anterior[2] > 0.0 && (anterior[0] - 100.0).abs() < 1e-4);
+
+        // 左側面：前方在畫面左方，左半球靠近觀看者
+        let side = Camera { yaw: -std::f32::consts::FRAC_PI_2, ..front };
+        assert!(side.project([0.0, 40.0, 10.0], 200.0, 100.0)[0] < 100.0);
+        assert!(side.project([-40.0, -18.0, 10.0], 200.0, 100.0)[2] > 0.0);
+
+        // 檢視座標與 MNI 座標的轉換互逆
+        let camera = Camera::default();
+        let p = [13.0, -4.0, 25.0];
+        let view = camera.mni_to_view(p);
+        let back: Vec<f32> = camera.view_to_mni(view).iter().zip(BRAIN_CENTER).map(|(v, c)| v + c).collect();
+        assert!(p.iter().zip(back).all(|(a, b)| (a - b).abs() < 1e-4));
+
+        let mut camera = Camera::default();
+        camera.orbit(0.0, 10.0);
+        assert_eq!(camera.pitch, std::f32::consts::FRAC_PI_2);
+        camera.zoom_by(100.0);
+        assert_eq!(camera.zoom, 4.0);
+    }
+
+    #[test]
+    fn test_render_and_pick() {
+        let scene = GlassBrainScene::build(&model(), &[impact("前額葉", 0.9)]);
+        let camera = Camera { yaw: 0.0, pitch: 0.0, zoom: 1.0 };
+        let (width, height) = (120, 90);
+        let image = render(&scene, camera, width, height, [255, 255, 255], None);
+        assert_eq!(image.rgba.len(), width * height * 4);
+
+        let pixel = |[x, y]: [f32; 2]| {
+            let i = (y as usize * width + x as usize) * 4;
+            [image.rgba[i], image.rgba[i + 1], image.rgba[i + 2]]
+        };
+        // 角落為背景，球體中心偏紅（Critical），腦內有玻璃的淡色
+        assert_eq!(pixel([0.0, 0.0]), [255, 255, 255]);
+        let [x, y, _] = camera.project(scene.nodes[1].position, width as f32, height as f32);
+        let [r, g, b] = pixel([x, y]);
+        assert!(r > g && r > b, "球體顏色 {:?}", [r, g, b]);
+        let inside = pixel([width as f32 / 2.0, height as f32 / 2.0 + 10.0]);
+        assert!(inside[2] < 255 && inside[2] > 150, "外殼顏色 {:?}", inside);
+
+        assert_eq!(pick(&scene, camera, width as f32, height as f32, [x, y]), Some(1));
+        assert_eq!(pick(&scene, camera, width as f32, height as f32, [0.0, 0.0]), None);
+        // 中線的前扣帶皮質沒有分數，仍可點選
+        let acc = camera.project(scene.nodes[2].position, width as f32, height as f32);
+        assert_eq!(pick(&scene, camera, width as f32, height as f32, [acc[0], acc[1]]), Some(2));
+
+        let highlighted = render(&scene, camera, width, height, [255, 255, 255], Some("前額葉"));
+        assert_ne!(highlighted, image);
+    }
+}
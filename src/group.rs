@@ -0,0 +1,871 @@
+//! 族群層級分析
+//!
+//! 由批次匯入的結果（每位受試者的 JSON 匯出檔，或含多位受試者的長格式 CSV）計算：
+//!
+//! - 各腦區影響分數的平均、標準差與範圍（[`CohortSummary`]）
+//! - 各風險等級的受試者比例
+//! - 兩組（例如病人與對照）的逐腦區比較（[`GroupComparison`]）：Welch t 檢定、
+//!   Mann-Whitney U 檢定（常態近似，含同分校正與連續性校正）、Hedges g 與等級雙列相關
+//! - 群體平均腦圖（[`CohortSummary::average_result`]）
+//!
+//! 每位受試者以每個行為最新的一筆結果代表；同一腦區出現在多個行為時取最高分，
+//! 風險等級取最高者。受試者沒有某腦區的分數時，該腦區不計入此受試者（n 因而可能不同）。
+
+use crate::error::{BrainVizError, Result};
+use crate::i18n::{name_in, tr_in, trf_in, Language};
+use crate::export::{self, csv_field, split_csv_line, ImportedResults};
+use crate::model::{BehaviorInput, CalculationResult, RegionImpactScore, RiskLevel};
+use std::path::{Path, PathBuf};
+
+/// 族群中的一位受試者
+#[derive(Debug, Clone)]
+pub struct CohortSubject {
+    pub subject_id: String,
+    /// 組別（例如 "patient"、"control"），未指派時為 `None`
+    pub group: Option<String>,
+    pub results: Vec<CalculationResult>,
+}
+
+impl CohortSubject {
+    /// 代表此受試者的結果：每個行為最新的一筆；指定 `behavior` 時只取該行為
+    pub fn latest_results(&self, behavior: Option<&str>) -> Vec<&CalculationResult> {
+        let mut latest: Vec<&CalculationResult> = Vec::new();
+        for result in &self.results {
+            let input = &result.behavior_input;
+            if behavior.is_some_and(|behavior| behavior != input.behavior_type) {
+                continue;
+            }
+            match latest.iter_mut().find(|r| r.behavior_input.behavior_type == input.behavior_type) {
+                Some(existing) if existing.behavior_input.timestamp < input.timestamp => *existing = result,
+                Some(_) => {}
+                None => latest.push(result),
+            }
+        }
+        latest
+    }
+
+    /// 各腦區的影響分數，依結果中出現的順序
+    pub fn region_scores(&self, behavior: Option<&str>) -> Vec<(String, f32)> {
+        let mut scores: Vec<(String, f32)> = Vec::new();
+        for impact in self.latest_results(behavior).into_iter().flat_map(|r| &r.impact_scores) {
+            match scores.iter_mut().find(|(region, _)| *region == impact.region) {
+                Some((_, score)) => *score = score.max(impact.impact_score),
+                None => scores.push((impact.region.clone(), impact.impact_score)),
+            }
+        }
+        scores
+    }
+
+    /// 最高的風險等級；沒有符合的結果時為 `None`
+    pub fn risk_level(&self, behavior: Option<&str>) -> Option<RiskLevel> {
+        self.latest_results(behavior)
+            .into_iter()
+            .map(|r| r.risk_level.clone())
+            .max_by(|a, b| a.lower_bound().total_cmp(&b.lower_bound()))
+    }
+
+    /// 代表結果的平均總影響
+    fn total_impact(&self, behavior: Option<&str>) -> Option<f32> {
+        let results = self.latest_results(behavior);
+        (!results.is_empty()).then(|| results.iter().map(|r| r.total_impact).sum::<f32>() / results.len() as f32)
+    }
+}
+
+/// 批次匯入的受試者
+#[derive(Debug, Clone, Default)]
+pub struct Cohort {
+    pub subjects: Vec<CohortSubject>,
+}
+
+impl Cohort {
+    /// 合併匯入的結果；同一受試者編號的結果併為一位，沒有受試者資料的檔案以「未命名-N」編號
+    pub fn from_imports(imports: impl IntoIterator<Item = ImportedResults>) -> Self {
+        let mut cohort = Cohort::default();
+        let mut unnamed = 0;
+        for import in imports {
+            let subject_id = match import.subject.map(|s| s.subject_id).filter(|id| !id.is_empty()) {
+                Some(id) => id,
+                None => {
+                    unnamed += 1;
+                    format!("未命名-{}", unnamed)
+                }
+            };
+            match cohort.subjects.iter_mut().find(|s| s.subject_id == subject_id) {
+                Some(subject) => subject.results.extend(import.results),
+                None => cohort.subjects.push(CohortSubject {
+                    subject_id,
+                    group: None,
+                    results: import.results,
+                }),
+            }
+        }
+        cohort
+    }
+
+    /// 讀取匯出檔：`.json` 為單一受試者，`.csv` 為長格式（可含多位受試者）；
+    /// 目錄則讀取其中所有 JSON 與 CSV 匯出檔
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut imports = Vec::new();
+        for path in paths {
+            let files = if path.is_dir() { discover_exports(path) } else { vec![path.clone()] };
+            for file in files {
+                if has_extension(&file, "csv") {
+                    imports.extend(export::import_long_csv(&file)?);
+                } else {
+                    imports.push(export::import_json(&file)?);
+                }
+            }
+        }
+        Ok(Self::from_imports(imports))
+    }
+
+    /// 依 (受試者編號, 組別) 指派組別，回傳沒有出現在匯入結果中的受試者編號
+    pub fn assign_groups(&mut self, assignments: &[(String, String)]) -> Vec<String> {
+        let mut missing = Vec::new();
+        for (subject_id, group) in assignments {
+            match self.subjects.iter_mut().find(|s| s.subject_id == *subject_id) {
+                Some(subject) => subject.group = Some(group.clone()),
+                None => missing.push(subject_id.clone()),
+            }
+        }
+        missing
+    }
+
+    /// 已指派的組別，依第一次出現的順序
+    pub fn groups(&self) -> Vec<&str> {
+        let mut groups: Vec<&str> = Vec::new();
+        for group in self.subjects.iter().filter_map(|s| s.group.as_deref()) {
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+        groups
+    }
+
+    /// 全體（`group` 為 `None`）或某一組的描述統計
+    pub fn summary(&self, group: Option<&str>, behavior: Option<&str>) -> CohortSummary {
+        CohortSummary::new(self.members(group), group, behavior)
+    }
+
+    /// 兩組的逐腦區比較，差值與效果量的方向為 `groups[0]` − `groups[1]`
+    pub fn compare(&self, groups: [&str; 2], behavior: Option<&str>) -> GroupComparison {
+        GroupComparison::new([self.members(Some(groups[0])), self.members(Some(groups[1]))], groups, behavior)
+    }
+
+    fn members(&self, group: Option<&str>) -> Vec<&CohortSubject> {
+        self.subjects
+            .iter()
+            .filter(|s| group.is_none() || s.group.as_deref() == group)
+            .collect()
+    }
+}
+
+/// 讀取組別對照 CSV：需有 `subject_id` 與 `group` 欄位，其餘欄位忽略
+pub fn load_group_assignments(path: impl AsRef<Path>) -> Result<Vec<(String, String)>> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| BrainVizError::io(path, e))?;
+    let mut lines = text.trim_start_matches('\u{feff}').lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let parse_error = |line: usize, message: String| BrainVizError::Parse {
+        path: path.to_path_buf(),
+        line: line + 1,
+        column: 1,
+        message,
+    };
+
+    let Some((header_line, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let header = split_csv_line(header);
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim() == name)
+            .ok_or_else(|| parse_error(header_line, format!("缺少欄位 {}", name)))
+    };
+    let (subject_column, group_column) = (column("subject_id")?, column("group")?);
+
+    let mut assignments = Vec::new();
+    for (line, text) in lines {
+        let fields = split_csv_line(text);
+        let field = |column: usize| fields.get(column).map(|f| f.trim()).unwrap_or_default();
+        if field(subject_column).is_empty() || field(group_column).is_empty() {
+            return Err(parse_error(line, "受試者編號與組別不可空白".to_string()));
+        }
+        assignments.push((field(subject_column).to_string(), field(group_column).to_string()));
+    }
+    Ok(assignments)
+}
+
+/// 目錄中的 JSON 與長格式 CSV 匯出檔，依檔名排序；其他 CSV（例如圖譜向量、族群統計表）略過
+fn discover_exports(dir: &Path) -> Vec<PathBuf> {
+    let is_long_csv = |path: &Path| {
+        has_extension(path, "csv")
+            && std::fs::read_to_string(path).is_ok_and(|text| {
+                let header = split_csv_line(text.trim_start_matches('\u{feff}').lines().next().unwrap_or_default());
+                header.len() == export::LONG_FORMAT_COLUMNS.len() && header.iter().zip(export::LONG_FORMAT_COLUMNS).all(|(a, b)| a == b)
+            })
+    };
+    let mut files = export::discover_json_exports(dir);
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        files.extend(entries.flatten().map(|e| e.path()).filter(|p| is_long_csv(p)));
+    }
+    files.sort();
+    files
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// 單一腦區的描述統計
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionSummary {
+    pub region: String,
+    pub n: usize,
+    pub mean: f32,
+    /// 樣本標準差（n − 1）；n < 2 時為 0
+    pub sd: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// 全體或一組受試者的描述統計
+#[derive(Debug, Clone, PartialEq)]
+pub struct CohortSummary {
+    pub group: Option<String>,
+    pub behavior: Option<String>,
+    /// 有符合結果的受試者人數
+    pub subjects: usize,
+    pub regions: Vec<RegionSummary>,
+    /// 各風險等級的人數，依 [`RiskLevel::ALL`] 順序
+    pub risk_counts: Vec<(RiskLevel, usize)>,
+    /// 受試者總影響的平均
+    pub mean_total_impact: f32,
+}
+
+impl CohortSummary {
+    fn new(subjects: Vec<&CohortSubject>, group: Option<&str>, behavior: Option<&str>) -> Self {
+        let subjects: Vec<&CohortSubject> = subjects.into_iter().filter(|s| s.risk_level(behavior).is_some()).collect();
+        let regions = region_values(&subjects, behavior)
+            .into_iter()
+            .map(|(region, values)| {
+                let (mean, sd) = mean_sd(&values);
+                RegionSummary {
+                    region,
+                    n: values.len(),
+                    mean: mean as f32,
+                    sd: sd as f32,
+                    min: values.iter().copied().fold(f64::INFINITY, f64::min) as f32,
+                    max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max) as f32,
+                }
+            })
+            .collect();
+        let levels: Vec<RiskLevel> = subjects.iter().filter_map(|s| s.risk_level(behavior)).collect();
+        let risk_counts = RiskLevel::ALL
+            .iter()
+            .map(|level| (level.clone(), levels.iter().filter(|l| *l == level).count()))
+            .collect();
+        let totals: Vec<f64> = subjects.iter().filter_map(|s| s.total_impact(behavior)).map(f64::from).collect();
+
+        Self {
+            group: group.map(str::to_string),
+            behavior: behavior.map(str::to_string),
+            subjects: subjects.len(),
+            regions,
+            risk_counts,
+            mean_total_impact: mean_sd(&totals).0 as f32,
+        }
+    }
+
+    /// 某風險等級的受試者百分比（0–100）
+    pub fn risk_percentage(&self, level: &RiskLevel) -> f32 {
+        let count = self.risk_counts.iter().find(|(l, _)| l == level).map_or(0, |(_, count)| *count);
+        if self.subjects == 0 {
+            0.0
+        } else {
+            100.0 * count as f32 / self.subjects as f32
+        }
+    }
+
+    /// 群體平均腦圖的標題，依 `language`
+    pub fn map_title(&self, language: Language) -> String {
+        trf_in(
+            language,
+            "figure.group_title",
+            &[
+                &self.group.as_deref().unwrap_or(tr_in(language, "group.all")),
+                &self.subjects,
+                &self.behavior.as_deref().map_or(tr_in(language, "group.all_behaviors"), |b| name_in(language, b)),
+                &format!("{:.2}", self.mean_total_impact),
+                &RiskLevel::from_score(self.mean_total_impact).label_in(language),
+            ],
+        )
+    }
+
+    /// 檔名用的組別代稱（僅保留英數字、`-` 與 `_`）
+    pub fn file_label(&self) -> String {
+        self.group
+            .as_deref()
+            .unwrap_or(ALL_SUBJECTS)
+            .replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_")
+    }
+
+    /// 以各腦區平均分數組成的結果，用於繪製群體平均腦圖
+    ///
+    /// 權重與 z 分數在不同行為間不可平均，故設為 0；風險等級依平均總影響判定。
+    pub fn average_result(&self) -> CalculationResult {
+        CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: self.behavior.clone().unwrap_or_default(),
+                value: 0.0,
+                unit: String::new(),
+                timestamp: chrono::Utc::now(),
+            },
+            impact_scores: self
+                .regions
+                .iter()
+                .map(|region| RegionImpactScore {
+                    region: region.region.clone(),
+                    impact_score: region.mean,
+                    normalized_input: 0.0,
+                    weight: 0.0,
+                })
+                .collect(),
+            total_impact: self.mean_total_impact,
+            risk_level: RiskLevel::from_score(self.mean_total_impact),
+        }
+    }
+}
+
+/// 單一腦區的兩組比較
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionGroupTest {
+    pub region: String,
+    pub n: [usize; 2],
+    pub mean: [f32; 2],
+    pub sd: [f32; 2],
+    /// Welch t 檢定；任一組少於兩人或兩組皆無變異時為 `None`
+    pub welch: Option<WelchTest>,
+    /// Mann-Whitney U 檢定；任一組沒有資料時為 `None`
+    pub mann_whitney: Option<MannWhitneyTest>,
+    /// 以合併標準差計算並做小樣本校正的 Hedges g
+    pub hedges_g: Option<f32>,
+}
+
+/// 兩組的逐腦區比較
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupComparison {
+    pub groups: [String; 2],
+    pub behavior: Option<String>,
+    /// 兩組有符合結果的人數
+    pub subjects: [usize; 2],
+    pub regions: Vec<RegionGroupTest>,
+}
+
+impl GroupComparison {
+    fn new(members: [Vec<&CohortSubject>; 2], groups: [&str; 2], behavior: Option<&str>) -> Self {
+        let subjects = [0, 1].map(|i| members[i].iter().filter(|s| s.risk_level(behavior).is_some()).count());
+        let [first, second] = members.map(|subjects| region_values(&subjects, behavior));
+        let mut names: Vec<&str> = Vec::new();
+        for (region, _) in first.iter().chain(&second) {
+            if !names.contains(&region.as_str()) {
+                names.push(region);
+            }
+        }
+        let values = |group: &[(String, Vec<f64>)], region: &str| {
+            group.iter().find(|(r, _)| r == region).map(|(_, v)| v.clone()).unwrap_or_default()
+        };
+
+        let regions = names
+            .into_iter()
+            .map(|region| {
+                let (a, b) = (values(&first, region), values(&second, region));
+                let ((mean_a, sd_a), (mean_b, sd_b)) = (mean_sd(&a), mean_sd(&b));
+                RegionGroupTest {
+                    region: region.to_string(),
+                    n: [a.len(), b.len()],
+                    mean: [mean_a as f32, mean_b as f32],
+                    sd: [sd_a as f32, sd_b as f32],
+                    welch: welch_t_test(&a, &b),
+                    mann_whitney: mann_whitney_u(&a, &b),
+                    hedges_g: hedges_g(&a, &b).map(|g| g as f32),
+                }
+            })
+            .collect();
+
+        Self {
+            groups: groups.map(str::to_string),
+            behavior: behavior.map(str::to_string),
+            subjects,
+            regions,
+        }
+    }
+}
+
+/// 全體列在 CSV 的 `group` 欄中的名稱
+pub const ALL_SUBJECTS: &str = "all";
+
+/// 腦區描述統計表（長格式 CSV，CRLF 換行）
+pub fn summary_csv(summaries: &[CohortSummary]) -> String {
+    let mut csv = String::from("group,behavior_type,region,n,mean,sd,min,max\r\n");
+    for summary in summaries {
+        for region in &summary.regions {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\r\n",
+                csv_field(summary.group.as_deref().unwrap_or(ALL_SUBJECTS)),
+                csv_field(summary.behavior.as_deref().unwrap_or_default()),
+                csv_field(&region.region),
+                region.n,
+                region.mean,
+                region.sd,
+                region.min,
+                region.max
+            ));
+        }
+    }
+    csv
+}
+
+/// 風險等級分布表，百分比為 0–100
+pub fn risk_csv(summaries: &[CohortSummary]) -> String {
+    let mut csv = String::from("group,risk_level,n,percent\r\n");
+    for summary in summaries {
+        for (level, count) in &summary.risk_counts {
+            csv.push_str(&format!(
+                "{},{:?},{},{}\r\n",
+                csv_field(summary.group.as_deref().unwrap_or(ALL_SUBJECTS)),
+                level,
+                count,
+                summary.risk_percentage(level)
+            ));
+        }
+    }
+    csv
+}
+
+/// 兩組比較表；無法計算的統計量留空
+pub fn comparison_csv(comparison: &GroupComparison) -> String {
+    let mut csv = String::from(
+        "group_1,group_2,region,n_1,mean_1,sd_1,n_2,mean_2,sd_2,t,df,t_p,u,z,u_p,rank_biserial,hedges_g\r\n",
+    );
+    let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    for region in &comparison.regions {
+        let welch = region.welch.as_ref();
+        let mann_whitney = region.mann_whitney.as_ref();
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\r\n",
+            csv_field(&comparison.groups[0]),
+            csv_field(&comparison.groups[1]),
+            csv_field(&region.region),
+            region.n[0],
+            region.mean[0],
+            region.sd[0],
+            region.n[1],
+            region.mean[1],
+            region.sd[1],
+            optional(welch.map(|w| w.t)),
+            optional(welch.map(|w| w.df)),
+            optional(welch.map(|w| w.p)),
+            optional(mann_whitney.map(|m| m.u)),
+            optional(mann_whitney.map(|m| m.z)),
+            optional(mann_whitney.map(|m| m.p)),
+            optional(mann_whitney.map(|m| m.rank_biserial)),
+            optional(region.hedges_g.map(f64::from))
+        ));
+    }
+    csv
+}
+
+/// 將統計表寫入 `output_dir`：`cohort_regions_*.csv`、`cohort_risk_*.csv`，
+/// 有兩組比較時另寫 `cohort_comparison_*.csv`；回傳寫出的檔案
+pub fn export_tables(summaries: &[CohortSummary], comparison: Option<&GroupComparison>, output_dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(output_dir).map_err(|e| BrainVizError::io(output_dir, e))?;
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let mut tables = vec![("regions", summary_csv(summaries)), ("risk", risk_csv(summaries))];
+    if let Some(comparison) = comparison {
+        tables.push(("comparison", comparison_csv(comparison)));
+    }
+    tables
+        .into_iter()
+        .map(|(table, csv)| {
+            let out = output_dir.join(format!("cohort_{}_{}.csv", table, stamp));
+            std::fs::write(&out, csv).map_err(|e| BrainVizError::io(&out, e))?;
+            Ok(out)
+        })
+        .collect()
+}
+
+/// 依腦區收集各受試者的分數
+fn region_values(subjects: &[&CohortSubject], behavior: Option<&str>) -> Vec<(String, Vec<f64>)> {
+    let mut regions: Vec<(String, Vec<f64>)> = Vec::new();
+    for (region, score) in subjects.iter().flat_map(|s| s.region_scores(behavior)) {
+        match regions.iter_mut().find(|(r, _)| *r == region) {
+            Some((_, values)) => values.push(f64::from(score)),
+            None => regions.push((region, vec![f64::from(score)])),
+        }
+    }
+    regions
+}
+
+/// Welch t 檢定結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WelchTest {
+    pub t: f64,
+    /// Welch–Satterthwaite 自由度
+    pub df: f64,
+    /// 雙尾 p 值
+    pub p: f64,
+}
+
+/// Mann-Whitney U 檢定結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitneyTest {
+    /// 第一組的 U 值
+    pub u: f64,
+    pub z: f64,
+    /// 雙尾 p 值（常態近似）
+    pub p: f64,
+    /// 等級雙列相關 r = 2U / (n₁n₂) − 1，正值表示第一組較高
+    pub rank_biserial: f64,
+}
+
+/// 平均與樣本標準差；少於兩筆時標準差為 0
+fn mean_sd(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let sd = if values.len() < 2 {
+        0.0
+    } else {
+        (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    };
+    (mean, sd)
+}
+
+/// 不假設變異數相等的兩樣本 t 檢定
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Option<WelchTest> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let ((mean_a, sd_a), (mean_b, sd_b)) = (mean_sd(a), mean_sd(b));
+    let (va, vb) = (sd_a.powi(2) / a.len() as f64, sd_b.powi(2) / b.len() as f64);
+    let se = (va + vb).sqrt();
+    if se <= 0.0 {
+        return None;
+    }
+    let t = (mean_a - mean_b) / se;
+    let df = (va + vb).powi(2) / (va.powi(2) / (a.len() - 1) as f64 + vb.powi(2) / (b.len() - 1) as f64);
+    Some(WelchTest { t, df, p: t_two_sided_p(t, df) })
+}
+
+/// Mann-Whitney U 檢定，常態近似含同分校正與連續性校正
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<MannWhitneyTest> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+
+    // 合併排序後給平均等級，同時累計同分校正項 Σ(t³ − t)
+    let mut pooled: Vec<(f64, bool)> = a.iter().map(|&v| (v, true)).chain(b.iter().map(|&v| (v, false))).collect();
+    pooled.sort_by(|x, y| x.0.total_cmp(&y.0));
+    let (mut rank_sum, mut ties) = (0.0, 0.0);
+    let mut start = 0;
+    while start < pooled.len() {
+        let end = start + pooled[start..].iter().take_while(|(v, _)| *v == pooled[start].0).count();
+        let rank = (start + end + 1) as f64 / 2.0;
+        rank_sum += rank * pooled[start..end].iter().filter(|(_, first)| *first).count() as f64;
+        let t = (end - start) as f64;
+        ties += t.powi(3) - t;
+        start = end;
+    }
+
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let expected = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    let (z, p) = if variance > 0.0 {
+        let difference = u - expected;
+        let corrected = (difference.abs() - 0.5).max(0.0) * difference.signum();
+        let z = corrected / variance.sqrt();
+        (z, erfc(z.abs() / std::f64::consts::SQRT_2))
+    } else {
+        (0.0, 1.0)
+    };
+    Some(MannWhitneyTest {
+        u,
+        z,
+        p,
+        rank_biserial: 2.0 * u / (n1 * n2) - 1.0,
+    })
+}
+
+/// 合併標準差的標準化平均差，乘上小樣本校正 1 − 3 / (4(n₁ + n₂) − 9)
+pub fn hedges_g(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let ((mean_a, sd_a), (mean_b, sd_b)) = (mean_sd(a), mean_sd(b));
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let pooled = (((n1 - 1.0) * sd_a.powi(2) + (n2 - 1.0) * sd_b.powi(2)) / (n1 + n2 - 2.0)).sqrt();
+    if pooled <= 0.0 {
+        return None;
+    }
+    Some((mean_a - mean_b) / pooled * (1.0 - 3.0 / (4.0 * (n1 + n2) - 9.0)))
+}
+
+/// t 分布的雙尾 p 值：I_{df/(df+t²)}(df/2, 1/2)
+fn t_two_sided_p(t: f64, df: f64) -> f64 {
+    regularized_beta(df / (df + t * t), df / 2.0, 0.5).clamp(0.0, 1.0)
+}
+
+/// 正則化不完全 beta 函數 I_x(a, b)，以連分數展開計算（Numerical Recipes 6.4）
+fn regularized_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+/// ln Γ(x)，Lanczos 近似（g = 7）
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // 反射公式
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// 互補誤差函數，Chebyshev 近似，相對誤差 < 1.2e-7（Numerical Recipes 6.2）
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98 + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let value = t * polynomial.exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::SubjectInfo;
+    use chrono::TimeZone;
+
+    fn result(behavior: &str, day: u32, scores: &[(&str, f32)]) -> CalculationResult {
+        let impact_scores: Vec<RegionImpactScore> = scores
+            .iter()
+            .map(|&(region, impact_score)| RegionImpactScore {
+                region: region.to_string(),
+                impact_score,
+                normalized_input: 1.0,
+                weight: impact_score,
+            })
+            .collect();
+        let total_impact = impact_scores.iter().map(|s| s.impact_score).sum::<f32>() / impact_scores.len() as f32;
+        CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: behavior.to_string(),
+                value: 1.0,
+                unit: "次".to_string(),
+                timestamp: chrono::Utc.with_ymd_and_hms(2024, 1, day, 9, 0, 0).unwrap(),
+            },
+            impact_scores,
+            total_impact,
+            risk_level: RiskLevel::from_score(total_impact),
+        }
+    }
+
+    fn import(subject_id: &str, results: Vec<CalculationResult>) -> ImportedResults {
+        ImportedResults {
+            subject: Some(SubjectInfo {
+                subject_id: subject_id.to_string(),
+                ..Default::default()
+            }),
+            results,
+        }
+    }
+
+    /// 病人 P1–P5 的前額葉分數為 0.1–0.5，對照 C1–C5 為 0.2–1.0
+    fn cohort() -> Cohort {
+        let mut imports = Vec::new();
+        let mut assignments = Vec::new();
+        for i in 1..=5 {
+            let patient = format!("P{}", i);
+            imports.push(import(&patient, vec![result("注意力", 1, &[("前額葉", 0.1 * i as f32)])]));
+            assignments.push((patient, "patient".to_string()));
+            let control = format!("C{}", i);
+            imports.push(import(&control, vec![result("注意力", 1, &[("前額葉", 0.2 * i as f32)])]));
+            assignments.push((control, "control".to_string()));
+        }
+        let mut cohort = Cohort::from_imports(imports);
+        assert!(cohort.assign_groups(&assignments).is_empty());
+        cohort
+    }
+
+    #[test]
+    fn test_subject_latest_results() {
+        let subject = Cohort::from_imports([
+            import("S001", vec![result("注意力", 1, &[("前額葉", 0.9)]), result("步行", 1, &[("小腦", 0.4), ("前額葉", 0.2)])]),
+            import("S001", vec![result("注意力", 3, &[("前額葉", 0.3)])]),
+        ])
+        .subjects
+        .remove(0);
+
+        assert_eq!(subject.results.len(), 3);
+        assert_eq!(subject.latest_results(None).len(), 2);
+        assert_eq!(subject.region_scores(Some("注意力")), vec![("前額葉".to_string(), 0.3)]);
+        assert_eq!(subject.region_scores(None), vec![("前額葉".to_string(), 0.3), ("小腦".to_string(), 0.4)]);
+        assert_eq!(subject.risk_level(None), Some(RiskLevel::Medium));
+        assert_eq!(subject.risk_level(Some("跑步")), None);
+    }
+
+    #[test]
+    fn test_cohort_summary() {
+        let cohort = cohort();
+        assert_eq!(cohort.groups(), vec!["patient", "control"]);
+
+        let summary = cohort.summary(Some("patient"), None);
+        assert_eq!(summary.subjects, 5);
+        let region = &summary.regions[0];
+        assert_eq!(region.n, 5);
+        assert!((region.mean - 0.3).abs() < 1e-6);
+        assert!((region.sd - 0.158_114).abs() < 1e-5);
+        assert!((region.min - 0.1).abs() < 1e-6 && (region.max - 0.5).abs() < 1e-6);
+        // 0.1、0.2 為 Low，0.3、0.4、0.5 為 Medium
+        assert_eq!(summary.risk_counts[0], (RiskLevel::Low, 2));
+        assert!((summary.risk_percentage(&RiskLevel::Medium) - 60.0).abs() < 1e-4);
+
+        let all = cohort.summary(None, Some("注意力"));
+        assert_eq!(all.subjects, 10);
+        let average = all.average_result();
+        assert_eq!(average.behavior_input.behavior_type, "注意力");
+        assert!((average.impact_scores[0].impact_score - 0.45).abs() < 1e-6);
+        assert_eq!(average.risk_level, RiskLevel::Medium);
+
+        let csv = summary_csv(&[all, summary]);
+        assert!(csv.starts_with("group,behavior_type,region,n,mean,sd,min,max\r\n"));
+        assert_eq!(csv.lines().count(), 3);
+        assert!(risk_csv(&[cohort.summary(None, None)]).contains("all,Critical,2,20\r\n"));
+
+        let overall = cohort.summary(None, None);
+        assert!(overall.map_title(Language::En).starts_with("All subjects average (n = 10): all behaviors"));
+        assert!(overall.map_title(Language::ZhTw).starts_with("全體受試者 平均（n = 10）：所有行為"));
+    }
+
+    #[test]
+    fn test_group_comparison() {
+        let comparison = cohort().compare(["patient", "control"], None);
+        assert_eq!(comparison.subjects, [5, 5]);
+        let region = &comparison.regions[0];
+        assert_eq!(region.n, [5, 5]);
+
+        // 參考值以 mpmath 計算
+        let welch = region.welch.unwrap();
+        assert!((welch.t + 1.897_367).abs() < 1e-4);
+        assert!((welch.df - 5.882_353).abs() < 1e-3);
+        assert!((welch.p - 0.107_531).abs() < 1e-4);
+
+        let mann_whitney = region.mann_whitney.unwrap();
+        assert!((mann_whitney.u - 5.0).abs() < 1e-9);
+        assert!((mann_whitney.z + 1.471_196).abs() < 1e-4);
+        assert!((mann_whitney.p - 0.141_238).abs() < 1e-4);
+        assert!((mann_whitney.rank_biserial + 0.6).abs() < 1e-9);
+        assert!((region.hedges_g.unwrap() + 1.083_871).abs() < 1e-4);
+
+        let csv = comparison_csv(&comparison);
+        assert!(csv.lines().nth(1).unwrap().starts_with("patient,control,前額葉,5,"));
+    }
+
+    #[test]
+    fn test_distributions() {
+        // t(10) = 2.228 為雙尾 .05 臨界值；z = 1.96 亦同
+        assert!((t_two_sided_p(2.228, 10.0) - 0.050_012).abs() < 1e-5);
+        assert!((erfc(1.96 / std::f64::consts::SQRT_2) - 0.049_996).abs() < 1e-5);
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-10);
+        assert_eq!(welch_t_test(&[1.0], &[1.0, 2.0]), None);
+        assert_eq!(welch_t_test(&[1.0, 1.0], &[1.0, 1.0]), None);
+        assert_eq!(mann_whitney_u(&[1.0, 1.0], &[1.0]).unwrap().p, 1.0);
+    }
+
+    #[test]
+    fn test_load_group_assignments() {
+        let path = std::env::temp_dir().join("bbv_group_assignments.csv");
+        std::fs::write(&path, "\u{feff}group,subject_id,age\r\npatient,P1,70\r\n\"control, healthy\",C1,68\r\n").unwrap();
+        assert_eq!(
+            load_group_assignments(&path).unwrap(),
+            vec![("P1".to_string(), "patient".to_string()), ("C1".to_string(), "control, healthy".to_string())]
+        );
+        std::fs::write(&path, "subject_id\r\nP1\r\n").unwrap();
+        assert!(matches!(load_group_assignments(&path), Err(BrainVizError::Parse { line: 1, .. })));
+        std::fs::remove_file(&path).ok();
+    }
+}
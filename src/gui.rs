@@ -1,13 +1,27 @@
 use eframe::egui;
+use crate::config::{dataset_label, AppConfig};
 use crate::model::*;
-use crate::mapping::MappingEngine;
+use crate::mapping::{load_behavior_brain_map, MappingEngine};
+use crate::export::{self, ExportFormat, ImportedResults};
+use crate::atlas::{load_atlases, AtlasRegionMap, AtlasVector, StandardAtlas};
+use crate::brain_map::{delta_color, impact_color, parse_path, AtlasView, BrainAtlas, BrainView, PathCommand, PlacedImpact};
+use crate::compare::{ChangeFlag, ResultComparison};
+use crate::figure::{export_brain_map, FigureOptions, ImageFormat};
+use crate::glass_brain::{self, BrainNetworkModel, Camera, GlassBrainScene};
+use crate::i18n::{self, name, tr, trf, Language};
+use crate::report::{file_name_prefix, html, ReportData};
 use crate::risk::{RiskAssessment, RiskReport};
+use crate::stream::{self, SlidingWindow, StreamEvent, StreamHandle, StreamSource};
 
 /// GUI 主應用程式結構
 pub struct BehaviorBrainApp {
     // 核心引擎
     mapping_engine: MappingEngine,
     risk_assessment: RiskAssessment,
+
+    // 設定與資料集
+    config: AppConfig,
+    active_dataset: usize,
     
     // UI 狀態
     selected_behavior: usize,
@@ -19,50 +33,631 @@ pub struct BehaviorBrainApp {
     current_risk_report: Option<RiskReport>,
     
     // 腦圖相關
-    brain_svg_data: String,
+    atlas: BrainAtlas,
+    current_view: BrainView,
+    // 標準圖譜與腦區對照（匯出圖譜向量用）
+    standard_atlases: Vec<StandardAtlas>,
+    atlas_region_map: AtlasRegionMap,
+    // 3D 玻璃腦：腦區質心與網路、相機與上次點陣化的材質
+    brain_network: BrainNetworkModel,
+    show_glass_brain: bool,
+    glass_camera: Camera,
+    glass_cache: Option<GlassBrainCache>,
     show_brain_regions: bool,
+    /// 點選後在側邊面板顯示詳情的腦區（映射表中的名稱）
+    selected_region: Option<String>,
     
     // 歷史記錄
     calculation_history: Vec<CalculationResult>,
+
+    // 受試者資料（用於匯出報告）
+    subject: SubjectInfo,
+
+    // 比較模式：兩筆結果可來自本次的歷史（來源 0）或輸出目錄中的 JSON 匯出檔
+    show_comparison: bool,
+    compare_imports: Vec<(std::path::PathBuf, ImportedResults)>,
+    compare_selection: [CompareChoice; 2],
+
+    // 即時串流：來源 URI、時間窗長度與執行中的串流；設定中指定來源時於第一個畫面開始接收
+    show_stream: bool,
+    stream_uri: String,
+    stream_window_secs: f32,
+    live_stream: Option<LiveStream>,
+    stream_autostart: bool,
+
+    // 錯誤與狀態訊息
+    error_message: Option<String>,
+    status_message: Option<String>,
 }
 
 impl BehaviorBrainApp {
-    pub fn new(behavior_maps: Vec<BehaviorBrainMap>) -> Self {
-        let brain_svg_data = std::fs::read_to_string("assets/brain.svg")
-            .unwrap_or_else(|_| "<svg>Brain SVG not found</svg>".to_string());
-        
-        Self {
+    pub fn new(config: AppConfig, behavior_maps: Vec<BehaviorBrainMap>, risk_assessment: RiskAssessment) -> Self {
+        let atlas = BrainAtlas::load(&config.atlas_svg, &config.atlas_views);
+        let (standard_atlases, atlas_errors) = load_atlases(&config.atlas_tables);
+        for e in &atlas_errors {
+            eprintln!("警告：{}", e);
+        }
+        let atlas_region_map = if standard_atlases.is_empty() {
+            AtlasRegionMap::default()
+        } else {
+            AtlasRegionMap::load(&config.atlas_region_map).unwrap_or_else(|e| {
+                eprintln!("警告：{}", e);
+                AtlasRegionMap::default()
+            })
+        };
+
+        let brain_network = BrainNetworkModel::load(&config.brain_network).unwrap_or_else(|e| {
+            eprintln!("警告：{}", e);
+            BrainNetworkModel::default()
+        });
+
+        let stream_autostart = config.stream.is_some();
+        let stream_uri = config
+            .stream
+            .clone()
+            .unwrap_or_else(|| format!("tcp://{}", stream::DEFAULT_STREAM_ADDR));
+
+        let mut app = Self {
             mapping_engine: MappingEngine::new(behavior_maps),
-            risk_assessment: RiskAssessment::default(),
+            risk_assessment,
+            config,
+            active_dataset: 0,
             selected_behavior: 0,
             input_value: 0.0,
             input_unit: "次".to_string(),
             current_result: None,
             current_risk_report: None,
-            brain_svg_data,
+            atlas,
+            current_view: BrainView::Overview,
+            standard_atlases,
+            atlas_region_map,
+            brain_network,
+            show_glass_brain: false,
+            glass_camera: Camera::default(),
+            glass_cache: None,
             show_brain_regions: true,
+            selected_region: None,
             calculation_history: Vec::new(),
+            subject: SubjectInfo::default(),
+            show_comparison: false,
+            compare_imports: Vec::new(),
+            compare_selection: [CompareChoice::default(); 2],
+            show_stream: false,
+            stream_uri,
+            stream_window_secs: stream::DEFAULT_WINDOW_SECS,
+            live_stream: None,
+            stream_autostart,
+            error_message: None,
+            status_message: None,
+        };
+        app.sync_input_unit();
+        app
+    }
+
+    /// 在介面上顯示錯誤訊息
+    pub fn show_error(&mut self, message: impl Into<String>) {
+        self.error_message = Some(message.into());
+    }
+
+    /// 渲染頂部選單列
+    fn render_menu_bar(&mut self, ui: &mut egui::Ui) {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button(tr("gui.menu.dataset"), |ui| {
+                let mut selected = None;
+                for (i, path) in self.config.mapping_files.iter().enumerate() {
+                    let label = dataset_label(path);
+                    if ui
+                        .selectable_label(self.active_dataset == i, label)
+                        .on_hover_text(path.display().to_string())
+                        .clicked()
+                    {
+                        selected = Some(i);
+                    }
+                }
+                if let Some(i) = selected {
+                    self.switch_dataset(i);
+                    ui.close_menu();
+                }
+            });
+
+            ui.menu_button(tr("gui.menu.export"), |ui| {
+                let has_result = self.current_result.is_some();
+
+                if ui.add_enabled(has_result, egui::Button::new(tr("gui.export.html"))).clicked() {
+                    self.export_html_report();
+                    ui.close_menu();
+                }
+
+                #[cfg(feature = "pdf")]
+                if ui.add_enabled(has_result, egui::Button::new(tr("gui.export.pdf"))).clicked() {
+                    self.export_pdf_report();
+                    ui.close_menu();
+                }
+                #[cfg(not(feature = "pdf"))]
+                ui.add_enabled(false, egui::Button::new(tr("gui.export.pdf")))
+                    .on_disabled_hover_text(tr("gui.export.pdf_unavailable"));
+
+                ui.separator();
+                ui.label(tr("gui.export.brain_map"));
+                if ui.add_enabled(has_result, egui::Button::new(tr("gui.export.brain_map_svg"))).clicked() {
+                    self.export_brain_map(ImageFormat::Svg);
+                    ui.close_menu();
+                }
+                #[cfg(feature = "png")]
+                if ui.add_enabled(has_result, egui::Button::new(tr("gui.export.brain_map_png"))).clicked() {
+                    self.export_brain_map(ImageFormat::Png { width: 1600 });
+                    ui.close_menu();
+                }
+
+                ui.separator();
+                ui.label(tr("gui.export.atlas"));
+                if self.standard_atlases.is_empty() {
+                    ui.label(tr("gui.export.atlas_none"));
+                }
+                let mut selected_atlas = None;
+                for (i, atlas) in self.standard_atlases.iter().enumerate() {
+                    let label = format!("{} ({}, {})", atlas.name, atlas.kind.display_name(), atlas.labels.len());
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(has_result, egui::Button::new(label)).clicked() {
+                            selected_atlas = Some((i, false));
+                        }
+                        if atlas.volume.is_some()
+                            && ui
+                                .add_enabled(has_result, egui::Button::new(tr("gui.export.atlas_nifti")))
+                                .on_hover_text(tr("gui.export.atlas_nifti_hint"))
+                                .clicked()
+                        {
+                            selected_atlas = Some((i, true));
+                        }
+                    });
+                }
+                if let Some((i, nifti)) = selected_atlas {
+                    self.export_atlas_vector(i, nifti);
+                    ui.close_menu();
+                }
+
+                ui.separator();
+                ui.label(tr("gui.export.current"));
+                for format in ExportFormat::ALL {
+                    if ui.add_enabled(has_result, egui::Button::new(format.label())).clicked() {
+                        self.export_results(format, false);
+                        ui.close_menu();
+                    }
+                }
+
+                ui.separator();
+                ui.label(trf("gui.export.history", &[&self.calculation_history.len()]));
+                let has_history = !self.calculation_history.is_empty();
+                for format in ExportFormat::ALL {
+                    if ui.add_enabled(has_history, egui::Button::new(format.label())).clicked() {
+                        self.export_results(format, true);
+                        ui.close_menu();
+                    }
+                }
+
+                if !has_result {
+                    ui.label(tr("gui.export.need_result"));
+                }
+            });
+
+            if ui.button(tr("gui.menu.compare")).clicked() {
+                self.open_comparison();
+            }
+
+            let stream_label = if self.live_stream.is_some() {
+                format!("{} ●", tr("gui.menu.stream"))
+            } else {
+                tr("gui.menu.stream").to_string()
+            };
+            if ui.button(stream_label).clicked() {
+                self.show_stream = true;
+            }
+
+            ui.menu_button(tr("gui.menu.language"), |ui| {
+                let current = i18n::language();
+                for language in Language::ALL {
+                    if ui.selectable_label(current == language, language.native_name()).clicked() {
+                        self.switch_language(language);
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
+    }
+
+    /// 開啟比較視窗：重新掃描輸出目錄中的 JSON 匯出檔，預設比較本次最近兩筆結果
+    fn open_comparison(&mut self) {
+        self.compare_imports = export::discover_json_exports(&self.config.output_dir)
+            .into_iter()
+            .filter_map(|path| match export::import_json(&path) {
+                Ok(imported) if !imported.results.is_empty() => Some((path, imported)),
+                Ok(_) => None,
+                Err(e) => {
+                    eprintln!("警告：{}", e);
+                    None
+                }
+            })
+            .collect();
+        let latest = self.calculation_history.len().saturating_sub(1);
+        self.compare_selection = [
+            CompareChoice { source: 0, result: latest.saturating_sub(1) },
+            CompareChoice { source: 0, result: latest },
+        ];
+        self.show_comparison = true;
+    }
+
+    /// 比較來源的名稱：本次工作階段或匯出檔名，附受試者編號
+    fn compare_source_label(&self, source: usize) -> String {
+        let (label, subject) = match source {
+            0 => (tr("gui.compare.session").to_string(), self.current_subject()),
+            i => {
+                let (path, imported) = &self.compare_imports[i - 1];
+                (dataset_label(path), imported.subject.as_ref())
+            }
+        };
+        match subject.filter(|s| !s.subject_id.trim().is_empty()) {
+            Some(subject) => format!("{} ({})", label, subject.subject_id),
+            None => label,
+        }
+    }
+
+    fn compare_source_results(&self, source: usize) -> &[CalculationResult] {
+        match source {
+            0 => &self.calculation_history,
+            i => self.compare_imports.get(i - 1).map_or(&[], |(_, imported)| &imported.results),
+        }
+    }
+
+    fn compare_result(&self, choice: CompareChoice) -> Option<&CalculationResult> {
+        self.compare_source_results(choice.source).get(choice.result)
+    }
+
+    /// 比較視窗：兩筆結果的腦圖、差異圖與逐腦區變化表
+    fn render_comparison(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button(tr("gui.compare.rescan")).clicked() {
+                let selection = self.compare_selection;
+                self.open_comparison();
+                self.compare_selection = selection;
+            }
+            ui.small(trf("gui.compare.sources", &[&self.config.output_dir.display()]));
+        });
+        ui.separator();
+
+        ui.columns(2, |columns| {
+            for (side, ui) in columns.iter_mut().enumerate() {
+                ui.strong(tr(if side == 0 { "gui.compare.baseline" } else { "gui.compare.comparison" }));
+                self.render_compare_selector(ui, side);
+            }
+        });
+
+        let (Some(baseline), Some(comparison)) = (
+            self.compare_result(self.compare_selection[0]),
+            self.compare_result(self.compare_selection[1]),
+        ) else {
+            ui.label(tr("gui.compare.need_two"));
+            return;
+        };
+        let params = self
+            .mapping_engine
+            .get_behavior_map(&baseline.behavior_input.behavior_type)
+            .and_then(|map| map.normalization_params.as_ref());
+        let result = ResultComparison::new(baseline, comparison, params);
+        let max_abs = result.max_abs_delta();
+        let view = self.current_atlas_view();
+
+        // 兩筆結果與差異圖並排；差異圖的圓大小依 |差值|，顏色依差值正負
+        ui.columns(3, |columns| {
+            for (ui, (title, scores)) in columns.iter_mut().zip([
+                (tr("gui.compare.baseline"), &baseline.impact_scores),
+                (tr("gui.compare.comparison"), &comparison.impact_scores),
+            ]) {
+                ui.label(title);
+                let circles: Vec<(PlacedImpact, egui::Color32)> = view
+                    .place(scores)
+                    .into_iter()
+                    .map(|placed| {
+                        let [r, g, b] = impact_color(placed.impact_score);
+                        (placed, egui::Color32::from_rgb(r, g, b))
+                    })
+                    .collect();
+                paint_static_map(ui, view, &circles);
+            }
+
+            let ui = &mut columns[2];
+            ui.label(tr("gui.compare.difference"));
+            let deltas: Vec<RegionImpactScore> = result
+                .regions
+                .iter()
+                .map(|change| RegionImpactScore {
+                    region: change.region.clone(),
+                    impact_score: change.delta,
+                    normalized_input: 0.0,
+                    weight: 0.0,
+                })
+                .collect();
+            let circles: Vec<(PlacedImpact, egui::Color32)> = view
+                .place(&deltas)
+                .into_iter()
+                .map(|placed| {
+                    let [r, g, b] = delta_color(placed.impact_score, max_abs);
+                    (placed, egui::Color32::from_rgb(r, g, b))
+                })
+                .collect();
+            paint_static_map(ui, view, &circles);
+            ui.horizontal(|ui| {
+                let [r, g, b] = delta_color(-1.0, 1.0);
+                ui.colored_label(egui::Color32::from_rgb(r, g, b), tr("gui.compare.legend_decrease"));
+                let [r, g, b] = delta_color(1.0, 1.0);
+                ui.colored_label(egui::Color32::from_rgb(r, g, b), tr("gui.compare.legend_increase"));
+            });
+        });
+
+        ui.separator();
+        ui.label(trf("gui.compare.total_delta", &[&format!("{:+.3}", result.total_delta)]));
+        match result.reliability {
+            Some(r) => {
+                let key = if result.reliability_assumed { "gui.compare.reliability_assumed" } else { "gui.compare.reliability" };
+                ui.label(trf(key, &[&format!("{:.2}", r), &result.reliable_changes()]));
+            }
+            None if !result.same_behavior => {
+                ui.colored_label(egui::Color32::from_rgb(255, 170, 0), tr("gui.compare.different_behavior"));
+            }
+            None => {
+                ui.label(tr("gui.compare.no_norms"));
+            }
+        }
+
+        egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
+            egui::Grid::new("compare_table").striped(true).num_columns(6).show(ui, |ui| {
+                for key in ["gui.compare.col.region", "gui.compare.col.baseline", "gui.compare.col.comparison", "gui.compare.col.delta", "gui.compare.col.rci", "gui.compare.col.flag"] {
+                    ui.strong(tr(key));
+                }
+                ui.end_row();
+                let score = |s: Option<f32>| s.map_or_else(|| "—".to_string(), |s| format!("{:.3}", s));
+                for change in &result.regions {
+                    ui.label(name(&change.region));
+                    ui.label(score(change.baseline));
+                    ui.label(score(change.comparison));
+                    let [r, g, b] = delta_color(change.delta, max_abs);
+                    ui.colored_label(egui::Color32::from_rgb(r, g, b), format!("{:+.3}", change.delta));
+                    ui.label(change.rci.map_or_else(|| "—".to_string(), |rci| format!("{:+.2}", rci)));
+                    let color = match change.flag {
+                        ChangeFlag::Increase => egui::Color32::from_rgb(214, 39, 40),
+                        ChangeFlag::Decrease => egui::Color32::from_rgb(31, 119, 180),
+                        ChangeFlag::Stable | ChangeFlag::NotComparable => ui.visuals().weak_text_color(),
+                    };
+                    ui.colored_label(color, format!("{} {}", change.flag.symbol(), change.flag.label()));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    /// 比較的一邊：選擇來源與其中一筆結果
+    fn render_compare_selector(&mut self, ui: &mut egui::Ui, side: usize) {
+        let mut choice = self.compare_selection[side];
+        egui::ComboBox::from_id_source(("compare_source", side))
+            .width(ui.available_width() * 0.9)
+            .selected_text(self.compare_source_label(choice.source))
+            .show_ui(ui, |ui| {
+                for source in 0..=self.compare_imports.len() {
+                    if ui.selectable_label(choice.source == source, self.compare_source_label(source)).clicked() {
+                        choice = CompareChoice { source, result: self.compare_source_results(source).len().saturating_sub(1) };
+                    }
+                }
+            });
+        let results = self.compare_source_results(choice.source);
+        let label = |result: &CalculationResult| {
+            let input = &result.behavior_input;
+            format!(
+                "{} {} {} {}",
+                input.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                name(&input.behavior_type),
+                input.value,
+                name(&input.unit)
+            )
+        };
+        egui::ComboBox::from_id_source(("compare_result", side))
+            .width(ui.available_width() * 0.9)
+            .selected_text(results.get(choice.result).map_or_else(|| tr("gui.compare.no_results").to_string(), label))
+            .show_ui(ui, |ui| {
+                for (i, result) in results.iter().enumerate() {
+                    ui.selectable_value(&mut choice.result, i, label(result));
+                }
+            });
+        self.compare_selection[side] = choice;
+    }
+
+    /// 切換介面與報告語言，並以新語言重新產生目前的報告
+    fn switch_language(&mut self, language: Language) {
+        i18n::set_language(language);
+        if let Some(result) = &self.current_result {
+            self.current_risk_report = Some(self.risk_assessment.generate_contextual_report(
+                result,
+                self.current_subject(),
+                &self.calculation_history,
+                language,
+            ));
+        }
+    }
+
+    /// 目前顯示的腦圖視角；匯出報告與腦圖亦使用此視角
+    fn current_atlas_view(&self) -> &AtlasView {
+        self.atlas
+            .view(self.current_view)
+            .or_else(|| self.atlas.views.first())
+            .expect("總覽視角一定存在")
+    }
+
+    /// 已填寫編號的受試者資料
+    fn current_subject(&self) -> Option<&SubjectInfo> {
+        (!self.subject.subject_id.trim().is_empty()).then_some(&self.subject)
+    }
+
+    /// 目前結果的報告資料；尚未計算時為 None
+    fn report_data(&self) -> Option<ReportData<'_>> {
+        Some(ReportData {
+            subject: self.current_subject(),
+            result: self.current_result.as_ref()?,
+            report: self.current_risk_report.as_ref()?,
+            atlas_svg: &self.current_atlas_view().svg,
+            language: i18n::language(),
+        })
+    }
+
+    /// 顯示匯出結果
+    fn finish_export(&mut self, exported: crate::error::Result<std::path::PathBuf>) {
+        match exported {
+            Ok(path) => {
+                self.error_message = None;
+                self.status_message = Some(trf("gui.status.exported", &[&path.display()]));
+            }
+            Err(e) => self.show_error(e.to_string()),
+        }
+    }
+
+    /// 匯出目前結果或全部歷史的長格式資料
+    fn export_results(&mut self, format: ExportFormat, whole_history: bool) {
+        let (results, scope) = if whole_history {
+            (self.calculation_history.as_slice(), "history")
+        } else {
+            (self.current_result.as_slice(), "current")
+        };
+        let subject = self.current_subject();
+        let exported = export::export_results(subject, results, format, scope, &self.config.output_dir);
+        self.finish_export(exported);
+    }
+
+    /// 將目前結果展開為標準圖譜的標籤向量並匯出 CSV，或以標籤影像匯出 NIfTI 影像
+    fn export_atlas_vector(&mut self, index: usize, nifti: bool) {
+        let (Some(result), Some(atlas)) = (&self.current_result, self.standard_atlases.get(index)) else {
+            return;
+        };
+        let vector = AtlasVector::from_result(result, atlas, &self.atlas_region_map);
+        let exported = if nifti {
+            export::export_impact_nifti(self.current_subject(), result, atlas, &vector, &self.config.output_dir)
+        } else {
+            export::export_atlas_vector(self.current_subject(), result, &vector, &self.config.output_dir)
+        };
+        self.finish_export(exported);
+        if !vector.unmapped.is_empty() {
+            if let Some(status) = &mut self.status_message {
+                status.push_str(&trf("gui.status.atlas_unmapped", &[&vector.unmapped.len()]));
+            }
+        }
+    }
+
+    /// 匯出目前結果的腦圖（含標籤、圖例與色條）
+    fn export_brain_map(&mut self, format: ImageFormat) {
+        let Some(result) = &self.current_result else {
+            return;
+        };
+        let subject = self.current_subject();
+        let out = self.config.output_dir.join(format!(
+            "{}_brain_map_{}.{}",
+            file_name_prefix(subject),
+            result.behavior_input.timestamp.format("%Y%m%d_%H%M%S"),
+            format.extension()
+        ));
+        let exported = std::fs::create_dir_all(&self.config.output_dir)
+            .map_err(|e| crate::error::BrainVizError::io(&self.config.output_dir, e))
+            .and_then(|_| {
+                export_brain_map(
+                    result,
+                    &self.current_atlas_view().svg,
+                    &FigureOptions::default(),
+                    i18n::language(),
+                    format,
+                    self.config.font.as_deref(),
+                    &out,
+                )
+            })
+            .map(|_| out);
+        self.finish_export(exported);
+    }
+
+    /// 匯出含歷史趨勢的 HTML 報告至輸出目錄
+    fn export_html_report(&mut self) {
+        let Some(data) = self.report_data() else {
+            return;
+        };
+        let exported = html::export_html_report(&data, &self.calculation_history, &self.config.output_dir);
+        self.finish_export(exported);
+    }
+
+    /// 匯出目前結果的 PDF 報告至輸出目錄
+    #[cfg(feature = "pdf")]
+    fn export_pdf_report(&mut self) {
+        let Some(font) = crate::fonts::load_cjk_font(self.config.font.as_deref()) else {
+            self.show_error(tr("gui.error.no_cjk_font"));
+            return;
+        };
+        let Some(data) = self.report_data() else {
+            return;
+        };
+        let exported = crate::report::pdf::export_pdf_report(&data, &font.data, &self.config.output_dir);
+        self.finish_export(exported);
+    }
+
+    /// 切換行為-腦區映射資料集
+    fn switch_dataset(&mut self, index: usize) {
+        let Some(path) = self.config.mapping_files.get(index) else {
+            return;
+        };
+
+        match load_behavior_brain_map(path) {
+            Ok(behavior_maps) => {
+                self.mapping_engine = MappingEngine::new(behavior_maps);
+                self.active_dataset = index;
+                self.selected_behavior = 0;
+                self.current_result = None;
+                self.current_risk_report = None;
+                self.selected_region = None;
+                self.error_message = None;
+                self.sync_input_unit();
+            }
+            Err(e) => self.show_error(e.to_string()),
+        }
+    }
+
+    /// 將輸入單位同步為目前行為的預期單位
+    fn sync_input_unit(&mut self) {
+        let behavior_types = self.mapping_engine.get_behavior_types();
+        if let Some(unit) = behavior_types
+            .get(self.selected_behavior)
+            .and_then(|behavior| self.mapping_engine.get_behavior_unit(behavior))
+        {
+            self.input_unit = unit.to_string();
         }
     }
 
     /// 渲染左側控制面板
     fn render_control_panel(&mut self, ui: &mut egui::Ui) {
-        ui.heading("🧠 行為輸入");
+        ui.heading(tr("gui.input.heading"));
         
         ui.separator();
         
         // 行為選擇
         ui.horizontal(|ui| {
-            ui.label("行為類型:");
+            ui.label(tr("gui.input.behavior"));
             let behavior_types = self.mapping_engine.get_behavior_types();
             if !behavior_types.is_empty() {
+                let previous = self.selected_behavior;
                 egui::ComboBox::from_id_source("behavior_combo")
-                    .selected_text(behavior_types.get(self.selected_behavior).unwrap_or(&"未選擇".to_string()))
+                    .selected_text(
+                        behavior_types
+                            .get(self.selected_behavior)
+                            .map_or(tr("gui.input.none_selected"), |behavior| name(behavior)),
+                    )
                     .show_ui(ui, |cb| {
                         for (i, behavior) in behavior_types.iter().enumerate() {
-                            cb.selectable_value(&mut self.selected_behavior, i, behavior);
+                            cb.selectable_value(&mut self.selected_behavior, i, name(behavior));
                         }
                     });
+                if previous != self.selected_behavior {
+                    self.sync_input_unit();
+                }
             }
         });
 
@@ -70,42 +665,97 @@ impl BehaviorBrainApp {
 
         // 數值輸入
         ui.horizontal(|ui| {
-            ui.label("數值:");
+            ui.label(tr("gui.input.value"));
             ui.add(egui::DragValue::new(&mut self.input_value).speed(0.1));
         });
 
         ui.horizontal(|ui| {
-            ui.label("單位:");
+            ui.label(tr("gui.input.unit"));
             egui::ComboBox::from_id_source("unit_combo")
-                .selected_text(&self.input_unit)
+                .selected_text(name(&self.input_unit))
                 .show_ui(ui, |cb| {
-                    cb.selectable_value(&mut self.input_unit, "次".to_string(), "次");
-                    cb.selectable_value(&mut self.input_unit, "秒".to_string(), "秒");
-                    cb.selectable_value(&mut self.input_unit, "毫秒".to_string(), "毫秒");
-                    cb.selectable_value(&mut self.input_unit, "比率".to_string(), "比率");
-                    cb.selectable_value(&mut self.input_unit, "分數".to_string(), "分數");
+                    for unit in ["次", "秒", "毫秒", "比率", "分數", "公尺/秒"] {
+                        cb.selectable_value(&mut self.input_unit, unit.to_string(), name(unit));
+                    }
                 });
         });
 
         ui.add_space(20.0);
 
         // 計算按鈕
-        if ui.add_sized([200.0, 40.0], egui::Button::new("🔍 計算影響分數")).clicked() {
+        if ui.add_sized([200.0, 40.0], egui::Button::new(tr("gui.input.calculate"))).clicked() {
             self.calculate_impact();
         }
 
+        // 錯誤訊息
+        if let Some(message) = &self.error_message {
+            ui.add_space(10.0);
+            ui.colored_label(egui::Color32::from_rgb(200, 30, 30), format!("⚠️ {}", message));
+        }
+        if let Some(message) = &self.status_message {
+            ui.add_space(10.0);
+            ui.colored_label(egui::Color32::from_rgb(30, 140, 60), format!("✅ {}", message));
+        }
+
         ui.separator();
+
+        // 受試者資料
+        ui.collapsing(tr("gui.subject.heading"), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("gui.subject.id"));
+                ui.text_edit_singleline(&mut self.subject.subject_id);
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("gui.subject.age"));
+                let mut age = self.subject.age.unwrap_or(0);
+                ui.add(egui::DragValue::new(&mut age).clamp_range(0..=120));
+                self.subject.age = (age > 0).then_some(age);
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("gui.subject.sex"));
+                egui::ComboBox::from_id_source("sex_combo")
+                    .selected_text(self.subject.sex.as_deref().map_or(tr("gui.subject.unspecified"), name))
+                    .show_ui(ui, |cb| {
+                        cb.selectable_value(&mut self.subject.sex, None, tr("gui.subject.unspecified"));
+                        // 儲存資料檔中的原名，只翻譯顯示文字
+                        for sex in ["男", "女", "其他"] {
+                            cb.selectable_value(&mut self.subject.sex, Some(sex.to_string()), name(sex));
+                        }
+                    });
+            });
+            ui.label(tr("gui.subject.notes"));
+            let mut notes = self.subject.notes.clone().unwrap_or_default();
+            if ui.text_edit_multiline(&mut notes).changed() {
+                self.subject.notes = (!notes.trim().is_empty()).then_some(notes);
+            }
+        });
         
         // 設定選項
-        ui.collapsing("⚙️ 顯示設定", |ui| {
-            ui.checkbox(&mut self.show_brain_regions, "顯示腦區標籤");
+        ui.collapsing(tr("gui.settings.heading"), |ui| {
+            ui.checkbox(&mut self.show_brain_regions, tr("gui.settings.show_labels"));
         });
     }
 
     /// 渲染右側腦圖面板
     fn render_brain_map_panel(&mut self, ui: &mut egui::Ui) {
-        ui.heading("🗺️ 腦區影響圖");
-        
+        ui.heading(tr("gui.map.heading"));
+
+        let has_glass_brain = !self.brain_network.regions.is_empty();
+        if self.atlas.views.len() > 1 || has_glass_brain {
+            ui.horizontal(|ui| {
+                ui.label(tr("gui.map.view"));
+                for view in self.atlas.views.iter().map(|v| v.view) {
+                    if ui.selectable_label(!self.show_glass_brain && self.current_view == view, view.label()).clicked() {
+                        self.current_view = view;
+                        self.show_glass_brain = false;
+                    }
+                }
+                if has_glass_brain && ui.selectable_label(self.show_glass_brain, tr("gui.map.glass")).clicked() {
+                    self.show_glass_brain = true;
+                }
+            });
+        }
+
         ui.separator();
         
         // 腦圖顯示區域
@@ -116,42 +766,85 @@ impl BehaviorBrainApp {
         );
         
         ui.allocate_ui_at_rect(brain_rect, |ui| {
-            ui.centered_and_justified(|ui| {
-                if self.brain_svg_data.contains("<svg") {
-                    // 這裡應該實現真正的 SVG 渲染
-                    // 目前顯示placeholder
-                    let painter = ui.painter();
-                    let rect = ui.max_rect();
-                    
-                    // 繪製腦部輪廓（簡化版）
-                    painter.rect_filled(rect, 10.0, egui::Color32::from_gray(240));
-                    painter.rect_stroke(rect, 10.0, egui::Stroke::new(2.0, egui::Color32::DARK_GRAY));
-                    
-                    // 如果有計算結果，顯示影響區域
-                    if let Some(result) = &self.current_result {
-                        self.render_impact_overlay(&painter, &rect, &result.impact_scores);
-                    }
-                    
-                    ui.label("腦圖 (SVG 渲染開發中...)");
-                } else {
-                    ui.label("❌ 找不到 brain.svg 文件");
+            if self.show_glass_brain {
+                self.render_glass_brain(ui);
+            } else if self.current_atlas_view().svg.contains("<svg") {
+                self.render_interactive_map(ui);
+            } else {
+                ui.centered_and_justified(|ui| {
+                    ui.label(tr("gui.map.missing"));
+                });
+            }
+        });
+
+        // 不在目前視角的腦區
+        if let Some(result) = &self.current_result {
+            let hidden: Vec<&str> = if self.show_glass_brain {
+                result
+                    .impact_scores
+                    .iter()
+                    .filter(|impact| self.brain_network.region_index(&impact.region).is_none())
+                    .map(|impact| name(&impact.region))
+                    .collect()
+            } else if self.current_view != BrainView::Overview {
+                let layout = &self.current_atlas_view().layout;
+                result
+                    .impact_scores
+                    .iter()
+                    .filter(|impact| layout.find_region(&impact.region).is_none())
+                    .map(|impact| name(&impact.region))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            if !hidden.is_empty() {
+                ui.small(trf("gui.map.not_in_view", &[&hidden.join(tr("list.separator"))]));
+            }
+        }
+        if self.show_glass_brain {
+            ui.small(tr("gui.map.glass_hint"));
+            ui.horizontal_wrapped(|ui| {
+                for network in &self.brain_network.networks {
+                    let [r, g, b] = network.rgb();
+                    ui.colored_label(egui::Color32::from_rgb(r, g, b), format!("━ {}", name(&network.name)));
                 }
             });
-        });
+        }
 
         ui.add_space(10.0);
 
         // 影響分數詳情
         if let Some(result) = &self.current_result {
-            ui.collapsing("📊 詳細影響分數", |ui| {
+            let explanation = self.mapping_engine.explain(result).ok();
+            ui.collapsing(tr("gui.scores.heading"), |ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
-                    for impact in &result.impact_scores {
+                    for (index, impact) in result.impact_scores.iter().enumerate() {
                         ui.horizontal(|ui| {
                             let color = self.get_impact_color(impact.impact_score);
                             ui.colored_label(color, "●");
-                            ui.label(&impact.region);
+                            ui.label(name(&impact.region));
                             ui.label(format!("{:.3}", impact.impact_score));
                         });
+                        // 展開後逐步說明此分數的由來
+                        if let Some(explanation) = &explanation {
+                            egui::CollapsingHeader::new(tr("gui.scores.explain"))
+                                .id_source(("explain_region", index))
+                                .show(ui, |ui| {
+                                    for step in explanation
+                                        .input_steps(i18n::language())
+                                        .iter()
+                                        .chain(&explanation.region_steps(&explanation.regions[index], i18n::language()))
+                                    {
+                                        ui.small(step);
+                                    }
+                                });
+                        }
+                    }
+                    if let Some(explanation) = &explanation {
+                        ui.separator();
+                        for step in explanation.total_steps(i18n::language()) {
+                            ui.small(step);
+                        }
                     }
                 });
             });
@@ -162,7 +855,7 @@ impl BehaviorBrainApp {
     fn render_results_panel(&mut self, ui: &mut egui::Ui) {
         if let Some(risk_report) = &self.current_risk_report {
             ui.separator();
-            ui.heading("📋 風險評估報告");
+            ui.heading(tr("gui.report.heading"));
             
             // 風險等級顯示
             ui.horizontal(|ui| {
@@ -173,8 +866,8 @@ impl BehaviorBrainApp {
                     RiskLevel::Critical => (egui::Color32::from_rgb(139, 0, 0), "🔴"),
                 };
                 ui.label(icon);
-                ui.colored_label(color, format!("風險等級: {}", risk_report.risk_level.description()));
-                ui.label(format!("總影響分數: {:.3}", risk_report.total_impact));
+                ui.colored_label(color, trf("gui.report.risk_level", &[&risk_report.risk_level.description()]));
+                ui.label(trf("gui.report.total_impact", &[&format!("{:.3}", risk_report.total_impact)]));
             });
 
             ui.add_space(10.0);
@@ -182,16 +875,17 @@ impl BehaviorBrainApp {
             // 分欄顯示詳細資訊
             ui.columns(2, |columns| {
                 // 左欄：受影響功能
-                columns[0].collapsing("⚠️ 可能受影響的功能", |ui| {
+                columns[0].collapsing(tr("gui.report.functions"), |ui| {
                     for function in &risk_report.affected_functions {
-                        ui.label(format!("• {}", function));
+                        ui.label(format!("• {}", name(function)));
                     }
                 });
 
                 // 右欄：潛在疾病風險
-                columns[1].collapsing("🏥 潜在疾病風險", |ui| {
-                    for disease in &risk_report.potential_diseases {
-                        ui.label(format!("• {}", disease));
+                columns[1].collapsing(tr("gui.report.diseases"), |ui| {
+                    for (rank, risk) in risk_report.disease_ranking.iter().enumerate() {
+                        ui.label(format!("{}. {} ({:.2})", rank + 1, name(&risk.disease), risk.score))
+                            .on_hover_text(trf("gui.report.supporting_regions", &[&risk.contributors_label(i18n::language())]));
                     }
                 });
             });
@@ -199,14 +893,42 @@ impl BehaviorBrainApp {
             ui.add_space(10.0);
 
             // 建議事項
-            ui.collapsing("💡 建議事項", |ui| {
-                for recommendation in &risk_report.recommendations {
-                    ui.label(format!("• {}", recommendation));
+            ui.collapsing(tr("gui.report.recommendations"), |ui| {
+                for (recommendation, rule_id) in risk_report
+                    .recommendations
+                    .iter()
+                    .zip(&risk_report.recommendation_rule_ids)
+                {
+                    ui.label(format!("• {}", recommendation))
+                        .on_hover_text(trf("gui.report.rule", &[rule_id]));
                 }
             });
+
+            ui.add_space(10.0);
+            self.render_trends(ui);
         }
     }
 
+    /// 趨勢圖：最近結果的總影響分數與目前行為的數值，串流時即時更新
+    fn render_trends(&self, ui: &mut egui::Ui) {
+        let recent = &self.calculation_history[self.calculation_history.len().saturating_sub(TREND_POINTS)..];
+        egui::CollapsingHeader::new(tr("gui.trend.heading"))
+            .default_open(self.live_stream.is_some())
+            .show(ui, |ui| {
+                let totals: Vec<_> = recent.iter().map(|r| (r.behavior_input.timestamp, r.total_impact)).collect();
+                paint_trend(ui, tr("gui.trend.total"), &totals, Some((0.0, 1.0)));
+                if let Some(current) = &self.current_result {
+                    let input = &current.behavior_input;
+                    let values: Vec<_> = recent
+                        .iter()
+                        .filter(|r| r.behavior_input.behavior_type == input.behavior_type)
+                        .map(|r| (r.behavior_input.timestamp, r.behavior_input.value))
+                        .collect();
+                    paint_trend(ui, &format!("{} ({})", name(&input.behavior_type), name(&input.unit)), &values, None);
+                }
+            });
+    }
+
     /// 計算影響分數
     fn calculate_impact(&mut self) {
         let behavior_types = self.mapping_engine.get_behavior_types();
@@ -218,40 +940,406 @@ impl BehaviorBrainApp {
                 timestamp: chrono::Utc::now(),
             };
 
-            if let Some(result) = self.mapping_engine.calculate_impact(&input) {
-                let risk_report = self.risk_assessment.generate_risk_report(&result);
-                
-                self.calculation_history.push(result.clone());
-                self.current_result = Some(result);
-                self.current_risk_report = Some(risk_report);
+            match self.mapping_engine.calculate_impact(&input) {
+                Ok(result) => {
+                    self.record_result(result);
+                    self.error_message = None;
+                }
+                Err(e) => self.show_error(e.to_string()),
+            }
+        }
+    }
+
+    /// 將計算結果設為目前結果並加入歷史記錄
+    fn record_result(&mut self, result: CalculationResult) {
+        let risk_report = self.risk_assessment.generate_contextual_report(
+            &result,
+            self.current_subject(),
+            &self.calculation_history,
+            i18n::language(),
+        );
+        self.calculation_history.push(result.clone());
+        let excess = self.calculation_history.len().saturating_sub(HISTORY_LIMIT);
+        self.calculation_history.drain(..excess);
+        // 比較視窗以索引指向本次工作階段的歷史，刪除最舊的記錄後同步平移
+        for choice in self.compare_selection.iter_mut().filter(|choice| choice.source == 0) {
+            choice.result = choice.result.saturating_sub(excess);
+        }
+        self.current_result = Some(result);
+        self.current_risk_report = Some(risk_report);
+    }
+
+    /// 開始接收串流；收到樣本時請介面重繪
+    fn start_stream(&mut self, ctx: &egui::Context) {
+        self.live_stream = None;
+        let started = StreamSource::parse(&self.stream_uri).and_then(|source| {
+            let ctx = ctx.clone();
+            stream::start(source, move || ctx.request_repaint())
+        });
+        match started {
+            Ok(handle) => {
+                self.live_stream = Some(LiveStream {
+                    handle,
+                    window: SlidingWindow::new(self.stream_window_secs, stream::DEFAULT_SCORE_INTERVAL_SECS),
+                    peers: Vec::new(),
+                    samples: 0,
+                    scored: 0,
+                    last_warning: None,
+                });
+                self.error_message = None;
             }
+            Err(e) => self.show_error(e.to_string()),
         }
     }
 
-    /// 繪製影響疊加層
-    fn render_impact_overlay(&self, painter: &egui::Painter, rect: &egui::Rect, impacts: &[RegionImpactScore]) {
-        // 簡化版：在腦圖上顯示影響點
-        for (i, impact) in impacts.iter().enumerate() {
-            if impact.impact_score > 0.1 {
-                let color = self.get_impact_color(impact.impact_score);
-                let pos = egui::Pos2::new(
-                    rect.min.x + (i as f32 + 1.0) * rect.width() / (impacts.len() as f32 + 1.0),
-                    rect.center().y + (i as f32 % 2.0 - 0.5) * 30.0,
+    /// 取出串流事件，依滑動時間窗計分並更新腦圖與歷史記錄
+    fn poll_stream(&mut self, ctx: &egui::Context) {
+        if std::mem::take(&mut self.stream_autostart) {
+            self.start_stream(ctx);
+        }
+        let Some(live) = &mut self.live_stream else {
+            return;
+        };
+        for event in live.handle.try_events() {
+            match event {
+                StreamEvent::Sample(sample) => {
+                    live.samples += 1;
+                    live.window.push(sample);
+                }
+                StreamEvent::Connected(peer) => {
+                    live.peers.retain(|p| *p != peer);
+                    if live.peers.len() >= PEER_LIMIT {
+                        live.peers.remove(0);
+                    }
+                    live.peers.push(peer);
+                }
+                StreamEvent::Warning(message) => live.last_warning = Some(message),
+            }
+        }
+
+        let engine = &self.mapping_engine;
+        let inputs = live
+            .window
+            .due(chrono::Utc::now(), |behavior| engine.get_behavior_unit(behavior).unwrap_or_default().to_string());
+        let mut results = Vec::new();
+        for input in inputs {
+            match engine.calculate_impact(&input) {
+                Ok(result) => results.push(result),
+                Err(e) => live.last_warning = Some(e.to_string()),
+            }
+        }
+        live.scored += results.len();
+        for result in results {
+            self.record_result(result);
+        }
+        // 尚未到計分間隔的樣本稍後再計分
+        ctx.request_repaint_after(std::time::Duration::from_secs_f32(stream::DEFAULT_SCORE_INTERVAL_SECS));
+    }
+
+    /// 串流視窗：來源、時間窗設定與接收狀態
+    fn render_stream_window(&mut self, ui: &mut egui::Ui) {
+        let running = self.live_stream.is_some();
+        ui.horizontal(|ui| {
+            ui.label(tr("gui.stream.source"));
+            ui.add_enabled(!running, egui::TextEdit::singleline(&mut self.stream_uri).desired_width(260.0));
+        });
+        ui.small(tr("gui.stream.source_hint"));
+        ui.horizontal(|ui| {
+            ui.label(tr("gui.stream.window"));
+            ui.add_enabled(
+                !running,
+                egui::DragValue::new(&mut self.stream_window_secs).clamp_range(1.0..=600.0).speed(0.5),
+            );
+        });
+        ui.horizontal(|ui| {
+            if running {
+                if ui.button(tr("gui.stream.stop")).clicked() {
+                    self.live_stream = None;
+                }
+            } else if ui.button(tr("gui.stream.start")).clicked() {
+                let ctx = ui.ctx().clone();
+                self.start_stream(&ctx);
+            }
+        });
+        ui.separator();
+
+        let Some(live) = &self.live_stream else {
+            ui.label(tr("gui.stream.stopped"));
+            return;
+        };
+        let address = live.handle.local_addr.map_or(live.handle.source.to_string(), |addr| addr.to_string());
+        ui.colored_label(egui::Color32::from_rgb(30, 140, 60), trf("gui.stream.listening", &[&address]));
+        if !live.peers.is_empty() {
+            ui.label(trf("gui.stream.connections", &[&live.peers.join(tr("list.separator"))]));
+        }
+        ui.label(trf("gui.stream.samples", &[&live.samples, &live.scored]));
+        for behavior in self.mapping_engine.get_behavior_types() {
+            let count = live.window.sample_count(&behavior);
+            if count > 0 {
+                ui.small(trf("gui.stream.window_samples", &[&name(&behavior), &count]));
+            }
+        }
+        if let Some(warning) = &live.last_warning {
+            ui.colored_label(egui::Color32::from_rgb(200, 120, 0), trf("gui.stream.warning", &[warning]));
+        }
+        ui.small(tr("gui.stream.hint"));
+        ui.separator();
+        self.render_trends(ui);
+    }
+
+    /// 依腦圖版面繪製輪廓與影響圓；滑鼠停留顯示腦區資訊，點擊開啟側邊詳情面板
+    fn render_interactive_map(&mut self, ui: &mut egui::Ui) {
+        let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::click());
+        let rect = response.rect;
+        painter.rect_filled(rect, 10.0, egui::Color32::from_gray(245));
+
+        // 等比例縮放腦圖座標至繪圖區
+        let view = self.current_atlas_view();
+        let layout = &view.layout;
+        let scale = (rect.width() / layout.width.max(1.0)).min(rect.height() / layout.height.max(1.0));
+        let origin = rect.center() - egui::vec2(layout.width, layout.height) * scale / 2.0;
+        let to_screen = |p: [f32; 2]| origin + egui::vec2(p[0], p[1]) * scale;
+
+        if let Some(outline) = &layout.outline {
+            paint_path(&painter, &parse_path(outline), to_screen, egui::Stroke::new(1.5, egui::Color32::GRAY));
+        }
+        let placed = match &self.current_result {
+            Some(result) => view.place(&result.impact_scores),
+            None => Vec::new(),
+        };
+        for region in &layout.regions {
+            let center = to_screen(region.center);
+            painter.circle_stroke(center, region.radius * scale, egui::Stroke::new(1.0, egui::Color32::from_gray(200)));
+            // 沒有影響分數的腦區以灰字標示，方便辨認各視角的位置
+            let impacted = placed.iter().any(|p| p.map_region_id.as_deref() == Some(region.id.as_str()));
+            if self.show_brain_regions && !impacted {
+                painter.text(
+                    center,
+                    egui::Align2::CENTER_CENTER,
+                    region.names.first().map_or(region.id.as_str(), |n| name(n)),
+                    egui::FontId::proportional(10.0),
+                    egui::Color32::GRAY,
                 );
-                let radius = 5.0 + impact.impact_score * 10.0;
-                painter.circle_filled(pos, radius, color);
-                
-                if self.show_brain_regions {
-                    painter.text(
-                        pos + egui::Vec2::new(0.0, radius + 5.0),
-                        egui::Align2::CENTER_TOP,
-                        &impact.region,
-                        egui::FontId::default(),
-                        egui::Color32::BLACK,
-                    );
+            }
+        }
+        // 重疊時取最小的圓，讓深部小腦區也能被指到
+        let hovered = response.hover_pos().and_then(|pointer| {
+            placed
+                .iter()
+                .filter(|impact| to_screen(impact.center).distance(pointer) <= impact.radius * scale)
+                .min_by(|a, b| a.radius.total_cmp(&b.radius))
+        });
+
+        for impact in &placed {
+            let center = to_screen(impact.center);
+            let radius = impact.radius * scale;
+            painter.circle_filled(center, radius, self.get_impact_color(impact.impact_score).gamma_multiply(0.85));
+            let highlighted = hovered.is_some_and(|h| std::ptr::eq(h, impact))
+                || self.selected_region.as_deref() == Some(impact.region.as_str());
+            if highlighted {
+                painter.circle_stroke(center, radius + 2.0, egui::Stroke::new(2.5, egui::Color32::BLACK));
+            }
+            if self.show_brain_regions {
+                painter.text(
+                    center + egui::vec2(0.0, radius + 4.0),
+                    egui::Align2::CENTER_TOP,
+                    name(&impact.region),
+                    egui::FontId::proportional(12.0),
+                    egui::Color32::BLACK,
+                );
+            }
+        }
+
+        if let Some(hovered) = hovered {
+            if response.clicked() {
+                self.selected_region = Some(hovered.region.clone());
+            }
+            response.on_hover_ui_at_pointer(|ui| self.region_tooltip(ui, &hovered.region, Some(hovered.impact_score)));
+        }
+    }
+
+    /// 3D 玻璃腦：拖曳旋轉、滾輪縮放、雙擊重設視角；提示與點選同平面腦圖
+    fn render_glass_brain(&mut self, ui: &mut egui::Ui) {
+        let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        let rect = response.rect;
+        if response.dragged() {
+            let delta = response.drag_delta();
+            self.glass_camera.orbit(delta.x * 0.01, delta.y * 0.01);
+        }
+        if response.double_clicked() {
+            self.glass_camera = Camera::default();
+        }
+        if response.hovered() {
+            let scroll = ui.input(|i| i.scroll_delta.y);
+            if scroll != 0.0 {
+                self.glass_camera.zoom_by((scroll * 0.002).exp());
+            }
+        }
+
+        let impacts = self.current_result.as_ref().map_or(&[][..], |result| &result.impact_scores);
+        let scene = GlassBrainScene::build(&self.brain_network, impacts);
+        let camera = self.glass_camera;
+        let hovered = response.hover_pos().and_then(|pointer| {
+            let local = pointer - rect.min;
+            glass_brain::pick(&scene, camera, rect.width(), rect.height(), [local.x, local.y])
+        });
+        let highlight = hovered
+            .map(|i| scene.nodes[i].region.clone())
+            .or_else(|| self.selected_region.clone());
+
+        // 以實際像素點陣化，高解析度螢幕上才不會模糊；場景與視角不變時沿用上次的材質
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let size = [
+            (rect.width() * pixels_per_point).round().max(1.0) as usize,
+            (rect.height() * pixels_per_point).round().max(1.0) as usize,
+        ];
+        let background = ui.visuals().extreme_bg_color;
+        let background = [background.r(), background.g(), background.b()];
+        let cached = self.glass_cache.as_ref().is_some_and(|cache| {
+            cache.scene == scene && cache.camera == camera && cache.size == size && cache.background == background && cache.highlight == highlight
+        });
+        if !cached {
+            let image = glass_brain::render(&scene, camera, size[0], size[1], background, highlight.as_deref());
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, &image.rgba);
+            let texture = match self.glass_cache.take() {
+                Some(mut cache) => {
+                    cache.texture.set(image, egui::TextureOptions::LINEAR);
+                    cache.texture
+                }
+                None => ui.ctx().load_texture("glass_brain", image, egui::TextureOptions::LINEAR),
+            };
+            self.glass_cache = Some(GlassBrainCache { scene: scene.clone(), camera, size, background, highlight, texture });
+        }
+        if let Some(cache) = &self.glass_cache {
+            let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+            painter.image(cache.texture.id(), rect, uv, egui::Color32::WHITE);
+        }
+
+        // 每個有分數的腦區在最靠近觀看者的球體下方標示名稱
+        if self.show_brain_regions {
+            let mut labelled: Vec<&str> = Vec::new();
+            let mut nodes: Vec<(&glass_brain::SceneNode, [f32; 3])> = scene
+                .nodes
+                .iter()
+                .filter(|node| node.impact_score.is_some())
+                .map(|node| (node, camera.project(node.position, rect.width(), rect.height())))
+                .collect();
+            nodes.sort_by(|a, b| b.1[2].total_cmp(&a.1[2]));
+            let scale = camera.pixels_per_mm(rect.width(), rect.height());
+            for (node, [x, y, _]) in nodes {
+                if labelled.contains(&node.region.as_str()) {
+                    continue;
                 }
+                labelled.push(&node.region);
+                painter.text(
+                    rect.min + egui::vec2(x, y + node.radius * scale + 4.0),
+                    egui::Align2::CENTER_TOP,
+                    name(&node.region),
+                    egui::FontId::proportional(12.0),
+                    ui.visuals().text_color(),
+                );
             }
         }
+
+        if let Some(node) = hovered.map(|i| &scene.nodes[i]) {
+            if response.clicked() {
+                self.selected_region = Some(node.region.clone());
+            }
+            response.on_hover_ui_at_pointer(|ui| self.region_tooltip(ui, &node.region, node.impact_score));
+        }
+    }
+
+    /// 腦區的滑鼠停留提示：分數、權重、相關功能與疾病
+    fn region_tooltip(&self, ui: &mut egui::Ui, region: &str, impact_score: Option<f32>) {
+        ui.strong(name(region));
+        if let Some(impact_score) = impact_score {
+            ui.label(trf("gui.map.hover_score", &[&format!("{:.3}", impact_score)]));
+        }
+        if let Some(score) = self.current_result.as_ref().and_then(|r| r.region_score(region)) {
+            ui.label(trf("gui.map.hover_weight", &[&format!("{:.2}", score.weight)]));
+        }
+
+        let separator = tr("list.separator");
+        let functions = self.risk_assessment.region_functions(region);
+        if !functions.is_empty() {
+            let functions: Vec<&str> = functions.iter().map(|function| name(function)).collect();
+            ui.label(trf("gui.map.hover_functions", &[&functions.join(separator)]));
+        }
+        let diseases = self.risk_assessment.region_diseases(region);
+        if !diseases.is_empty() {
+            let diseases: Vec<String> = diseases
+                .iter()
+                .map(|link| format!("{} ({:.1})", name(&link.disease), link.strength))
+                .collect();
+            ui.label(trf("gui.map.hover_diseases", &[&diseases.join(separator)]));
+        }
+        ui.small(tr("gui.map.click_hint"));
+    }
+
+    /// 側邊面板：影響所選腦區的所有行為，以及各行為最近一次在此腦區的分數
+    fn render_region_panel(&mut self, ui: &mut egui::Ui, region: &str) {
+        ui.horizontal(|ui| {
+            ui.heading(tr("gui.region.heading"));
+            if ui.button(tr("gui.region.close")).clicked() {
+                self.selected_region = None;
+            }
+        });
+        ui.strong(name(region));
+        let views = self.atlas.views_for_region(region);
+        if !views.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(tr("gui.region.views"));
+                for view in views {
+                    ui.selectable_value(&mut self.current_view, view, view.label());
+                }
+            });
+        }
+        ui.separator();
+
+        let behaviors = self.mapping_engine.get_behaviors_for_region(region);
+        if behaviors.is_empty() {
+            ui.label(tr("gui.region.none"));
+            return;
+        }
+        ui.label(trf("gui.region.behaviors", &[&behaviors.len()]));
+        egui::Grid::new("region_behaviors").striped(true).show(ui, |ui| {
+            ui.strong(tr("gui.region.col.behavior"));
+            ui.strong(tr("gui.region.col.weight"));
+            ui.strong(tr("gui.region.col.latest"));
+            ui.strong(tr("gui.region.col.time"));
+            ui.end_row();
+
+            for behavior in behaviors {
+                let weight = self
+                    .mapping_engine
+                    .get_behavior_map(behavior)
+                    .and_then(|map| map.brain_regions.iter().find(|r| r.region == region))
+                    .map(|r| r.weight);
+                let latest = CalculationResult::latest_for_behavior(&self.calculation_history, behavior)
+                    .and_then(|result| Some((result, result.region_score(region)?)));
+
+                ui.label(name(behavior));
+                ui.label(weight.map(|w| format!("{:.2}", w)).unwrap_or_default());
+                match latest {
+                    Some((result, score)) => {
+                        ui.colored_label(self.get_impact_color(score.impact_score), format!("{:.3}", score.impact_score));
+                        ui.label(
+                            result
+                                .behavior_input
+                                .timestamp
+                                .with_timezone(&chrono::Local)
+                                .format("%m-%d %H:%M")
+                                .to_string(),
+                        );
+                    }
+                    None => {
+                        ui.label(tr("gui.region.no_record"));
+                        ui.label("");
+                    }
+                }
+                ui.end_row();
+            }
+        });
     }
 
     /// 根據影響分數獲取顏色
@@ -264,9 +1352,38 @@ impl BehaviorBrainApp {
 
 impl eframe::App for BehaviorBrainApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            self.render_menu_bar(ui);
+        });
+
+        if self.show_comparison {
+            let mut open = true;
+            egui::Window::new(tr("gui.compare.title"))
+                .open(&mut open)
+                .default_width(900.0)
+                .show(ctx, |ui| self.render_comparison(ui));
+            self.show_comparison = open;
+        }
+
+        self.poll_stream(ctx);
+        if self.show_stream {
+            let mut open = true;
+            egui::Window::new(tr("gui.stream.title"))
+                .open(&mut open)
+                .default_width(420.0)
+                .show(ctx, |ui| self.render_stream_window(ui));
+            self.show_stream = open;
+        }
+
+        if let Some(region) = self.selected_region.clone() {
+            egui::SidePanel::right("region_panel").min_width(260.0).show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| self.render_region_panel(ui, &region));
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // 主標題
-            ui.heading("🧠 行為-腦網路連續量化可視化工具");
+            ui.heading(format!("🧠 {}", tr("app.title")));
             ui.add_space(10.0);
 
             // 主要內容區域：左右分欄
@@ -299,3 +1416,181 @@ impl eframe::App for BehaviorBrainApp {
         });
     }
 }
+
+/// 趨勢圖顯示的最近結果筆數
+const TREND_POINTS: usize = 300;
+
+/// 歷史記錄保留的筆數上限；長時間串流時捨棄最舊的結果
+const HISTORY_LIMIT: usize = 5_000;
+
+/// 串流視窗列出的最近連線數
+const PEER_LIMIT: usize = 8;
+
+/// 執行中的串流與其統計
+struct LiveStream {
+    handle: StreamHandle,
+    window: SlidingWindow,
+    /// 最近連線的傳送端（TCP 位址或管道路徑），最多 `PEER_LIMIT` 個
+    peers: Vec<String>,
+    samples: usize,
+    scored: usize,
+    last_warning: Option<String>,
+}
+
+/// 比較的一邊：來源（0 為本次工作階段）與該來源中的結果索引
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CompareChoice {
+    source: usize,
+    result: usize,
+}
+
+/// 不可互動的腦圖：輪廓、腦區外框與指定顏色的圓，寬度填滿、高度依腦圖比例（比較視窗用）
+fn paint_static_map(ui: &mut egui::Ui, view: &AtlasView, circles: &[(PlacedImpact, egui::Color32)]) {
+    let layout = &view.layout;
+    let width = ui.available_width();
+    let height = width * layout.height / layout.width.max(1.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 6.0, egui::Color32::from_gray(245));
+
+    let scale = width / layout.width.max(1.0);
+    let to_screen = |p: [f32; 2]| rect.min + egui::vec2(p[0], p[1]) * scale;
+    if let Some(outline) = &layout.outline {
+        paint_path(&painter, &parse_path(outline), to_screen, egui::Stroke::new(1.0, egui::Color32::GRAY));
+    }
+    for region in &layout.regions {
+        painter.circle_stroke(to_screen(region.center), region.radius * scale, egui::Stroke::new(1.0, egui::Color32::from_gray(210)));
+    }
+    for (placed, color) in circles {
+        let center = to_screen(placed.center);
+        painter.circle_filled(center, placed.radius * scale, color.gamma_multiply(0.9));
+        painter.text(
+            center + egui::vec2(0.0, placed.radius * scale + 2.0),
+            egui::Align2::CENTER_TOP,
+            name(&placed.region),
+            egui::FontId::proportional(10.0),
+            egui::Color32::BLACK,
+        );
+    }
+}
+
+/// 折線趨勢圖；`range` 未指定時依資料決定縱軸，固定為 0–1 時以風險等級區間為背景
+fn paint_trend(ui: &mut egui::Ui, title: &str, points: &[(chrono::DateTime<chrono::Utc>, f32)], range: Option<(f32, f32)>) {
+    ui.label(egui::RichText::new(title).strong());
+    if points.len() < 2 {
+        ui.small(tr("report.chart.insufficient"));
+        return;
+    }
+    let mut points = points.to_vec();
+    points.sort_by_key(|(time, _)| *time);
+
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 120.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4.0, egui::Color32::from_gray(248));
+
+    let (min, max) = range.unwrap_or_else(|| {
+        let min = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+        let max = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+        let pad = ((max - min) * 0.1).max(f32::EPSILON.max(max.abs() * 0.05));
+        (min - pad, max + pad)
+    });
+    let (first, last) = (points[0].0, points[points.len() - 1].0);
+    let span = (last - first).num_milliseconds() as f32;
+    let x = |i: usize, time: chrono::DateTime<chrono::Utc>| {
+        let fraction = if span > 0.0 {
+            (time - first).num_milliseconds() as f32 / span
+        } else {
+            i as f32 / (points.len() - 1) as f32
+        };
+        rect.left() + fraction * rect.width()
+    };
+    let y = |value: f32| rect.bottom() - (value - min) / (max - min) * rect.height();
+
+    if range == Some((0.0, 1.0)) {
+        for level in RiskLevel::ALL {
+            let high = level.next().map_or(1.0, |next| next.lower_bound());
+            let [r, g, b] = level.color();
+            painter.rect_filled(
+                egui::Rect::from_x_y_ranges(rect.x_range(), y(high)..=y(level.lower_bound())),
+                0.0,
+                egui::Color32::from_rgba_unmultiplied(r, g, b, 30),
+            );
+        }
+    }
+
+    let line: Vec<egui::Pos2> = points.iter().enumerate().map(|(i, &(time, value))| egui::pos2(x(i, time), y(value))).collect();
+    let color = egui::Color32::from_rgb(33, 150, 243);
+    painter.add(egui::Shape::line(line.clone(), egui::Stroke::new(1.5, color)));
+    for point in &line {
+        painter.circle_filled(*point, 2.0, color);
+    }
+
+    let font = egui::FontId::proportional(10.0);
+    let gray = egui::Color32::GRAY;
+    let local_time = |time: chrono::DateTime<chrono::Utc>| time.with_timezone(&chrono::Local).format("%H:%M:%S").to_string();
+    painter.text(rect.left_top() + egui::vec2(4.0, 2.0), egui::Align2::LEFT_TOP, format!("{:.2}", max), font.clone(), gray);
+    painter.text(rect.left_bottom() + egui::vec2(4.0, -2.0), egui::Align2::LEFT_BOTTOM, format!("{:.2}", min), font.clone(), gray);
+    painter.text(rect.right_bottom() + egui::vec2(-4.0, -2.0), egui::Align2::RIGHT_BOTTOM, local_time(last), font, gray);
+
+    // 滑鼠停留時顯示最接近的資料點
+    if let Some(pointer) = response.hover_pos() {
+        if let Some(index) = (0..line.len()).min_by(|&a, &b| (line[a].x - pointer.x).abs().total_cmp(&(line[b].x - pointer.x).abs())) {
+            painter.circle_stroke(line[index], 4.0, egui::Stroke::new(1.5, egui::Color32::BLACK));
+            response.on_hover_text(format!("{}  {:.3}", local_time(points[index].0), points[index].1));
+        }
+    }
+}
+
+/// 上次點陣化的玻璃腦；輸入完全相同時沿用材質，不重新點陣化
+struct GlassBrainCache {
+    scene: GlassBrainScene,
+    camera: Camera,
+    size: [usize; 2],
+    background: [u8; 3],
+    highlight: Option<String>,
+    texture: egui::TextureHandle,
+}
+
+/// 以 egui 畫出 SVG path 指令
+fn paint_path(
+    painter: &egui::Painter,
+    commands: &[PathCommand],
+    to_screen: impl Fn([f32; 2]) -> egui::Pos2,
+    stroke: egui::Stroke,
+) {
+    let mut start = None;
+    let mut current = None;
+    for command in commands {
+        match *command {
+            PathCommand::MoveTo(p) => {
+                start = Some(to_screen(p));
+                current = start;
+            }
+            PathCommand::LineTo(p) => {
+                let p = to_screen(p);
+                if let Some(from) = current {
+                    painter.line_segment([from, p], stroke);
+                }
+                current = Some(p);
+            }
+            PathCommand::CubicTo(a, b, p) => {
+                let p = to_screen(p);
+                if let Some(from) = current {
+                    painter.add(egui::epaint::CubicBezierShape::from_points_stroke(
+                        [from, to_screen(a), to_screen(b), p],
+                        false,
+                        egui::Color32::TRANSPARENT,
+                        stroke,
+                    ));
+                }
+                current = Some(p);
+            }
+            PathCommand::Close => {
+                if let (Some(from), Some(to)) = (current, start) {
+                    painter.line_segment([from, to], stroke);
+                }
+                current = start;
+            }
+        }
+    }
+}
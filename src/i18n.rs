@@ -0,0 +1,242 @@
+//! 介面與報告的多語系支援
+//!
+//! 訊息目錄放在 `data/locales/<語言代碼>.json`，分為兩部分：
+//! `messages` 為介面與報告文字（以鍵值查詢），`names` 為行為、腦區、功能、
+//! 疾病與單位等資料檔中名稱的顯示翻譯（以資料檔中的原名查詢）。
+//! 內建目錄編譯進執行檔；資料目錄下的 `locales/<語言代碼>.json` 可覆寫或補充個別條目。
+//!
+//! 錯誤訊息維持繁體中文，不在翻譯範圍內。
+
+use crate::error::{read_json, BrainVizError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// 內建訊息目錄，順序與 [`Language::ALL`] 相同
+const BUILTIN_CATALOGS: [&str; 3] = [
+    include_str!("../data/locales/zh-TW.json"),
+    include_str!("../data/locales/zh-CN.json"),
+    include_str!("../data/locales/en.json"),
+];
+
+/// 支援的語言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "zh-TW")]
+    ZhTw,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en")]
+    En,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::ZhTw, Language::ZhCn, Language::En];
+
+    /// 語言代碼，亦為目錄檔名
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::ZhTw => "zh-TW",
+            Language::ZhCn => "zh-CN",
+            Language::En => "en",
+        }
+    }
+
+    /// 以該語言本身書寫的名稱，供語言選單使用
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::ZhTw => "繁體中文",
+            Language::ZhCn => "简体中文",
+            Language::En => "English",
+        }
+    }
+
+    /// HTML `lang` 屬性值
+    pub fn html_lang(&self) -> &'static str {
+        match self {
+            Language::ZhTw => "zh-Hant",
+            Language::ZhCn => "zh-Hans",
+            Language::En => "en",
+        }
+    }
+
+    /// 解析語言代碼；不分大小寫，接受 `zh_TW`、`zh-Hant`、`en-US` 等寫法
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().replace('_', "-").to_ascii_lowercase();
+        match code.as_str() {
+            "zh-tw" | "zh-hant" | "zh-hk" | "zh-mo" => Some(Language::ZhTw),
+            "zh-cn" | "zh-hans" | "zh-sg" | "zh" => Some(Language::ZhCn),
+            _ if code == "en" || code.starts_with("en-") => Some(Language::En),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// 單一語言的訊息目錄
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Catalog {
+    pub messages: HashMap<String, String>,
+    pub names: HashMap<String, String>,
+}
+
+impl Catalog {
+    fn merge(&mut self, other: Catalog) {
+        self.messages.extend(other.messages);
+        self.names.extend(other.names);
+    }
+}
+
+static CATALOGS: OnceLock<[Catalog; 3]> = OnceLock::new();
+static CURRENT: AtomicU8 = AtomicU8::new(Language::ZhTw as u8);
+
+fn builtin_catalogs() -> [Catalog; 3] {
+    BUILTIN_CATALOGS.map(|text| serde_json::from_str(text).expect("內建語系檔格式錯誤"))
+}
+
+fn catalogs() -> &'static [Catalog; 3] {
+    CATALOGS.get_or_init(builtin_catalogs)
+}
+
+/// 載入資料目錄下 `locales/` 中的覆寫檔；須在第一次查詢翻譯前呼叫
+///
+/// 檔案不存在時沿用內建目錄；目錄已初始化時回傳錯誤且不做任何變更。
+pub fn init(data_dir: impl AsRef<Path>) -> Result<()> {
+    let mut loaded = builtin_catalogs();
+    for (language, catalog) in Language::ALL.iter().zip(loaded.iter_mut()) {
+        let path = data_dir.as_ref().join("locales").join(format!("{}.json", language.code()));
+        if path.exists() {
+            catalog.merge(read_json(&path)?);
+        }
+    }
+    CATALOGS
+        .set(loaded)
+        .map_err(|_| BrainVizError::Config("語系目錄已初始化，無法再載入覆寫檔".to_string()))
+}
+
+/// 目前的顯示語言
+pub fn language() -> Language {
+    Language::ALL[CURRENT.load(Ordering::Relaxed) as usize]
+}
+
+/// 切換顯示語言；影響之後產生的介面文字與報告
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+/// 以指定語言查詢訊息；缺漏時退回繁體中文，仍缺漏則回傳鍵本身
+pub fn tr_in(language: Language, key: &'static str) -> &'static str {
+    let catalogs = catalogs();
+    catalogs[language.index()]
+        .messages
+        .get(key)
+        .or_else(|| catalogs[Language::ZhTw.index()].messages.get(key))
+        .map_or(key, String::as_str)
+}
+
+/// 以目前語言查詢訊息
+pub fn tr(key: &'static str) -> &'static str {
+    tr_in(language(), key)
+}
+
+/// 以指定語言查詢訊息，並依序以 `args` 代入其中的 `{}`
+pub fn trf_in(language: Language, key: &'static str, args: &[&dyn fmt::Display]) -> String {
+    let template = tr_in(language, key);
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
+
+/// 以目前語言查詢並代入參數
+pub fn trf(key: &'static str, args: &[&dyn fmt::Display]) -> String {
+    trf_in(language(), key, args)
+}
+
+/// 以指定語言顯示資料檔中的名稱；沒有翻譯時回傳原名
+pub fn name_in(language: Language, canonical: &str) -> &str {
+    catalogs()[language.index()]
+        .names
+        .get(canonical)
+        .map_or(canonical, String::as_str)
+}
+
+/// 以目前語言顯示資料檔中的名稱
+pub fn name(canonical: &str) -> &str {
+    name_in(language(), canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 測試平行執行，這裡只使用指定語言的版本，不變更全域語言
+
+    #[test]
+    fn test_language_codes() {
+        for language in Language::ALL {
+            assert_eq!(Language::from_code(language.code()), Some(language));
+        }
+        assert_eq!(Language::from_code("zh_tw"), Some(Language::ZhTw));
+        assert_eq!(Language::from_code("zh-Hans"), Some(Language::ZhCn));
+        assert_eq!(Language::from_code("en-US"), Some(Language::En));
+        assert_eq!(Language::from_code("fr"), None);
+        assert_eq!(serde_json::to_string(&Language::ZhCn).unwrap(), "\"zh-CN\"");
+    }
+
+    #[test]
+    fn test_catalogs_complete() {
+        let catalogs = builtin_catalogs();
+        let reference = &catalogs[Language::ZhTw.index()].messages;
+        for (language, catalog) in Language::ALL.iter().zip(catalogs.iter()) {
+            let mut missing: Vec<&String> = reference.keys().filter(|key| !catalog.messages.contains_key(*key)).collect();
+            missing.sort();
+            assert!(missing.is_empty(), "{} 缺少訊息: {:?}", language, missing);
+            for (key, text) in &catalog.messages {
+                assert_eq!(
+                    text.matches("{}").count(),
+                    reference[key].matches("{}").count(),
+                    "{} 的 {} 參數數量不一致",
+                    language,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_fallback() {
+        assert_eq!(tr_in(Language::En, "report.col.region"), "Region");
+        assert_eq!(tr_in(Language::ZhCn, "report.col.region"), "脑区");
+        assert_eq!(tr_in(Language::En, "no.such.key"), "no.such.key");
+        assert_eq!(
+            trf_in(Language::En, "gui.report.total_impact", &[&format!("{:.3}", 0.5)]),
+            "Total impact: 0.500"
+        );
+
+        assert_eq!(name_in(Language::En, "小腦"), "Cerebellum");
+        assert_eq!(name_in(Language::ZhCn, "小腦"), "小脑");
+        assert_eq!(name_in(Language::ZhTw, "小腦"), "小腦");
+        assert_eq!(name_in(Language::En, "未知腦區"), "未知腦區");
+    }
+}
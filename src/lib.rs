@@ -0,0 +1,60 @@
+//! 行為-腦網路連續量化可視化工具的核心函式庫
+//!
+//! 提供行為-腦區映射計算引擎、風險評估、資料模型與載入函式，
+//! 可在不啟用 `gui` 功能的情況下供其他分析工具使用。
+//!
+//! ```no_run
+//! use behavior_brain_viz::{load_behavior_brain_map, BehaviorInput, Language, MappingEngine, RiskAssessment};
+//!
+//! let maps = load_behavior_brain_map("data/behavior_brain_map.json")?;
+//! let engine = MappingEngine::new(maps);
+//! let input = BehaviorInput {
+//!     behavior_type: "持續注意力指敲".to_string(),
+//!     value: 72.0,
+//!     unit: "次".to_string(),
+//!     timestamp: chrono::Utc::now(),
+//! };
+//! let result = engine.calculate_impact(&input)?;
+//! let report = RiskAssessment::default().generate_risk_report(&result, Language::En);
+//! println!("{}", report.risk_level.description_in(Language::En));
+//! # Ok::<(), behavior_brain_viz::BrainVizError>(())
+//! ```
+
+pub mod atlas;
+pub mod brain_map;
+pub mod compare;
+pub mod config;
+pub mod error;
+pub mod explain;
+pub mod export;
+pub mod ffi;
+pub mod figure;
+pub mod fonts;
+pub mod gait;
+pub mod glass_brain;
+pub mod group;
+pub mod i18n;
+pub mod mapping;
+pub mod model;
+pub mod nifti;
+pub mod recommend;
+pub mod report;
+pub mod risk;
+pub mod server;
+pub mod stream;
+pub mod trials;
+
+#[cfg(feature = "gui")]
+pub mod gui;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+pub use error::{BrainVizError, Result};
+pub use i18n::Language;
+pub use mapping::{load_behavior_brain_map, MappingEngine};
+pub use model::{
+    BehaviorBrainMap, BehaviorInput, BrainRegionImpact, CalculationResult, NormalizationParams,
+    RegionImpactScore, RiskLevel, SubjectInfo,
+};
+pub use risk::{load_risk_map, RiskAssessment, RiskReport};
@@ -1,13 +1,321 @@
-mod model;
-mod mapping;
-mod risk;
-mod gui;
+use behavior_brain_viz::config::{AppConfig, CliArgs, Command};
+use behavior_brain_viz::{
+    load_behavior_brain_map, load_risk_map, server, BehaviorInput, MappingEngine, RiskAssessment,
+};
+use behavior_brain_viz::i18n;
+use behavior_brain_viz::recommend::load_recommendation_rules;
 
-use eframe::egui;
-use gui::BehaviorBrainApp;
-use mapping::load_behavior_brain_map;
+fn main() {
+    // 解析命令列參數與設定
+    let cli = CliArgs::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("錯誤：{}\n\n{}", e, CliArgs::usage());
+        std::process::exit(2);
+    });
+    if cli.show_help {
+        print!("{}", CliArgs::usage());
+        return;
+    }
+    let config = AppConfig::resolve(&cli).unwrap_or_else(|e| {
+        eprintln!("錯誤：{}", e);
+        std::process::exit(2);
+    });
+
+    // 載入語系覆寫檔並套用語言設定
+    if let Err(e) = i18n::init(&config.data_dir) {
+        eprintln!("警告：{}，使用內建語系", e);
+    }
+    i18n::set_language(config.language);
+    if let Some(font) = config.font.as_deref().filter(|font| !font.is_file()) {
+        eprintln!("警告：找不到字型檔 {}，改用系統中文字型", font.display());
+    }
+
+    let outcome = match cli.command {
+        Command::Gui => run_gui(config).map_err(|e| e.to_string()),
+        Command::Serve => run_serve(config, cli.addr.as_deref().unwrap_or(server::DEFAULT_ADDR)),
+        Command::Render => run_render(config, &cli),
+        Command::Group => run_group(config, &cli),
+        Command::Trials => run_trials(config, &cli),
+    };
+    if let Err(e) = outcome {
+        eprintln!("錯誤：{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// 載入風險對照資料與建議規則（選用，失敗時使用內建資料）
+fn load_risk_assessment(config: &AppConfig) -> RiskAssessment {
+    let mut assessment = match load_risk_map(&config.risk_map) {
+        Ok(entries) => RiskAssessment::default().with_risk_map(entries),
+        Err(e) => {
+            eprintln!("警告：{}", e);
+            RiskAssessment::default()
+        }
+    };
+    match load_recommendation_rules(&config.recommendation_rules) {
+        Ok(rules) => assessment = assessment.with_recommendation_rules(rules),
+        Err(e) => eprintln!("警告：{}（使用內建建議規則）", e),
+    }
+    assessment
+}
+
+/// 啟動本機 HTTP/JSON 計分服務
+fn run_serve(config: AppConfig, addr: &str) -> Result<(), String> {
+    let path = config
+        .primary_mapping()
+        .ok_or_else(|| "沒有可用的行為映射資料集".to_string())?;
+    let behavior_maps = load_behavior_brain_map(path).map_err(|e| e.to_string())?;
+    let service = server::ScoringService::new(MappingEngine::new(behavior_maps), load_risk_assessment(&config))
+        .with_language(config.language);
+
+    println!("計分服務已啟動: http://{}", addr);
+    server::serve(addr, service).map_err(|e| format!("無法啟動服務 {}: {}", addr, e))
+}
+
+/// 不開啟介面，計算單筆數值並輸出腦圖圖檔
+fn run_render(config: AppConfig, cli: &CliArgs) -> Result<(), String> {
+    use behavior_brain_viz::figure::{export_brain_map, FigureOptions, ImageFormat};
+
+    let behavior = cli.behavior.as_deref().ok_or("render 模式需要 --behavior")?;
+    let value = cli.value.ok_or("render 模式需要 --value")?;
+    let out = cli.out.as_deref().ok_or("render 模式需要 --out")?;
+    let format = ImageFormat::from_path(out, cli.width.unwrap_or(1600)).map_err(|e| e.to_string())?;
+
+    let path = config
+        .primary_mapping()
+        .ok_or_else(|| "沒有可用的行為映射資料集".to_string())?;
+    let engine = MappingEngine::new(load_behavior_brain_map(path).map_err(|e| e.to_string())?);
+    let unit = match &cli.unit {
+        Some(unit) => unit.clone(),
+        None => engine.get_behavior_unit(behavior).unwrap_or_default().to_string(),
+    };
+    let input = BehaviorInput {
+        behavior_type: behavior.to_string(),
+        value,
+        unit,
+        timestamp: chrono::Utc::now(),
+    };
+    let result = engine.calculate_impact(&input).map_err(|e| e.to_string())?;
+    let atlas_svg = std::fs::read_to_string(&config.atlas_svg)
+        .map_err(|e| format!("無法讀取腦圖 {}: {}", config.atlas_svg.display(), e))?;
+
+    export_brain_map(
+        &result,
+        &atlas_svg,
+        &FigureOptions::default(),
+        config.language,
+        format,
+        config.font.as_deref(),
+        out,
+    )
+        .map_err(|e| e.to_string())?;
+    println!("已輸出 {}", out.display());
+    Ok(())
+}
+
+/// 由匯出的結果計算族群統計，輸出統計表與群體平均腦圖
+fn run_group(config: AppConfig, cli: &CliArgs) -> Result<(), String> {
+    use behavior_brain_viz::figure::{write_figure, Figure, FigureOptions, ImageFormat};
+    use behavior_brain_viz::group::{self, Cohort};
+
+    if cli.inputs.is_empty() {
+        return Err("group 模式需要 --input".to_string());
+    }
+    let mut cohort = Cohort::load(&cli.inputs).map_err(|e| e.to_string())?;
+    if cohort.subjects.is_empty() {
+        return Err("沒有讀到任何受試者的結果".to_string());
+    }
+
+    // 組別順序依組別對照檔
+    let mut groups: Vec<String> = Vec::new();
+    if let Some(path) = &cli.groups {
+        let assignments = group::load_group_assignments(path).map_err(|e| e.to_string())?;
+        for subject_id in cohort.assign_groups(&assignments) {
+            eprintln!("警告：組別對照檔中的受試者 {} 沒有匯入的結果", subject_id);
+        }
+        for (_, group) in assignments {
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+    }
+    let unassigned = cohort.subjects.iter().filter(|s| s.group.is_none()).count();
+    if !groups.is_empty() && unassigned > 0 {
+        eprintln!("警告：{} 位受試者沒有指派組別，只計入全體統計", unassigned);
+    }
+
+    let compared = match (&cli.compare, groups.as_slice()) {
+        (Some(pair), _) => {
+            if let Some(missing) = pair.iter().find(|g| !groups.contains(g)) {
+                return Err(format!("組別對照檔中沒有組別「{}」", missing));
+            }
+            Some([pair[0].as_str(), pair[1].as_str()])
+        }
+        (None, [first, second]) => Some([first.as_str(), second.as_str()]),
+        (None, groups) => {
+            if groups.len() > 2 {
+                eprintln!("提示：共有 {} 組，以 --compare 指定要比較的兩組", groups.len());
+            }
+            None
+        }
+    };
+
+    let behavior = cli.behavior.as_deref();
+    let mut summaries = vec![cohort.summary(None, behavior)];
+    summaries.extend(groups.iter().map(|g| cohort.summary(Some(g), behavior)));
+    if summaries[0].subjects == 0 {
+        return Err(format!("沒有受試者有「{}」的結果", behavior.unwrap_or_default()));
+    }
+    let comparison = compared.map(|pair| cohort.compare(pair, behavior));
+    let mut written = group::export_tables(&summaries, comparison.as_ref(), &config.output_dir).map_err(|e| e.to_string())?;
+
+    // 群體平均腦圖：全體與各組
+    let atlas_svg = std::fs::read_to_string(&config.atlas_svg)
+        .map_err(|e| format!("無法讀取腦圖 {}: {}", config.atlas_svg.display(), e))?;
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    #[cfg_attr(not(feature = "png"), allow(unused_mut))]
+    let mut formats = vec![ImageFormat::Svg];
+    #[cfg(feature = "png")]
+    formats.push(ImageFormat::Png { width: cli.width.unwrap_or(1600) });
+    for summary in summaries.iter().filter(|s| s.subjects > 0) {
+        let figure = Figure::with_title(
+            &summary.average_result(),
+            &summary.map_title(config.language),
+            &atlas_svg,
+            &FigureOptions::default(),
+            config.language,
+        );
+        for format in &formats {
+            let out = config
+                .output_dir
+                .join(format!("cohort_average_{}_{}.{}", summary.file_label(), stamp, format.extension()));
+            write_figure(&figure, *format, config.font.as_deref(), &out).map_err(|e| e.to_string())?;
+            written.push(out);
+        }
+    }
+
+    println!("受試者 {} 人", summaries[0].subjects);
+    for summary in &summaries[1..] {
+        println!("  {}：{} 人", summary.group.as_deref().unwrap_or_default(), summary.subjects);
+    }
+    if let Some(comparison) = &comparison {
+        let significant = comparison
+            .regions
+            .iter()
+            .filter(|r| r.welch.is_some_and(|w| w.p < 0.05) || r.mann_whitney.is_some_and(|m| m.p < 0.05))
+            .count();
+        println!(
+            "{} 與 {} 比較：{} 個腦區中有 {} 個 p < .05（未校正多重比較）",
+            comparison.groups[0],
+            comparison.groups[1],
+            comparison.regions.len(),
+            significant
+        );
+    }
+    for out in written {
+        println!("已輸出 {}", out.display());
+    }
+    Ok(())
+}
+
+/// 由試次層級資料推導行為指標，計分後輸出長格式 CSV（可再交給 group 模式）
+fn run_trials(config: AppConfig, cli: &CliArgs) -> Result<(), String> {
+    use behavior_brain_viz::export;
+    use behavior_brain_viz::gait::{self, GaitOptions};
+    use behavior_brain_viz::trials::{self, ColumnOverrides, Task, TaskProfiles, TrialTable};
+    use behavior_brain_viz::SubjectInfo;
+
+    let task = cli.task.ok_or("trials 模式需要 --task")?;
+    if cli.inputs.is_empty() {
+        return Err("trials 模式需要 --input".to_string());
+    }
+    let overrides = ColumnOverrides::parse(&cli.columns).map_err(|e| e.to_string())?;
+    let profiles = TaskProfiles::load(&config.task_metrics).map_err(|e| e.to_string())?;
+    for profile in profiles.profiles.iter().filter(|p| p.task == task) {
+        if let Some(note) = &profile.note {
+            println!("{} → {}：{}", profile.metric.id(), profile.behavior, note);
+        }
+    }
+    let path = config
+        .primary_mapping()
+        .ok_or_else(|| "沒有可用的行為映射資料集".to_string())?;
+    let engine = MappingEngine::new(load_behavior_brain_map(path).map_err(|e| e.to_string())?);
+    let gait_options = GaitOptions {
+        sample_rate: cli.sample_rate,
+        distance_m: cli.distance,
+        height_m: cli.height,
+        ..Default::default()
+    };
+
+    let mut csv = String::new();
+    for input in &cli.inputs {
+        let table = TrialTable::load(input).map_err(|e| e.to_string())?;
+        let timestamp = trials::file_timestamp(input);
+        let file_stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        let subjects = match task {
+            Task::Gait => gait::derive_metrics(&table, &overrides, &gait_options),
+            _ => trials::derive_metrics(&table, task, &overrides),
+        };
+        for subject in subjects.map_err(|e| e.to_string())? {
+            let subject_id = subject.subject_id.clone().unwrap_or_else(|| file_stem.to_string());
+            println!("{}（{}）", subject_id, input.display());
+            for metric in &subject.metrics {
+                println!("  {} = {:.3} {}（{} 筆）", metric.metric.id(), metric.value, metric.metric.unit(), metric.trials);
+            }
+            for (metric, reason) in &subject.skipped {
+                eprintln!("警告：{} 的 {} 無法計算：{}", subject_id, metric.id(), reason);
+            }
+
+            let mut results = Vec::new();
+            for behavior_input in profiles.behavior_inputs(task, &subject, timestamp) {
+                match engine.calculate_impact(&behavior_input) {
+                    Ok(result) => {
+                        println!(
+                            "  {}：總影響 {:.3}（{}）",
+                            behavior_input.behavior_type,
+                            result.total_impact,
+                            result.risk_level.label()
+                        );
+                        results.push(result);
+                    }
+                    Err(e) => eprintln!("警告：{} 無法計分：{}", subject_id, e),
+                }
+            }
+            if results.is_empty() {
+                continue;
+            }
+            let info = SubjectInfo { subject_id, ..Default::default() };
+            let subject_csv = export::to_csv(Some(&info), &results);
+            // 多位受試者寫入同一檔案，只保留第一個標題列
+            let skip = if csv.is_empty() { 0 } else { subject_csv.find("\r\n").map_or(0, |i| i + 2) };
+            csv.push_str(&subject_csv[skip..]);
+        }
+    }
+    if csv.is_empty() {
+        return Err("沒有可計分的指標".to_string());
+    }
+
+    std::fs::create_dir_all(&config.output_dir)
+        .map_err(|e| format!("無法建立輸出目錄 {}: {}", config.output_dir.display(), e))?;
+    let out = config.output_dir.join(format!(
+        "trials_{}_{}.csv",
+        task.id(),
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    ));
+    std::fs::write(&out, csv).map_err(|e| format!("無法寫入 {}: {}", out.display(), e))?;
+    println!("已輸出 {}", out.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_config: AppConfig) -> Result<(), String> {
+    Err("此版本未包含圖形介面，請以 `--features gui` 重新編譯".to_string())
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(config: AppConfig) -> Result<(), eframe::Error> {
+    use behavior_brain_viz::gui::BehaviorBrainApp;
+    use eframe::egui;
 
-fn main() -> Result<(), eframe::Error> {
     // 設定 egui 視窗選項
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -17,64 +325,66 @@ fn main() -> Result<(), eframe::Error> {
     };
 
     // 載入行為-腦區映射資料
-    let behavior_maps = load_behavior_brain_map("data/behavior_brain_map.json")
-        .unwrap_or_else(|e| {
-            eprintln!("警告：無法載入 behavior_brain_map.json: {}", e);
+    let mut startup_errors = Vec::new();
+    let behavior_maps = match config.primary_mapping() {
+        Some(path) => load_behavior_brain_map(path).unwrap_or_else(|e| {
+            eprintln!("警告：{}", e);
+            startup_errors.push(e.to_string());
             Vec::new()
-        });
+        }),
+        None => Vec::new(),
+    };
 
     if behavior_maps.is_empty() {
         eprintln!("警告：沒有載入到任何行為映射資料");
     }
 
+    // 載入風險對照資料（選用）
+    let risk_assessment = load_risk_assessment(&config);
+
     // 啟動應用程式
+    let custom_font = config.font.clone();
+    let title = format!("{} v{}", i18n::tr("app.title"), env!("CARGO_PKG_VERSION"));
     eframe::run_native(
-        "行為-腦網路連續量化可視化工具 v0.1.0",
+        &title,
         options,
-        Box::new(|cc| {
-            setup_custom_fonts(&cc.egui_ctx);
-            Box::new(BehaviorBrainApp::new(behavior_maps))
+        Box::new(move |cc| {
+            setup_custom_fonts(&cc.egui_ctx, custom_font.as_deref());
+            let mut app = BehaviorBrainApp::new(config, behavior_maps, risk_assessment);
+            if !startup_errors.is_empty() {
+                app.show_error(startup_errors.join("\n"));
+            }
+            Box::new(app)
         }),
     )
 }
 
-fn setup_custom_fonts(ctx: &egui::Context) {
+#[cfg(feature = "gui")]
+fn setup_custom_fonts(ctx: &eframe::egui::Context, custom: Option<&std::path::Path>) {
     use eframe::egui::{FontDefinitions, FontFamily, FontData};
     
     let mut fonts = FontDefinitions::default();
 
-    // 嘗試載入中文字體
-    let chinese_fonts = [
-        ("Microsoft JhengHei", "C:/Windows/Fonts/msjh.ttc"),
-        ("Microsoft YaHei", "C:/Windows/Fonts/msyh.ttc"),
-        ("DFKai-SB", "C:/Windows/Fonts/kaiu.ttf"),
-        ("SimSun", "C:/Windows/Fonts/simsun.ttc"),
-    ];
-
-    for (font_name, font_path) in &chinese_fonts {
-        if let Ok(font_data) = std::fs::read(font_path) {
-            fonts.font_data.insert(
-                font_name.to_string(),
-                FontData::from_owned(font_data),
-            );
-            
-            // 添加到字體家族
-            fonts
-                .families
-                .get_mut(&FontFamily::Proportional)
-                .unwrap()
-                .insert(0, font_name.to_string());
-            fonts
-                .families
-                .get_mut(&FontFamily::Monospace)
-                .unwrap()
-                .insert(0, font_name.to_string());
-            
-            println!("已載入字體: {}", font_name);
-            break; // 只要載入一個成功的字體就夠了
-        }
+    // 載入中文字體：指定字型檔 > 系統字體 > 內嵌備用字型
+    if let Some(font) = behavior_brain_viz::fonts::load_cjk_font(custom) {
+        fonts.font_data.insert(font.name.clone(), FontData::from_owned(font.data));
+
+        // 添加到字體家族
+        fonts
+            .families
+            .get_mut(&FontFamily::Proportional)
+            .unwrap()
+            .insert(0, font.name.clone());
+        fonts
+            .families
+            .get_mut(&FontFamily::Monospace)
+            .unwrap()
+            .insert(0, font.name.clone());
+
+        println!("已載入字體: {}", font.name);
+    } else {
+        eprintln!("警告：找不到中文字型，介面文字可能無法顯示；可用 --font 指定字型檔");
     }
 
     ctx.set_fonts(fonts);
 }
-
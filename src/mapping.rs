@@ -1,5 +1,8 @@
+use crate::error::{read_json, BrainVizError, Result};
+use crate::explain::{self, ScoreExplanation};
 use crate::model::{BehaviorBrainMap, BehaviorInput, CalculationResult, RegionImpactScore, RiskLevel};
 use std::collections::HashMap;
+use std::path::Path;
 
 /// 影響分數計算引擎
 pub struct MappingEngine {
@@ -28,20 +31,38 @@ impl MappingEngine {
     }
 
     /// 計算行為對各腦區的影響分數
-    pub fn calculate_impact(&self, input: &BehaviorInput) -> Option<CalculationResult> {
+    pub fn calculate_impact(&self, input: &BehaviorInput) -> Result<CalculationResult> {
         // 找到對應的行為映射
         let behavior_map = self.behavior_maps
             .iter()
-            .find(|map| map.behavior == input.behavior_type)?;
+            .find(|map| map.behavior == input.behavior_type)
+            .ok_or_else(|| BrainVizError::UnknownBehavior(input.behavior_type.clone()))?;
+
+        // 檢查輸入單位
+        if let Some(expected) = &behavior_map.unit {
+            if expected != &input.unit {
+                return Err(BrainVizError::UnitMismatch {
+                    behavior: behavior_map.behavior.clone(),
+                    expected: expected.clone(),
+                    found: input.unit.clone(),
+                });
+            }
+        }
 
         let mut impact_scores = Vec::new();
         let mut total_impact = 0.0;
 
         // 正規化輸入值
         let normalized_input = if let Some(norm_params) = &behavior_map.normalization_params {
+            if !norm_params.mean.is_finite() || !norm_params.std_dev.is_finite() || norm_params.std_dev <= 0.0 {
+                return Err(BrainVizError::InvalidNormalization {
+                    behavior: behavior_map.behavior.clone(),
+                    reason: format!("mean = {}, std_dev = {}（標準差必須為正數）", norm_params.mean, norm_params.std_dev),
+                });
+            }
             (input.value - norm_params.mean) / norm_params.std_dev
         } else {
-            input.value / 100.0 // 預設正規化
+            input.value / explain::DEFAULT_DIVISOR // 預設正規化
         };
 
         // 計算每個腦區的影響分數
@@ -57,12 +78,16 @@ impl MappingEngine {
             });
         }
 
-        // 正規化總影響分數到 0-1 範圍
-        total_impact = (total_impact / impact_scores.len() as f32).min(1.0).max(0.0);
+        // 正規化總影響分數到 0-1 範圍；沒有腦區時為 0（避免 0/0 產生 NaN）
+        total_impact = if impact_scores.is_empty() {
+            0.0
+        } else {
+            (total_impact / impact_scores.len() as f32).clamp(0.0, 1.0)
+        };
 
         let risk_level = RiskLevel::from_score(total_impact);
 
-        Some(CalculationResult {
+        Ok(CalculationResult {
             behavior_input: input.clone(),
             impact_scores,
             total_impact,
@@ -70,6 +95,27 @@ impl MappingEngine {
         })
     }
 
+    /// 拆解計算結果，說明各腦區分數的由來
+    pub fn explain(&self, result: &CalculationResult) -> Result<ScoreExplanation> {
+        let behavior = &result.behavior_input.behavior_type;
+        self.get_behavior_map(behavior)
+            .map(|map| explain::explain(map, result))
+            .ok_or_else(|| BrainVizError::UnknownBehavior(behavior.clone()))
+    }
+
+    /// 獲取特定行為的映射表
+    pub fn get_behavior_map(&self, behavior: &str) -> Option<&BehaviorBrainMap> {
+        self.behavior_maps.iter().find(|map| map.behavior == behavior)
+    }
+
+    /// 獲取行為預期的輸入單位
+    pub fn get_behavior_unit(&self, behavior: &str) -> Option<&str> {
+        self.behavior_maps
+            .iter()
+            .find(|map| map.behavior == behavior)
+            .and_then(|map| map.unit.as_deref())
+    }
+
     /// 獲取所有可用的行為類型
     pub fn get_behavior_types(&self) -> Vec<String> {
         self.behavior_maps.iter().map(|map| map.behavior.clone()).collect()
@@ -93,10 +139,8 @@ impl MappingEngine {
 }
 
 /// 從 JSON 文件載入行為-腦區映射資料
-pub fn load_behavior_brain_map(path: &str) -> Result<Vec<BehaviorBrainMap>, Box<dyn std::error::Error>> {
-    let data = std::fs::read_to_string(path)?;
-    let maps: Vec<BehaviorBrainMap> = serde_json::from_str(&data)?;
-    Ok(maps)
+pub fn load_behavior_brain_map(path: impl AsRef<Path>) -> Result<Vec<BehaviorBrainMap>> {
+    read_json(path)
 }
 
 #[cfg(test)]
@@ -109,36 +153,44 @@ mod tests {
         vec![
             BehaviorBrainMap {
                 behavior: "反應時間".to_string(),
+                unit: None,
                 brain_regions: vec![
                     BrainRegionImpact {
                         region: "前額葉皮質".to_string(),
                         weight: 0.8,
                         description: Some("執行功能相關".to_string()),
+                        source: None,
                     },
                     BrainRegionImpact {
                         region: "頂葉皮質".to_string(),
                         weight: 0.6,
                         description: Some("注意力處理".to_string()),
+                        source: None,
                     },
                 ],
                 normalization_params: Some(NormalizationParams {
                     mean: 300.0,
                     std_dev: 50.0,
                     sample_size: Some(1000),
+                    source: None,
+                    reliability: None,
                 }),
             },
             BehaviorBrainMap {
                 behavior: "記憶測試".to_string(),
+                unit: None,
                 brain_regions: vec![
                     BrainRegionImpact {
                         region: "海馬迴".to_string(),
                         weight: 0.9,
                         description: Some("記憶形成".to_string()),
+                        source: None,
                     },
                     BrainRegionImpact {
                         region: "前額葉皮質".to_string(),
                         weight: 0.7,
                         description: Some("工作記憶".to_string()),
+                        source: None,
                     },
                 ],
                 normalization_params: None,
@@ -216,7 +268,46 @@ mod tests {
         };
 
         let result = engine.calculate_impact(&input);
-        assert!(result.is_none());
+        assert!(matches!(result, Err(BrainVizError::UnknownBehavior(ref b)) if b == "不存在的行為"));
+    }
+
+    #[test]
+    fn test_unit_mismatch() {
+        let mut behavior_maps = create_test_behavior_maps();
+        behavior_maps[0].unit = Some("毫秒".to_string());
+        let engine = MappingEngine::new(behavior_maps);
+
+        let mut input = BehaviorInput {
+            behavior_type: "反應時間".to_string(),
+            value: 0.35,
+            unit: "秒".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let result = engine.calculate_impact(&input);
+        assert!(matches!(result, Err(BrainVizError::UnitMismatch { .. })));
+
+        input.unit = "毫秒".to_string();
+        assert!(engine.calculate_impact(&input).is_ok());
+        assert_eq!(engine.get_behavior_unit("反應時間"), Some("毫秒"));
+        assert_eq!(engine.get_behavior_unit("記憶測試"), None);
+    }
+
+    #[test]
+    fn test_invalid_normalization() {
+        let mut behavior_maps = create_test_behavior_maps();
+        behavior_maps[0].normalization_params.as_mut().unwrap().std_dev = 0.0;
+        let engine = MappingEngine::new(behavior_maps);
+
+        let input = BehaviorInput {
+            behavior_type: "反應時間".to_string(),
+            value: 350.0,
+            unit: "毫秒".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let result = engine.calculate_impact(&input);
+        assert!(matches!(result, Err(BrainVizError::InvalidNormalization { .. })));
     }
 
     #[test]
@@ -263,11 +354,13 @@ mod tests {
         let behavior_maps = vec![
             BehaviorBrainMap {
                 behavior: "風險測試".to_string(),
+                unit: None,
                 brain_regions: vec![
                     BrainRegionImpact {
                         region: "測試腦區".to_string(),
                         weight: 1.0,
                         description: None,
+                        source: None,
                     },
                 ],
                 normalization_params: None,
@@ -322,17 +415,21 @@ mod tests {
         let behavior_maps = vec![
             BehaviorBrainMap {
                 behavior: "負值測試".to_string(),
+                unit: None,
                 brain_regions: vec![
                     BrainRegionImpact {
                         region: "測試腦區".to_string(),
                         weight: 0.5,
                         description: None,
+                        source: None,
                     },
                 ],
                 normalization_params: Some(NormalizationParams {
                     mean: 100.0,
                     std_dev: 20.0,
                     sample_size: Some(100),
+                    source: None,
+                    reliability: None,
                 }),
             }
         ];
@@ -365,32 +462,58 @@ mod tests {
         };
         
         let result = engine.calculate_impact(&input);
-        assert!(result.is_none());
+        assert!(matches!(result, Err(BrainVizError::UnknownBehavior(_))));
         
         assert_eq!(engine.get_behavior_types().len(), 0);
         assert_eq!(engine.get_brain_regions().len(), 0);
     }
 
+    #[test]
+    fn test_behavior_without_regions() {
+        let engine = MappingEngine::new(vec![BehaviorBrainMap {
+            behavior: "無腦區".to_string(),
+            unit: None,
+            brain_regions: vec![],
+            normalization_params: None,
+        }]);
+
+        let input = BehaviorInput {
+            behavior_type: "無腦區".to_string(),
+            value: 50.0,
+            unit: "分數".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let result = engine.calculate_impact(&input).unwrap();
+        assert!(result.impact_scores.is_empty());
+        assert_eq!(result.total_impact, 0.0);
+        assert_eq!(result.risk_level, RiskLevel::Low);
+    }
+
     #[test]
     fn test_total_impact_normalization() {
         let behavior_maps = vec![
             BehaviorBrainMap {
                 behavior: "多腦區測試".to_string(),
+                unit: None,
                 brain_regions: vec![
                     BrainRegionImpact {
                         region: "腦區1".to_string(),
                         weight: 0.9,
                         description: None,
+                        source: None,
                     },
                     BrainRegionImpact {
                         region: "腦區2".to_string(),
                         weight: 0.8,
                         description: None,
+                        source: None,
                     },
                     BrainRegionImpact {
                         region: "腦區3".to_string(),
                         weight: 0.7,
                         description: None,
+                        source: None,
                     },
                 ],
                 normalization_params: None,
@@ -434,7 +557,7 @@ mod tests {
             }
         ]"#;
         
-        let maps: Result<Vec<BehaviorBrainMap>, _> = serde_json::from_str(json_data);
+        let maps: std::result::Result<Vec<BehaviorBrainMap>, _> = serde_json::from_str(json_data);
         assert!(maps.is_ok());
         
         let maps = maps.unwrap();
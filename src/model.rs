@@ -1,3 +1,4 @@
+use crate::i18n::{self, Language};
 use serde::{Deserialize, Serialize};
 
 /// 行為輸入資料結構
@@ -9,18 +10,36 @@ pub struct BehaviorInput {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// 受試者資料
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubjectInfo {
+    pub subject_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub age: Option<u32>,
+    #[serde(default)]
+    pub sex: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
 /// 腦區影響資料結構
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrainRegionImpact {
     pub region: String,
     pub weight: f32,
     pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>, // 權重出處（文獻、資料集或專家共識）
 }
 
 /// 行為-腦區映射表結構
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BehaviorBrainMap {
     pub behavior: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>, // 預期的輸入單位，未指定時不檢查
     pub brain_regions: Vec<BrainRegionImpact>,
     pub normalization_params: Option<NormalizationParams>,
 }
@@ -31,10 +50,14 @@ pub struct NormalizationParams {
     pub mean: f32,
     pub std_dev: f32,
     pub sample_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>, // 常模出處
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reliability: Option<f32>, // 再測信度 r，用於比較兩次結果的可靠改變指數
 }
 
 /// 計算結果結構
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalculationResult {
     pub behavior_input: BehaviorInput,
     pub impact_scores: Vec<RegionImpactScore>,
@@ -42,8 +65,26 @@ pub struct CalculationResult {
     pub risk_level: RiskLevel,
 }
 
+impl CalculationResult {
+    /// 指定腦區的影響分數；同一腦區出現多次時取最高分
+    pub fn region_score(&self, region: &str) -> Option<&RegionImpactScore> {
+        self.impact_scores
+            .iter()
+            .filter(|impact| impact.region == region)
+            .max_by(|a, b| a.impact_score.total_cmp(&b.impact_score))
+    }
+
+    /// 歷史中指定行為時間最新的一筆
+    pub fn latest_for_behavior<'a>(history: &'a [CalculationResult], behavior: &str) -> Option<&'a CalculationResult> {
+        history
+            .iter()
+            .filter(|result| result.behavior_input.behavior_type == behavior)
+            .max_by_key(|result| result.behavior_input.timestamp)
+    }
+}
+
 /// 腦區影響分數
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionImpactScore {
     pub region: String,
     pub impact_score: f32,
@@ -52,7 +93,7 @@ pub struct RegionImpactScore {
 }
 
 /// 風險等級
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -61,6 +102,9 @@ pub enum RiskLevel {
 }
 
 impl RiskLevel {
+    /// 由低到高的所有等級
+    pub const ALL: [RiskLevel; 4] = [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High, RiskLevel::Critical];
+
     pub fn from_score(score: f32) -> Self {
         if score >= 0.8 {
             RiskLevel::Critical
@@ -73,6 +117,26 @@ impl RiskLevel {
         }
     }
 
+    /// 此等級的下限（含），與 `from_score` 的門檻一致
+    pub fn lower_bound(&self) -> f32 {
+        match self {
+            RiskLevel::Low => 0.0,
+            RiskLevel::Medium => 0.3,
+            RiskLevel::High => 0.6,
+            RiskLevel::Critical => 0.8,
+        }
+    }
+
+    /// 下一個較高等級；已是最高等級時為 None
+    pub fn next(&self) -> Option<RiskLevel> {
+        match self {
+            RiskLevel::Low => Some(RiskLevel::Medium),
+            RiskLevel::Medium => Some(RiskLevel::High),
+            RiskLevel::High => Some(RiskLevel::Critical),
+            RiskLevel::Critical => None,
+        }
+    }
+
     pub fn color(&self) -> [u8; 3] {
         match self {
             RiskLevel::Low => [102, 204, 255],      // 淡藍
@@ -82,13 +146,34 @@ impl RiskLevel {
         }
     }
 
+    /// 目前語言的風險說明
     pub fn description(&self) -> &'static str {
-        match self {
-            RiskLevel::Low => "低風險：目前無明顯異常",
-            RiskLevel::Medium => "中風險：建議持續觀察",
-            RiskLevel::High => "高風險：請留意日常功能與相關疾病徵兆",
-            RiskLevel::Critical => "極高風險：建議諮詢專業醫師",
-        }
+        self.description_in(i18n::language())
+    }
+
+    /// 指定語言的風險說明
+    pub fn description_in(&self, language: Language) -> &'static str {
+        i18n::tr_in(language, match self {
+            RiskLevel::Low => "risk.low",
+            RiskLevel::Medium => "risk.medium",
+            RiskLevel::High => "risk.high",
+            RiskLevel::Critical => "risk.critical",
+        })
+    }
+
+    /// 目前語言的等級簡稱，用於圖例與表格
+    pub fn label(&self) -> &'static str {
+        self.label_in(i18n::language())
+    }
+
+    /// 指定語言的等級簡稱
+    pub fn label_in(&self, language: Language) -> &'static str {
+        i18n::tr_in(language, match self {
+            RiskLevel::Low => "risk.low.label",
+            RiskLevel::Medium => "risk.medium.label",
+            RiskLevel::High => "risk.high.label",
+            RiskLevel::Critical => "risk.critical.label",
+        })
     }
 }
 
@@ -119,6 +204,7 @@ mod tests {
             region: "前額葉皮質".to_string(),
             weight: 0.7,
             description: Some("執行功能相關".to_string()),
+            source: None,
         };
 
         assert_eq!(impact.region, "前額葉皮質");
@@ -133,6 +219,8 @@ mod tests {
             mean: 300.0,
             std_dev: 50.0,
             sample_size: Some(1000),
+            source: None,
+            reliability: None,
         };
 
         assert_eq!(params.mean, 300.0);
@@ -147,16 +235,19 @@ mod tests {
                 region: "前額葉皮質".to_string(),
                 weight: 0.8,
                 description: None,
+                source: None,
             },
             BrainRegionImpact {
                 region: "頂葉皮質".to_string(),
                 weight: 0.6,
                 description: None,
+                source: None,
             },
         ];
 
         let map = BehaviorBrainMap {
             behavior: "注意力測試".to_string(),
+            unit: None,
             brain_regions: brain_regions.clone(),
             normalization_params: None,
         };
@@ -196,6 +287,17 @@ mod tests {
         assert_eq!(RiskLevel::from_score(0.29), RiskLevel::Low);
     }
 
+    #[test]
+    fn test_risk_level_bounds() {
+        for level in [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High, RiskLevel::Critical] {
+            assert_eq!(RiskLevel::from_score(level.lower_bound()), level);
+            if let Some(next) = level.next() {
+                assert_eq!(RiskLevel::from_score(next.lower_bound() - 0.001), level);
+            }
+        }
+        assert_eq!(RiskLevel::Critical.next(), None);
+    }
+
     #[test]
     fn test_risk_level_color() {
         assert_eq!(RiskLevel::Low.color(), [102, 204, 255]);
@@ -242,6 +344,28 @@ mod tests {
         assert_eq!(result.impact_scores.len(), 1);
         assert_eq!(result.total_impact, 0.72);
         assert_eq!(result.risk_level, RiskLevel::High);
+        assert_eq!(result.region_score("海馬迴").unwrap().weight, 0.9);
+        assert!(result.region_score("小腦").is_none());
+    }
+
+    #[test]
+    fn test_latest_for_behavior() {
+        let at = |behavior: &str, hour: u32, total_impact: f32| CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: behavior.to_string(),
+                value: 1.0,
+                unit: "秒".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap(),
+            },
+            impact_scores: Vec::new(),
+            total_impact,
+            risk_level: RiskLevel::from_score(total_impact),
+        };
+        let history = vec![at("反應時間", 9, 0.1), at("反應時間", 11, 0.3), at("自然步行", 12, 0.5), at("反應時間", 10, 0.2)];
+
+        let latest = CalculationResult::latest_for_behavior(&history, "反應時間").unwrap();
+        assert_eq!(latest.total_impact, 0.3);
+        assert!(CalculationResult::latest_for_behavior(&history, "記憶測試").is_none());
     }
 
     #[test]
@@ -273,6 +397,7 @@ mod tests {
             region: "前額葉皮質".to_string(),
             weight: 0.7,
             description: Some("測試描述".to_string()),
+            source: None,
         };
 
         let json = serde_json::to_string(&impact).unwrap();
@@ -290,6 +415,7 @@ mod tests {
                 region: "前額葉皮質".to_string(),
                 weight: 0.8,
                 description: None,
+                source: None,
             },
         ];
 
@@ -297,10 +423,13 @@ mod tests {
             mean: 300.0,
             std_dev: 50.0,
             sample_size: Some(1000),
+            source: None,
+            reliability: None,
         };
 
         let map = BehaviorBrainMap {
             behavior: "測試行為".to_string(),
+            unit: None,
             brain_regions,
             normalization_params: Some(normalization_params),
         };
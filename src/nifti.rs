@@ -0,0 +1,496 @@
+//! NIfTI-1 影像讀寫
+//!
+//! 以圖譜的標籤影像（每個體素的值為標籤編號）為底，將 [`AtlasVector`] 的影響分數
+//! 寫入對應腦區的體素，輸出可在 FSLeyes、MRIcron 疊圖的 float32 影像。
+//! 只處理單檔格式（`.nii`，副檔名為 `.gz` 時以 gzip 壓縮）的前三個維度；
+//! 讀取時支援大小端與常見的整數、浮點資料型別，並套用 `scl_slope`/`scl_inter`。
+
+use crate::atlas::{AtlasLabel, AtlasVector};
+use crate::error::{BrainVizError, Result};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// NIfTI-1 標頭長度
+const HEADER_SIZE: usize = 348;
+
+/// 單檔 NIfTI-1 的資料起點（標頭 + 4 位元組的延伸旗標）
+const VOX_OFFSET: usize = 352;
+
+/// 資料型別代碼
+const DT_UINT8: i16 = 2;
+const DT_INT16: i16 = 4;
+const DT_INT32: i16 = 8;
+const DT_FLOAT32: i16 = 16;
+const DT_FLOAT64: i16 = 64;
+const DT_INT8: i16 = 256;
+const DT_UINT16: i16 = 512;
+const DT_UINT32: i16 = 768;
+
+/// 3D 影像與其空間資訊
+#[derive(Debug, Clone, PartialEq)]
+pub struct NiftiVolume {
+    /// 各軸體素數
+    pub dims: [usize; 3],
+    /// 體素大小（mm）
+    pub pixdim: [f32; 3],
+    /// qform 的 qfac（`pixdim[0]`，1 或 -1）
+    pub qfac: f32,
+    pub qform_code: i16,
+    /// qform 四元數 b、c、d 與平移
+    pub quatern: [f32; 3],
+    pub qoffset: [f32; 3],
+    pub sform_code: i16,
+    /// sform 仿射矩陣（體素 → mm）的前三列
+    pub srow: [[f32; 4]; 3],
+    /// 空間與時間單位（預設為 mm）
+    pub xyzt_units: u8,
+    pub descrip: String,
+    /// 體素值，x 變化最快
+    pub data: Vec<f32>,
+}
+
+impl NiftiVolume {
+    /// 讀取 `.nii` 或 `.nii.gz`
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| BrainVizError::io(path, e))?;
+        Self::from_bytes(&bytes).map_err(|message| nifti_error(path, message))
+    }
+
+    /// 解析 NIfTI-1 位元組；gzip 壓縮的內容會先解壓縮
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, String> {
+        let mut decompressed = Vec::new();
+        let bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+            flate2::read::GzDecoder::new(bytes)
+                .read_to_end(&mut decompressed)
+                .map_err(|e| format!("gzip 解壓縮失敗: {}", e))?;
+            &decompressed[..]
+        } else {
+            bytes
+        };
+        if bytes.len() < HEADER_SIZE {
+            return Err("檔案過短，不是 NIfTI-1 影像".to_string());
+        }
+
+        let reader = Reader {
+            bytes,
+            big_endian: match i32::from_le_bytes(bytes[0..4].try_into().unwrap()) {
+                348 => false,
+                _ if i32::from_be_bytes(bytes[0..4].try_into().unwrap()) == 348 => true,
+                _ => return Err("sizeof_hdr 不是 348，不是 NIfTI-1 影像".to_string()),
+            },
+        };
+        if &bytes[344..347] != b"n+1" && &bytes[344..347] != b"ni1" {
+            return Err("magic 欄位不是 n+1 或 ni1".to_string());
+        }
+        if &bytes[344..347] == b"ni1" {
+            return Err("不支援標頭與影像分開的 .hdr/.img 格式".to_string());
+        }
+
+        let ndim = reader.i16(40);
+        if !(1..=7).contains(&ndim) {
+            return Err(format!("維度數 {} 無效", ndim));
+        }
+        let mut dims = [1usize; 3];
+        for (axis, dim) in dims.iter_mut().enumerate().take(ndim.min(3) as usize) {
+            *dim = usize::try_from(reader.i16(42 + 2 * axis)).ok().filter(|&d| d > 0).ok_or("維度大小必須為正數")?;
+        }
+        // 第四維以上只讀第一個體積
+        let voxels = dims.iter().product::<usize>();
+
+        let datatype = reader.i16(70);
+        let size = match datatype {
+            DT_UINT8 | DT_INT8 => 1,
+            DT_INT16 | DT_UINT16 => 2,
+            DT_INT32 | DT_UINT32 | DT_FLOAT32 => 4,
+            DT_FLOAT64 => 8,
+            other => return Err(format!("不支援的資料型別 {}", other)),
+        };
+        let offset = reader.f32(108) as usize;
+        let end = offset + voxels * size;
+        if offset < HEADER_SIZE || bytes.len() < end {
+            return Err(format!("影像資料不完整（需要 {} 位元組，實際 {}）", end, bytes.len()));
+        }
+
+        let slope = reader.f32(112);
+        let inter = reader.f32(116);
+        let (slope, inter) = if slope == 0.0 || !slope.is_finite() { (1.0, 0.0) } else { (slope, inter) };
+        let data = (0..voxels)
+            .map(|i| {
+                let at = offset + i * size;
+                let raw = match datatype {
+                    DT_UINT8 => bytes[at] as f32,
+                    DT_INT8 => bytes[at] as i8 as f32,
+                    DT_INT16 => reader.i16(at) as f32,
+                    DT_UINT16 => reader.i16(at) as u16 as f32,
+                    DT_INT32 => reader.i32(at) as f32,
+                    DT_UINT32 => reader.i32(at) as u32 as f32,
+                    DT_FLOAT32 => reader.f32(at),
+                    _ => reader.f64(at) as f32,
+                };
+                raw * slope + inter
+            })
+            .collect();
+
+        let qfac = reader.f32(76);
+        let row = |at: usize| [reader.f32(at), reader.f32(at + 4), reader.f32(at + 8), reader.f32(at + 12)];
+        Ok(Self {
+            dims,
+            pixdim: [reader.f32(80), reader.f32(84), reader.f32(88)],
+            qfac: if qfac < 0.0 { -1.0 } else { 1.0 },
+            qform_code: reader.i16(252),
+            quatern: [reader.f32(256), reader.f32(260), reader.f32(264)],
+            qoffset: [reader.f32(268), reader.f32(272), reader.f32(276)],
+            sform_code: reader.i16(254),
+            srow: [row(280), row(296), row(312)],
+            xyzt_units: bytes[123],
+            descrip: String::from_utf8_lossy(&bytes[148..228]).trim_end_matches('\0').to_string(),
+            data,
+        })
+    }
+
+    /// 相同空間資訊、不同體素值的影像
+    pub fn with_data(&self, data: Vec<f32>, descrip: &str) -> Self {
+        assert_eq!(data.len(), self.data.len(), "體素數必須相同");
+        Self {
+            descrip: descrip.to_string(),
+            data,
+            ..self.clone()
+        }
+    }
+
+    /// 輸出為小端序、float32 的單檔 NIfTI-1
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = [0u8; HEADER_SIZE];
+        let mut put = |at: usize, bytes: &[u8]| header[at..at + bytes.len()].copy_from_slice(bytes);
+
+        put(0, &(HEADER_SIZE as i32).to_le_bytes());
+        put(38, b"r");
+        let ndim: i16 = if self.dims[2] > 1 { 3 } else if self.dims[1] > 1 { 2 } else { 1 };
+        put(40, &ndim.to_le_bytes());
+        for (axis, dim) in self.dims.iter().enumerate() {
+            put(42 + 2 * axis, &(*dim as i16).to_le_bytes());
+        }
+        for axis in 3..7 {
+            put(42 + 2 * axis, &1i16.to_le_bytes());
+        }
+        put(70, &DT_FLOAT32.to_le_bytes());
+        put(72, &32i16.to_le_bytes());
+        put(76, &self.qfac.to_le_bytes());
+        for (axis, size) in self.pixdim.iter().enumerate() {
+            put(80 + 4 * axis, &size.to_le_bytes());
+        }
+        put(108, &(VOX_OFFSET as f32).to_le_bytes());
+        put(112, &1f32.to_le_bytes());
+        put(123, &[self.xyzt_units]);
+        let (min, max) = self
+            .data
+            .iter()
+            .fold((0f32, 0f32), |(min, max), &v| (min.min(v), max.max(v)));
+        put(124, &max.to_le_bytes());
+        put(128, &min.to_le_bytes());
+        let descrip = self.descrip.as_bytes();
+        put(148, &descrip[..descrip.len().min(79)]);
+        put(252, &self.qform_code.to_le_bytes());
+        put(254, &self.sform_code.to_le_bytes());
+        for (i, value) in self.quatern.iter().chain(&self.qoffset).enumerate() {
+            put(256 + 4 * i, &value.to_le_bytes());
+        }
+        for (r, row) in self.srow.iter().enumerate() {
+            for (c, value) in row.iter().enumerate() {
+                put(280 + 16 * r + 4 * c, &value.to_le_bytes());
+            }
+        }
+        put(344, b"n+1\0");
+
+        let mut bytes = Vec::with_capacity(VOX_OFFSET + self.data.len() * 4);
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&[0; VOX_OFFSET - HEADER_SIZE]); // 無延伸標頭
+        for value in &self.data {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// 寫入檔案；副檔名為 `.gz` 時以 gzip 壓縮
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let bytes = self.to_bytes();
+        let bytes = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz")) {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(&bytes)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| BrainVizError::io(path, e))?
+        } else {
+            bytes
+        };
+        std::fs::write(path, bytes).map_err(|e| BrainVizError::io(path, e))
+    }
+
+    /// 體素座標 → 世界座標（mm）；優先使用 sform，其次 qform，都沒有時只乘上體素大小
+    pub fn voxel_to_world(&self, [i, j, k]: [f32; 3]) -> [f32; 3] {
+        if self.sform_code > 0 {
+            return self.srow.map(|row| row[0] * i + row[1] * j + row[2] * k + row[3]);
+        }
+        let scaled = [i * self.pixdim[0], j * self.pixdim[1], k * self.pixdim[2] * self.qfac];
+        if self.qform_code <= 0 {
+            return scaled;
+        }
+
+        let [b, c, d] = self.quatern;
+        let a = (1.0 - (b * b + c * c + d * d)).max(0.0).sqrt();
+        let rotation = [
+            [a * a + b * b - c * c - d * d, 2.0 * (b * c - a * d), 2.0 * (b * d + a * c)],
+            [2.0 * (b * c + a * d), a * a + c * c - b * b - d * d, 2.0 * (c * d - a * b)],
+            [2.0 * (b * d - a * c), 2.0 * (c * d + a * b), a * a + d * d - b * b - c * c],
+        ];
+        let mut world = self.qoffset;
+        for (axis, row) in rotation.iter().enumerate() {
+            world[axis] += row[0] * scaled[0] + row[1] * scaled[1] + row[2] * scaled[2];
+        }
+        world
+    }
+
+    /// 把體素值四捨五入為標籤編號；背景（0）與負值回傳 None
+    fn label_at(&self, voxel: usize) -> Option<u32> {
+        let value = self.data[voxel].round();
+        (value >= 1.0).then_some(value as u32)
+    }
+
+    /// 各標籤體素的質心（世界座標），可補上標籤表缺少的 MNI 座標
+    pub fn label_centroids(&self) -> Vec<AtlasLabel> {
+        let mut sums: BTreeMap<u32, ([f64; 3], usize)> = BTreeMap::new();
+        let [nx, ny, _] = self.dims;
+        for voxel in 0..self.data.len() {
+            let Some(label) = self.label_at(voxel) else {
+                continue;
+            };
+            let (sum, count) = sums.entry(label).or_insert(([0.0; 3], 0));
+            let ijk = [voxel % nx, (voxel / nx) % ny, voxel / (nx * ny)];
+            for axis in 0..3 {
+                sum[axis] += ijk[axis] as f64;
+            }
+            *count += 1;
+        }
+        sums.into_iter()
+            .map(|(index, (sum, count))| AtlasLabel {
+                index,
+                name: String::new(),
+                mni: Some(self.voxel_to_world(sum.map(|s| (s / count as f64) as f32))),
+            })
+            .collect()
+    }
+}
+
+/// 以標籤影像為底產生影響分數影像：每個腦區的體素值為該標籤的 `impact_score`，其餘為 0
+pub fn impact_volume(labels: &NiftiVolume, vector: &AtlasVector) -> NiftiVolume {
+    let scores: BTreeMap<u32, f32> = vector
+        .entries
+        .iter()
+        .map(|entry| (entry.index, entry.impact_score))
+        .collect();
+    let data = (0..labels.data.len())
+        .map(|voxel| {
+            labels
+                .label_at(voxel)
+                .and_then(|label| scores.get(&label).copied())
+                .unwrap_or(0.0)
+        })
+        .collect();
+    labels.with_data(data, &format!("behavior_brain_viz impact map ({})", vector.atlas))
+}
+
+/// 讀回影像中各標籤的平均值，用於檢查匯出的影像或匯入其他工具產生的分數圖
+pub fn parcel_values(labels: &NiftiVolume, image: &NiftiVolume) -> Result<BTreeMap<u32, f32>> {
+    if labels.dims != image.dims {
+        return Err(BrainVizError::Export(format!(
+            "影像大小 {:?} 與標籤影像 {:?} 不同",
+            image.dims, labels.dims
+        )));
+    }
+    let mut sums: BTreeMap<u32, (f64, usize)> = BTreeMap::new();
+    for voxel in 0..labels.data.len() {
+        if let Some(label) = labels.label_at(voxel) {
+            let (sum, count) = sums.entry(label).or_insert((0.0, 0));
+            *sum += image.data[voxel] as f64;
+            *count += 1;
+        }
+    }
+    Ok(sums
+        .into_iter()
+        .map(|(label, (sum, count))| (label, (sum / count as f64) as f32))
+        .collect())
+}
+
+fn nifti_error(path: &Path, message: String) -> BrainVizError {
+    BrainVizError::Parse {
+        path: path.to_path_buf(),
+        line: 0,
+        column: 0,
+        message,
+    }
+}
+
+/// 依標頭判斷的位元組序讀取數值
+struct Reader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl Reader<'_> {
+    fn array<const N: usize>(&self, at: usize) -> [u8; N] {
+        let mut array: [u8; N] = self.bytes[at..at + N].try_into().unwrap();
+        if self.big_endian {
+            array.reverse();
+        }
+        array
+    }
+
+    fn i16(&self, at: usize) -> i16 {
+        i16::from_le_bytes(self.array(at))
+    }
+
+    fn i32(&self, at: usize) -> i32 {
+        i32::from_le_bytes(self.array(at))
+    }
+
+    fn f32(&self, at: usize) -> f32 {
+        f32::from_le_bytes(self.array(at))
+    }
+
+    fn f64(&self, at: usize) -> f64 {
+        f64::from_le_bytes(self.array(at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atlas::{AtlasKind, AtlasVectorEntry};
+
+    /// 4×3×2、2mm 的標籤影像，原點在 (-4, -2, 0) mm；標籤 1 佔 x < 2，標籤 2 佔 x ≥ 2 且 z = 1
+    fn labels() -> NiftiVolume {
+        let data = (0..24)
+            .map(|voxel| match (voxel % 4, voxel / 12) {
+                (0 | 1, _) => 1.0,
+                (_, 1) => 2.0,
+                _ => 0.0,
+            })
+            .collect();
+        NiftiVolume {
+            dims: [4, 3, 2],
+            pixdim: [2.0, 2.0, 2.0],
+            qfac: 1.0,
+            qform_code: 0,
+            quatern: [0.0; 3],
+            qoffset: [0.0; 3],
+            sform_code: 4, // MNI152
+            srow: [[2.0, 0.0, 0.0, -4.0], [0.0, 2.0, 0.0, -2.0], [0.0, 0.0, 2.0, 0.0]],
+            xyzt_units: 2,
+            descrip: "test atlas".to_string(),
+            data,
+        }
+    }
+
+    fn vector() -> AtlasVector {
+        let entry = |index: u32, score: f32| AtlasVectorEntry {
+            index,
+            label: format!("L{}", index),
+            mni: None,
+            impact_score: score,
+            regions: Vec::new(),
+        };
+        AtlasVector {
+            atlas: "test".to_string(),
+            kind: AtlasKind::Custom,
+            entries: vec![entry(1, 0.25), entry(2, 0.75), entry(3, 0.5)],
+            unmapped: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_header_layout() {
+        let bytes = labels().to_bytes();
+        assert_eq!(bytes.len(), VOX_OFFSET + 24 * 4);
+        assert_eq!(i32::from_le_bytes(bytes[0..4].try_into().unwrap()), 348);
+        assert_eq!(i16::from_le_bytes(bytes[40..42].try_into().unwrap()), 3);
+        assert_eq!(i16::from_le_bytes(bytes[70..72].try_into().unwrap()), DT_FLOAT32);
+        assert_eq!(f32::from_le_bytes(bytes[108..112].try_into().unwrap()), 352.0);
+        assert_eq!(&bytes[344..348], b"n+1\0");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let volume = labels();
+        assert_eq!(NiftiVolume::from_bytes(&volume.to_bytes()).unwrap(), volume);
+
+        let path = std::env::temp_dir().join("bbv_nifti_round_trip.nii.gz");
+        volume.write(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap()[..2], [0x1f, 0x8b]);
+        assert_eq!(NiftiVolume::read(&path).unwrap(), volume);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_integer_big_endian() {
+        // 以大端序、int16、scl_slope = 2 寫成的 2×1×1 影像
+        let mut bytes = vec![0u8; VOX_OFFSET + 4];
+        bytes[0..4].copy_from_slice(&348i32.to_be_bytes());
+        bytes[40..42].copy_from_slice(&3i16.to_be_bytes());
+        for (axis, dim) in [2i16, 1, 1].iter().enumerate() {
+            bytes[42 + 2 * axis..44 + 2 * axis].copy_from_slice(&dim.to_be_bytes());
+        }
+        bytes[70..72].copy_from_slice(&DT_INT16.to_be_bytes());
+        bytes[108..112].copy_from_slice(&(VOX_OFFSET as f32).to_be_bytes());
+        bytes[112..116].copy_from_slice(&2f32.to_be_bytes());
+        bytes[344..348].copy_from_slice(b"n+1\0");
+        bytes[352..354].copy_from_slice(&3i16.to_be_bytes());
+        bytes[354..356].copy_from_slice(&(-1i16).to_be_bytes());
+
+        let volume = NiftiVolume::from_bytes(&bytes).unwrap();
+        assert_eq!(volume.dims, [2, 1, 1]);
+        assert_eq!(volume.data, vec![6.0, -2.0]);
+
+        bytes[344] = b'x';
+        assert!(NiftiVolume::from_bytes(&bytes).is_err());
+        assert!(NiftiVolume::from_bytes(&bytes[..100]).is_err());
+    }
+
+    #[test]
+    fn test_impact_volume_round_trip() {
+        let labels = labels();
+        let impact = impact_volume(&labels, &vector());
+        assert_eq!(impact.srow, labels.srow);
+        assert_eq!(impact.data[0], 0.25);
+        assert_eq!(impact.data[2], 0.0); // 背景
+        assert_eq!(impact.data[14], 0.75);
+
+        let reread = NiftiVolume::from_bytes(&impact.to_bytes()).unwrap();
+        let values = parcel_values(&labels, &reread).unwrap();
+        assert_eq!(values.into_iter().collect::<Vec<_>>(), vec![(1, 0.25), (2, 0.75)]);
+
+        let other = NiftiVolume { dims: [2, 2, 2], data: vec![0.0; 8], ..labels.clone() };
+        assert!(parcel_values(&labels, &other).is_err());
+    }
+
+    #[test]
+    fn test_centroids_and_qform() {
+        let centroids = labels().label_centroids();
+        assert_eq!(centroids.len(), 2);
+        // 標籤 1：i ∈ {0, 1}、j ∈ {0, 1, 2}、k ∈ {0, 1} → 體素質心 (0.5, 1, 0.5)
+        assert_eq!(centroids[0].mni, Some([-3.0, 0.0, 1.0]));
+        assert_eq!(centroids[1].mni, Some([1.0, 0.0, 2.0]));
+
+        // 只有 qform：繞 z 軸旋轉 180°（b = c = 0、d = 1）
+        let volume = NiftiVolume {
+            sform_code: 0,
+            qform_code: 1,
+            quatern: [0.0, 0.0, 1.0],
+            qoffset: [10.0, 20.0, 30.0],
+            ..labels()
+        };
+        assert_eq!(volume.voxel_to_world([1.0, 1.0, 1.0]), [8.0, 18.0, 32.0]);
+    }
+}
@@ -0,0 +1,475 @@
+//! Python 綁定（`python` 功能）
+//!
+//! 以 maturin 建置後可在 Python 中使用：
+//!
+//! ```python
+//! import pandas as pd
+//! import behavior_brain_viz as bbv
+//!
+//! engine = bbv.MappingEngine("data/behavior_brain_map.json")
+//! result = engine.calculate_impact(bbv.BehaviorInput("自然步行", 0.9, "公尺/秒"))
+//! report = bbv.RiskAssessment("data/risk_map.json").generate_risk_report(result)
+//!
+//! df = pd.DataFrame(engine.score_batch(["自然步行"] * 3, [0.9, 1.2, 1.5]))
+//! ```
+
+use crate::error::BrainVizError;
+use crate::i18n::{self, Language};
+use crate::mapping::{load_behavior_brain_map, MappingEngine};
+use crate::model::{BehaviorInput, CalculationResult};
+use crate::recommend::load_recommendation_rules;
+use crate::risk::{load_risk_map, RiskAssessment, RiskReport};
+use chrono::{DateTime, Utc};
+use pyo3::exceptions::{PyKeyError, PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+impl From<BrainVizError> for PyErr {
+    fn from(err: BrainVizError) -> Self {
+        let message = err.to_string();
+        match err {
+            BrainVizError::UnknownBehavior(_) => PyKeyError::new_err(message),
+            BrainVizError::Io { .. } => PyOSError::new_err(message),
+            _ => PyValueError::new_err(message),
+        }
+    }
+}
+
+/// 行為輸入
+#[pyclass(name = "BehaviorInput", module = "behavior_brain_viz")]
+#[derive(Clone)]
+pub struct PyBehaviorInput {
+    inner: BehaviorInput,
+}
+
+#[pymethods]
+impl PyBehaviorInput {
+    #[new]
+    #[pyo3(signature = (behavior_type, value, unit, timestamp = None))]
+    fn new(behavior_type: String, value: f32, unit: String, timestamp: Option<DateTime<Utc>>) -> Self {
+        Self {
+            inner: BehaviorInput {
+                behavior_type,
+                value,
+                unit,
+                timestamp: timestamp.unwrap_or_else(Utc::now),
+            },
+        }
+    }
+
+    #[getter]
+    fn behavior_type(&self) -> &str {
+        &self.inner.behavior_type
+    }
+
+    #[getter]
+    fn value(&self) -> f32 {
+        self.inner.value
+    }
+
+    #[getter]
+    fn unit(&self) -> &str {
+        &self.inner.unit
+    }
+
+    #[getter]
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.inner.timestamp
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BehaviorInput(behavior_type={:?}, value={}, unit={:?})",
+            self.inner.behavior_type, self.inner.value, self.inner.unit
+        )
+    }
+}
+
+/// 計算結果
+#[pyclass(name = "CalculationResult", module = "behavior_brain_viz")]
+#[derive(Clone)]
+pub struct PyCalculationResult {
+    inner: CalculationResult,
+}
+
+#[pymethods]
+impl PyCalculationResult {
+    #[getter]
+    fn total_impact(&self) -> f32 {
+        self.inner.total_impact
+    }
+
+    #[getter]
+    fn risk_level(&self) -> String {
+        format!("{:?}", self.inner.risk_level)
+    }
+
+    /// 各腦區分數（region -> impact_score）
+    #[getter]
+    fn impact_scores<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for impact in &self.inner.impact_scores {
+            dict.set_item(&impact.region, impact.impact_score)?;
+        }
+        Ok(dict)
+    }
+
+    /// 轉換為巢狀 dict
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        result_to_dict(py, &self.inner)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CalculationResult(behavior_type={:?}, total_impact={:.3}, risk_level={:?})",
+            self.inner.behavior_input.behavior_type, self.inner.total_impact, self.inner.risk_level
+        )
+    }
+}
+
+/// 影響分數計算引擎
+#[pyclass(name = "MappingEngine", module = "behavior_brain_viz")]
+pub struct PyMappingEngine {
+    engine: MappingEngine,
+}
+
+#[pymethods]
+impl PyMappingEngine {
+    #[new]
+    fn new(mapping_path: PathBuf) -> PyResult<Self> {
+        Ok(Self {
+            engine: MappingEngine::new(load_behavior_brain_map(mapping_path)?),
+        })
+    }
+
+    fn behavior_types(&self) -> Vec<String> {
+        self.engine.get_behavior_types()
+    }
+
+    fn brain_regions(&self) -> Vec<String> {
+        let mut regions = self.engine.get_brain_regions();
+        regions.sort();
+        regions
+    }
+
+    fn behaviors_for_region(&self, region: &str) -> Vec<String> {
+        self.engine
+            .get_behaviors_for_region(region)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn behavior_unit(&self, behavior: &str) -> Option<String> {
+        self.engine.get_behavior_unit(behavior).map(str::to_string)
+    }
+
+    fn calculate_impact(&self, input: &PyBehaviorInput) -> PyResult<PyCalculationResult> {
+        Ok(PyCalculationResult {
+            inner: self.engine.calculate_impact(&input.inner)?,
+        })
+    }
+
+    /// 向量化計算：回傳長格式欄位 dict（每個腦區一列），可直接傳給 `pandas.DataFrame`
+    ///
+    /// 未指定 `units` 時使用映射表中的預期單位。
+    #[pyo3(signature = (behavior_types, values, units = None))]
+    fn score_batch<'py>(
+        &self,
+        py: Python<'py>,
+        behavior_types: Vec<String>,
+        values: Vec<f32>,
+        units: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        if behavior_types.len() != values.len() {
+            return Err(PyValueError::new_err(format!(
+                "behavior_types 與 values 長度不一致 ({} != {})",
+                behavior_types.len(),
+                values.len()
+            )));
+        }
+        if let Some(units) = &units {
+            if units.len() != values.len() {
+                return Err(PyValueError::new_err("units 與 values 長度不一致"));
+            }
+        }
+
+        BatchColumns::score(&self.engine, &behavior_types, &values, units.as_deref())?.into_dict(py)
+    }
+}
+
+/// 風險評估引擎
+#[pyclass(name = "RiskAssessment", module = "behavior_brain_viz")]
+pub struct PyRiskAssessment {
+    inner: RiskAssessment,
+}
+
+#[pymethods]
+impl PyRiskAssessment {
+    #[new]
+    #[pyo3(signature = (risk_map_path = None, recommendation_rules_path = None))]
+    fn new(risk_map_path: Option<PathBuf>, recommendation_rules_path: Option<PathBuf>) -> PyResult<Self> {
+        let mut inner = match risk_map_path {
+            Some(path) => RiskAssessment::default().with_risk_map(load_risk_map(path)?),
+            None => RiskAssessment::default(),
+        };
+        if let Some(path) = recommendation_rules_path {
+            inner = inner.with_recommendation_rules(load_recommendation_rules(path)?);
+        }
+        Ok(Self { inner })
+    }
+
+    /// `language` 省略時使用 `set_language` 設定的語言
+    #[pyo3(signature = (result, language = None))]
+    fn generate_risk_report<'py>(
+        &self,
+        py: Python<'py>,
+        result: &PyCalculationResult,
+        language: Option<&str>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let language = language.map_or(Ok(i18n::language()), parse_language)?;
+        report_to_dict(py, &self.inner.generate_risk_report(&result.inner, language), language)
+    }
+}
+
+/// 批次計算的長格式欄位
+#[derive(Default)]
+struct BatchColumns {
+    row: Vec<usize>,
+    behavior_type: Vec<String>,
+    value: Vec<f32>,
+    unit: Vec<String>,
+    region: Vec<String>,
+    impact_score: Vec<f32>,
+    normalized_input: Vec<f32>,
+    weight: Vec<f32>,
+    total_impact: Vec<f32>,
+    risk_level: Vec<String>,
+}
+
+impl BatchColumns {
+    /// 逐列計算並展開成長格式；呼叫端需先確認各輸入長度一致
+    fn score(
+        engine: &MappingEngine,
+        behavior_types: &[String],
+        values: &[f32],
+        units: Option<&[String]>,
+    ) -> crate::Result<Self> {
+        let mut columns = Self::default();
+        let timestamp = Utc::now();
+
+        for (row, (behavior_type, value)) in behavior_types.iter().zip(values).enumerate() {
+            let unit = match units {
+                Some(units) => units[row].clone(),
+                None => engine.get_behavior_unit(behavior_type).unwrap_or_default().to_string(),
+            };
+            let input = BehaviorInput {
+                behavior_type: behavior_type.clone(),
+                value: *value,
+                unit,
+                timestamp,
+            };
+            columns.push(row, &engine.calculate_impact(&input)?);
+        }
+        Ok(columns)
+    }
+
+    fn push(&mut self, row: usize, result: &CalculationResult) {
+        for impact in &result.impact_scores {
+            self.row.push(row);
+            self.behavior_type.push(result.behavior_input.behavior_type.clone());
+            self.value.push(result.behavior_input.value);
+            self.unit.push(result.behavior_input.unit.clone());
+            self.region.push(impact.region.clone());
+            self.impact_score.push(impact.impact_score);
+            self.normalized_input.push(impact.normalized_input);
+            self.weight.push(impact.weight);
+            self.total_impact.push(result.total_impact);
+            self.risk_level.push(format!("{:?}", result.risk_level));
+        }
+    }
+
+    fn into_dict(self, py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("row", self.row)?;
+        dict.set_item("behavior_type", self.behavior_type)?;
+        dict.set_item("value", self.value)?;
+        dict.set_item("unit", self.unit)?;
+        dict.set_item("region", self.region)?;
+        dict.set_item("impact_score", self.impact_score)?;
+        dict.set_item("normalized_input", self.normalized_input)?;
+        dict.set_item("weight", self.weight)?;
+        dict.set_item("total_impact", self.total_impact)?;
+        dict.set_item("risk_level", self.risk_level)?;
+        Ok(dict)
+    }
+}
+
+fn result_to_dict<'py>(py: Python<'py>, result: &CalculationResult) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("behavior_type", &result.behavior_input.behavior_type)?;
+    dict.set_item("value", result.behavior_input.value)?;
+    dict.set_item("unit", &result.behavior_input.unit)?;
+    dict.set_item("timestamp", result.behavior_input.timestamp)?;
+    dict.set_item("total_impact", result.total_impact)?;
+    dict.set_item("risk_level", format!("{:?}", result.risk_level))?;
+
+    let scores = result
+        .impact_scores
+        .iter()
+        .map(|impact| {
+            let item = PyDict::new(py);
+            item.set_item("region", &impact.region)?;
+            item.set_item("impact_score", impact.impact_score)?;
+            item.set_item("normalized_input", impact.normalized_input)?;
+            item.set_item("weight", impact.weight)?;
+            Ok(item)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("impact_scores", scores)?;
+    Ok(dict)
+}
+
+fn report_to_dict<'py>(py: Python<'py>, report: &RiskReport, language: Language) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("risk_level", format!("{:?}", report.risk_level))?;
+    dict.set_item("description", report.risk_level.description_in(language))?;
+    dict.set_item("total_impact", report.total_impact)?;
+    dict.set_item("high_impact_regions", &report.high_impact_regions)?;
+    dict.set_item("affected_functions", &report.affected_functions)?;
+    dict.set_item("potential_diseases", &report.potential_diseases)?;
+    let ranking = report
+        .disease_ranking
+        .iter()
+        .map(|risk| {
+            let item = PyDict::new(py);
+            item.set_item("disease", &risk.disease)?;
+            item.set_item("score", risk.score)?;
+            let regions = risk
+                .contributing_regions
+                .iter()
+                .map(|c| {
+                    let region = PyDict::new(py);
+                    region.set_item("region", &c.region)?;
+                    region.set_item("impact_score", c.impact_score)?;
+                    region.set_item("strength", c.strength)?;
+                    region.set_item("evidence", c.evidence)?;
+                    Ok(region)
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            item.set_item("contributing_regions", regions)?;
+            Ok(item)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("disease_ranking", ranking)?;
+    dict.set_item("recommendations", &report.recommendations)?;
+    dict.set_item("recommendation_rule_ids", &report.recommendation_rule_ids)?;
+    dict.set_item("trend", report.trend.map(|trend| format!("{:?}", trend).to_lowercase()))?;
+    Ok(dict)
+}
+
+fn parse_language(code: &str) -> PyResult<Language> {
+    Language::from_code(code).ok_or_else(|| PyValueError::new_err(format!("不支援的語言「{}」", code)))
+}
+
+/// 設定說明與建議文字的預設語言（"zh-TW"、"zh-CN"、"en"）
+#[pyfunction]
+fn set_language(code: &str) -> PyResult<()> {
+    i18n::set_language(parse_language(code)?);
+    Ok(())
+}
+
+/// Python 模組入口
+#[pymodule]
+fn behavior_brain_viz(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBehaviorInput>()?;
+    m.add_class::<PyCalculationResult>()?;
+    m.add_class::<PyMappingEngine>()?;
+    m.add_class::<PyRiskAssessment>()?;
+    m.add_function(wrap_pyfunction!(set_language, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BehaviorBrainMap, BrainRegionImpact};
+
+    fn engine() -> MappingEngine {
+        let region = |region: &str, weight: f32| BrainRegionImpact {
+            region: region.to_string(),
+            weight,
+            description: None,
+            source: None,
+        };
+        MappingEngine::new(vec![
+            BehaviorBrainMap {
+                behavior: "自然步行".to_string(),
+                unit: Some("公尺/秒".to_string()),
+                brain_regions: vec![region("運動皮質", 0.8), region("小腦", 0.4)],
+                normalization_params: None,
+            },
+            BehaviorBrainMap {
+                behavior: "反應時間".to_string(),
+                unit: Some("毫秒".to_string()),
+                brain_regions: vec![region("前額葉", 0.6)],
+                normalization_params: None,
+            },
+        ])
+    }
+
+    #[test]
+    fn test_score_batch_columns() {
+        let engine = engine();
+        let behavior_types = ["自然步行", "反應時間", "自然步行"].map(String::from);
+        let columns = BatchColumns::score(&engine, &behavior_types, &[0.9, 350.0, 1.5], None).unwrap();
+
+        // 每個行為 × 腦區一列，所有欄位等長
+        assert_eq!(columns.row, [0, 0, 1, 2, 2]);
+        for len in [
+            columns.behavior_type.len(),
+            columns.value.len(),
+            columns.unit.len(),
+            columns.region.len(),
+            columns.impact_score.len(),
+            columns.normalized_input.len(),
+            columns.weight.len(),
+            columns.total_impact.len(),
+            columns.risk_level.len(),
+        ] {
+            assert_eq!(len, 5);
+        }
+        assert_eq!(columns.behavior_type, ["自然步行", "自然步行", "反應時間", "自然步行", "自然步行"]);
+        assert_eq!(columns.region, ["運動皮質", "小腦", "前額葉", "運動皮質", "小腦"]);
+        assert_eq!(columns.value, [0.9, 0.9, 350.0, 1.5, 1.5]);
+        assert_eq!(columns.weight, [0.8, 0.4, 0.6, 0.8, 0.4]);
+        // 未指定 units 時沿用映射表的單位
+        assert_eq!(columns.unit, ["公尺/秒", "公尺/秒", "毫秒", "公尺/秒", "公尺/秒"]);
+
+        // 同一輸入列的總分與風險等級在展開後保持一致
+        let single = engine
+            .calculate_impact(&BehaviorInput {
+                behavior_type: "自然步行".to_string(),
+                value: 1.5,
+                unit: "公尺/秒".to_string(),
+                timestamp: Utc::now(),
+            })
+            .unwrap();
+        assert_eq!(columns.total_impact[3], single.total_impact);
+        assert_eq!(columns.total_impact[4], single.total_impact);
+        assert_eq!(columns.impact_score[3], single.impact_scores[0].impact_score);
+        assert_eq!(columns.risk_level[4], format!("{:?}", single.risk_level));
+    }
+
+    #[test]
+    fn test_score_batch_errors() {
+        let engine = engine();
+        let unknown = ["自然步行", "不存在"].map(String::from);
+        assert!(BatchColumns::score(&engine, &unknown, &[0.9, 1.0], None).is_err());
+
+        let walking = ["自然步行"].map(String::from);
+        let wrong_unit = ["秒"].map(String::from);
+        assert!(BatchColumns::score(&engine, &walking, &[0.9], Some(&wrong_unit)).is_err());
+    }
+}
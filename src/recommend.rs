@@ -0,0 +1,282 @@
+//! 建議規則引擎
+//!
+//! 建議由資料檔中的規則產生，每條規則可依風險等級、高影響腦區、受影響功能、
+//! 行為類型、受試者年齡與性別，以及與上次測量相比的趨勢篩選。
+//! 報告會記錄採用的規則 ID，以便稽核建議的來源。
+
+use crate::error::{read_json, Result};
+use crate::i18n::Language;
+use crate::model::{CalculationResult, RiskLevel, SubjectInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 內建規則（與 `data/recommendation_rules.json` 相同）
+const BUILTIN_RULES: &str = include_str!("../data/recommendation_rules.json");
+
+/// 總影響分數變化小於此值時視為持平
+pub const TREND_TOLERANCE: f32 = 0.05;
+
+/// 與上次同一行為測量相比的趨勢
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trend {
+    Improving,
+    Stable,
+    Worsening,
+}
+
+impl Trend {
+    /// 找出歷史中同一行為、時間早於 `result` 的最近一筆，比較總影響分數
+    pub fn from_history(result: &CalculationResult, history: &[CalculationResult]) -> Option<Self> {
+        let input = &result.behavior_input;
+        let previous = history
+            .iter()
+            .filter(|past| {
+                past.behavior_input.behavior_type == input.behavior_type
+                    && past.behavior_input.timestamp < input.timestamp
+            })
+            .max_by_key(|past| past.behavior_input.timestamp)?;
+
+        let delta = result.total_impact - previous.total_impact;
+        Some(if delta > TREND_TOLERANCE {
+            Trend::Worsening
+        } else if delta < -TREND_TOLERANCE {
+            Trend::Improving
+        } else {
+            Trend::Stable
+        })
+    }
+}
+
+/// 規則條件；各欄位皆為選填，清單內任一項符合即可，不同欄位之間須全部符合
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleConditions {
+    pub risk_levels: Vec<RiskLevel>,
+    pub regions: Vec<String>,
+    pub functions: Vec<String>,
+    pub behaviors: Vec<String>,
+    pub sexes: Vec<String>,
+    pub min_age: Option<u32>,
+    pub max_age: Option<u32>,
+    pub trend: Option<Trend>,
+}
+
+/// 單一建議規則
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationRule {
+    pub id: String,
+    /// 繁體中文建議文字，也是缺少翻譯時的預設
+    pub text: String,
+    /// 其他語言的建議文字，鍵為語言代碼（如 "en"、"zh-CN"）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub translations: HashMap<String, String>,
+    /// 數值越大越先列出；相同時依檔案順序
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub when: RuleConditions,
+}
+
+/// 規則比對所需的資訊
+#[derive(Debug, Clone)]
+pub struct RuleContext<'a> {
+    pub risk_level: &'a RiskLevel,
+    pub regions: &'a [String],
+    pub functions: &'a [String],
+    pub behavior: &'a str,
+    pub subject: Option<&'a SubjectInfo>,
+    pub trend: Option<Trend>,
+}
+
+/// 採用的建議與其規則 ID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub rule_id: String,
+    pub text: String,
+}
+
+impl RecommendationRule {
+    /// 指定語言的建議文字
+    pub fn text_in(&self, language: Language) -> &str {
+        self.translations.get(language.code()).unwrap_or(&self.text)
+    }
+
+    pub fn matches(&self, context: &RuleContext) -> bool {
+        let when = &self.when;
+        let any = |wanted: &[String], actual: &[String]| wanted.is_empty() || wanted.iter().any(|w| actual.contains(w));
+        let age = context.subject.and_then(|subject| subject.age);
+        let sex = context.subject.and_then(|subject| subject.sex.as_deref());
+
+        (when.risk_levels.is_empty() || when.risk_levels.contains(context.risk_level))
+            && any(&when.regions, context.regions)
+            && any(&when.functions, context.functions)
+            && (when.behaviors.is_empty() || when.behaviors.iter().any(|b| b == context.behavior))
+            && (when.sexes.is_empty() || sex.is_some_and(|sex| when.sexes.iter().any(|s| s == sex)))
+            && when.min_age.is_none_or(|min| age.is_some_and(|age| age >= min))
+            && when.max_age.is_none_or(|max| age.is_some_and(|age| age <= max))
+            && when.trend.is_none_or(|trend| context.trend == Some(trend))
+    }
+}
+
+/// 建議規則引擎
+#[derive(Debug, Clone)]
+pub struct RecommendationEngine {
+    rules: Vec<RecommendationRule>,
+}
+
+impl Default for RecommendationEngine {
+    fn default() -> Self {
+        Self::new(serde_json::from_str(BUILTIN_RULES).expect("內建建議規則格式錯誤"))
+    }
+}
+
+impl RecommendationEngine {
+    pub fn new(rules: Vec<RecommendationRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn rules(&self) -> &[RecommendationRule] {
+        &self.rules
+    }
+
+    /// 依優先順序列出符合的建議（`language` 語言）；文字相同的建議只保留第一條
+    pub fn recommend(&self, context: &RuleContext, language: Language) -> Vec<Recommendation> {
+        let mut matched: Vec<&RecommendationRule> = self.rules.iter().filter(|rule| rule.matches(context)).collect();
+        matched.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+
+        let mut recommendations: Vec<Recommendation> = Vec::new();
+        for rule in matched {
+            let text = rule.text_in(language);
+            if recommendations.iter().all(|r| r.text != text) {
+                recommendations.push(Recommendation {
+                    rule_id: rule.id.clone(),
+                    text: text.to_string(),
+                });
+            }
+        }
+        recommendations
+    }
+}
+
+/// 從 JSON 文件載入建議規則
+pub fn load_recommendation_rules(path: impl AsRef<Path>) -> Result<Vec<RecommendationRule>> {
+    read_json(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::BehaviorInput;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn context<'a>(risk_level: &'a RiskLevel, regions: &'a [String], subject: Option<&'a SubjectInfo>) -> RuleContext<'a> {
+        RuleContext {
+            risk_level,
+            regions,
+            functions: &[],
+            behavior: "自然步行",
+            subject,
+            trend: None,
+        }
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let engine = RecommendationEngine::default();
+        let mut ids: Vec<&str> = engine.rules().iter().map(|rule| rule.id.as_str()).collect();
+        let total = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), total, "規則 ID 必須唯一");
+
+        let file = load_recommendation_rules(format!("{}/data/recommendation_rules.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+        assert_eq!(file.len(), total);
+
+        for rule in engine.rules() {
+            for language in [Language::ZhCn, Language::En] {
+                assert!(rule.translations.contains_key(language.code()), "規則 {} 缺少 {} 翻譯", rule.id, language);
+            }
+        }
+        let consult = engine.rules().iter().find(|rule| rule.id == "level.high.consult").unwrap();
+        assert_eq!(consult.text_in(Language::ZhTw), consult.text);
+        assert_ne!(consult.text_in(Language::En), consult.text);
+    }
+
+    #[test]
+    fn test_region_and_priority() {
+        let engine = RecommendationEngine::default();
+        let regions = vec!["小腦".to_string()];
+        let recommendations = engine.recommend(&context(&RiskLevel::High, &regions, None), Language::ZhTw);
+
+        assert_eq!(recommendations[0].rule_id, "level.high.consult");
+        assert!(recommendations.iter().any(|r| r.rule_id == "region.cerebellum.balance"));
+        assert!(recommendations.iter().any(|r| r.rule_id == "behavior.gait.retest"));
+        assert!(!recommendations.iter().any(|r| r.rule_id == "region.hippocampus.memory"));
+        // 低風險時不給腦區特定建議
+        let low = engine.recommend(&context(&RiskLevel::Low, &regions, None), Language::ZhTw);
+        assert!(low.iter().all(|r| r.rule_id.starts_with("level.low")));
+
+        // 建議文字依呼叫端指定的語言，不受全域顯示語言影響
+        let consult = engine.rules().iter().find(|rule| rule.id == "level.high.consult").unwrap();
+        let english = engine.recommend(&context(&RiskLevel::High, &regions, None), Language::En);
+        assert_eq!(recommendations[0].text, consult.text);
+        assert_eq!(english[0].text, consult.text_in(Language::En));
+    }
+
+    #[test]
+    fn test_demographic_conditions() {
+        let engine = RecommendationEngine::default();
+        let older = SubjectInfo {
+            subject_id: "S1".to_string(),
+            age: Some(72),
+            ..Default::default()
+        };
+        let unknown_age = SubjectInfo {
+            subject_id: "S2".to_string(),
+            ..Default::default()
+        };
+
+        let has = |subject: Option<&SubjectInfo>| {
+            engine
+                .recommend(&context(&RiskLevel::Medium, &[], subject), Language::ZhTw)
+                .iter()
+                .any(|r| r.rule_id == "demographic.older.cga")
+        };
+        assert!(has(Some(&older)));
+        assert!(!has(Some(&unknown_age)));
+        assert!(!has(None));
+    }
+
+    #[test]
+    fn test_trend_from_history() {
+        let at = |days: i64, total_impact: f32| CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: "自然步行".to_string(),
+                value: 1.0,
+                unit: "公尺/秒".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(days),
+            },
+            impact_scores: Vec::new(),
+            total_impact,
+            risk_level: RiskLevel::from_score(total_impact),
+        };
+        let history = vec![at(0, 0.2), at(7, 0.4), at(30, 0.9)];
+
+        assert_eq!(Trend::from_history(&at(14, 0.6), &history), Some(Trend::Worsening));
+        assert_eq!(Trend::from_history(&at(14, 0.2), &history), Some(Trend::Improving));
+        assert_eq!(Trend::from_history(&at(14, 0.42), &history), Some(Trend::Stable));
+        assert_eq!(Trend::from_history(&at(-1, 0.5), &history), None);
+
+        let engine = RecommendationEngine::default();
+        let recommendations = engine.recommend(
+            &RuleContext {
+                trend: Some(Trend::Worsening),
+                ..context(&RiskLevel::Low, &[], None)
+            },
+            Language::ZhTw,
+        );
+        assert_eq!(recommendations[0].rule_id, "trend.worsening.shorten_interval");
+    }
+}
@@ -0,0 +1,492 @@
+//! HTML 報告
+//!
+//! 產生單一 HTML 檔：樣式、腦圖 SVG 與趨勢圖全部內嵌，不引用任何外部 CSS/JS，
+//! 可直接以電子郵件附件分享並離線開啟。
+
+use super::{medical_disclaimer, report_file_name, ReportData};
+use crate::brain_map::{colorize_svg, escape_xml as escape, hex_color, impact_color};
+use crate::error::{BrainVizError, Result};
+use crate::i18n::{name_in, tr_in, trf_in, Language};
+use crate::model::{CalculationResult, RiskLevel};
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const STYLE: &str = "
+body { font-family: 'Microsoft JhengHei', 'PingFang TC', 'Noto Sans CJK TC', sans-serif; margin: 0; background: #f5f5f5; color: #222; }
+main { max-width: 860px; margin: 0 auto; padding: 24px; background: #fff; }
+h1 { font-size: 24px; margin-bottom: 4px; }
+h2 { font-size: 18px; border-bottom: 2px solid #2196f3; padding-bottom: 4px; margin-top: 28px; }
+.meta { color: #666; font-size: 13px; }
+table { border-collapse: collapse; width: 100%; font-size: 14px; }
+th, td { border: 1px solid #ddd; padding: 6px 8px; text-align: left; }
+th { background: #eee; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { display: inline-block; height: 10px; border-radius: 2px; vertical-align: middle; }
+.level { display: inline-block; padding: 2px 8px; border-radius: 10px; color: #fff; font-size: 13px; }
+.brain svg { width: 100%; max-width: 520px; height: auto; display: block; margin: 0 auto; }
+.legend span { margin-right: 14px; font-size: 13px; }
+.legend i { display: inline-block; width: 12px; height: 12px; border-radius: 6px; margin-right: 4px; vertical-align: middle; }
+.chart { margin: 12px 0; }
+.rule { color: #999; font-size: 11px; margin-left: 6px; }
+.disclaimer { margin-top: 32px; padding: 12px; background: #fff8e1; border-left: 4px solid #ffa000; font-size: 13px; }
+";
+
+/// 產生 HTML 報告（`data.language` 語言）；`history` 為同一受試者的歷次計算結果（可為空），用於趨勢圖
+pub fn render_html_report(data: &ReportData, history: &[CalculationResult]) -> String {
+    let language = data.language;
+    let result = data.result;
+    let report = data.report;
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n",
+        language.html_lang(),
+        tr_in(language, "report.title"),
+        STYLE
+    );
+    let _ = writeln!(html, "<h1>🧠 {}</h1>", tr_in(language, "report.title"));
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">{}</p>",
+        trf_in(language, "report.generated_at", &[&chrono::Local::now().format("%Y-%m-%d %H:%M")])
+    );
+
+    if let Some(subject) = data.subject {
+        let _ = writeln!(html, "<h2>{}</h2>\n<table>", tr_in(language, "report.subject"));
+        let mut row = |label: &str, value: &str| {
+            let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", label, escape(value));
+        };
+        row(tr_in(language, "report.subject.id"), &subject.subject_id);
+        if let Some(name) = &subject.name {
+            row(tr_in(language, "report.subject.name"), name);
+        }
+        if let Some(age) = subject.age {
+            row(tr_in(language, "report.subject.age"), &age.to_string());
+        }
+        if let Some(sex) = &subject.sex {
+            row(tr_in(language, "report.subject.sex"), name_in(language, sex));
+        }
+        if let Some(notes) = &subject.notes {
+            row(tr_in(language, "report.subject.notes"), notes);
+        }
+        let _ = writeln!(html, "</table>");
+    }
+
+    let input = &result.behavior_input;
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><td>{}</td></tr>\n\
+         <tr><th>{}</th><td>{:.2} {}</td></tr>\n<tr><th>{}</th><td>{}</td></tr>\n</table>",
+        tr_in(language, "report.input"),
+        tr_in(language, "report.input.behavior"),
+        escape(name_in(language, &input.behavior_type)),
+        tr_in(language, "report.input.value"),
+        input.value,
+        escape(name_in(language, &input.unit)),
+        tr_in(language, "report.input.measured_at"),
+        input.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+
+    let placed = data.layout().place(&result.impact_scores);
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<div class=\"brain\">{}</div>\n{}",
+        tr_in(language, "report.brain_map"),
+        strip_xml_prolog(&colorize_svg(data.atlas_svg, &placed)),
+        legend(language)
+    );
+
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>",
+        tr_in(language, "report.region_scores"),
+        tr_in(language, "report.col.region"),
+        tr_in(language, "report.col.weight"),
+        tr_in(language, "report.col.normalized"),
+        tr_in(language, "report.col.impact"),
+        tr_in(language, "report.col.level")
+    );
+    for impact in &result.impact_scores {
+        let color = hex_color(impact_color(impact.impact_score));
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.3}</td>\
+             <td class=\"num\">{:.3} <span class=\"bar\" style=\"width:{:.0}px;background:{}\"></span></td><td>{}</td></tr>",
+            escape(name_in(language, &impact.region)),
+            impact.weight,
+            impact.normalized_input,
+            impact.impact_score,
+            impact.impact_score * 80.0,
+            color,
+            level_badge(&RiskLevel::from_score(impact.impact_score), language)
+        );
+    }
+    let _ = writeln!(html, "</table>");
+
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<p>{}</p>\n<p>{}</p>",
+        tr_in(language, "report.risk"),
+        trf_in(
+            language,
+            "report.overall_risk",
+            &[&level_badge(&report.risk_level, language), &format!("{:.3}", report.total_impact)]
+        ),
+        escape(report.risk_level.description_in(language))
+    );
+    for (title, items) in [
+        (tr_in(language, "report.high_impact_regions"), &report.high_impact_regions),
+        (tr_in(language, "report.affected_functions"), &report.affected_functions),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        let _ = writeln!(html, "<h3>{}</h3>\n<ul>", title);
+        for item in items {
+            let _ = writeln!(html, "<li>{}</li>", escape(name_in(language, item)));
+        }
+        let _ = writeln!(html, "</ul>");
+    }
+    if !report.recommendations.is_empty() {
+        let _ = writeln!(html, "<h3>{}</h3>\n<ul>", tr_in(language, "report.recommendations"));
+        for (index, recommendation) in report.recommendations.iter().enumerate() {
+            let rule = report.recommendation_rule_ids.get(index).map(String::as_str).unwrap_or_default();
+            let _ = writeln!(html, "<li>{}<span class=\"rule\">{}</span></li>", escape(recommendation), escape(rule));
+        }
+        let _ = writeln!(html, "</ul>");
+    }
+    if !report.disease_ranking.is_empty() {
+        let _ = writeln!(
+            html,
+            "<h3>{}</h3>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th></tr>",
+            tr_in(language, "report.diseases_ranked"),
+            tr_in(language, "report.col.disease"),
+            tr_in(language, "report.col.score"),
+            tr_in(language, "report.col.supporting")
+        );
+        for risk in &report.disease_ranking {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{:.3} <span class=\"bar\" style=\"width:{:.0}px;background:{}\"></span></td><td>{}</td></tr>",
+                escape(name_in(language, &risk.disease)),
+                risk.score,
+                risk.score * 80.0,
+                hex_color(impact_color(risk.score)),
+                escape(&risk.contributors_label(language))
+            );
+        }
+        let _ = writeln!(html, "</table>");
+    }
+
+    if !history.is_empty() {
+        html.push_str(&history_section(history, language));
+    }
+
+    let _ = writeln!(
+        html,
+        "<div class=\"disclaimer\"><strong>⚠️ {}</strong><br>{}</div>\n</main>\n</body>\n</html>",
+        tr_in(language, "report.disclaimer.title"),
+        escape(medical_disclaimer(language))
+    );
+    html
+}
+
+/// 產生 HTML 報告並寫入檔案
+pub fn write_html_report(data: &ReportData, history: &[CalculationResult], out: &Path) -> Result<()> {
+    std::fs::write(out, render_html_report(data, history)).map_err(|e| BrainVizError::io(out, e))
+}
+
+/// 以預設檔名將 HTML 報告寫入 `output_dir`（目錄不存在時建立），回傳檔案路徑
+pub fn export_html_report(data: &ReportData, history: &[CalculationResult], output_dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(output_dir).map_err(|e| BrainVizError::io(output_dir, e))?;
+    let out = output_dir.join(report_file_name(data.subject, data.result, "html"));
+    write_html_report(data, history, &out)?;
+    Ok(out)
+}
+
+/// 歷史記錄：總影響分數趨勢、各行為數值趨勢與明細表
+fn history_section(history: &[CalculationResult], language: Language) -> String {
+    let mut sorted: Vec<&CalculationResult> = history.iter().collect();
+    sorted.sort_by_key(|r| r.behavior_input.timestamp);
+
+    let mut html = format!("<h2>{}</h2>\n", tr_in(language, "report.history"));
+    let totals: Vec<(DateTime<Utc>, f32)> = sorted
+        .iter()
+        .map(|r| (r.behavior_input.timestamp, r.total_impact))
+        .collect();
+    html.push_str(&trend_chart(tr_in(language, "report.history.total"), &totals, Some((0.0, 1.0)), language));
+
+    // 依首次出現順序列出各行為
+    let mut behaviors: Vec<&str> = Vec::new();
+    for r in &sorted {
+        if !behaviors.contains(&r.behavior_input.behavior_type.as_str()) {
+            behaviors.push(&r.behavior_input.behavior_type);
+        }
+    }
+    for behavior in behaviors {
+        let records: Vec<&&CalculationResult> = sorted
+            .iter()
+            .filter(|r| r.behavior_input.behavior_type == behavior)
+            .collect();
+        let points: Vec<(DateTime<Utc>, f32)> = records
+            .iter()
+            .map(|r| (r.behavior_input.timestamp, r.behavior_input.value))
+            .collect();
+        let title = format!(
+            "{} ({})",
+            name_in(language, behavior),
+            name_in(language, &records[0].behavior_input.unit)
+        );
+        html.push_str(&trend_chart(&title, &points, None, language));
+    }
+
+    let _ = writeln!(
+        html,
+        "<h3>{}</h3>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>",
+        tr_in(language, "report.history.records"),
+        tr_in(language, "report.col.time"),
+        tr_in(language, "report.input.behavior"),
+        tr_in(language, "report.input.value"),
+        tr_in(language, "report.history.total"),
+        tr_in(language, "report.col.level")
+    );
+    for r in &sorted {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td class=\"num\">{:.2} {}</td><td class=\"num\">{:.3}</td><td>{}</td></tr>",
+            r.behavior_input.timestamp.format("%Y-%m-%d %H:%M"),
+            escape(name_in(language, &r.behavior_input.behavior_type)),
+            r.behavior_input.value,
+            escape(name_in(language, &r.behavior_input.unit)),
+            r.total_impact,
+            level_badge(&r.risk_level, language)
+        );
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+/// 內嵌 SVG 折線圖；`range` 未指定時依資料自動決定縱軸範圍
+fn trend_chart(title: &str, points: &[(DateTime<Utc>, f32)], range: Option<(f32, f32)>, language: Language) -> String {
+    const WIDTH: f32 = 640.0;
+    const HEIGHT: f32 = 200.0;
+    const LEFT: f32 = 50.0;
+    const RIGHT: f32 = 20.0;
+    const TOP: f32 = 30.0;
+    const BOTTOM: f32 = 30.0;
+
+    let mut svg = format!(
+        "<div class=\"chart\"><svg viewBox=\"0 0 {w} {h}\" width=\"100%\" xmlns=\"http://www.w3.org/2000/svg\" role=\"img\">\n\
+         <text x=\"{LEFT}\" y=\"18\" font-size=\"14\" font-weight=\"bold\">{title}</text>\n",
+        w = WIDTH,
+        h = HEIGHT,
+        title = escape(title)
+    );
+    if points.len() < 2 {
+        let _ = write!(
+            svg,
+            "<text x=\"{LEFT}\" y=\"{}\" font-size=\"12\" fill=\"#888\">{}</text>\n</svg></div>\n",
+            HEIGHT / 2.0,
+            tr_in(language, "report.chart.insufficient")
+        );
+        return svg;
+    }
+
+    let (min, max) = range.unwrap_or_else(|| {
+        let min = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+        let max = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+        let pad = ((max - min) * 0.1).max(f32::EPSILON.max(max.abs() * 0.05));
+        (min - pad, max + pad)
+    });
+    let first = points[0].0.timestamp() as f32;
+    let last = points[points.len() - 1].0.timestamp() as f32;
+    let plot_w = WIDTH - LEFT - RIGHT;
+    let plot_h = HEIGHT - TOP - BOTTOM;
+    let x = |i: usize, t: DateTime<Utc>| {
+        if last > first {
+            LEFT + (t.timestamp() as f32 - first) / (last - first) * plot_w
+        } else {
+            LEFT + i as f32 / (points.len() - 1) as f32 * plot_w
+        }
+    };
+    let y = |v: f32| TOP + (1.0 - (v - min) / (max - min)) * plot_h;
+
+    // 固定 0-1 範圍時以風險等級區間為背景
+    if range == Some((0.0, 1.0)) {
+        for (low, high, level) in [
+            (0.0, 0.3, RiskLevel::Low),
+            (0.3, 0.6, RiskLevel::Medium),
+            (0.6, 0.8, RiskLevel::High),
+            (0.8, 1.0, RiskLevel::Critical),
+        ] {
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{LEFT}\" y=\"{:.1}\" width=\"{plot_w}\" height=\"{:.1}\" fill=\"{}\" opacity=\"0.12\"/>",
+                y(high),
+                y(low) - y(high),
+                hex_color(level.color())
+            );
+        }
+    }
+
+    let _ = writeln!(
+        svg,
+        "<line x1=\"{LEFT}\" y1=\"{TOP}\" x2=\"{LEFT}\" y2=\"{b}\" stroke=\"#999\"/>\
+         <line x1=\"{LEFT}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"#999\"/>\n\
+         <text x=\"{lx}\" y=\"{ty}\" font-size=\"11\" text-anchor=\"end\">{max:.2}</text>\
+         <text x=\"{lx}\" y=\"{b}\" font-size=\"11\" text-anchor=\"end\">{min:.2}</text>\n\
+         <text x=\"{LEFT}\" y=\"{dy}\" font-size=\"11\">{d0}</text>\
+         <text x=\"{r}\" y=\"{dy}\" font-size=\"11\" text-anchor=\"end\">{d1}</text>",
+        b = TOP + plot_h,
+        r = LEFT + plot_w,
+        lx = LEFT - 4.0,
+        ty = TOP + 4.0,
+        dy = HEIGHT - 8.0,
+        d0 = points[0].0.format("%Y-%m-%d"),
+        d1 = points[points.len() - 1].0.format("%Y-%m-%d"),
+    );
+
+    let polyline: Vec<String> = points
+        .iter()
+        .enumerate()
+        .map(|(i, (t, v))| format!("{:.1},{:.1}", x(i, *t), y(*v)))
+        .collect();
+    let _ = writeln!(
+        svg,
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"#2196f3\" stroke-width=\"2\"/>",
+        polyline.join(" ")
+    );
+    for (i, (t, v)) in points.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3.5\" fill=\"#2196f3\"><title>{} — {:.3}</title></circle>",
+            x(i, *t),
+            y(*v),
+            t.format("%Y-%m-%d %H:%M"),
+            v
+        );
+    }
+    svg.push_str("</svg></div>\n");
+    svg
+}
+
+/// 風險等級色塊標籤
+fn level_badge(level: &RiskLevel, language: Language) -> String {
+    let color = level.color();
+    // 淺色背景改用深色字
+    let text = if color.iter().map(|&c| c as u32).sum::<u32>() > 500 { "#222" } else { "#fff" };
+    format!(
+        "<span class=\"level\" style=\"background:{};color:{}\">{}</span>",
+        hex_color(color),
+        text,
+        level.label_in(language)
+    )
+}
+
+fn legend(language: Language) -> String {
+    let items: String = [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High, RiskLevel::Critical]
+        .iter()
+        .map(|level| {
+            format!(
+                "<span><i style=\"background:{}\"></i>{}</span>",
+                hex_color(level.color()),
+                level.label_in(language)
+            )
+        })
+        .collect();
+    format!("<p class=\"legend\">{}</p>", items)
+}
+
+/// 移除 `<?xml ...?>` 宣告，使 SVG 可直接內嵌於 HTML
+fn strip_xml_prolog(svg: &str) -> &str {
+    let trimmed = svg.trim_start();
+    if trimmed.starts_with("<?xml") {
+        trimmed.find("?>").map_or(trimmed, |end| trimmed[end + 2..].trim_start())
+    } else {
+        trimmed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{load_behavior_brain_map, MappingEngine};
+    use crate::model::{BehaviorInput, SubjectInfo};
+    use crate::risk::RiskAssessment;
+    use chrono::{Duration, TimeZone};
+
+    fn score(engine: &MappingEngine, value: f32, days: i64) -> CalculationResult {
+        engine
+            .calculate_impact(&BehaviorInput {
+                behavior_type: "自然步行".to_string(),
+                value,
+                unit: "公尺/秒".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap() + Duration::days(days),
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_html_report() {
+        let base = env!("CARGO_MANIFEST_DIR");
+        let engine = MappingEngine::new(load_behavior_brain_map(format!("{}/data/behavior_brain_map.json", base)).unwrap());
+        let atlas_svg = std::fs::read_to_string(format!("{}/assets/brain.svg", base)).unwrap();
+        let history = vec![score(&engine, 1.2, 0), score(&engine, 0.9, 7), score(&engine, 0.6, 14)];
+        let result = history.last().unwrap();
+        let report = RiskAssessment::default().generate_risk_report(result, Language::ZhTw);
+        let subject = SubjectInfo {
+            subject_id: "<S001>".to_string(),
+            ..Default::default()
+        };
+        let data = ReportData {
+            subject: Some(&subject),
+            result,
+            report: &report,
+            atlas_svg: &atlas_svg,
+            language: Language::ZhTw,
+        };
+
+        let html = render_html_report(&data, &history);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;S001&gt;"));
+        assert!(!html.contains("<?xml"));
+        assert!(html.contains("data-impact="));
+        assert!(html.contains("<polyline"));
+        assert!(html.contains(&escape(medical_disclaimer(Language::ZhTw))));
+        assert!(html.contains("<html lang=\"zh-Hant\">"));
+        if let Some(top) = report.disease_ranking.first() {
+            assert!(html.contains("依證據排序"));
+            assert!(html.contains(&escape(&top.disease)));
+        }
+        // 完全離線：不引用外部資源
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<link"));
+        assert!(!html.contains("src=\"http"));
+
+        // 語言由 ReportData 指定，不受全域顯示語言影響
+        let english = render_html_report(&ReportData { language: Language::En, ..data }, &history);
+        assert!(english.contains("<html lang=\"en\">"));
+        assert!(english.contains(&escape(medical_disclaimer(Language::En))));
+        assert!(english.contains(RiskLevel::Critical.label_in(Language::En)));
+    }
+
+    #[test]
+    fn test_trend_chart_needs_two_points() {
+        let t = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert!(trend_chart("x", &[(t, 1.0)], None, Language::ZhTw).contains("資料點不足"));
+        let chart = trend_chart("x", &[(t, 1.0), (t + Duration::days(1), 1.0)], None, Language::ZhTw);
+        assert_eq!(chart.matches("<circle").count(), 2);
+        assert!(!chart.contains("NaN"));
+    }
+
+    #[test]
+    fn test_strip_xml_prolog() {
+        assert_eq!(strip_xml_prolog("<?xml version=\"1.0\"?>\n<svg/>"), "<svg/>");
+        assert_eq!(strip_xml_prolog("<svg/>"), "<svg/>");
+    }
+}
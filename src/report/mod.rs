@@ -0,0 +1,81 @@
+//! 評估報告匯出
+
+use crate::brain_map::BrainMapLayout;
+use crate::i18n::{self, Language};
+use crate::model::{CalculationResult, SubjectInfo};
+use crate::risk::RiskReport;
+
+pub mod html;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+
+/// 報告中的醫療免責聲明
+pub fn medical_disclaimer(language: Language) -> &'static str {
+    i18n::tr_in(language, "report.disclaimer")
+}
+
+/// 產生一份報告所需的資料
+#[derive(Debug, Clone, Copy)]
+pub struct ReportData<'a> {
+    pub subject: Option<&'a SubjectInfo>,
+    pub result: &'a CalculationResult,
+    pub report: &'a RiskReport,
+    pub atlas_svg: &'a str, // 腦圖 SVG 原文
+    /// 報告文字與名稱使用的語言
+    pub language: Language,
+}
+
+impl ReportData<'_> {
+    /// 從腦圖 SVG 解析版面
+    pub fn layout(&self) -> BrainMapLayout {
+        BrainMapLayout::from_svg(self.atlas_svg)
+    }
+}
+
+/// 報告預設檔名，例如 `S001_20240101_120000.pdf`
+pub fn report_file_name(subject: Option<&SubjectInfo>, result: &CalculationResult, extension: &str) -> String {
+    format!(
+        "{}_{}.{}",
+        file_name_prefix(subject),
+        result.behavior_input.timestamp.format("%Y%m%d_%H%M%S"),
+        extension
+    )
+}
+
+/// 以受試者編號作為檔名前綴（僅保留英數字、`-` 與 `_`），無編號時為 `report`
+pub(crate) fn file_name_prefix(subject: Option<&SubjectInfo>) -> String {
+    subject
+        .map(|s| s.subject_id.trim())
+        .filter(|id| !id.is_empty())
+        .map(|id| id.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_"))
+        .unwrap_or_else(|| "report".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BehaviorInput, RiskLevel};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_report_file_name() {
+        let result = CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: "自然步行".to_string(),
+                value: 1.0,
+                unit: "公尺/秒".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap(),
+            },
+            impact_scores: vec![],
+            total_impact: 0.0,
+            risk_level: RiskLevel::Low,
+        };
+        let subject = SubjectInfo {
+            subject_id: "S 001/a".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(report_file_name(Some(&subject), &result, "pdf"), "S_001_a_20240101_120000.pdf");
+        assert_eq!(report_file_name(None, &result, "html"), "report_20240101_120000.html");
+    }
+}
@@ -0,0 +1,542 @@
+//! PDF 報告（`pdf` 功能）
+//!
+//! 報告為 A4 版面，依序包含受試者資料、輸入數值、腦圖、各腦區分數表、
+//! 風險說明與免責聲明。PDF 內嵌呼叫端提供的字型，需使用含中文字符的 TTF/TTC。
+
+use super::{medical_disclaimer, report_file_name, ReportData};
+use crate::brain_map::{impact_color, parse_path, PathCommand};
+use crate::error::{BrainVizError, Result};
+use crate::fonts::load_font_bytes;
+use crate::i18n::{name_in, tr_in, trf_in, Language};
+use crate::model::RiskLevel;
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::{
+    Color, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Polygon, Rgb,
+};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const PT_TO_MM: f32 = 0.3528;
+
+/// 產生 PDF 報告並寫入檔案
+pub fn write_pdf_report(data: &ReportData, font_path: &Path, out: &Path) -> Result<()> {
+    let font = load_font_bytes(font_path)?;
+    let bytes = render_pdf_report(data, &font)?;
+    std::fs::write(out, bytes).map_err(|e| BrainVizError::io(out, e))
+}
+
+/// 以預設檔名將 PDF 報告寫入 `output_dir`（目錄不存在時建立），回傳檔案路徑
+///
+/// `font` 為單一字型的內容，通常取自 [`crate::fonts::load_cjk_font`]。
+pub fn export_pdf_report(data: &ReportData, font: &[u8], output_dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(output_dir).map_err(|e| BrainVizError::io(output_dir, e))?;
+    let out = output_dir.join(report_file_name(data.subject, data.result, "pdf"));
+    let bytes = render_pdf_report(data, font)?;
+    std::fs::write(&out, bytes).map_err(|e| BrainVizError::io(&out, e))?;
+    Ok(out)
+}
+
+/// 產生 PDF 報告內容（`data.language` 語言）；`font` 為單一字型（TTF/OTF）的檔案內容
+pub fn render_pdf_report(data: &ReportData, font: &[u8]) -> Result<Vec<u8>> {
+    layout_report(data, font)?.finish()
+}
+
+/// 依序排版報告各段落
+fn layout_report(data: &ReportData, font: &[u8]) -> Result<PdfWriter> {
+    let language = data.language;
+    let mut writer = PdfWriter::new(tr_in(language, "report.title"), font)?;
+    let result = data.result;
+    let report = data.report;
+
+    writer.heading(tr_in(language, "report.title"), 18.0);
+    writer.paragraph(
+        &trf_in(language, "report.generated_at", &[&chrono::Local::now().format("%Y-%m-%d %H:%M")]),
+        9.0,
+    );
+    writer.gap(4.0);
+
+    if let Some(subject) = data.subject {
+        writer.heading(tr_in(language, "report.subject"), 13.0);
+        writer.paragraph(&format!("{}: {}", tr_in(language, "report.subject.id"), subject.subject_id), 10.0);
+        if let Some(subject_name) = &subject.name {
+            writer.paragraph(&format!("{}: {}", tr_in(language, "report.subject.name"), subject_name), 10.0);
+        }
+        if let Some(age) = subject.age {
+            writer.paragraph(&format!("{}: {}", tr_in(language, "report.subject.age"), age), 10.0);
+        }
+        if let Some(sex) = &subject.sex {
+            writer.paragraph(&format!("{}: {}", tr_in(language, "report.subject.sex"), name_in(language, sex)), 10.0);
+        }
+        if let Some(notes) = &subject.notes {
+            writer.paragraph(&format!("{}: {}", tr_in(language, "report.subject.notes"), notes), 10.0);
+        }
+        writer.gap(4.0);
+    }
+
+    writer.heading(tr_in(language, "report.input"), 13.0);
+    let input = &result.behavior_input;
+    writer.paragraph(
+        &format!("{}: {}", tr_in(language, "report.input.behavior"), name_in(language, &input.behavior_type)),
+        10.0,
+    );
+    writer.paragraph(
+        &format!("{}: {:.2} {}", tr_in(language, "report.input.value"), input.value, name_in(language, &input.unit)),
+        10.0,
+    );
+    writer.paragraph(
+        &format!(
+            "{}: {}",
+            tr_in(language, "report.input.measured_at"),
+            input.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        ),
+        10.0,
+    );
+    writer.gap(4.0);
+
+    writer.heading(tr_in(language, "report.brain_map"), 13.0);
+    writer.brain_map(data);
+    writer.gap(4.0);
+
+    writer.heading(tr_in(language, "report.region_scores"), 13.0);
+    writer.table(
+        &[
+            tr_in(language, "report.col.region"),
+            tr_in(language, "report.col.weight"),
+            tr_in(language, "report.col.normalized"),
+            tr_in(language, "report.col.impact"),
+            tr_in(language, "report.col.level"),
+        ],
+        &[0.36, 0.12, 0.18, 0.16, 0.18],
+        result
+            .impact_scores
+            .iter()
+            .map(|impact| {
+                vec![
+                    name_in(language, &impact.region).to_string(),
+                    format!("{:.2}", impact.weight),
+                    format!("{:.3}", impact.normalized_input),
+                    format!("{:.3}", impact.impact_score),
+                    RiskLevel::from_score(impact.impact_score).label_in(language).to_string(),
+                ]
+            })
+            .collect(),
+    );
+    writer.gap(4.0);
+
+    writer.heading(tr_in(language, "report.risk"), 13.0);
+    writer.paragraph(
+        &trf_in(
+            language,
+            "report.overall_risk",
+            &[&report.risk_level.label_in(language), &format!("{:.3}", report.total_impact)],
+        ),
+        10.0,
+    );
+    writer.paragraph(report.risk_level.description_in(language), 10.0);
+    writer.list(tr_in(language, "report.high_impact_regions"), &localized(&report.high_impact_regions, language));
+    writer.list(tr_in(language, "report.affected_functions"), &localized(&report.affected_functions, language));
+    writer.list(
+        tr_in(language, "report.recommendations_with_ids"),
+        &report
+            .recommendations
+            .iter()
+            .zip(&report.recommendation_rule_ids)
+            .map(|(recommendation, rule_id)| format!("{} ({})", recommendation, rule_id))
+            .collect::<Vec<_>>(),
+    );
+    writer.list(
+        tr_in(language, "report.diseases_ranked_detail"),
+        &report
+            .disease_ranking
+            .iter()
+            .map(|risk| {
+                format!(
+                    "{} {:.3} ({})",
+                    name_in(language, &risk.disease),
+                    risk.score,
+                    risk.contributors_label(language)
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    writer.gap(6.0);
+
+    writer.heading(tr_in(language, "report.disclaimer.title"), 11.0);
+    writer.paragraph(medical_disclaimer(language), 9.0);
+
+    Ok(writer)
+}
+
+/// 將資料檔名稱清單轉為指定語言的顯示名稱
+fn localized(names: &[String], language: Language) -> Vec<String> {
+    names.iter().map(|canonical| name_in(language, canonical).to_string()).collect()
+}
+
+/// 由上而下排版的簡易 PDF 寫入器，內容超出頁面時自動換頁
+struct PdfWriter {
+    doc: PdfDocumentReference,
+    font: IndirectFontRef,
+    layer: PdfLayerReference,
+    y: f32, // 目前位置與頁面頂端的距離 (mm)
+    /// 已繪製的文字，測試用以檢查字型是否涵蓋所有字元
+    #[cfg(test)]
+    drawn: String,
+}
+
+impl PdfWriter {
+    fn new(title: &str, font: &[u8]) -> Result<Self> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "內容");
+        let font = doc.add_external_font(Cursor::new(font)).map_err(pdf_error)?;
+        let layer = doc.get_page(page).get_layer(layer);
+        Ok(Self {
+            doc,
+            font,
+            layer,
+            y: MARGIN,
+            #[cfg(test)]
+            drawn: String::new(),
+        })
+    }
+
+    fn finish(self) -> Result<Vec<u8>> {
+        self.doc.save_to_bytes().map_err(pdf_error)
+    }
+
+    /// 確保剩餘空間足夠，否則換頁
+    fn ensure_space(&mut self, height: f32) {
+        if self.y + height > PAGE_HEIGHT - MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "內容");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = MARGIN;
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y += height;
+    }
+
+    fn text_at(&mut self, text: &str, size: f32, x: f32, baseline: f32) {
+        #[cfg(test)]
+        self.drawn.push_str(text);
+        self.layer.set_fill_color(rgb([0, 0, 0]));
+        self.layer
+            .use_text(text, size, Mm(x), Mm(PAGE_HEIGHT - baseline), &self.font);
+    }
+
+    fn heading(&mut self, text: &str, size: f32) {
+        let line_height = size * PT_TO_MM * 1.6;
+        self.ensure_space(line_height * 2.0);
+        self.y += line_height;
+        self.text_at(text, size, MARGIN, self.y);
+    }
+
+    /// 自動換行的段落
+    fn paragraph(&mut self, text: &str, size: f32) {
+        self.indented_paragraph(text, size, 0.0);
+    }
+
+    fn indented_paragraph(&mut self, text: &str, size: f32, indent: f32) {
+        let line_height = size * PT_TO_MM * 1.5;
+        for line in wrap_text(text, size, CONTENT_WIDTH - indent) {
+            self.ensure_space(line_height);
+            self.y += line_height;
+            self.text_at(&line, size, MARGIN + indent, self.y);
+        }
+    }
+
+    fn list(&mut self, title: &str, items: &[String]) {
+        if items.is_empty() {
+            return;
+        }
+        self.gap(1.0);
+        self.paragraph(&format!("{}:", title), 10.0);
+        for item in items {
+            self.indented_paragraph(&format!("• {}", item), 10.0, 4.0);
+        }
+    }
+
+    /// 表格；`widths` 為各欄佔內容寬度的比例
+    fn table(&mut self, headers: &[&str], widths: &[f32], rows: Vec<Vec<String>>) {
+        let size = 9.0;
+        let row_height = size * PT_TO_MM * 2.0;
+        let draw_row = |writer: &mut Self, cells: &[&str], shaded: bool| {
+            writer.ensure_space(row_height);
+            if shaded {
+                writer.layer.set_fill_color(rgb([230, 230, 230]));
+                writer.layer.add_polygon(Polygon {
+                    rings: vec![printpdf::utils::calculate_points_for_rect(
+                        Mm(CONTENT_WIDTH),
+                        Mm(row_height),
+                        Mm(MARGIN + CONTENT_WIDTH / 2.0),
+                        Mm(PAGE_HEIGHT - writer.y - row_height / 2.0),
+                    )],
+                    mode: PaintMode::Fill,
+                    winding_order: WindingOrder::NonZero,
+                });
+            }
+            let baseline = writer.y + row_height * 0.7;
+            let mut x = MARGIN + 1.5;
+            for (cell, width) in cells.iter().zip(widths) {
+                let column_width = CONTENT_WIDTH * width;
+                let text = wrap_text(cell, size, column_width - 2.0).into_iter().next().unwrap_or_default();
+                writer.text_at(&text, size, x, baseline);
+                x += column_width;
+            }
+            writer.y += row_height;
+        };
+
+        draw_row(self, headers, true);
+        for row in &rows {
+            let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            draw_row(self, &cells, false);
+        }
+    }
+
+    /// 繪製腦圖輪廓、各腦區影響圓與圖例
+    fn brain_map(&mut self, data: &ReportData) {
+        let layout = &data.layout();
+        let scale = (CONTENT_WIDTH * 0.8) / layout.width.max(1.0);
+        let height = layout.height * scale;
+        self.ensure_space(height + 12.0);
+
+        let origin_x = MARGIN + (CONTENT_WIDTH - layout.width * scale) / 2.0;
+        let top = self.y;
+        let to_point = |p: [f32; 2]| Point::new(Mm(origin_x + p[0] * scale), Mm(PAGE_HEIGHT - top - p[1] * scale));
+
+        if let Some(outline) = &layout.outline {
+            self.layer.set_outline_color(rgb([120, 120, 120]));
+            self.layer.set_outline_thickness(1.0);
+            for line in outline_lines(&parse_path(outline), to_point) {
+                self.layer.add_line(line);
+            }
+        }
+
+        for placed in layout.place(&data.result.impact_scores) {
+            let center = to_point(placed.center);
+            self.layer.set_fill_color(rgb(impact_color(placed.impact_score)));
+            self.layer.add_polygon(Polygon {
+                rings: vec![printpdf::utils::calculate_points_for_circle(
+                    Mm(placed.radius * scale),
+                    Mm::from(center.x),
+                    Mm::from(center.y),
+                )],
+                mode: PaintMode::Fill,
+                winding_order: WindingOrder::NonZero,
+            });
+            let label = format!("{} {:.2}", name_in(data.language, &placed.region), placed.impact_score);
+            let label_x = origin_x + placed.center[0] * scale - text_width(&label, 7.0) / 2.0;
+            self.text_at(&label, 7.0, label_x, top + (placed.center[1] + placed.radius) * scale + 3.0);
+        }
+        self.y += height + 2.0;
+
+        // 圖例
+        let mut x = MARGIN;
+        let baseline = self.y + 4.0;
+        for level in [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High, RiskLevel::Critical] {
+            self.layer.set_fill_color(rgb(level.color()));
+            self.layer.add_polygon(Polygon {
+                rings: vec![printpdf::utils::calculate_points_for_circle(
+                    Mm(1.5),
+                    Mm(x + 1.5),
+                    Mm(PAGE_HEIGHT - baseline + 1.0),
+                )],
+                mode: PaintMode::Fill,
+                winding_order: WindingOrder::NonZero,
+            });
+            let label = level.label_in(data.language);
+            self.text_at(label, 8.0, x + 4.5, baseline);
+            x += 8.0 + text_width(label, 8.0);
+        }
+        self.y = baseline + 2.0;
+    }
+}
+
+/// 將 path 指令轉為 printpdf 線段；每個子路徑一條線
+///
+/// printpdf 以點上的旗標表示「下一點為貝茲控制點」。
+fn outline_lines(commands: &[PathCommand], to_point: impl Fn([f32; 2]) -> Point) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut points: Vec<(Point, bool)> = Vec::new();
+    let mut finish = |points: &mut Vec<(Point, bool)>, is_closed: bool| {
+        if points.len() > 1 {
+            lines.push(Line {
+                points: std::mem::take(points),
+                is_closed,
+            });
+        }
+        points.clear();
+    };
+
+    for command in commands {
+        match *command {
+            PathCommand::MoveTo(p) => {
+                finish(&mut points, false);
+                points.push((to_point(p), false));
+            }
+            PathCommand::LineTo(p) => points.push((to_point(p), false)),
+            PathCommand::CubicTo(c1, c2, p) => {
+                if let Some(last) = points.last_mut() {
+                    last.1 = true;
+                }
+                points.push((to_point(c1), true));
+                points.push((to_point(c2), false));
+                points.push((to_point(p), false));
+            }
+            PathCommand::Close => finish(&mut points, true),
+        }
+    }
+    finish(&mut points, false);
+    lines
+}
+
+/// 估計文字寬度 (mm)：全形字元以字級計，半形字元以一半計
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| if c.is_ascii() { 0.55 } else { 1.0 })
+        .sum::<f32>()
+        * size
+        * PT_TO_MM
+}
+
+/// 依估計寬度斷行；中文逐字斷行，英文盡量在空白處斷行
+fn wrap_text(text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        current.push(c);
+        if text_width(&current, size) > max_width {
+            let break_at = current
+                .char_indices()
+                .rev()
+                .find(|(_, ch)| *ch == ' ')
+                .map(|(i, _)| i)
+                .filter(|&i| i > 0 && c.is_ascii_alphanumeric());
+            let rest = match break_at {
+                Some(i) => current.split_off(i).trim_start().to_string(),
+                None => {
+                    current.pop();
+                    c.to_string()
+                }
+            };
+            lines.push(std::mem::replace(&mut current, rest));
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn rgb(color: [u8; 3]) -> Color {
+    Color::Rgb(Rgb::new(
+        color[0] as f32 / 255.0,
+        color[1] as f32 / 255.0,
+        color[2] as f32 / 255.0,
+        None,
+    ))
+}
+
+fn pdf_error(err: printpdf::Error) -> BrainVizError {
+    BrainVizError::Export(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{load_behavior_brain_map, MappingEngine};
+    use crate::model::{BehaviorInput, SubjectInfo};
+    use crate::risk::RiskAssessment;
+
+    #[test]
+    fn test_wrap_text() {
+        let lines = wrap_text("一二三四五六七八九十", 10.0, 10.0 * PT_TO_MM * 4.0);
+        assert_eq!(lines, vec!["一二三四", "五六七八", "九十"]);
+        assert_eq!(wrap_text("", 10.0, 50.0), vec![""]);
+    }
+
+    /// 以內附資料排版一份報告
+    fn render(language: Language, font: &[u8]) -> PdfWriter {
+        let base = env!("CARGO_MANIFEST_DIR");
+        let engine = MappingEngine::new(load_behavior_brain_map(format!("{}/data/behavior_brain_map.json", base)).unwrap());
+        let atlas_svg = std::fs::read_to_string(format!("{}/assets/brain.svg", base)).unwrap();
+        let result = engine
+            .calculate_impact(&BehaviorInput {
+                behavior_type: "自然步行".to_string(),
+                value: 0.6,
+                unit: "公尺/秒".to_string(),
+                timestamp: chrono::Utc::now(),
+            })
+            .unwrap();
+        let report = RiskAssessment::default().generate_risk_report(&result, language);
+        let subject = SubjectInfo {
+            subject_id: "S001".to_string(),
+            age: Some(70),
+            ..Default::default()
+        };
+
+        layout_report(
+            &ReportData {
+                subject: Some(&subject),
+                result: &result,
+                report: &report,
+                atlas_svg: &atlas_svg,
+                language,
+            },
+            font,
+        )
+        .unwrap()
+    }
+
+    /// 字型中沒有字形的字元（以 ab_glyph 查詢，printpdf 未公開字型解析）
+    #[cfg(feature = "png")]
+    fn missing_glyphs(font: &[u8], text: &str) -> Vec<char> {
+        use ab_glyph::Font;
+        let face = ab_glyph::FontRef::try_from_slice(font).unwrap();
+        let mut missing: Vec<char> = text.chars().filter(|c| !c.is_whitespace() && face.glyph_id(*c).0 == 0).collect();
+        missing.sort_unstable();
+        missing.dedup();
+        missing
+    }
+
+    #[test]
+    fn test_render_pdf_report() {
+        // 英文報告只需要拉丁字型，任何有 DejaVu Sans 等字型的環境都會執行
+        let fallback = crate::fonts::find_fallback_font().expect("找不到備用字型（如 DejaVu Sans），無法測試 PDF 報告");
+        let font = load_font_bytes(&fallback.path).unwrap();
+        let writer = render(Language::En, &font);
+
+        assert!(writer.drawn.contains("S001"));
+        assert!(writer.drawn.contains(RiskLevel::Low.label_in(Language::En)));
+        assert!(
+            writer.drawn.chars().all(|c| (c as u32) < 0x2E80),
+            "英文報告含有中日韓文字: {}",
+            writer.drawn
+        );
+        #[cfg(feature = "png")]
+        {
+            let missing = missing_glyphs(&font, &writer.drawn);
+            assert!(missing.is_empty(), "{} 缺少字元: {:?}", fallback.name, missing);
+        }
+
+        let bytes = writer.finish().unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+        assert!(bytes.len() > 1000);
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    #[ignore = "needs a CJK font"]
+    fn test_pdf_cjk_glyph_coverage() {
+        let font = crate::fonts::load_cjk_font(None).expect("找不到中文字型").data;
+        let writer = render(Language::ZhTw, &font);
+
+        assert!(writer.drawn.contains("S001"));
+        let missing = missing_glyphs(&font, &writer.drawn);
+        assert!(missing.is_empty(), "字型缺少字元: {:?}", missing);
+        assert!(writer.finish().unwrap().starts_with(b"%PDF"));
+    }
+}
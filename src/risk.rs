@@ -1,10 +1,93 @@
-use crate::model::{CalculationResult, RiskLevel};
+use crate::error::{read_json, Result};
+use crate::i18n::{self, Language};
+use crate::model::{CalculationResult, RiskLevel, SubjectInfo};
+use crate::recommend::{RecommendationEngine, RecommendationRule, RuleContext, Trend};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
 /// 風險評估和功能分析引擎
 pub struct RiskAssessment {
     region_functions: HashMap<String, Vec<String>>,
-    region_diseases: HashMap<String, Vec<String>>,
+    region_diseases: HashMap<String, Vec<DiseaseLink>>,
+    recommendations: RecommendationEngine,
+}
+
+/// 影響分數超過此值的腦區視為高影響腦區
+pub const HIGH_IMPACT_THRESHOLD: f32 = 0.5;
+
+/// 風險對照檔只列疾病名稱、未給強度時使用的預設連結強度
+pub const DEFAULT_LINK_STRENGTH: f32 = 0.5;
+
+/// 腦區與疾病的關聯及其證據強度（0–1）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "DiseaseLinkSpec")]
+pub struct DiseaseLink {
+    pub disease: String,
+    pub strength: f32,
+}
+
+impl DiseaseLink {
+    pub fn new(disease: &str, strength: f32) -> Self {
+        Self {
+            disease: disease.to_string(),
+            strength,
+        }
+    }
+}
+
+/// 風險對照檔中的疾病可寫成字串，或 `{"disease": ..., "strength": ...}`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DiseaseLinkSpec {
+    Name(String),
+    Weighted { disease: String, strength: f32 },
+}
+
+impl From<DiseaseLinkSpec> for DiseaseLink {
+    fn from(spec: DiseaseLinkSpec) -> Self {
+        match spec {
+            DiseaseLinkSpec::Name(disease) => Self {
+                disease,
+                strength: DEFAULT_LINK_STRENGTH,
+            },
+            DiseaseLinkSpec::Weighted { disease, strength } => Self {
+                disease,
+                strength: strength.clamp(0.0, 1.0),
+            },
+        }
+    }
+}
+
+/// 單一腦區對某疾病分數的貢獻
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiseaseContribution {
+    pub region: String,
+    pub impact_score: f32,
+    pub strength: f32,
+    /// min(impact_score, 1) × strength
+    pub evidence: f32,
+}
+
+/// 依證據加權排序的疾病風險
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiseaseRisk {
+    pub disease: String,
+    /// 0–1，各腦區證據以 noisy-OR 合併：1 − Π(1 − evidence)
+    pub score: f32,
+    /// 依貢獻由大到小排列
+    pub contributing_regions: Vec<DiseaseContribution>,
+}
+
+impl DiseaseRisk {
+    /// 例如「前額葉 0.82×0.8、頂葉 0.61×0.6」；腦區名稱與分隔符號依 `language`
+    pub fn contributors_label(&self, language: Language) -> String {
+        self.contributing_regions
+            .iter()
+            .map(|c| format!("{} {:.2}×{:.1}", i18n::name_in(language, &c.region), c.impact_score, c.strength))
+            .collect::<Vec<_>>()
+            .join(i18n::tr_in(language, "list.separator"))
+    }
 }
 
 impl Default for RiskAssessment {
@@ -12,7 +95,7 @@ impl Default for RiskAssessment {
         let mut region_functions = HashMap::new();
         let mut region_diseases = HashMap::new();
 
-        // 前額葉相關功能和疾病
+        // 前額葉相關功能和疾病；連結強度為示意值，請以文獻效果量取代
         region_functions.insert("前額葉".to_string(), vec![
             "執行功能".to_string(),
             "工作記憶".to_string(),
@@ -21,10 +104,10 @@ impl Default for RiskAssessment {
             "情緒調節".to_string(),
         ]);
         region_diseases.insert("前額葉".to_string(), vec![
-            "注意力缺陷過動症 (ADHD)".to_string(),
-            "憂鬱症".to_string(),
-            "焦慮症".to_string(),
-            "額顳葉型失智症".to_string(),
+            DiseaseLink::new("注意力缺陷過動症 (ADHD)", 0.8),
+            DiseaseLink::new("憂鬱症", 0.6),
+            DiseaseLink::new("焦慮症", 0.5),
+            DiseaseLink::new("額顳葉型失智症", 0.6),
         ]);
 
         // 頂葉相關功能和疾病
@@ -35,9 +118,9 @@ impl Default for RiskAssessment {
             "視覺空間處理".to_string(),
         ]);
         region_diseases.insert("頂葉".to_string(), vec![
-            "阿茲海默症".to_string(),
-            "失用症".to_string(),
-            "視覺忽略症".to_string(),
+            DiseaseLink::new("阿茲海默症", 0.6),
+            DiseaseLink::new("失用症", 0.7),
+            DiseaseLink::new("視覺忽略症", 0.8),
         ]);
 
         // 小腦相關功能和疾病
@@ -48,9 +131,9 @@ impl Default for RiskAssessment {
             "精細動作控制".to_string(),
         ]);
         region_diseases.insert("小腦".to_string(), vec![
-            "小腦萎縮症".to_string(),
-            "帕金森氏症".to_string(),
-            "運動失調症".to_string(),
+            DiseaseLink::new("小腦萎縮症", 0.9),
+            DiseaseLink::new("帕金森氏症", 0.4),
+            DiseaseLink::new("運動失調症", 0.8),
         ]);
 
         // 運動皮質相關功能和疾病
@@ -60,9 +143,9 @@ impl Default for RiskAssessment {
             "肌肉協調".to_string(),
         ]);
         region_diseases.insert("運動皮質".to_string(), vec![
-            "中風".to_string(),
-            "肌萎縮性側索硬化症 (ALS)".to_string(),
-            "運動皮質病變".to_string(),
+            DiseaseLink::new("中風", 0.7),
+            DiseaseLink::new("肌萎縮性側索硬化症 (ALS)", 0.7),
+            DiseaseLink::new("運動皮質病變", 0.8),
         ]);
 
         // HCP 相關腦區
@@ -72,9 +155,9 @@ impl Default for RiskAssessment {
             "Cognitive Flexibility".to_string(),
         ]);
         region_diseases.insert("Middle Frontal Gyrus".to_string(), vec![
-            "ADHD".to_string(),
-            "Schizophrenia".to_string(),
-            "Frontotemporal Dementia".to_string(),
+            DiseaseLink::new("ADHD", 0.7),
+            DiseaseLink::new("Schizophrenia", 0.5),
+            DiseaseLink::new("Frontotemporal Dementia", 0.5),
         ]);
 
         region_functions.insert("Anterior Cingulate Cortex (ACC)".to_string(), vec![
@@ -83,9 +166,9 @@ impl Default for RiskAssessment {
             "Error Detection".to_string(),
         ]);
         region_diseases.insert("Anterior Cingulate Cortex (ACC)".to_string(), vec![
-            "Depression".to_string(),
-            "Anxiety Disorders".to_string(),
-            "OCD".to_string(),
+            DiseaseLink::new("Depression", 0.7),
+            DiseaseLink::new("Anxiety Disorders", 0.6),
+            DiseaseLink::new("OCD", 0.6),
         ]);
 
         region_functions.insert("Hippocampus".to_string(), vec![
@@ -94,47 +177,97 @@ impl Default for RiskAssessment {
             "Memory Consolidation".to_string(),
         ]);
         region_diseases.insert("Hippocampus".to_string(), vec![
-            "Alzheimer's Disease".to_string(),
-            "Mild Cognitive Impairment".to_string(),
-            "PTSD".to_string(),
+            DiseaseLink::new("Alzheimer's Disease", 0.9),
+            DiseaseLink::new("Mild Cognitive Impairment", 0.8),
+            DiseaseLink::new("PTSD", 0.5),
         ]);
 
         Self {
             region_functions,
             region_diseases,
+            recommendations: RecommendationEngine::default(),
         }
     }
 }
 
 impl RiskAssessment {
-    /// 基於計算結果生成風險評估報告
-    pub fn generate_risk_report(&self, result: &CalculationResult) -> RiskReport {
+    /// 以風險對照檔的內容擴充預設的功能與疾病對照表
+    pub fn with_risk_map(mut self, entries: HashMap<String, RiskMapEntry>) -> Self {
+        for (region, entry) in entries {
+            self.region_functions.insert(region.clone(), entry.functions);
+            self.region_diseases.insert(region, entry.diseases);
+        }
+        self
+    }
+
+    /// 以資料檔中的建議規則取代內建規則
+    pub fn with_recommendation_rules(mut self, rules: Vec<RecommendationRule>) -> Self {
+        self.recommendations = RecommendationEngine::new(rules);
+        self
+    }
+
+    /// 基於計算結果生成風險評估報告；建議文字使用 `language`
+    pub fn generate_risk_report(&self, result: &CalculationResult, language: Language) -> RiskReport {
+        self.generate_contextual_report(result, None, &[], language)
+    }
+
+    /// 腦區對應的功能；沒有資料時為空
+    pub fn region_functions(&self, region: &str) -> &[String] {
+        self.region_functions.get(region).map_or(&[], Vec::as_slice)
+    }
+
+    /// 腦區對應的疾病連結；沒有資料時為空
+    pub fn region_diseases(&self, region: &str) -> &[DiseaseLink] {
+        self.region_diseases.get(region).map_or(&[], Vec::as_slice)
+    }
+
+    /// 生成風險評估報告；建議會參考受試者資料與歷次測量的趨勢，文字使用 `language`
+    pub fn generate_contextual_report(
+        &self,
+        result: &CalculationResult,
+        subject: Option<&SubjectInfo>,
+        history: &[CalculationResult],
+        language: Language,
+    ) -> RiskReport {
         let mut affected_functions = Vec::new();
-        let mut potential_diseases = Vec::new();
         let mut high_impact_regions = Vec::new();
 
         // 分析高影響的腦區
         for impact in &result.impact_scores {
-            if impact.impact_score > 0.5 {
+            if impact.impact_score > HIGH_IMPACT_THRESHOLD {
                 high_impact_regions.push(impact.region.clone());
 
                 // 收集相關的功能
                 if let Some(functions) = self.region_functions.get(&impact.region) {
                     affected_functions.extend(functions.clone());
                 }
-
-                // 收集相關的疾病
-                if let Some(diseases) = self.region_diseases.get(&impact.region) {
-                    potential_diseases.extend(diseases.clone());
-                }
             }
         }
 
         // 去重
         affected_functions.sort();
         affected_functions.dedup();
-        potential_diseases.sort();
-        potential_diseases.dedup();
+
+        let disease_ranking = self.rank_diseases(result);
+        let potential_diseases = disease_ranking.iter().map(|risk| risk.disease.clone()).collect();
+
+        let trend = Trend::from_history(result, history);
+        let (recommendation_rule_ids, recommendations) = self
+            .recommendations
+            .recommend(
+                &RuleContext {
+                    risk_level: &result.risk_level,
+                    regions: &high_impact_regions,
+                    functions: &affected_functions,
+                    behavior: &result.behavior_input.behavior_type,
+                    subject,
+                    trend,
+                },
+                language,
+            )
+            .into_iter()
+            .map(|recommendation| (recommendation.rule_id, recommendation.text))
+            .unzip();
 
         RiskReport {
             risk_level: result.risk_level.clone(),
@@ -142,48 +275,94 @@ impl RiskAssessment {
             high_impact_regions,
             affected_functions,
             potential_diseases,
-            recommendations: self.generate_recommendations(&result.risk_level),
+            disease_ranking,
+            trend,
+            recommendations,
+            recommendation_rule_ids,
         }
     }
 
-    fn generate_recommendations(&self, risk_level: &RiskLevel) -> Vec<String> {
-        match risk_level {
-            RiskLevel::Low => vec![
-                "持續保持良好的生活習慣".to_string(),
-                "定期進行腦力訓練活動".to_string(),
-                "維持規律的運動習慣".to_string(),
-            ],
-            RiskLevel::Medium => vec![
-                "建議增加認知訓練活動".to_string(),
-                "注意睡眠品質和作息規律".to_string(),
-                "考慮減少壓力來源".to_string(),
-                "定期追蹤相關指標".to_string(),
-            ],
-            RiskLevel::High => vec![
-                "建議諮詢神經科或精神科醫師".to_string(),
-                "進行更詳細的神經心理學評估".to_string(),
-                "考慮認知復健訓練".to_string(),
-                "密切監控症狀變化".to_string(),
-            ],
-            RiskLevel::Critical => vec![
-                "立即諮詢專業醫療人員".to_string(),
-                "安排完整的神經學檢查".to_string(),
-                "考慮影像學檢查 (MRI/fMRI)".to_string(),
-                "制定個人化治療計畫".to_string(),
-            ],
+    /// 將高影響腦區的疾病連結依證據強度合併成排序後的疾病清單
+    pub fn rank_diseases(&self, result: &CalculationResult) -> Vec<DiseaseRisk> {
+        // 同一腦區出現多次時取最高分，避免重複計入證據
+        let mut region_scores: Vec<(&str, f32)> = Vec::new();
+        for impact in &result.impact_scores {
+            if impact.impact_score <= HIGH_IMPACT_THRESHOLD {
+                continue;
+            }
+            match region_scores.iter_mut().find(|(region, _)| *region == impact.region) {
+                Some((_, score)) => *score = score.max(impact.impact_score),
+                None => region_scores.push((&impact.region, impact.impact_score)),
+            }
+        }
+
+        let mut ranking: Vec<DiseaseRisk> = Vec::new();
+        for (region, impact_score) in region_scores {
+            for link in self.region_diseases.get(region).into_iter().flatten() {
+                let contribution = DiseaseContribution {
+                    region: region.to_string(),
+                    impact_score,
+                    strength: link.strength,
+                    evidence: impact_score.min(1.0) * link.strength,
+                };
+                match ranking.iter_mut().find(|risk| risk.disease == link.disease) {
+                    Some(risk) => risk.contributing_regions.push(contribution),
+                    None => ranking.push(DiseaseRisk {
+                        disease: link.disease.clone(),
+                        score: 0.0,
+                        contributing_regions: vec![contribution],
+                    }),
+                }
+            }
+        }
+
+        for risk in &mut ranking {
+            risk.contributing_regions
+                .sort_by(|a, b| b.evidence.total_cmp(&a.evidence));
+            risk.score = 1.0
+                - risk
+                    .contributing_regions
+                    .iter()
+                    .map(|c| 1.0 - c.evidence)
+                    .product::<f32>();
         }
+        ranking.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.disease.cmp(&b.disease)));
+        ranking
     }
 }
 
 /// 風險評估報告
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskReport {
     pub risk_level: RiskLevel,
     pub total_impact: f32,
     pub high_impact_regions: Vec<String>,
     pub affected_functions: Vec<String>,
+    /// 依 `disease_ranking` 排序的疾病名稱
     pub potential_diseases: Vec<String>,
+    #[serde(default)]
+    pub disease_ranking: Vec<DiseaseRisk>,
+    /// 與上次同一行為測量相比的趨勢；沒有較早的測量時為 None
+    #[serde(default)]
+    pub trend: Option<Trend>,
     pub recommendations: Vec<String>,
+    /// 產生各建議的規則 ID，與 `recommendations` 同順序
+    #[serde(default)]
+    pub recommendation_rule_ids: Vec<String>,
+}
+
+/// 風險對照檔中的單一腦區項目
+#[derive(Debug, Clone, Deserialize)]
+pub struct RiskMapEntry {
+    #[serde(default)]
+    pub functions: Vec<String>,
+    #[serde(default)]
+    pub diseases: Vec<DiseaseLink>,
+}
+
+/// 從 JSON 文件載入腦區功能-疾病對照資料
+pub fn load_risk_map(path: impl AsRef<Path>) -> Result<HashMap<String, RiskMapEntry>> {
+    read_json(path)
 }
 
 /// 簡化的風險估算函數（向後相容）
@@ -216,7 +395,9 @@ mod tests {
         
         // 測試疾病映射
         let frontal_diseases = assessment.region_diseases.get("前額葉").unwrap();
-        assert!(frontal_diseases.contains(&"注意力缺陷過動症 (ADHD)".to_string()));
+        assert!(frontal_diseases.iter().any(|link| link.disease == "注意力缺陷過動症 (ADHD)"));
+        assert_eq!(assessment.region_diseases("前額葉").len(), frontal_diseases.len());
+        assert!(assessment.region_functions("未知腦區").is_empty());
     }
 
     #[test]
@@ -245,7 +426,7 @@ mod tests {
             risk_level: RiskLevel::Low,
         };
 
-        let report = assessment.generate_risk_report(&result);
+        let report = assessment.generate_risk_report(&result, Language::ZhTw);
         
         assert_eq!(report.risk_level, RiskLevel::Low);
         assert_eq!(report.total_impact, 0.08);
@@ -285,7 +466,7 @@ mod tests {
             risk_level: RiskLevel::High,
         };
 
-        let report = assessment.generate_risk_report(&result);
+        let report = assessment.generate_risk_report(&result, Language::ZhTw);
         
         assert_eq!(report.risk_level, RiskLevel::High);
         assert_eq!(report.total_impact, 0.75);
@@ -308,18 +489,69 @@ mod tests {
     #[test]
     fn test_generate_recommendations() {
         let assessment = RiskAssessment::default();
-        
-        let low_rec = assessment.generate_recommendations(&RiskLevel::Low);
-        assert!(low_rec.contains(&"持續保持良好的生活習慣".to_string()));
-        
-        let medium_rec = assessment.generate_recommendations(&RiskLevel::Medium);
-        assert!(medium_rec.contains(&"建議增加認知訓練活動".to_string()));
-        
-        let high_rec = assessment.generate_recommendations(&RiskLevel::High);
-        assert!(high_rec.contains(&"建議諮詢神經科或精神科醫師".to_string()));
-        
-        let critical_rec = assessment.generate_recommendations(&RiskLevel::Critical);
-        assert!(critical_rec.contains(&"立即諮詢專業醫療人員".to_string()));
+        let recommendations = |risk_level: RiskLevel| {
+            let result = CalculationResult {
+                behavior_input: BehaviorInput {
+                    behavior_type: "測試".to_string(),
+                    value: 0.0,
+                    unit: "分數".to_string(),
+                    timestamp: Utc::now(),
+                },
+                impact_scores: vec![],
+                total_impact: risk_level.lower_bound(),
+                risk_level,
+            };
+            assessment.generate_risk_report(&result, Language::ZhTw).recommendations
+        };
+
+        assert!(recommendations(RiskLevel::Low).contains(&"持續保持良好的生活習慣".to_string()));
+        assert!(recommendations(RiskLevel::Medium).contains(&"建議增加認知訓練活動".to_string()));
+        assert!(recommendations(RiskLevel::High).contains(&"建議諮詢神經科或精神科醫師".to_string()));
+        assert!(recommendations(RiskLevel::Critical).contains(&"立即諮詢專業醫療人員".to_string()));
+    }
+
+    #[test]
+    fn test_contextual_recommendations() {
+        let assessment = RiskAssessment::default();
+        let result_at = |days: i64, impact_score: f32| CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: "自然步行".to_string(),
+                value: 0.6,
+                unit: "公尺/秒".to_string(),
+                timestamp: Utc::now() + chrono::Duration::days(days),
+            },
+            impact_scores: vec![RegionImpactScore {
+                region: "小腦".to_string(),
+                impact_score,
+                normalized_input: 1.0,
+                weight: impact_score,
+            }],
+            total_impact: impact_score,
+            risk_level: RiskLevel::from_score(impact_score),
+        };
+        let history = vec![result_at(-7, 0.4)];
+        let subject = SubjectInfo {
+            subject_id: "S1".to_string(),
+            age: Some(70),
+            ..Default::default()
+        };
+
+        let report =
+            assessment.generate_contextual_report(&result_at(0, 0.7), Some(&subject), &history, Language::ZhTw);
+        assert_eq!(report.trend, Some(Trend::Worsening));
+        assert_eq!(report.recommendations.len(), report.recommendation_rule_ids.len());
+        for id in ["level.high.consult", "region.cerebellum.balance", "demographic.older.cga", "trend.worsening.shorten_interval"] {
+            assert!(report.recommendation_rule_ids.contains(&id.to_string()), "缺少 {}", id);
+        }
+
+        // 規則檔可取代內建規則
+        let rules: Vec<RecommendationRule> =
+            serde_json::from_str(r#"[{ "id": "custom.1", "text": "自訂建議", "when": { "regions": ["小腦"] } }]"#).unwrap();
+        let report = RiskAssessment::default()
+            .with_recommendation_rules(rules)
+            .generate_risk_report(&result_at(0, 0.7), Language::ZhTw);
+        assert_eq!(report.recommendations, vec!["自訂建議".to_string()]);
+        assert_eq!(report.recommendation_rule_ids, vec!["custom.1".to_string()]);
     }
 
     #[test]
@@ -355,7 +587,7 @@ mod tests {
             risk_level: RiskLevel::High,
         };
 
-        let report = assessment.generate_risk_report(&result);
+        let report = assessment.generate_risk_report(&result, Language::ZhTw);
         
         // 測試去重功能
         assert_eq!(report.high_impact_regions.len(), 2); // 兩個相同的腦區都被記錄
@@ -384,7 +616,10 @@ mod tests {
             high_impact_regions: vec!["前額葉".to_string()],
             affected_functions: vec!["執行功能".to_string(), "工作記憶".to_string()],
             potential_diseases: vec!["ADHD".to_string()],
+            disease_ranking: Vec::new(),
+            trend: None,
             recommendations: vec!["建議增加認知訓練活動".to_string()],
+            recommendation_rule_ids: vec!["level.medium.training".to_string()],
         };
 
         assert_eq!(risk_report.risk_level, RiskLevel::Medium);
@@ -412,7 +647,7 @@ mod tests {
             risk_level: RiskLevel::Low,
         };
 
-        let report = assessment.generate_risk_report(&result);
+        let report = assessment.generate_risk_report(&result, Language::ZhTw);
         
         assert_eq!(report.risk_level, RiskLevel::Low);
         assert_eq!(report.total_impact, 0.0);
@@ -422,6 +657,93 @@ mod tests {
         assert!(!report.recommendations.is_empty()); // 應該還是有建議
     }
 
+    #[test]
+    fn test_with_risk_map() {
+        let json = r#"{
+            "DMN": {
+                "functions": ["內省"],
+                "diseases": ["抑鬱症"],
+                "thresholds": [{ "level": "low", "min": 0, "message": "低風險" }]
+            }
+        }"#;
+        let entries: HashMap<String, RiskMapEntry> = serde_json::from_str(json).unwrap();
+        let assessment = RiskAssessment::default().with_risk_map(entries);
+
+        assert_eq!(assessment.region_functions.get("DMN").unwrap(), &vec!["內省".to_string()]);
+        assert_eq!(assessment.region_diseases.get("DMN").unwrap(), &vec![DiseaseLink::new("抑鬱症", DEFAULT_LINK_STRENGTH)]);
+        // 預設對照表仍保留
+        assert!(assessment.region_functions.contains_key("前額葉"));
+    }
+
+    #[test]
+    fn test_rank_diseases_by_evidence() {
+        let json = r#"{
+            "A": { "diseases": [{ "disease": "甲", "strength": 0.6 }, "乙"] },
+            "B": { "diseases": [{ "disease": "乙", "strength": 0.8 }] }
+        }"#;
+        let entries: HashMap<String, RiskMapEntry> = serde_json::from_str(json).unwrap();
+        let assessment = RiskAssessment::default().with_risk_map(entries);
+        let result = CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: "測試".to_string(),
+                value: 1.0,
+                unit: "分數".to_string(),
+                timestamp: Utc::now(),
+            },
+            impact_scores: ["A", "B"]
+                .iter()
+                .map(|region| RegionImpactScore {
+                    region: region.to_string(),
+                    impact_score: 1.0,
+                    normalized_input: 1.0,
+                    weight: 1.0,
+                })
+                .collect(),
+            total_impact: 1.0,
+            risk_level: RiskLevel::Critical,
+        };
+
+        let ranking = assessment.rank_diseases(&result);
+        // 乙由兩個腦區支持：1 − (1 − 0.5)(1 − 0.8) = 0.9；甲只有 0.6
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(ranking[0].disease, "乙");
+        assert!((ranking[0].score - 0.9).abs() < 1e-6);
+        assert_eq!(ranking[0].contributing_regions.len(), 2);
+        assert_eq!(ranking[0].contributing_regions[0].region, "B");
+        assert!((ranking[1].score - 0.6).abs() < 1e-6);
+        assert_eq!(ranking[0].contributors_label(Language::En), "B 1.00×0.8, A 1.00×0.5");
+        assert_eq!(ranking[0].contributors_label(Language::ZhTw), "B 1.00×0.8、A 1.00×0.5");
+
+        let report = assessment.generate_risk_report(&result, Language::ZhTw);
+        assert_eq!(report.potential_diseases, vec!["乙".to_string(), "甲".to_string()]);
+    }
+
+    #[test]
+    fn test_rank_diseases_prefers_stronger_evidence() {
+        let assessment = RiskAssessment::default();
+        let result = CalculationResult {
+            behavior_input: BehaviorInput {
+                behavior_type: "測試".to_string(),
+                value: 1.0,
+                unit: "分數".to_string(),
+                timestamp: Utc::now(),
+            },
+            impact_scores: vec![RegionImpactScore {
+                region: "小腦".to_string(),
+                impact_score: 0.7,
+                normalized_input: 1.0,
+                weight: 0.7,
+            }],
+            total_impact: 0.7,
+            risk_level: RiskLevel::High,
+        };
+
+        let ranking = assessment.rank_diseases(&result);
+        assert_eq!(ranking[0].disease, "小腦萎縮症");
+        assert_eq!(ranking.last().unwrap().disease, "帕金森氏症");
+        assert!(ranking.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+
     #[test]
     fn test_unknown_region_handling() {
         let assessment = RiskAssessment::default();
@@ -448,7 +770,7 @@ mod tests {
             risk_level: RiskLevel::Medium,
         };
 
-        let report = assessment.generate_risk_report(&result);
+        let report = assessment.generate_risk_report(&result, Language::ZhTw);
         
         assert_eq!(report.high_impact_regions.len(), 1);
         assert!(report.high_impact_regions.contains(&"未知腦區".to_string()));
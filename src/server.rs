@@ -0,0 +1,560 @@
+//! 本機 HTTP/JSON 計分服務（`serve` 模式）
+//!
+//! | 方法 | 路徑 | 說明 |
+//! |------|------|------|
+//! | GET  | `/behaviors` | 列出行為與預期單位 |
+//! | GET  | `/regions` | 列出所有腦區 |
+//! | POST | `/score` | 計算單一輸入 |
+//! | POST | `/score/batch` | 計算多筆輸入（JSON 陣列） |
+//! | POST | `/report` | 計算並產生風險評估報告 |
+//! | POST | `/explain` | 計算並逐腦區說明分數由來 |
+
+use crate::error::BrainVizError;
+use crate::explain::ScoreExplanation;
+use crate::i18n::Language;
+use crate::mapping::MappingEngine;
+use crate::model::{BehaviorInput, CalculationResult};
+use crate::risk::{RiskAssessment, RiskReport};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 預設監聽位址
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8750";
+
+/// 連線資源上限
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerLimits {
+    /// 同時處理的連線數，超過時直接回應 503
+    pub max_connections: usize,
+    /// 單次讀寫逾時
+    pub io_timeout: Duration,
+    /// 請求列與標頭合計大小上限
+    pub max_header_bytes: usize,
+    /// 請求本文大小上限
+    pub max_body_bytes: usize,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        Self {
+            max_connections: 64,
+            io_timeout: Duration::from_secs(10),
+            max_header_bytes: 16 * 1024,
+            max_body_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// 計分請求；未提供單位時使用映射表中的預期單位
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreRequest {
+    pub behavior_type: String,
+    pub value: f32,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// 行為列表項目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BehaviorSummary {
+    pub behavior: String,
+    pub unit: Option<String>,
+}
+
+/// 風險報告回應
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportResponse {
+    pub result: CalculationResult,
+    pub report: RiskReport,
+}
+
+/// 分數說明回應
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainResponse {
+    pub result: CalculationResult,
+    pub explanation: ScoreExplanation,
+}
+
+/// HTTP 回應
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status, body },
+            Err(e) => Self::error(500, "internal_error", &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, code: &str, message: &str) -> Self {
+        let body = serde_json::json!({ "error": { "code": code, "message": message } });
+        Self {
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// 將引擎錯誤轉為 HTTP 錯誤回應
+fn error_response(err: &BrainVizError) -> HttpResponse {
+    let (status, code) = match err {
+        BrainVizError::UnknownBehavior(_) => (404, "unknown_behavior"),
+        BrainVizError::UnitMismatch { .. } => (422, "unit_mismatch"),
+        BrainVizError::InvalidNormalization { .. } => (422, "invalid_normalization"),
+        BrainVizError::Parse { .. } | BrainVizError::Config(_) => (400, "bad_request"),
+        BrainVizError::Io { .. } => (500, "io_error"),
+        BrainVizError::Export(_) => (500, "export_error"),
+    };
+    HttpResponse::error(status, code, &err.to_string())
+}
+
+/// 計分服務：包裝計算引擎與風險評估
+pub struct ScoringService {
+    engine: MappingEngine,
+    risk_assessment: RiskAssessment,
+    language: Language,
+}
+
+impl ScoringService {
+    pub fn new(engine: MappingEngine, risk_assessment: RiskAssessment) -> Self {
+        Self {
+            engine,
+            risk_assessment,
+            language: Language::default(),
+        }
+    }
+
+    /// 設定 `/report` 建議文字的語言（預設繁體中文）
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// 處理一個請求並回傳回應
+    pub fn handle(&self, method: &str, path: &str, body: &[u8]) -> HttpResponse {
+        // 忽略查詢字串
+        let path = path.split('?').next().unwrap_or(path).trim_end_matches('/');
+
+        match (method, path) {
+            ("GET", "/behaviors") => {
+                let behaviors: Vec<BehaviorSummary> = self
+                    .engine
+                    .get_behavior_types()
+                    .into_iter()
+                    .map(|behavior| BehaviorSummary {
+                        unit: self.engine.get_behavior_unit(&behavior).map(str::to_string),
+                        behavior,
+                    })
+                    .collect();
+                HttpResponse::json(200, &behaviors)
+            }
+            ("GET", "/regions") => {
+                let mut regions = self.engine.get_brain_regions();
+                regions.sort();
+                HttpResponse::json(200, &regions)
+            }
+            ("POST", "/score") => match parse_body::<ScoreRequest>(body) {
+                Ok(request) => match self.score(&request) {
+                    Ok(result) => HttpResponse::json(200, &result),
+                    Err(e) => error_response(&e),
+                },
+                Err(response) => response,
+            },
+            ("POST", "/score/batch") => match parse_body::<Vec<ScoreRequest>>(body) {
+                Ok(requests) => {
+                    let mut results = Vec::with_capacity(requests.len());
+                    for (index, request) in requests.iter().enumerate() {
+                        match self.score(request) {
+                            Ok(result) => results.push(result),
+                            Err(e) => {
+                                let mut response = error_response(&e);
+                                response.body = serde_json::json!({
+                                    "error": { "code": "batch_item_failed", "index": index, "message": e.to_string() }
+                                })
+                                .to_string();
+                                return response;
+                            }
+                        }
+                    }
+                    HttpResponse::json(200, &results)
+                }
+                Err(response) => response,
+            },
+            ("POST", "/report") => match parse_body::<ScoreRequest>(body) {
+                Ok(request) => match self.score(&request) {
+                    Ok(result) => {
+                        let report = self.risk_assessment.generate_risk_report(&result, self.language);
+                        HttpResponse::json(200, &ReportResponse { result, report })
+                    }
+                    Err(e) => error_response(&e),
+                },
+                Err(response) => response,
+            },
+            ("POST", "/explain") => match parse_body::<ScoreRequest>(body) {
+                Ok(request) => match self
+                    .score(&request)
+                    .and_then(|result| Ok((self.engine.explain(&result)?, result)))
+                {
+                    Ok((explanation, result)) => HttpResponse::json(200, &ExplainResponse { result, explanation }),
+                    Err(e) => error_response(&e),
+                },
+                Err(response) => response,
+            },
+            (_, "/behaviors" | "/regions" | "/score" | "/score/batch" | "/report" | "/explain") => {
+                HttpResponse::error(405, "method_not_allowed", &format!("{} 不支援 {}", path, method))
+            }
+            _ => HttpResponse::error(404, "not_found", &format!("找不到路徑 {}", path)),
+        }
+    }
+
+    fn score(&self, request: &ScoreRequest) -> crate::error::Result<CalculationResult> {
+        let unit = match &request.unit {
+            Some(unit) => unit.clone(),
+            None => self
+                .engine
+                .get_behavior_unit(&request.behavior_type)
+                .unwrap_or_default()
+                .to_string(),
+        };
+        let input = BehaviorInput {
+            behavior_type: request.behavior_type.clone(),
+            value: request.value,
+            unit,
+            timestamp: request.timestamp.unwrap_or_else(Utc::now),
+        };
+        self.engine.calculate_impact(&input)
+    }
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &[u8]) -> Result<T, HttpResponse> {
+    serde_json::from_slice(body).map_err(|e| {
+        HttpResponse::error(
+            400,
+            "invalid_json",
+            &format!("第 {} 行第 {} 欄解析失敗: {}", e.line(), e.column(), e),
+        )
+    })
+}
+
+/// 綁定位址並持續服務（阻塞）
+pub fn serve(addr: impl ToSocketAddrs, service: ScoringService) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    serve_listener(listener, Arc::new(service), ServerLimits::default())
+}
+
+/// 在已綁定的 listener 上服務，每個連線一個執行緒，同時連線數受 `limits` 限制
+pub fn serve_listener(listener: TcpListener, service: Arc<ScoringService>, limits: ServerLimits) -> std::io::Result<()> {
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("警告：無法接受連線: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = stream.set_read_timeout(Some(limits.io_timeout)).and_then(|_| stream.set_write_timeout(Some(limits.io_timeout))) {
+            eprintln!("警告：無法設定連線逾時: {}", e);
+            continue;
+        }
+        let Some(slot) = ConnectionSlot::acquire(&active, limits.max_connections) else {
+            let _ = write_response(stream, &HttpResponse::error(503, "too_many_connections", "同時連線數已達上限"));
+            continue;
+        };
+        let service = Arc::clone(&service);
+        std::thread::spawn(move || {
+            let _slot = slot;
+            if let Err(e) = handle_connection(stream, &service, &limits) {
+                eprintln!("警告：連線處理失敗: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// 佔用一個連線名額，釋放時歸還
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn acquire(active: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < max).then_some(n + 1))
+            .ok()
+            .map(|_| Self(Arc::clone(active)))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn handle_connection(stream: TcpStream, service: &ScoringService, limits: &ServerLimits) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader, limits) {
+        Ok(Some(Ok(RawRequest { method, path, body }))) => service.handle(&method, &path, &body),
+        Ok(Some(Err(response))) => response,
+        Ok(None) => return Ok(()),
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+            HttpResponse::error(408, "request_timeout", "讀取請求逾時")
+        }
+        Err(e) => return Err(e),
+    };
+    write_response(stream, &response)
+}
+
+/// 原始 HTTP 請求
+struct RawRequest {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+/// 讀取一個 HTTP/1.1 請求；標頭或本文超過上限時回傳對應的錯誤回應
+fn read_request(reader: &mut impl BufRead, limits: &ServerLimits) -> std::io::Result<Option<Result<RawRequest, HttpResponse>>> {
+    let too_large = || HttpResponse::error(431, "header_too_large", "請求標頭過大");
+    let mut remaining = limits.max_header_bytes;
+
+    let Some(request_line) = read_header_line(reader, &mut remaining)? else {
+        return Ok(Some(Err(too_large())));
+    };
+    if request_line.is_empty() {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or("/").to_string();
+
+    let mut content_length = 0usize;
+    loop {
+        let Some(line) = read_header_line(reader, &mut remaining)? else {
+            return Ok(Some(Err(too_large())));
+        };
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let Ok(length) = value.trim().parse() else {
+                    return Ok(Some(Err(HttpResponse::error(400, "bad_request", "Content-Length 格式錯誤"))));
+                };
+                content_length = length;
+            }
+        }
+    }
+
+    if content_length > limits.max_body_bytes {
+        return Ok(Some(Err(HttpResponse::error(413, "payload_too_large", "請求本文過大"))));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(Ok(RawRequest { method, path, body })))
+}
+
+/// 在剩餘的標頭額度內讀取一行；額度用完仍未遇到換行時回傳 None
+fn read_header_line(reader: &mut impl BufRead, remaining: &mut usize) -> std::io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let read = reader.by_ref().take(*remaining as u64).read_until(b'\n', &mut line)?;
+    *remaining -= read;
+    if *remaining == 0 && !line.ends_with(b"\n") {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+fn write_response(mut stream: TcpStream, response: &HttpResponse) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BehaviorBrainMap, BrainRegionImpact, NormalizationParams};
+
+    fn create_service() -> ScoringService {
+        let maps = vec![BehaviorBrainMap {
+            behavior: "自然步行".to_string(),
+            unit: Some("公尺/秒".to_string()),
+            brain_regions: vec![BrainRegionImpact {
+                region: "小腦".to_string(),
+                weight: 0.7,
+                description: None,
+                source: None,
+            }],
+            normalization_params: Some(NormalizationParams {
+                mean: 1.2,
+                std_dev: 0.3,
+                sample_size: None,
+                source: None,
+                reliability: None,
+            }),
+        }];
+        ScoringService::new(MappingEngine::new(maps), RiskAssessment::default())
+    }
+
+    #[test]
+    fn test_list_endpoints() {
+        let service = create_service();
+
+        let response = service.handle("GET", "/behaviors", b"");
+        assert_eq!(response.status, 200);
+        let behaviors: Vec<BehaviorSummary> = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(behaviors[0].behavior, "自然步行");
+        assert_eq!(behaviors[0].unit.as_deref(), Some("公尺/秒"));
+
+        let response = service.handle("GET", "/regions/", b"");
+        assert_eq!(response.body, r#"["小腦"]"#);
+    }
+
+    #[test]
+    fn test_score_and_report() {
+        let service = create_service();
+
+        let response = service.handle("POST", "/score", r#"{"behavior_type": "自然步行", "value": 0.6}"#.as_bytes());
+        assert_eq!(response.status, 200);
+        let result: CalculationResult = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(result.behavior_input.unit, "公尺/秒");
+
+        let response = service.handle("POST", "/report", r#"{"behavior_type": "自然步行", "value": 0.6}"#.as_bytes());
+        let report: ReportResponse = serde_json::from_str(&response.body).unwrap();
+        assert!(report.report.high_impact_regions.contains(&"小腦".to_string()));
+
+        let response = service.handle("POST", "/explain", r#"{"behavior_type": "自然步行", "value": 0.6}"#.as_bytes());
+        assert_eq!(response.status, 200);
+        let explained: ExplainResponse = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(explained.explanation.regions[0].region, "小腦");
+        assert_eq!(explained.explanation.total_impact, explained.result.total_impact);
+
+        let response = service.handle(
+            "POST",
+            "/score/batch",
+            r#"[{"behavior_type": "自然步行", "value": 0.9}, {"behavior_type": "自然步行", "value": 1.5}]"#.as_bytes(),
+        );
+        let results: Vec<CalculationResult> = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let service = create_service();
+
+        assert_eq!(service.handle("POST", "/score", b"{").status, 400);
+        assert_eq!(service.handle("POST", "/score", br#"{"behavior_type": "X", "value": 1}"#).status, 404);
+        assert_eq!(
+            service.handle("POST", "/score", r#"{"behavior_type": "自然步行", "value": 1, "unit": "秒"}"#.as_bytes()).status,
+            422
+        );
+        assert_eq!(service.handle("GET", "/score", b"").status, 405);
+        assert_eq!(service.handle("GET", "/nothing", b"").status, 404);
+
+        let response = service.handle("POST", "/score/batch", r#"[{"behavior_type": "自然步行", "value": 1}, {"behavior_type": "X", "value": 1}]"#.as_bytes());
+        assert_eq!(response.status, 404);
+        assert!(response.body.contains(r#""index":1"#));
+    }
+
+    #[test]
+    fn test_serve_over_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let service = Arc::new(create_service());
+        std::thread::spawn(move || serve_listener(listener, service, ServerLimits::default()));
+
+        let body = r#"{"behavior_type": "自然步行", "value": 0.6}"#;
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /score HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"total_impact\""));
+    }
+
+    #[test]
+    fn test_request_limits() {
+        let limits = ServerLimits {
+            max_header_bytes: 64,
+            max_body_bytes: 4,
+            ..ServerLimits::default()
+        };
+        let status = |raw: &str| match read_request(&mut raw.as_bytes(), &limits).unwrap() {
+            Some(Ok(_)) => 200,
+            Some(Err(response)) => response.status,
+            None => 0,
+        };
+
+        assert_eq!(status("POST /score HTTP/1.1\r\nContent-Length: 4\r\n\r\n{}{}"), 200);
+        assert_eq!(status("POST /score HTTP/1.1\r\nContent-Length: 5\r\n\r\n{}{} "), 413);
+        assert_eq!(status("POST /score HTTP/1.1\r\nContent-Length: abc\r\n\r\n{}"), 400);
+        assert_eq!(status("POST /score HTTP/1.1\r\nContent-Length: -1\r\n\r\n{}"), 400);
+        assert_eq!(status(&format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(64))), 431);
+        assert_eq!(status(&format!("GET / HTTP/1.1\r\nX-Pad: {}\r\n\r\n", "a".repeat(40))), 431);
+        assert_eq!(status(""), 0);
+    }
+
+    #[test]
+    fn test_connection_limit_and_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let limits = ServerLimits {
+            max_connections: 1,
+            io_timeout: Duration::from_millis(300),
+            ..ServerLimits::default()
+        };
+        let service = Arc::new(create_service());
+        std::thread::spawn(move || serve_listener(listener, service, limits));
+
+        // 第一個連線不送資料，佔住唯一名額直到讀取逾時
+        let mut idle = TcpStream::connect(addr).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let mut rejected = String::new();
+        TcpStream::connect(addr).unwrap().read_to_string(&mut rejected).unwrap();
+        assert!(rejected.starts_with("HTTP/1.1 503"));
+
+        let mut timed_out = String::new();
+        idle.read_to_string(&mut timed_out).unwrap();
+        assert!(timed_out.starts_with("HTTP/1.1 408"));
+    }
+}
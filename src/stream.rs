@@ -0,0 +1,700 @@
+//! 即時串流輸入
+//!
+//! 由裝置或實驗軟體持續送入行為樣本，依滑動時間窗計分。每筆樣本為一行 UTF-8 文字，可為：
+//!
+//! - JSON：與 HTTP 服務 `/score` 相同的 [`ScoreRequest`]，例如
+//!   `{"behavior_type":"持續注意力指敲","value":72,"unit":"次"}`
+//! - 逗號分隔：`行為,數值[,單位]`
+//!
+//! 來源（[`StreamSource`]）：
+//!
+//! | URI | 說明 |
+//! |-----|------|
+//! | `tcp://127.0.0.1:8751` | 監聽 TCP，可同時接受多個連線 |
+//! | `udp://127.0.0.1:8751` | 監聽 UDP，一個封包可含多行 |
+//! | `pipe:/tmp/bbv.fifo` | 讀取具名管道（Unix FIFO 需先以 `mkfifo` 建立；Windows 為 `\\.\pipe\名稱`） |
+//!
+//! Lab Streaming Layer（LSL）串流可經 `tools/lsl_bridge.py` 轉送到 UDP 來源。
+//! 未提供時間戳記的樣本以接收時間計。
+
+use crate::error::{BrainVizError, Result};
+use crate::model::BehaviorInput;
+use crate::server::ScoreRequest;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+/// 預設的串流監聽位址（HTTP 服務的下一個埠）
+pub const DEFAULT_STREAM_ADDR: &str = "127.0.0.1:8751";
+
+/// 預設的滑動時間窗（秒）
+pub const DEFAULT_WINDOW_SECS: f32 = 10.0;
+
+/// 同一行為兩次計分的最短間隔（秒），避免高頻樣本使歷史記錄暴增
+pub const DEFAULT_SCORE_INTERVAL_SECS: f32 = 1.0;
+
+/// 背景執行緒檢查停止旗標的間隔
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// UDP 封包大小上限
+const MAX_DATAGRAM_BYTES: usize = 64 * 1024;
+
+/// 單行樣本大小上限；超過的行略過並送出警告
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// TCP 與管道每次讀取的緩衝區大小
+const READ_BUFFER_BYTES: usize = 8 * 1024;
+
+/// 串流來源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamSource {
+    Tcp(String),
+    Udp(String),
+    Pipe(PathBuf),
+}
+
+impl StreamSource {
+    /// 解析 `tcp://位址`、`udp://位址` 或 `pipe:路徑`
+    pub fn parse(uri: &str) -> Result<Self> {
+        let uri = uri.trim();
+        let source = if let Some(addr) = uri.strip_prefix("tcp://") {
+            StreamSource::Tcp(addr.to_string())
+        } else if let Some(addr) = uri.strip_prefix("udp://") {
+            StreamSource::Udp(addr.to_string())
+        } else if let Some(path) = uri.strip_prefix("pipe:") {
+            StreamSource::Pipe(PathBuf::from(path))
+        } else {
+            return Err(BrainVizError::Config(format!(
+                "無法辨識的串流來源「{}」，可用 tcp://位址、udp://位址 或 pipe:路徑",
+                uri
+            )));
+        };
+        match &source {
+            StreamSource::Tcp(addr) | StreamSource::Udp(addr) if addr.is_empty() => {
+                Err(BrainVizError::Config(format!("串流來源「{}」缺少位址", uri)))
+            }
+            StreamSource::Pipe(path) if path.as_os_str().is_empty() => {
+                Err(BrainVizError::Config(format!("串流來源「{}」缺少路徑", uri)))
+            }
+            _ => Ok(source),
+        }
+    }
+}
+
+impl std::fmt::Display for StreamSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamSource::Tcp(addr) => write!(f, "tcp://{}", addr),
+            StreamSource::Udp(addr) => write!(f, "udp://{}", addr),
+            StreamSource::Pipe(path) => write!(f, "pipe:{}", path.display()),
+        }
+    }
+}
+
+/// 一筆行為樣本
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamSample {
+    pub behavior_type: String,
+    pub value: f32,
+    /// 未提供時使用映射表中的預期單位
+    pub unit: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 解析一行樣本；`received` 為沒有時間戳記時使用的時間
+pub fn parse_sample(line: &str, received: DateTime<Utc>) -> std::result::Result<StreamSample, String> {
+    let line = line.trim().trim_start_matches('\u{feff}');
+    if line.starts_with('{') {
+        let request: ScoreRequest = serde_json::from_str(line).map_err(|e| format!("JSON 樣本格式錯誤: {}", e))?;
+        return Ok(StreamSample {
+            behavior_type: request.behavior_type,
+            value: request.value,
+            unit: request.unit.filter(|unit| !unit.is_empty()),
+            timestamp: request.timestamp.unwrap_or(received),
+        });
+    }
+
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    match fields.as_slice() {
+        [behavior, value, rest @ ..] if !behavior.is_empty() && rest.len() <= 1 => Ok(StreamSample {
+            behavior_type: behavior.to_string(),
+            value: value.parse().map_err(|_| format!("樣本數值「{}」不是數字", value))?,
+            unit: rest.first().filter(|unit| !unit.is_empty()).map(|unit| unit.to_string()),
+            timestamp: received,
+        }),
+        _ => Err(format!("無法解析樣本「{}」，格式為 行為,數值[,單位] 或 JSON", line)),
+    }
+}
+
+/// 背景執行緒送出的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    Sample(StreamSample),
+    /// 新的連線或管道已開啟，內容為對方位址或路徑
+    Connected(String),
+    /// 無法解析的樣本或連線錯誤；串流仍繼續
+    Warning(String),
+}
+
+/// 執行中的串流；丟棄時停止監聽
+pub struct StreamHandle {
+    pub source: StreamSource,
+    /// 實際監聽的位址（埠號為 0 時由系統指派）；管道為 `None`
+    pub local_addr: Option<std::net::SocketAddr>,
+    receiver: Receiver<StreamEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl StreamHandle {
+    /// 取出目前已收到的事件，不會阻塞
+    pub fn try_events(&self) -> Vec<StreamEvent> {
+        self.receiver.try_iter().collect()
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 開始在背景接收樣本；每收到事件呼叫一次 `notify`（例如請介面重繪）
+///
+/// 綁定位址失敗時立即回傳錯誤。Unix FIFO 在沒有寫入端時仍會檢查停止旗標；
+/// 其他平台的管道讀取會阻塞，停止後要等寫入端送出資料或關閉才會結束執行緒。
+pub fn start(source: StreamSource, notify: impl Fn() + Send + Sync + 'static) -> Result<StreamHandle> {
+    let (sender, receiver) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let notify: Arc<dyn Fn() + Send + Sync> = Arc::new(notify);
+    let events = EventSender { sender, notify, stop: Arc::clone(&stop) };
+    let bind_error = |addr: &str, e: std::io::Error| BrainVizError::Config(format!("無法監聽串流位址 {}: {}", addr, e));
+
+    let local_addr = match &source {
+        StreamSource::Tcp(addr) => {
+            let listener = TcpListener::bind(addr.as_str()).map_err(|e| bind_error(addr, e))?;
+            listener.set_nonblocking(true).map_err(|e| bind_error(addr, e))?;
+            let local_addr = listener.local_addr().ok();
+            std::thread::spawn(move || accept_tcp(listener, events));
+            local_addr
+        }
+        StreamSource::Udp(addr) => {
+            let socket = UdpSocket::bind(addr.as_str()).map_err(|e| bind_error(addr, e))?;
+            socket.set_read_timeout(Some(POLL_INTERVAL)).map_err(|e| bind_error(addr, e))?;
+            let local_addr = socket.local_addr().ok();
+            std::thread::spawn(move || receive_udp(socket, events));
+            local_addr
+        }
+        StreamSource::Pipe(path) => {
+            let path = path.clone();
+            std::thread::spawn(move || read_pipe(path, events));
+            None
+        }
+    };
+
+    Ok(StreamHandle { source, local_addr, receiver, stop })
+}
+
+/// 背景執行緒共用的事件出口
+#[derive(Clone)]
+struct EventSender {
+    sender: Sender<StreamEvent>,
+    notify: Arc<dyn Fn() + Send + Sync>,
+    stop: Arc<AtomicBool>,
+}
+
+impl EventSender {
+    /// 送出事件；接收端已關閉或要求停止時回傳 false
+    fn send(&self, event: StreamEvent) -> bool {
+        let sent = self.sender.send(event).is_ok();
+        (self.notify)();
+        sent && !self.stopped()
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// 逐行解析並送出；回傳 false 表示應結束
+    fn send_lines(&self, text: &str) -> bool {
+        let received = Utc::now();
+        text.lines().filter(|line| !line.trim().is_empty()).all(|line| {
+            self.send(match parse_sample(line, received) {
+                Ok(sample) => StreamEvent::Sample(sample),
+                Err(message) => StreamEvent::Warning(message),
+            })
+        })
+    }
+}
+
+fn accept_tcp(listener: TcpListener, events: EventSender) {
+    while !events.stopped() {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                if !events.send(StreamEvent::Connected(peer.to_string())) {
+                    return;
+                }
+                let events = events.clone();
+                std::thread::spawn(move || read_tcp(stream, events));
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                if !events.send(StreamEvent::Warning(format!("無法接受串流連線: {}", e))) {
+                    return;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+fn read_tcp(mut stream: TcpStream, events: EventSender) {
+    // 接受的連線可能繼承非阻塞模式，改為逾時讀取以便檢查停止旗標
+    if stream.set_nonblocking(false).and_then(|_| stream.set_read_timeout(Some(POLL_INTERVAL))).is_err() {
+        return;
+    }
+    let mut buffer = vec![0u8; READ_BUFFER_BYTES];
+    let mut lines = LineAssembler::default();
+    while !events.stopped() {
+        match stream.read(&mut buffer) {
+            Ok(0) => {
+                lines.finish(&events);
+                return;
+            }
+            Ok(len) => {
+                if !lines.push(&buffer[..len], &events) {
+                    return;
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => {
+                events.send(StreamEvent::Warning(format!("串流連線中斷: {}", e)));
+                return;
+            }
+        }
+    }
+}
+
+fn receive_udp(socket: UdpSocket, events: EventSender) {
+    let mut buffer = vec![0u8; MAX_DATAGRAM_BYTES];
+    while !events.stopped() {
+        match socket.recv_from(&mut buffer) {
+            Ok((len, _)) => {
+                if !events.send_lines(&String::from_utf8_lossy(&buffer[..len])) {
+                    return;
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => {
+                if !events.send(StreamEvent::Warning(format!("UDP 接收失敗: {}", e))) {
+                    return;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// 把任意切分的位元組組回完整的行；整行到齊才解碼，超過 `MAX_LINE_BYTES` 的行略過
+#[derive(Default)]
+struct LineAssembler {
+    line: Vec<u8>,
+    oversized: bool,
+}
+
+impl LineAssembler {
+    /// 加入收到的位元組並送出其中完整的行；回傳 false 表示應結束
+    fn push(&mut self, mut bytes: &[u8], events: &EventSender) -> bool {
+        while !bytes.is_empty() {
+            let end = bytes.iter().position(|&b| b == b'\n').map(|i| i + 1);
+            let (chunk, rest) = bytes.split_at(end.unwrap_or(bytes.len()));
+            bytes = rest;
+            // 超過上限的行丟棄到下一個換行為止
+            if !self.oversized {
+                self.line.extend_from_slice(chunk);
+                if self.line.len() > MAX_LINE_BYTES {
+                    self.line.clear();
+                    self.oversized = true;
+                    if !events.send(StreamEvent::Warning(format!("串流樣本超過 {} 位元組，已略過", MAX_LINE_BYTES))) {
+                        return false;
+                    }
+                }
+            }
+            if end.is_some() && !self.finish(events) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 送出目前累積的行（來源結束時最後一行可能沒有換行）
+    fn finish(&mut self, events: &EventSender) -> bool {
+        let line = std::mem::take(&mut self.line);
+        std::mem::take(&mut self.oversized) || events.send_lines(&String::from_utf8_lossy(&line))
+    }
+}
+
+/// 開啟管道；Unix 以非阻塞模式開啟，沒有寫入端時不會卡住，可隨時檢查停止旗標
+fn open_pipe(path: &std::path::Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NONBLOCK);
+    options.open(path)
+}
+
+/// 讀取具名管道；寫入端關閉後等待下一個寫入者
+///
+/// Unix FIFO 以非阻塞方式輪詢：讀到結尾表示目前沒有寫入端，稍後再讀即可接上新的寫入者。
+/// 其他平台在寫入端關閉後重新開啟管道。
+fn read_pipe(path: PathBuf, events: EventSender) {
+    let mut buffer = vec![0u8; READ_BUFFER_BYTES];
+    while !events.stopped() {
+        let mut file = match open_pipe(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                events.send(StreamEvent::Warning(format!("無法開啟管道 {}: {}", path.display(), e)));
+                return;
+            }
+        };
+        // 一般檔案沒有寫入者的概念，讀完即結束
+        let regular = file.metadata().map_or(true, |m| m.is_file());
+        let mut lines = LineAssembler::default();
+        let mut connected = false;
+        while !events.stopped() {
+            let read = file.read(&mut buffer);
+            // 非阻塞讀取回傳 WouldBlock 表示寫入端已開啟但尚無資料
+            let writer_present = matches!(&read, Ok(1..)) || matches!(&read, Err(e) if e.kind() == ErrorKind::WouldBlock);
+            if writer_present
+                && !std::mem::replace(&mut connected, true)
+                && !events.send(StreamEvent::Connected(path.display().to_string()))
+            {
+                return;
+            }
+            match read {
+                Ok(0) => {
+                    if !lines.finish(&events) || regular {
+                        return;
+                    }
+                    connected = false;
+                    if !cfg!(unix) {
+                        break;
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Ok(len) => {
+                    if !lines.push(&buffer[..len], &events) {
+                        return;
+                    }
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::Interrupted) => std::thread::sleep(POLL_INTERVAL),
+                Err(e) => {
+                    events.send(StreamEvent::Warning(format!("讀取管道 {} 失敗: {}", path.display(), e)));
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// 依滑動時間窗彙整樣本：每個行為的計分輸入為時間窗內樣本的平均值
+#[derive(Debug, Clone)]
+pub struct SlidingWindow {
+    window: Duration,
+    interval: Duration,
+    behaviors: Vec<BehaviorWindow>,
+}
+
+#[derive(Debug, Clone)]
+struct BehaviorWindow {
+    behavior_type: String,
+    unit: Option<String>,
+    samples: VecDeque<(DateTime<Utc>, f32)>,
+    /// 上次計分的時間（接收端時鐘）
+    last_scored: Option<DateTime<Utc>>,
+    pending: bool,
+}
+
+impl Default for SlidingWindow {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW_SECS, DEFAULT_SCORE_INTERVAL_SECS)
+    }
+}
+
+impl SlidingWindow {
+    /// `window_secs` 為時間窗長度，`interval_secs` 為同一行為兩次計分的最短間隔
+    pub fn new(window_secs: f32, interval_secs: f32) -> Self {
+        let millis = |secs: f32| Duration::milliseconds((secs.max(0.0) * 1000.0) as i64);
+        Self {
+            window: millis(window_secs),
+            interval: millis(interval_secs),
+            behaviors: Vec::new(),
+        }
+    }
+
+    pub fn window_secs(&self) -> f32 {
+        self.window.num_milliseconds() as f32 / 1000.0
+    }
+
+    /// 加入樣本；超出時間窗（以該行為最新的樣本時間為準）的舊樣本會被移除
+    pub fn push(&mut self, sample: StreamSample) {
+        let index = match self.behaviors.iter().position(|b| b.behavior_type == sample.behavior_type) {
+            Some(index) => index,
+            None => {
+                self.behaviors.push(BehaviorWindow {
+                    behavior_type: sample.behavior_type.clone(),
+                    unit: None,
+                    samples: VecDeque::new(),
+                    last_scored: None,
+                    pending: false,
+                });
+                self.behaviors.len() - 1
+            }
+        };
+        let behavior = &mut self.behaviors[index];
+        if sample.unit.is_some() {
+            behavior.unit = sample.unit;
+        }
+        // 依時間排序插入，容許稍微亂序的樣本
+        let position = behavior.samples.iter().rposition(|(t, _)| *t <= sample.timestamp).map_or(0, |i| i + 1);
+        behavior.samples.insert(position, (sample.timestamp, sample.value));
+        let newest = behavior.samples.back().map(|(t, _)| *t).unwrap_or(sample.timestamp);
+        while behavior.samples.front().is_some_and(|(t, _)| *t < newest - self.window) {
+            behavior.samples.pop_front();
+        }
+        behavior.pending = true;
+    }
+
+    /// 目前時間窗內的樣本數
+    pub fn sample_count(&self, behavior_type: &str) -> usize {
+        self.behaviors
+            .iter()
+            .find(|b| b.behavior_type == behavior_type)
+            .map_or(0, |b| b.samples.len())
+    }
+
+    /// 有新樣本且距上次計分已超過間隔的行為，回傳其時間窗平均值組成的輸入
+    ///
+    /// 未提供單位的樣本，單位以 `default_unit(行為)` 補上。
+    pub fn due(&mut self, now: DateTime<Utc>, default_unit: impl Fn(&str) -> String) -> Vec<BehaviorInput> {
+        let interval = self.interval;
+        self.behaviors
+            .iter_mut()
+            .filter(|b| b.pending && !b.samples.is_empty())
+            .filter(|b| b.last_scored.is_none_or(|last| now - last >= interval))
+            .map(|b| {
+                b.pending = false;
+                b.last_scored = Some(now);
+                let value = b.samples.iter().map(|(_, v)| f64::from(*v)).sum::<f64>() / b.samples.len() as f64;
+                BehaviorInput {
+                    behavior_type: b.behavior_type.clone(),
+                    value: value as f32,
+                    unit: b.unit.clone().unwrap_or_else(|| default_unit(&b.behavior_type)),
+                    timestamp: b.samples.back().map_or(now, |(t, _)| *t),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::io::Write;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap() + Duration::seconds(secs)
+    }
+
+    fn sample(behavior: &str, value: f32, secs: i64) -> StreamSample {
+        StreamSample {
+            behavior_type: behavior.to_string(),
+            value,
+            unit: None,
+            timestamp: at(secs),
+        }
+    }
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(StreamSource::parse("tcp://127.0.0.1:8751").unwrap(), StreamSource::Tcp("127.0.0.1:8751".to_string()));
+        assert_eq!(StreamSource::parse(" udp://0.0.0.0:9000").unwrap().to_string(), "udp://0.0.0.0:9000");
+        assert_eq!(StreamSource::parse("pipe:/tmp/bbv.fifo").unwrap(), StreamSource::Pipe(PathBuf::from("/tmp/bbv.fifo")));
+        assert!(StreamSource::parse("http://localhost").is_err());
+        assert!(StreamSource::parse("tcp://").is_err());
+    }
+
+    #[test]
+    fn test_parse_sample() {
+        let received = at(0);
+        let json = parse_sample(r#"{"behavior_type":"持續注意力指敲","value":72,"unit":"次","timestamp":"2024-01-01T09:00:05Z"}"#, received).unwrap();
+        assert_eq!(json.value, 72.0);
+        assert_eq!(json.unit.as_deref(), Some("次"));
+        assert_eq!(json.timestamp, at(5));
+
+        let text = parse_sample("自然步行, 0.85\r\n", received).unwrap();
+        assert_eq!(text.behavior_type, "自然步行");
+        assert_eq!(text.unit, None);
+        assert_eq!(text.timestamp, received);
+        assert_eq!(parse_sample("自然步行,0.85,公尺/秒", received).unwrap().unit.as_deref(), Some("公尺/秒"));
+
+        assert!(parse_sample("自然步行", received).is_err());
+        assert!(parse_sample("自然步行,快", received).is_err());
+        assert!(parse_sample("{\"value\":1}", received).is_err());
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let mut window = SlidingWindow::new(10.0, 1.0);
+        let unit = |_: &str| "次".to_string();
+        window.push(sample("注意力", 60.0, 0));
+        window.push(sample("注意力", 80.0, 5));
+
+        let due = window.due(at(5), unit);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].value, 70.0);
+        assert_eq!(due[0].unit, "次");
+        assert_eq!(due[0].timestamp, at(5));
+        // 沒有新樣本時不再計分
+        assert!(window.due(at(7), unit).is_empty());
+
+        // 第 12 秒的樣本使第 0 秒的樣本離開時間窗；亂序樣本依時間插入
+        window.push(sample("注意力", 100.0, 12));
+        window.push(sample("注意力", 90.0, 11));
+        assert_eq!(window.sample_count("注意力"), 3);
+        // 距上次計分不到 1 秒時延後
+        assert!(window.due(at(5) + Duration::milliseconds(500), unit).is_empty());
+        let due = window.due(at(12), unit);
+        assert_eq!(due[0].value, 90.0);
+        assert_eq!(due[0].timestamp, at(12));
+    }
+
+    /// 等待直到收到 `count` 筆樣本或逾時，回傳期間的所有事件
+    fn wait_for(handle: &StreamHandle, count: usize) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            events.extend(handle.try_events());
+            if samples(&events).len() >= count {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        events
+    }
+
+    fn samples(events: &[StreamEvent]) -> Vec<&StreamSample> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::Sample(sample) => Some(sample),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tcp_and_udp_streams() {
+        let tcp = start(StreamSource::Tcp("127.0.0.1:0".to_string()), || {}).unwrap();
+        let mut client = TcpStream::connect(tcp.local_addr.unwrap()).unwrap();
+        client.write_all("注意力,60\n不是樣本\n注意力,".as_bytes()).unwrap();
+        client.flush().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(150));
+        client.write_all("80,次\n".as_bytes()).unwrap();
+        let events = wait_for(&tcp, 2);
+        assert!(matches!(events[0], StreamEvent::Connected(_)));
+        let samples = samples(&events);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].value, 80.0);
+        assert!(events.iter().any(|e| matches!(e, StreamEvent::Warning(_))));
+
+        let udp = start(StreamSource::Udp("127.0.0.1:0".to_string()), || {}).unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.send_to("步行,0.8\n步行,0.9".as_bytes(), udp.local_addr.unwrap()).unwrap();
+        let events = wait_for(&udp, 2);
+        assert_eq!(events.iter().filter(|e| matches!(e, StreamEvent::Sample(_))).count(), 2);
+
+        // 一般檔案讀完即結束
+        let path = std::env::temp_dir().join("bbv_stream_pipe.txt");
+        std::fs::write(&path, "步行,0.8\n").unwrap();
+        let pipe = start(StreamSource::Pipe(path.clone()), || {}).unwrap();
+        let events = wait_for(&pipe, 1);
+        assert!(matches!(&events[..], [StreamEvent::Connected(_), StreamEvent::Sample(_)]));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_tcp_split_character_and_long_line() {
+        let tcp = start(StreamSource::Tcp("127.0.0.1:0".to_string()), || {}).unwrap();
+        let mut client = TcpStream::connect(tcp.local_addr.unwrap()).unwrap();
+
+        // 在「注」的三個位元組中間停頓，超過讀取逾時
+        let line = "持續注意力指敲,72\n".as_bytes();
+        let split = "持續".len() + 1;
+        client.write_all(&line[..split]).unwrap();
+        client.flush().unwrap();
+        std::thread::sleep(POLL_INTERVAL * 3);
+        client.write_all(&line[split..]).unwrap();
+
+        // 超長的行略過，之後的樣本照常解析；最後一行沒有換行
+        client.write_all(&vec![b'x'; MAX_LINE_BYTES * 2]).unwrap();
+        client.write_all("\n步行,0.8".as_bytes()).unwrap();
+        drop(client);
+
+        let events = wait_for(&tcp, 2);
+        let received = samples(&events);
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].behavior_type, "持續注意力指敲");
+        assert_eq!(received[0].value, 72.0);
+        assert_eq!(received[1].behavior_type, "步行");
+        let warnings: Vec<_> = events.iter().filter(|e| matches!(e, StreamEvent::Warning(_))).collect();
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fifo_reconnect_and_stop() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::env::temp_dir().join(format!("bbv_stream_{}.fifo", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+        // notify 閉包持有的計數在背景執行緒結束時釋放
+        let alive = Arc::new(());
+        let marker = Arc::clone(&alive);
+        let pipe = start(StreamSource::Pipe(path.clone()), move || {
+            let _ = &marker;
+        })
+        .unwrap();
+
+        // 寫入端關閉後，下一個寫入者仍可接上
+        for value in ["0.8", "0.9"] {
+            let mut writer = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+            writeln!(writer, "步行,{}", value).unwrap();
+            drop(writer);
+            let events = wait_for(&pipe, 1);
+            assert!(matches!(&events[..], [StreamEvent::Connected(_), StreamEvent::Sample(_)]));
+        }
+
+        // 沒有寫入端時停止也會結束執行緒
+        drop(pipe);
+        for _ in 0..50 {
+            if Arc::strong_count(&alive) == 1 {
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        assert_eq!(Arc::strong_count(&alive), 1);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,835 @@
+//! 試次層級資料匯入與行為指標推導
+//!
+//! Flanker、N-back、指敲與愛荷華賭局作業（IGT）的原始輸出是每個試次一列的表格，
+//! 引擎需要的卻是單一摘要數值。本模組讀取 PsychoPy（逗號分隔 CSV）與 E-Prime
+//! （E-DataAid 匯出的 Tab 分隔文字檔，常為 UTF-16）格式的試次表（[`TrialTable`]），
+//! 依作業推導指標（[`Metric`]），再依 `data/task_metrics.json`（[`TaskProfiles`]）轉成 [`BehaviorInput`]。
+//!
+//! 欄位依常見名稱自動辨識（見 [`ColumnRole::aliases`]），也可用 `角色=欄位名稱` 覆寫。
+//! 檔案中有受試者欄位（PsychoPy `participant`、E-Prime `Subject`）時，各受試者分開推導。
+
+use crate::error::{read_json, BrainVizError, Result};
+use crate::export::split_csv_line;
+use crate::gait::{self, GaitOptions};
+use crate::model::BehaviorInput;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 試次表
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrialTable {
+    pub path: PathBuf,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// 標題列的行號（從 1 起算），用於錯誤訊息
+    header_line: usize,
+}
+
+impl TrialTable {
+    /// 讀取試次檔；支援 UTF-8 與帶 BOM 的 UTF-16
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| BrainVizError::io(path, e))?;
+        let text = decode_text(&bytes).ok_or_else(|| BrainVizError::Parse {
+            path: path.to_path_buf(),
+            line: 1,
+            column: 1,
+            message: "無法辨識的文字編碼（需為 UTF-8 或 UTF-16）".to_string(),
+        })?;
+        Self::parse(path, &text)
+    }
+
+    /// 解析試次表文字；分隔符號（逗號、Tab、分號）依標題列判斷，
+    /// 標題列之前只有單一欄位的說明行（E-DataAid 會寫入檔案路徑）會略過。
+    /// 整個檔案都只有一欄時（例如每行一個敲擊時間）以第一行為標題
+    pub fn parse(path: impl AsRef<Path>, text: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let lines: Vec<(usize, &str)> = text
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .collect();
+        let delimiter = |line: &str| {
+            [',', '\t', ';']
+                .into_iter()
+                .max_by_key(|d| line.matches(*d).count())
+                .filter(|d| line.contains(*d))
+        };
+        if lines.is_empty() {
+            return Err(BrainVizError::Parse {
+                path: path.to_path_buf(),
+                line: 1,
+                column: 1,
+                message: "試次檔沒有內容".to_string(),
+            });
+        }
+        let header_index = lines.iter().position(|(_, line)| delimiter(line).is_some()).unwrap_or(0);
+        let (header_line, header) = lines[header_index];
+        let delimiter = delimiter(header).unwrap_or(',');
+        let split = |line: &str| -> Vec<String> {
+            if delimiter == ',' {
+                split_csv_line(line)
+            } else {
+                line.split(delimiter).map(|field| field.trim_matches('"').to_string()).collect()
+            }
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            columns: split(header).into_iter().map(|c| c.trim().to_string()).collect(),
+            rows: lines[header_index + 1..].iter().map(|(_, line)| split(line)).collect(),
+            header_line: header_line + 1,
+        })
+    }
+
+    /// 依覆寫或別名找出欄位索引
+    pub fn find_column(&self, role: ColumnRole, overrides: &ColumnOverrides) -> Option<usize> {
+        if let Some(name) = overrides.get(role) {
+            return self.columns.iter().position(|c| c == name);
+        }
+        let lower: Vec<String> = self.columns.iter().map(|c| c.to_lowercase()).collect();
+        role.aliases().iter().find_map(|alias| {
+            lower.iter().position(|column| match alias.strip_prefix('*') {
+                Some(suffix) => column.ends_with(suffix),
+                None => column == alias,
+            })
+        })
+    }
+
+    pub(crate) fn missing_column(&self, role: ColumnRole, overrides: &ColumnOverrides) -> BrainVizError {
+        self.header_error(match overrides.get(role) {
+            Some(name) => format!("找不到指定的{}欄位「{}」", role.label(), name),
+            None => format!("找不到{}欄位，可用 {}=欄位名稱 指定", role.label(), role.key()),
+        })
+    }
+
+    /// 指向標題列的解析錯誤
+    pub(crate) fn header_error(&self, message: String) -> BrainVizError {
+        BrainVizError::Parse {
+            path: self.path.clone(),
+            line: self.header_line,
+            column: 1,
+            message,
+        }
+    }
+
+    pub(crate) fn cell<'a>(&self, row: &'a [String], column: Option<usize>) -> Option<&'a str> {
+        column
+            .and_then(|c| row.get(c))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty() && !is_missing(value))
+    }
+
+    /// 依受試者欄位分組；沒有受試者欄位時整個檔案為一組
+    pub(crate) fn subjects(&self, overrides: &ColumnOverrides) -> Vec<(Option<String>, Vec<&Vec<String>>)> {
+        let Some(column) = self.find_column(ColumnRole::Subject, overrides) else {
+            return vec![(None, self.rows.iter().collect())];
+        };
+        let mut groups: Vec<(Option<String>, Vec<&Vec<String>>)> = Vec::new();
+        for row in &self.rows {
+            let subject = self.cell(row, Some(column)).map(str::to_string);
+            match groups.iter_mut().find(|(s, _)| *s == subject) {
+                Some((_, rows)) => rows.push(row),
+                None => groups.push((subject, vec![row])),
+            }
+        }
+        groups
+    }
+}
+
+/// UTF-16（依 BOM）或 UTF-8
+fn decode_text(bytes: &[u8]) -> Option<String> {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| from([pair[0], pair[1]])).collect();
+        String::from_utf16(&units).ok()
+    };
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8(bytes.to_vec()).ok(),
+    }
+}
+
+/// PsychoPy 與 E-Prime 表示無資料的值
+fn is_missing(value: &str) -> bool {
+    matches!(value, "None" | "NA" | "NaN" | "nan" | "NULL" | "[]" | "?")
+}
+
+/// 是否為「真」：1、true、yes、y 等
+fn is_truthy(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "1.0" | "true" | "yes" | "y" | "correct" | "target" | "match" => Some(true),
+        "0" | "0.0" | "false" | "no" | "n" | "incorrect" | "error" | "nontarget" | "non-target" | "lure" | "nonmatch" => Some(false),
+        _ => None,
+    }
+}
+
+/// 解析數值；PsychoPy 多次按鍵的 `[0.31, 0.62]` 取所有數值
+pub(crate) fn parse_numbers(value: &str) -> Vec<f64> {
+    value
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .filter_map(|part| part.trim().parse::<f64>().ok())
+        .filter(|n| n.is_finite())
+        .collect()
+}
+
+/// 試次表中的欄位角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnRole {
+    Subject,
+    /// 反應時間（秒或毫秒，自動判斷）
+    Rt,
+    /// 是否正確（1/0、True/False）
+    Correct,
+    /// Flanker 一致或不一致
+    Condition,
+    /// N-back 是否為目標試次
+    Target,
+    /// 按鍵（有值表示有反應）
+    Response,
+    /// 指敲時間（每次敲擊一列，或一格中的多個時間），或感測器的取樣時間
+    Time,
+    /// 每個試次的敲擊次數
+    Taps,
+    /// 每個試次的秒數
+    Duration,
+    /// IGT 選擇的牌堆
+    Deck,
+    /// 三軸加速度（g 或 m/s²）
+    AccX,
+    AccY,
+    AccZ,
+}
+
+impl ColumnRole {
+    pub const ALL: [ColumnRole; 13] = [
+        ColumnRole::Subject,
+        ColumnRole::Rt,
+        ColumnRole::Correct,
+        ColumnRole::Condition,
+        ColumnRole::Target,
+        ColumnRole::Response,
+        ColumnRole::Time,
+        ColumnRole::Taps,
+        ColumnRole::Duration,
+        ColumnRole::Deck,
+        ColumnRole::AccX,
+        ColumnRole::AccY,
+        ColumnRole::AccZ,
+    ];
+
+    /// 覆寫時使用的名稱
+    pub fn key(&self) -> &'static str {
+        match self {
+            ColumnRole::Subject => "subject",
+            ColumnRole::Rt => "rt",
+            ColumnRole::Correct => "correct",
+            ColumnRole::Condition => "condition",
+            ColumnRole::Target => "target",
+            ColumnRole::Response => "response",
+            ColumnRole::Time => "time",
+            ColumnRole::Taps => "taps",
+            ColumnRole::Duration => "duration",
+            ColumnRole::Deck => "deck",
+            ColumnRole::AccX => "acc_x",
+            ColumnRole::AccY => "acc_y",
+            ColumnRole::AccZ => "acc_z",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ColumnRole::Subject => "受試者",
+            ColumnRole::Rt => "反應時間",
+            ColumnRole::Correct => "正確與否",
+            ColumnRole::Condition => "一致性條件",
+            ColumnRole::Target => "目標試次",
+            ColumnRole::Response => "反應",
+            ColumnRole::Time => "時間",
+            ColumnRole::Taps => "敲擊次數",
+            ColumnRole::Duration => "試次秒數",
+            ColumnRole::Deck => "牌堆",
+            ColumnRole::AccX => "X 軸加速度",
+            ColumnRole::AccY => "Y 軸加速度",
+            ColumnRole::AccZ => "Z 軸加速度",
+        }
+    }
+
+    /// 小寫的欄位別名，依優先順序；`*` 開頭表示比對結尾（如 PsychoPy 的 `key_resp.rt`、E-Prime 的 `Stimulus.RT`）。
+    /// 加速度與時間另含 phyphox（`Acceleration x (m/s^2)`、`Time (s)`）與 Sensor Logger（`seconds_elapsed`）的欄位名稱
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            ColumnRole::Subject => &["participant", "subject", "subject_id", "participant_id"],
+            ColumnRole::Rt => &["rt", "response_time", "reaction_time", "*.rt", "*_rt"],
+            ColumnRole::Correct => &["correct", "corr", "acc", "accuracy", "*.corr", "*.acc", "*_corr", "*_acc"],
+            ColumnRole::Condition => &["congruency", "congruent", "condition", "trial_type", "trialtype"],
+            ColumnRole::Target => &["target", "is_target", "istarget", "match", "*.target"],
+            ColumnRole::Response => &["response", "resp", "keys", "*.keys", "*.resp", "*_keys", "*_resp"],
+            ColumnRole::Time => &["tap_time", "time", "timestamp", "onset", "*.tap_time", "time (s)", "seconds_elapsed", "t"],
+            ColumnRole::Taps => &["taps", "tap_count", "n_taps", "count"],
+            ColumnRole::Duration => &["duration", "trial_duration", "secs", "seconds"],
+            ColumnRole::Deck => &["deck", "choice", "deck_choice", "*.deck"],
+            ColumnRole::AccX => &["acc_x", "accel_x", "accx", "ax", "x", "acceleration_x", "acceleration x (m/s^2)", "*_acc_x", "*.acc_x", "*_accel_x"],
+            ColumnRole::AccY => &["acc_y", "accel_y", "accy", "ay", "y", "acceleration_y", "acceleration y (m/s^2)", "*_acc_y", "*.acc_y", "*_accel_y"],
+            ColumnRole::AccZ => &["acc_z", "accel_z", "accz", "az", "z", "acceleration_z", "acceleration z (m/s^2)", "*_acc_z", "*.acc_z", "*_accel_z"],
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|role| role.key() == key.trim().to_lowercase())
+    }
+}
+
+/// 欄位覆寫（角色 → 欄位名稱）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnOverrides(Vec<(ColumnRole, String)>);
+
+impl ColumnOverrides {
+    /// 解析 `角色=欄位名稱`，例如 `rt=Stimulus.RT`
+    pub fn parse(specs: &[String]) -> Result<Self> {
+        let mut overrides = Vec::new();
+        for spec in specs {
+            let role = spec
+                .split_once('=')
+                .and_then(|(key, column)| Some((ColumnRole::from_key(key)?, column.trim())))
+                .filter(|(_, column)| !column.is_empty());
+            match role {
+                Some((role, column)) => overrides.push((role, column.to_string())),
+                None => {
+                    let roles: Vec<&str> = ColumnRole::ALL.iter().map(ColumnRole::key).collect();
+                    return Err(BrainVizError::Config(format!(
+                        "欄位對應「{}」格式錯誤，需為 角色=欄位名稱，角色可為 {}",
+                        spec,
+                        roles.join("、")
+                    )));
+                }
+            }
+        }
+        Ok(Self(overrides))
+    }
+
+    pub fn get(&self, role: ColumnRole) -> Option<&str> {
+        self.0.iter().rev().find(|(r, _)| *r == role).map(|(_, column)| column.as_str())
+    }
+}
+
+/// 作業類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Task {
+    Flanker,
+    NBack,
+    Tapping,
+    Igt,
+    /// 穿戴式加速度計的自然步行紀錄（見 [`crate::gait`]）
+    Gait,
+}
+
+impl Task {
+    pub const ALL: [Task; 5] = [Task::Flanker, Task::NBack, Task::Tapping, Task::Igt, Task::Gait];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Task::Flanker => "flanker",
+            Task::NBack => "n_back",
+            Task::Tapping => "tapping",
+            Task::Igt => "igt",
+            Task::Gait => "gait",
+        }
+    }
+
+    /// 解析作業名稱（不分大小寫，`nback`、`n-back` 皆可）
+    pub fn parse(name: &str) -> Option<Self> {
+        let normalized = name.trim().to_lowercase().replace(['-', ' '], "_");
+        match normalized.as_str() {
+            "nback" => Some(Task::NBack),
+            "finger_tapping" => Some(Task::Tapping),
+            "iowa_gambling_task" => Some(Task::Igt),
+            "walking" | "imu" => Some(Task::Gait),
+            _ => Self::ALL.into_iter().find(|task| task.id() == normalized),
+        }
+    }
+
+    /// 此作業推導的指標
+    pub fn metrics(&self) -> &'static [Metric] {
+        match self {
+            Task::Flanker => &[Metric::Accuracy, Metric::MeanRt, Metric::CongruencyEffect],
+            Task::NBack => &[Metric::Accuracy, Metric::MeanRt, Metric::DPrime],
+            Task::Tapping => &[Metric::TapCount, Metric::TapRate],
+            Task::Igt => &[Metric::NetScore],
+            Task::Gait => &[Metric::GaitSpeed, Metric::Cadence, Metric::StrideTimeCv, Metric::StepAsymmetry],
+        }
+    }
+}
+
+/// 推導的行為指標
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// 正確率（%）
+    Accuracy,
+    /// 正確試次的平均反應時間（毫秒）；沒有正確與否欄位時使用所有有反應的試次
+    MeanRt,
+    /// 不一致 − 一致的平均反應時間（毫秒）
+    CongruencyEffect,
+    /// 訊號偵測 d′，以 log-linear 校正（Hautus, 1995）避免命中率或誤報率為 0 或 1
+    DPrime,
+    /// 總敲擊次數
+    TapCount,
+    /// 每秒敲擊次數
+    TapRate,
+    /// IGT 淨分數：(C + D) − (A + B)
+    NetScore,
+    /// 步行速度（公尺/秒）
+    GaitSpeed,
+    /// 步頻（步/分）
+    Cadence,
+    /// 步幅時間變異係數（%）
+    StrideTimeCv,
+    /// 相鄰兩步的步時間不對稱指數（%）
+    StepAsymmetry,
+}
+
+impl Metric {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Metric::Accuracy => "accuracy",
+            Metric::MeanRt => "mean_rt",
+            Metric::CongruencyEffect => "congruency_effect",
+            Metric::DPrime => "d_prime",
+            Metric::TapCount => "tap_count",
+            Metric::TapRate => "tap_rate",
+            Metric::NetScore => "net_score",
+            Metric::GaitSpeed => "gait_speed",
+            Metric::Cadence => "cadence",
+            Metric::StrideTimeCv => "stride_time_cv",
+            Metric::StepAsymmetry => "step_asymmetry",
+        }
+    }
+
+    /// 指標本身的單位；對應到行為時可由 [`TaskProfile::unit`] 改寫
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Metric::Accuracy | Metric::StrideTimeCv | Metric::StepAsymmetry => "%",
+            Metric::MeanRt | Metric::CongruencyEffect => "毫秒",
+            Metric::DPrime | Metric::NetScore => "分數",
+            Metric::TapCount => "次",
+            Metric::TapRate => "次/秒",
+            Metric::GaitSpeed => "公尺/秒",
+            Metric::Cadence => "步/分",
+        }
+    }
+}
+
+/// 一位受試者的一個指標
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivedMetric {
+    pub metric: Metric,
+    pub value: f32,
+    /// 計算此指標使用的試次（或敲擊、步伐）數
+    pub trials: usize,
+}
+
+/// 一位受試者的推導結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubjectMetrics {
+    pub subject_id: Option<String>,
+    pub metrics: Vec<DerivedMetric>,
+    /// 無法計算的指標與原因（例如缺少欄位）
+    pub skipped: Vec<(Metric, String)>,
+}
+
+/// 推導作業的所有指標；個別受試者算不出的指標與原因記錄在 `skipped`，
+/// 所有受試者都沒有任何指標時回傳遇到的第一個錯誤
+///
+/// 步態紀錄以預設的 [`GaitOptions`] 交給 [`gait::derive_metrics`]；需要指定距離或身高時直接呼叫該函式。
+pub fn derive_metrics(table: &TrialTable, task: Task, overrides: &ColumnOverrides) -> Result<Vec<SubjectMetrics>> {
+    if task == Task::Gait {
+        return gait::derive_metrics(table, overrides, &GaitOptions::default());
+    }
+    let mut subjects = Vec::new();
+    let mut first_error = None;
+    for (subject_id, rows) in table.subjects(overrides) {
+        let mut metrics = Vec::new();
+        let mut skipped = Vec::new();
+        for &metric in task.metrics() {
+            match derive_metric(table, &rows, metric, overrides) {
+                Ok(Some(derived)) => metrics.push(derived),
+                Ok(None) => skipped.push((metric, "沒有可用的試次".to_string())),
+                Err(e) => {
+                    skipped.push((metric, e.to_string()));
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        subjects.push(SubjectMetrics { subject_id, metrics, skipped });
+    }
+    if subjects.iter().all(|s| s.metrics.is_empty()) {
+        return Err(first_error.unwrap_or_else(|| table.header_error(format!("沒有可用的試次，無法推導 {} 指標", task.id()))));
+    }
+    Ok(subjects)
+}
+
+fn derive_metric(
+    table: &TrialTable,
+    rows: &[&Vec<String>],
+    metric: Metric,
+    overrides: &ColumnOverrides,
+) -> Result<Option<DerivedMetric>> {
+    let column = |role| table.find_column(role, overrides);
+    let require = |role| column(role).ok_or_else(|| table.missing_column(role, overrides));
+    let derived = |value: f64, trials: usize| Some(DerivedMetric { metric, value: value as f32, trials });
+
+    Ok(match metric {
+        Metric::Accuracy => {
+            let correct = require(ColumnRole::Correct)?;
+            let values: Vec<bool> = rows.iter().filter_map(|row| table.cell(row, Some(correct)).and_then(is_truthy)).collect();
+            (!values.is_empty()).then(|| 100.0 * values.iter().filter(|c| **c).count() as f64 / values.len() as f64).and_then(|v| derived(v, values.len()))
+        }
+        Metric::MeanRt => {
+            let rts = response_times(table, rows, overrides, |_| true)?;
+            mean(&rts).and_then(|m| derived(m, rts.len()))
+        }
+        Metric::CongruencyEffect => {
+            let condition = require(ColumnRole::Condition)?;
+            let congruency = |row: &Vec<String>| {
+                let value = table.cell(row, Some(condition))?.to_lowercase();
+                if value.starts_with("incon") || value == "0" || value == "false" {
+                    Some(false)
+                } else if value.starts_with("con") || value == "1" || value == "true" {
+                    Some(true)
+                } else {
+                    None
+                }
+            };
+            let incongruent = response_times(table, rows, overrides, |row| congruency(row) == Some(false))?;
+            let congruent = response_times(table, rows, overrides, |row| congruency(row) == Some(true))?;
+            match (mean(&incongruent), mean(&congruent)) {
+                (Some(i), Some(c)) => derived(i - c, incongruent.len() + congruent.len()),
+                _ => None,
+            }
+        }
+        Metric::DPrime => {
+            let target = require(ColumnRole::Target)?;
+            let response = column(ColumnRole::Response);
+            let rt = column(ColumnRole::Rt);
+            if response.is_none() && rt.is_none() {
+                return Err(table.missing_column(ColumnRole::Response, overrides));
+            }
+            // 按鍵欄有值，或反應時間大於 0，視為有反應
+            let responded = |row: &Vec<String>| {
+                table.cell(row, response).is_some()
+                    || table.cell(row, rt).is_some_and(|value| parse_numbers(value).iter().any(|n| *n > 0.0))
+            };
+            let (mut hits, mut targets, mut false_alarms, mut non_targets) = (0usize, 0usize, 0usize, 0usize);
+            for row in rows {
+                match table.cell(row, Some(target)).and_then(is_truthy) {
+                    Some(true) => {
+                        targets += 1;
+                        hits += usize::from(responded(row));
+                    }
+                    Some(false) => {
+                        non_targets += 1;
+                        false_alarms += usize::from(responded(row));
+                    }
+                    None => {}
+                }
+            }
+            (targets > 0 && non_targets > 0).then(|| d_prime(hits, targets, false_alarms, non_targets)).and_then(|d| derived(d, targets + non_targets))
+        }
+        Metric::TapCount | Metric::TapRate => {
+            let (count, seconds) = tap_summary(table, rows, overrides)?;
+            match metric {
+                Metric::TapCount => derived(count as f64, count),
+                _ => seconds.filter(|s| *s > 0.0).and_then(|s| derived(count as f64 / s, count)),
+            }
+        }
+        Metric::NetScore => {
+            let deck = require(ColumnRole::Deck)?;
+            let choices: Vec<i32> = rows
+                .iter()
+                .filter_map(|row| match table.cell(row, Some(deck))?.to_uppercase().trim_start_matches("DECK").trim() {
+                    "A" | "1" | "B" | "2" => Some(-1),
+                    "C" | "3" | "D" | "4" => Some(1),
+                    _ => None,
+                })
+                .collect();
+            (!choices.is_empty()).then(|| choices.iter().sum::<i32>() as f64).and_then(|net| derived(net, choices.len()))
+        }
+        // 步態指標需要整段訊號，由 gait 模組一次推導
+        Metric::GaitSpeed | Metric::Cadence | Metric::StrideTimeCv | Metric::StepAsymmetry => None,
+    })
+}
+
+/// 符合條件的反應時間（毫秒）；有正確與否欄位時只取正確試次
+///
+/// 反應時間中位數小於 10 時視為秒（PsychoPy），否則為毫秒（E-Prime）。
+fn response_times(
+    table: &TrialTable,
+    rows: &[&Vec<String>],
+    overrides: &ColumnOverrides,
+    include: impl Fn(&Vec<String>) -> bool,
+) -> Result<Vec<f64>> {
+    let rt = table.find_column(ColumnRole::Rt, overrides).ok_or_else(|| table.missing_column(ColumnRole::Rt, overrides))?;
+    let correct = table.find_column(ColumnRole::Correct, overrides);
+    let mut all: Vec<f64> = rows
+        .iter()
+        .filter_map(|row| table.cell(row, Some(rt)))
+        .filter_map(|value| parse_numbers(value).first().copied())
+        .filter(|rt| *rt > 0.0)
+        .collect();
+    all.sort_by(f64::total_cmp);
+    let scale = if all.get(all.len() / 2).is_some_and(|median| *median < 10.0) { 1000.0 } else { 1.0 };
+
+    Ok(rows
+        .iter()
+        .filter(|row| include(row))
+        .filter(|row| correct.is_none() || table.cell(row, correct).and_then(is_truthy) == Some(true))
+        .filter_map(|row| table.cell(row, Some(rt)).and_then(|value| parse_numbers(value).first().copied()))
+        .filter(|rt| *rt > 0.0)
+        .map(|rt| rt * scale)
+        .collect())
+}
+
+/// 敲擊次數與總秒數
+///
+/// 有敲擊次數欄位時加總各試次（秒數取試次秒數欄位的總和）；
+/// 否則每個敲擊時間算一次，秒數為第一次到最後一次敲擊的間隔（次數 − 1 個間隔）。
+fn tap_summary(table: &TrialTable, rows: &[&Vec<String>], overrides: &ColumnOverrides) -> Result<(usize, Option<f64>)> {
+    if let Some(taps) = table.find_column(ColumnRole::Taps, overrides) {
+        let duration = table.find_column(ColumnRole::Duration, overrides);
+        let count: f64 = rows.iter().filter_map(|row| table.cell(row, Some(taps))).filter_map(|v| v.parse::<f64>().ok()).sum();
+        let seconds: f64 = rows.iter().filter_map(|row| table.cell(row, duration)).filter_map(|v| v.parse::<f64>().ok()).sum();
+        return Ok((count.max(0.0) as usize, (seconds > 0.0).then_some(seconds)));
+    }
+    let time = table
+        .find_column(ColumnRole::Time, overrides)
+        .or_else(|| table.find_column(ColumnRole::Rt, overrides))
+        .ok_or_else(|| table.missing_column(ColumnRole::Taps, overrides))?;
+    let times: Vec<f64> = rows.iter().filter_map(|row| table.cell(row, Some(time))).flat_map(parse_numbers).collect();
+    let first = times.iter().copied().fold(f64::INFINITY, f64::min);
+    let last = times.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let seconds = (times.len() >= 2 && last > first).then(|| {
+        // 以毫秒記錄時換算為秒；敲擊次數 n 有 n − 1 個間隔，以此推估每秒次數
+        let span = if last > 1000.0 { (last - first) / 1000.0 } else { last - first };
+        span * times.len() as f64 / (times.len() - 1) as f64
+    });
+    Ok((times.len(), seconds))
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// d′ = z(命中率) − z(誤報率)，比率以 (次數 + 0.5) / (試次 + 1) 校正
+fn d_prime(hits: usize, targets: usize, false_alarms: usize, non_targets: usize) -> f64 {
+    let rate = |count: usize, total: usize| (count as f64 + 0.5) / (total as f64 + 1.0);
+    inverse_normal(rate(hits, targets)) - inverse_normal(rate(false_alarms, non_targets))
+}
+
+/// 標準常態分布的分位數，Acklam 有理函數近似（相對誤差 < 1.2e-9）
+fn inverse_normal(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2, 1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2, 6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838, -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5]) / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// 指標與行為的對應
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskProfile {
+    pub task: Task,
+    pub metric: Metric,
+    /// 映射表中的行為名稱
+    pub behavior: String,
+    /// 送入引擎的單位；未指定時為指標本身的單位。需與映射表中該行為的單位一致
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// 對應的依據或限制（例如常模是否以相同指標建立）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// 作業指標與行為的對應表（`data/task_metrics.json`）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskProfiles {
+    pub profiles: Vec<TaskProfile>,
+}
+
+impl TaskProfiles {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        read_json(path)
+    }
+
+    /// 依對應表將推導的指標轉成行為輸入；沒有對應的指標不產生輸入
+    pub fn behavior_inputs(&self, task: Task, metrics: &SubjectMetrics, timestamp: DateTime<Utc>) -> Vec<BehaviorInput> {
+        self.profiles
+            .iter()
+            .filter(|profile| profile.task == task)
+            .filter_map(|profile| {
+                let derived = metrics.metrics.iter().find(|m| m.metric == profile.metric)?;
+                Some(BehaviorInput {
+                    behavior_type: profile.behavior.clone(),
+                    value: derived.value,
+                    unit: profile.unit.clone().unwrap_or_else(|| profile.metric.unit().to_string()),
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 試次檔的時間：檔案修改時間，無法取得時為現在
+pub fn file_timestamp(path: &Path) -> DateTime<Utc> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(subject: &SubjectMetrics, metric: Metric) -> f32 {
+        subject.metrics.iter().find(|m| m.metric == metric).map(|m| m.value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_psychopy_flanker() {
+        // PsychoPy：秒為單位，含練習前的指導語列（沒有反應）
+        let csv = "participant,congruency,key_resp.keys,key_resp.corr,key_resp.rt\n\
+                   P01,,,,\n\
+                   P01,congruent,left,1,0.400\n\
+                   P01,congruent,right,1,0.500\n\
+                   P01,incongruent,left,1,0.600\n\
+                   P01,incongruent,left,0,0.300\n\
+                   P01,incongruent,right,1,0.700\n";
+        let table = TrialTable::parse("flanker.csv", csv).unwrap();
+        let subjects = derive_metrics(&table, Task::Flanker, &ColumnOverrides::default()).unwrap();
+        assert_eq!(subjects.len(), 1);
+        let subject = &subjects[0];
+        assert_eq!(subject.subject_id.as_deref(), Some("P01"));
+        assert!((metric(subject, Metric::Accuracy) - 80.0).abs() < 1e-4);
+        assert!((metric(subject, Metric::MeanRt) - 550.0).abs() < 1e-3);
+        // 不一致 650 − 一致 450
+        assert!((metric(subject, Metric::CongruencyEffect) - 200.0).abs() < 1e-3);
+        assert!(subject.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_parse_eprime_utf16_nback() {
+        // E-DataAid 匯出：UTF-16 LE、Tab 分隔、第一行為檔案路徑，反應時間為毫秒
+        let text = "C:\\data\\nback.edat2\r\nSubject\tIsTarget\tStimulus.RESP\tStimulus.RT\tStimulus.ACC\r\n\
+                    7\t1\t1\t650\t1\r\n7\t1\t1\t550\t1\r\n7\t1\t\t0\t0\r\n7\t0\t\t0\t1\r\n7\t0\t1\t700\t0\r\n7\t0\t\t0\t1\r\n";
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let path = std::env::temp_dir().join("bbv_nback_eprime.txt");
+        std::fs::write(&path, bytes).unwrap();
+
+        let table = TrialTable::load(&path).unwrap();
+        assert_eq!(table.columns[0], "Subject");
+        let overrides = ColumnOverrides::parse(&["target=IsTarget".to_string()]).unwrap();
+        let subject = &derive_metrics(&table, Task::NBack, &overrides).unwrap()[0];
+        assert_eq!(subject.subject_id.as_deref(), Some("7"));
+        assert!((metric(subject, Metric::Accuracy) - 400.0 / 6.0).abs() < 1e-3);
+        assert!((metric(subject, Metric::MeanRt) - 600.0).abs() < 1e-3);
+        // 命中率 (2 + 0.5) / 4，誤報率 (1 + 0.5) / 4：d′ = z(0.625) − z(0.375) ≈ 0.6372
+        assert!((metric(subject, Metric::DPrime) - 0.637_279).abs() < 1e-4);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_tapping_and_igt() {
+        // 每次敲擊一列（秒）：5 次敲擊、間隔 0.25 秒，每秒 4 次
+        let table = TrialTable::parse("tap.csv", "tap_time\n0.0\n0.25\n0.5\n0.75\n1.0\n").unwrap();
+        let subject = &derive_metrics(&table, Task::Tapping, &ColumnOverrides::default()).unwrap()[0];
+        assert_eq!(metric(subject, Metric::TapCount), 5.0);
+        assert!((metric(subject, Metric::TapRate) - 4.0).abs() < 1e-4);
+
+        // 每個試次一列：敲擊次數與秒數
+        let table = TrialTable::parse("tap.csv", "trial;taps;duration\n1;52;10\n2;48;10\n").unwrap();
+        let subject = &derive_metrics(&table, Task::Tapping, &ColumnOverrides::default()).unwrap()[0];
+        assert_eq!(metric(subject, Metric::TapCount), 100.0);
+        assert!((metric(subject, Metric::TapRate) - 5.0).abs() < 1e-4);
+
+        let table = TrialTable::parse("igt.csv", "trial,deck\n1,A\n2,C\n3,D\n4,Deck B\n5,4\n6,?\n").unwrap();
+        let subject = &derive_metrics(&table, Task::Igt, &ColumnOverrides::default()).unwrap()[0];
+        assert_eq!(metric(subject, Metric::NetScore), 1.0);
+        assert_eq!(subject.metrics[0].trials, 5);
+    }
+
+    #[test]
+    fn test_missing_columns() {
+        let table = TrialTable::parse("x.csv", "trial,stim\n1,a\n").unwrap();
+        let err = derive_metrics(&table, Task::Igt, &ColumnOverrides::default()).unwrap_err();
+        assert!(matches!(err, BrainVizError::Parse { line: 1, .. }));
+        // 所有指標都算不出來時回傳第一個指標（正確率）的原因
+        let err = derive_metrics(&table, Task::Flanker, &ColumnOverrides::default()).unwrap_err();
+        assert_eq!(err.to_string(), table.missing_column(ColumnRole::Correct, &ColumnOverrides::default()).to_string());
+
+        // 沒有試次的受試者記錄在 skipped，不影響其他受試者
+        let table = TrialTable::parse("x.csv", "subject,rt,correct\nA,0.5,1\nA,0.7,1\nB,,\n").unwrap();
+        let subjects = derive_metrics(&table, Task::Flanker, &ColumnOverrides::default()).unwrap();
+        assert_eq!(subjects.len(), 2);
+        assert_eq!(subjects[0].metrics.len(), 2);
+        assert!(subjects[1].metrics.is_empty());
+        assert_eq!(subjects[1].skipped.len(), 3);
+        let table = TrialTable::parse("x.csv", "subject,rt,correct\nB,,\n").unwrap();
+        assert!(derive_metrics(&table, Task::Flanker, &ColumnOverrides::default()).is_err());
+
+        // 缺少一致性條件時仍算出其他指標
+        let table = TrialTable::parse("x.csv", "rt,correct\n0.5,1\n0.7,1\n").unwrap();
+        let subject = &derive_metrics(&table, Task::Flanker, &ColumnOverrides::default()).unwrap()[0];
+        assert_eq!(subject.metrics.len(), 2);
+        assert_eq!(subject.skipped[0].0, Metric::CongruencyEffect);
+
+        assert!(ColumnOverrides::parse(&["latency=RT".to_string()]).is_err());
+        assert!(TrialTable::parse("x.csv", "\n\n").is_err());
+    }
+
+    #[test]
+    fn test_task_profiles() {
+        assert_eq!(Task::parse("N-Back"), Some(Task::NBack));
+        assert_eq!(Task::parse("finger tapping"), Some(Task::Tapping));
+        assert_eq!(Task::parse("stroop"), None);
+
+        let profiles = TaskProfiles::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("data/task_metrics.json")).unwrap();
+        let metrics = SubjectMetrics {
+            subject_id: None,
+            metrics: vec![DerivedMetric { metric: Metric::NetScore, value: 12.0, trials: 100 }],
+            skipped: Vec::new(),
+        };
+        let inputs = profiles.behavior_inputs(Task::Igt, &metrics, Utc::now());
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].value, 12.0);
+
+        // 對應表中的行為與單位須存在於預設映射表
+        let engine = crate::mapping::MappingEngine::new(
+            crate::mapping::load_behavior_brain_map(Path::new(env!("CARGO_MANIFEST_DIR")).join("data/behavior_brain_map.json")).unwrap(),
+        );
+        for profile in &profiles.profiles {
+            let unit = profile.unit.clone().unwrap_or_else(|| profile.metric.unit().to_string());
+            assert_eq!(engine.get_behavior_unit(&profile.behavior), Some(unit.as_str()), "{}", profile.behavior);
+            assert!(profile.task.metrics().contains(&profile.metric));
+        }
+    }
+}
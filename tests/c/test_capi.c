@@ -0,0 +1,41 @@
+/* C API 冒煙測試：由 tests/c_api.rs 編譯並執行 */
+#include <stdio.h>
+#include <string.h>
+#include "behavior_brain_viz.h"
+
+#define CHECK(cond)                                                  \
+    do {                                                             \
+        if (!(cond)) {                                               \
+            fprintf(stderr, "%s:%d: 檢查失敗: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                \
+        }                                                            \
+    } while (0)
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+
+    BbvEngine *engine = bbv_engine_new_from_json(argv[1]);
+    CHECK(engine != NULL);
+
+    BbvResult *result = bbv_engine_score(engine, "持續注意力指敲", 95.0f, "次");
+    CHECK(result != NULL);
+    CHECK(bbv_result_risk_level(result) == BBV_RISK_CRITICAL);
+    CHECK(bbv_result_total_impact(result) > 0.0f);
+    CHECK(bbv_result_region_count(result) == 3);
+    CHECK(strcmp(bbv_result_region_name(result, 0), "前額葉") == 0);
+    CHECK(bbv_result_region_name(result, 3) == NULL);
+    CHECK(bbv_result_recommendation_count(result) > 0);
+
+    for (size_t i = 0; i < bbv_result_region_count(result); i++) {
+        printf("%s\t%.3f\n", bbv_result_region_name(result, i), bbv_result_region_score(result, i));
+    }
+    bbv_result_free(result);
+
+    /* 錯誤處理 */
+    CHECK(bbv_engine_score(engine, "自然步行", 1.0f, "秒") == NULL);
+    CHECK(bbv_last_error_message() != NULL);
+    CHECK(bbv_engine_new_from_json("/nonexistent/map.json") == NULL);
+
+    bbv_engine_free(engine);
+    return 0;
+}
@@ -0,0 +1,63 @@
+//! 以系統 C 編譯器建置 tests/c/test_capi.c 並連結 cdylib 執行
+
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn cdylib_path(target_dir: &Path) -> PathBuf {
+    let name = if cfg!(target_os = "macos") {
+        "libbehavior_brain_viz.dylib"
+    } else {
+        "libbehavior_brain_viz.so"
+    };
+    target_dir.join(name)
+}
+
+#[test]
+fn test_c_program_links_and_runs() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // 測試執行檔與 cargo test 建置的 cdylib 皆位於 target/<profile>/deps/
+    let exe = std::env::current_exe().unwrap();
+    let target_dir = exe.parent().unwrap().to_path_buf();
+    let library = cdylib_path(&target_dir);
+
+    if !library.exists() {
+        eprintln!("略過：找不到 {}", library.display());
+        return;
+    }
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    if Command::new(&cc).arg("--version").output().is_err() {
+        eprintln!("略過：找不到 C 編譯器 {}", cc);
+        return;
+    }
+
+    let out_dir = std::env::temp_dir().join(format!("bbv_capi_{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let program = out_dir.join("test_capi");
+
+    let status = Command::new(&cc)
+        .arg(manifest_dir.join("tests/c/test_capi.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-o")
+        .arg(&program)
+        .arg(&library)
+        .arg(format!("-Wl,-rpath,{}", target_dir.display()))
+        .status()
+        .unwrap();
+    assert!(status.success(), "C 測試程式編譯失敗");
+
+    let output = Command::new(&program)
+        .arg(manifest_dir.join("data/behavior_brain_map.json"))
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&out_dir);
+
+    assert!(
+        output.status.success(),
+        "C 測試程式失敗:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("前額葉"));
+}
@@ -0,0 +1,66 @@
+use behavior_brain_viz::{
+    load_behavior_brain_map, load_risk_map, BehaviorInput, BrainVizError, Language, MappingEngine,
+    RiskAssessment, RiskLevel,
+};
+use chrono::Utc;
+use std::path::PathBuf;
+
+fn data_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("data").join(name)
+}
+
+fn input(behavior: &str, value: f32, unit: &str) -> BehaviorInput {
+    BehaviorInput {
+        behavior_type: behavior.to_string(),
+        value,
+        unit: unit.to_string(),
+        timestamp: Utc::now(),
+    }
+}
+
+#[test]
+fn test_bundled_mapping_scores_every_behavior() {
+    let maps = load_behavior_brain_map(data_path("behavior_brain_map.json")).unwrap();
+    let engine = MappingEngine::new(maps);
+
+    for behavior in engine.get_behavior_types() {
+        let unit = engine.get_behavior_unit(&behavior).unwrap_or("分數").to_string();
+        let result = engine.calculate_impact(&input(&behavior, 50.0, &unit)).unwrap();
+
+        assert!(!result.impact_scores.is_empty());
+        assert!((0.0..=1.0).contains(&result.total_impact));
+    }
+}
+
+#[test]
+fn test_library_risk_report_pipeline() {
+    let maps = load_behavior_brain_map(data_path("behavior_brain_map.json")).unwrap();
+    let engine = MappingEngine::new(maps);
+    let assessment = RiskAssessment::default()
+        .with_risk_map(load_risk_map(data_path("risk_map.json")).unwrap());
+
+    let result = engine.calculate_impact(&input("持續注意力指敲", 95.0, "次")).unwrap();
+    let report = assessment.generate_risk_report(&result, Language::ZhTw);
+
+    assert_eq!(report.risk_level, RiskLevel::Critical);
+    assert!(report.high_impact_regions.contains(&"前額葉".to_string()));
+    assert!(!report.recommendations.is_empty());
+}
+
+#[test]
+fn test_library_errors() {
+    let engine = MappingEngine::new(load_behavior_brain_map(data_path("behavior_brain_map.json")).unwrap());
+
+    assert!(matches!(
+        engine.calculate_impact(&input("不存在的行為", 1.0, "次")),
+        Err(BrainVizError::UnknownBehavior(_))
+    ));
+    assert!(matches!(
+        engine.calculate_impact(&input("自然步行", 1.0, "秒")),
+        Err(BrainVizError::UnitMismatch { .. })
+    ));
+    assert!(matches!(
+        load_behavior_brain_map(data_path("does_not_exist.json")),
+        Err(BrainVizError::Io { .. })
+    ));
+}
@@ -0,0 +1,55 @@
+#!/usr/bin/env python3
+"""將 Lab Streaming Layer（LSL）串流轉送到 behavior_brain_viz 的 UDP 串流來源。
+
+每個 LSL 樣本的第一個聲道視為行為數值，以 JSON 行送出（時間戳記換算為 UTC）：
+
+    python tools/lsl_bridge.py --name Tapping --behavior 持續注意力指敲 --unit 次
+    cargo run -- --stream udp://127.0.0.1:8751
+
+需要 pylsl（pip install pylsl）。
+"""
+
+import argparse
+import datetime
+import json
+import socket
+import time
+
+import pylsl
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--name", required=True, help="LSL 串流名稱")
+    parser.add_argument("--behavior", required=True, help="對應的行為類型（映射表中的名稱）")
+    parser.add_argument("--unit", help="單位，未指定時使用映射表中的預期單位")
+    parser.add_argument("--channel", type=int, default=0, help="使用的聲道（從 0 起算）")
+    parser.add_argument("--addr", default="127.0.0.1:8751", help="behavior_brain_viz 的 UDP 位址")
+    args = parser.parse_args()
+
+    host, port = args.addr.rsplit(":", 1)
+    target = (host, int(port))
+    sock = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+
+    print(f"等待 LSL 串流 {args.name} ...")
+    streams = pylsl.resolve_byprop("name", args.name)
+    inlet = pylsl.StreamInlet(streams[0])
+    # LSL 時鐘與系統時鐘的差，用於換算樣本時間
+    offset = time.time() - pylsl.local_clock()
+    print(f"已連線，轉送到 udp://{args.addr}")
+
+    while True:
+        sample, timestamp = inlet.pull_sample()
+        moment = datetime.datetime.fromtimestamp(timestamp + inlet.time_correction() + offset, datetime.timezone.utc)
+        record = {
+            "behavior_type": args.behavior,
+            "value": float(sample[args.channel]),
+            "timestamp": moment.isoformat().replace("+00:00", "Z"),
+        }
+        if args.unit:
+            record["unit"] = args.unit
+        sock.sendto((json.dumps(record, ensure_ascii=False) + "\n").encode("utf-8"), target)
+
+
+if __name__ == "__main__":
+    main()